@@ -7,9 +7,11 @@
 // Borrowed format strings are valid for file dialogs
 #![allow(clippy::needless_borrows_for_generic_args)]
 
-use crate::state::{AppState, ExportFormat, ExportSettings, TerminalLevel};
+use crate::state::{AppState, ExportFormat, ExportSettings, MeshAlgorithm, TerminalLevel};
 use dioxus::desktop::{window, Config, LogicalSize, WindowBuilder};
 use dioxus::prelude::*;
+use soyuz_script::ParamValue;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use tracing::warn;
 
@@ -23,8 +25,10 @@ pub fn open_export_window(state: Signal<AppState>) {
     let close_after_export = initial_state.export_settings.close_after_export;
     let format = initial_state.export_settings.format;
     let resolution = initial_state.export_settings.resolution;
+    let algorithm = initial_state.export_settings.algorithm;
     let optimize = initial_state.export_settings.optimize;
     let code = initial_state.code();
+    let params = initial_state.param_overrides.clone();
     drop(initial_state);
 
     // Compute default path
@@ -42,15 +46,17 @@ pub fn open_export_window(state: Signal<AppState>) {
             initial_filename: default_filename,
             initial_format: format,
             initial_resolution: resolution,
+            initial_algorithm: algorithm,
             initial_optimize: optimize,
             initial_close_after: close_after_export,
             initial_code: code,
+            initial_params: params,
         },
     );
 
     let window_builder = WindowBuilder::new()
         .with_title("Export - Soyuz Studio")
-        .with_inner_size(LogicalSize::new(420.0, 520.0))
+        .with_inner_size(LogicalSize::new(420.0, 590.0))
         .with_resizable(true);
 
     let config = Config::new()
@@ -97,9 +103,12 @@ struct ExportWindowProps {
     initial_filename: String,
     initial_format: ExportFormat,
     initial_resolution: u32,
+    initial_algorithm: MeshAlgorithm,
     initial_optimize: bool,
     initial_close_after: bool,
     initial_code: String,
+    /// Parameter slider values, applied to the script before exporting
+    initial_params: BTreeMap<String, ParamValue>,
 }
 
 /// The export window component
@@ -110,20 +119,22 @@ fn ExportWindow(props: ExportWindowProps) -> Element {
     let mut filename = use_signal(|| props.initial_filename.clone());
     let mut format = use_signal(|| props.initial_format);
     let mut resolution = use_signal(|| props.initial_resolution);
+    let mut algorithm = use_signal(|| props.initial_algorithm);
     let mut optimize = use_signal(|| props.initial_optimize);
     let mut close_after_export = use_signal(|| props.initial_close_after);
     let mut is_exporting = use_signal(|| false);
     let mut status_message = use_signal(|| None::<String>);
     let code = use_signal(|| props.initial_code.clone());
+    let params = use_signal(|| props.initial_params.clone());
     let mut main_state = props.main_state;
 
     // Resize window when STL is selected (to accommodate the info message)
     use_effect(move || {
         let current_format = *format.read();
         let height = if current_format == ExportFormat::Stl {
-            580.0 // Taller to fit the STL info message
+            650.0 // Taller to fit the STL info message
         } else {
-            520.0 // Standard height
+            590.0 // Standard height
         };
         window().set_inner_size(LogicalSize::new(420.0, height));
     });
@@ -166,15 +177,18 @@ fn ExportWindow(props: ExportWindowProps) -> Element {
         let full_path = path.join(&name);
         let export_format = *format.read();
         let export_resolution = *resolution.read();
+        let export_algorithm = *algorithm.read();
         let export_optimize = *optimize.read();
         let settings = ExportSettings {
             format: export_format,
             resolution: export_resolution,
+            algorithm: export_algorithm,
             optimize: export_optimize,
             last_export_dir: Some(path.clone()),
             close_after_export: *close_after_export.read(),
         };
         let export_code = code.read().clone();
+        let export_params = params.read().clone();
         let should_close = *close_after_export.read();
 
         // Clone path for use after spawn_blocking
@@ -192,7 +206,7 @@ fn ExportWindow(props: ExportWindowProps) -> Element {
             );
 
             let result = tokio::task::spawn_blocking(move || {
-                export_mesh(&export_code, &full_path, &settings)
+                export_mesh(&export_code, &export_params, &full_path, &settings)
             })
             .await;
 
@@ -210,6 +224,7 @@ fn ExportWindow(props: ExportWindowProps) -> Element {
                     main_state.write().export_settings.close_after_export = should_close;
                     main_state.write().export_settings.format = export_format;
                     main_state.write().export_settings.resolution = export_resolution;
+                    main_state.write().export_settings.algorithm = export_algorithm;
                     main_state.write().export_settings.optimize = export_optimize;
 
                     // Handle post-export action
@@ -350,6 +365,31 @@ fn ExportWindow(props: ExportWindowProps) -> Element {
                 }
             }
 
+            // Mesher
+            div { class: "export-section",
+                label { class: "export-section-label", "Mesher" }
+                div { class: "export-format-buttons",
+                    MesherButton {
+                        algorithm: MeshAlgorithm::MarchingCubes,
+                        label: "Smooth",
+                        current: *algorithm.read(),
+                        on_select: move |a| algorithm.set(a)
+                    }
+                    MesherButton {
+                        algorithm: MeshAlgorithm::DualContouring,
+                        label: "Sharp Edges",
+                        current: *algorithm.read(),
+                        on_select: move |a| algorithm.set(a)
+                    }
+                    MesherButton {
+                        algorithm: MeshAlgorithm::Adaptive,
+                        label: "Adaptive",
+                        current: *algorithm.read(),
+                        on_select: move |a| algorithm.set(a)
+                    }
+                }
+            }
+
             // Options
             div { class: "export-section",
                 label { class: "export-section-label", "Options" }
@@ -438,6 +478,27 @@ fn FormatButton(
     }
 }
 
+/// Mesher choice: marching cubes rounds edges, dual contouring keeps them
+/// sharp, and the adaptive octree is fastest at high resolutions
+#[component]
+fn MesherButton(
+    algorithm: MeshAlgorithm,
+    label: &'static str,
+    current: MeshAlgorithm,
+    on_select: EventHandler<MeshAlgorithm>,
+) -> Element {
+    let is_selected = algorithm == current;
+
+    rsx! {
+        button {
+            class: if is_selected { "export-format-btn active" } else { "export-format-btn" },
+            title: "{algorithm.name()}",
+            onclick: move |_| on_select.call(algorithm),
+            "{label}"
+        }
+    }
+}
+
 /// Open a folder in the system file manager
 fn open_folder(path: &std::path::Path) {
     #[cfg(target_os = "linux")]
@@ -491,10 +552,12 @@ fn open_file(path: &std::path::Path) {
 
 /// Export mesh from script
 ///
-/// Evaluates the script to get an SDF, then uses parallel marching cubes
-/// (via Rayon) to generate a mesh for export.
+/// Evaluates the script with the given parameter overrides to get an SDF,
+/// then uses parallel marching cubes or dual contouring (via Rayon) to
+/// generate a mesh for export.
 pub fn export_mesh(
     code: &str,
+    params: &BTreeMap<String, ParamValue>,
     output_path: &std::path::Path,
     settings: &ExportSettings,
 ) -> anyhow::Result<String> {
@@ -502,11 +565,15 @@ pub fn export_mesh(
 
     // Create engine and run script
     let mut engine = Engine::new();
+    for (name, value) in params {
+        engine.set_param(name.clone(), *value);
+    }
     engine.run_script(code)?;
 
     // Export using Engine API
     let options = ExportOptions::new(output_path)
         .with_resolution(settings.resolution)
+        .with_algorithm(settings.algorithm)
         .with_optimize(settings.optimize);
 
     let result = engine.export(&options)?;
@@ -6,6 +6,7 @@ mod export;
 mod markdown_panel;
 mod js_interop;
 mod pane;
+mod params_panel;
 mod preview;
 mod session;
 mod settings;
@@ -125,6 +126,9 @@ fn App() -> Element {
     let mut state = use_context::<Signal<AppState>>();
     let mut palette = use_context::<Signal<command_palette::PaletteState>>();
 
+    // Clean up the parameters file when the window closes
+    use_drop(AppState::remove_params_file);
+
     // Auto-save session every 30 seconds using use_future for background tasks
     use_future(move || async move {
         loop {
@@ -225,6 +229,7 @@ fn App() -> Element {
                                     if let Err(e) = std::process::Command::new(exe).spawn() {
                                         tracing::error!("Failed to restart application: {e}");
                                     } else {
+                                        AppState::remove_params_file();
                                         std::process::exit(0);
                                     }
                                 }
@@ -272,6 +277,18 @@ fn App() -> Element {
                                 pane::PaneTree {}
                             }
                         }
+
+                        // Right sidebar: sliders for the script's parameters
+                        // (only shown when it declares some)
+                        ErrorBoundary {
+                            handle_error: |error| rsx! {
+                                PanelError {
+                                    panel_name: "Parameters".to_string(),
+                                    error_msg: format!("{error:?}")
+                                }
+                            },
+                            params_panel::ParamsPanel {}
+                        }
                     }
 
                     // Terminal panel (bottom-docked, collapsible)
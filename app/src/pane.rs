@@ -703,8 +703,37 @@ fn highlight_rhai(code: &str) -> String {
         "hex_prism",
         "tri_prism",
         "rounded_box",
+        "capped_cone",
+        "round_cone",
+        "box_frame",
+        "capped_torus",
+        "cut_sphere",
+        "cut_hollow_sphere",
+        "rhombus",
+        "solid_angle",
+        "capsule_between",
+        "cylinder_between",
+        "infinite_cylinder",
         "mandelbulb",
-        "menger",
+        "menger_sponge",
+        "sierpinski",
+        "julia",
+        "circle",
+        "rect",
+        "rounded_rect",
+        "polygon",
+        "arc",
+        "offset",
+        "extrude",
+        "revolve",
+        "polyline",
+        "bezier",
+        "catmull_rom",
+        "tube",
+        "sweep",
+        "sweep_rect",
+        "sweep_rounded_rect",
+        "sweep_circle",
         "union",
         "subtract",
         "intersect",
@@ -719,6 +748,10 @@ fn highlight_rhai(code: &str) -> String {
         "rotate_x",
         "rotate_y",
         "rotate_z",
+        "rotate_quat",
+        "align_to",
+        "look_at",
+        "transform",
         "scale",
         "scale_xyz",
         "mirror",
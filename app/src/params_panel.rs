@@ -0,0 +1,173 @@
+//! Parameter sliders for scripts that declare `param()`s
+//!
+//! The panel evaluates the active script to find its parameters and shows a
+//! slider (or checkbox) for each. Evaluation waits for a pause in typing and
+//! runs off the UI thread, since scripts can take a while to build.
+//!
+//! Moving a slider updates the overrides, which a running preview reloads
+//! from the parameters file; exports use them too.
+
+use crate::state::AppState;
+use dioxus::prelude::*;
+use soyuz_script::{ParamValue, ScriptEngine, ScriptParam};
+use std::path::Path;
+use std::time::Duration;
+
+/// Steps across a float slider's range
+const FLOAT_STEPS: f64 = 200.0;
+
+/// Pause after the last edit before the script is evaluated again
+const EVAL_DELAY: Duration = Duration::from_millis(300);
+
+/// Parameters the script declares, or `None` if it doesn't evaluate
+fn declared_params(code: &str, path: Option<&Path>) -> Option<Vec<ScriptParam>> {
+    let engine = ScriptEngine::new();
+    let result = match path {
+        Some(path) => engine
+            .eval_scene_with_path(code, path)
+            .map(|scene| scene.params),
+        None => engine.params(code),
+    };
+    result.ok()
+}
+
+/// Side panel with one control per script parameter
+#[component]
+pub fn ParamsPanel() -> Element {
+    let mut state = use_context::<Signal<AppState>>();
+    let script = use_memo(move || {
+        let s = state.read();
+        (s.code(), s.current_file())
+    });
+
+    // Keep showing the last good parameters while the script is mid-edit.
+    // Each edit restarts the resource, cancelling a pending evaluation
+    let mut params = use_signal(Vec::<ScriptParam>::new);
+    let _ = use_resource(move || async move {
+        let (code, path) = script();
+        tokio::time::sleep(EVAL_DELAY).await;
+        let evaluated =
+            tokio::task::spawn_blocking(move || declared_params(&code, path.as_deref())).await;
+        let Ok(Some(declared)) = evaluated else {
+            return;
+        };
+
+        // Drop overrides the script no longer accepts
+        let keep = |name: &String, value: &ParamValue| {
+            declared
+                .iter()
+                .any(|p| &p.name == name && p.accept(*value).is_ok())
+        };
+        let stale = state
+            .peek()
+            .param_overrides
+            .iter()
+            .any(|(n, v)| !keep(n, v));
+        if stale {
+            state.write().param_overrides.retain(|n, v| keep(n, v));
+            state.peek().write_params_file();
+        }
+
+        params.set(declared);
+    });
+
+    if params.read().is_empty() {
+        return rsx! {};
+    }
+
+    let overrides = state.read().param_overrides.clone();
+    let rows: Vec<(String, ScriptParam, ParamValue)> = params
+        .read()
+        .iter()
+        .map(|p| {
+            let value = overrides.get(&p.name).copied().unwrap_or(p.default);
+            (p.name.clone(), p.clone(), value)
+        })
+        .collect();
+
+    rsx! {
+        div { class: "panel params-panel",
+            div { class: "explorer-header",
+                span { class: "explorer-title", "Parameters" }
+                button {
+                    class: "explorer-button",
+                    title: "Reset to defaults",
+                    disabled: overrides.is_empty(),
+                    onclick: move |_| state.write().reset_param_overrides(),
+                    "↺"
+                }
+            }
+            div { class: "params-list",
+                for (key, param, value) in rows {
+                    ParamControl { key: "{key}", param, value }
+                }
+            }
+        }
+    }
+}
+
+/// Slider or checkbox for one parameter
+#[component]
+fn ParamControl(param: ScriptParam, value: ParamValue) -> Element {
+    let mut state = use_context::<Signal<AppState>>();
+    let name = param.name.clone();
+
+    let control = match param.default {
+        ParamValue::Bool(_) => {
+            let checked = matches!(value, ParamValue::Bool(true));
+            rsx! {
+                input {
+                    r#type: "checkbox",
+                    class: "settings-checkbox",
+                    checked: "{checked}",
+                    onchange: move |evt| {
+                        state.write().set_param_override(name.clone(), ParamValue::Bool(evt.checked()));
+                    }
+                }
+            }
+        }
+        ParamValue::Int(_) | ParamValue::Float(_) => {
+            let is_int = matches!(param.default, ParamValue::Int(_));
+            let (min, max) = (param.min.as_f64(), param.max.as_f64());
+            let step = if is_int {
+                1.0
+            } else {
+                (max - min) / FLOAT_STEPS
+            };
+            let current = value.as_f64();
+            rsx! {
+                input {
+                    r#type: "range",
+                    class: "export-slider",
+                    min: "{min}",
+                    max: "{max}",
+                    step: "{step}",
+                    value: "{current}",
+                    oninput: move |evt| {
+                        let Ok(v) = evt.value().parse::<f64>() else {
+                            return;
+                        };
+                        #[allow(clippy::cast_possible_truncation)]
+                        let v = if is_int { ParamValue::Int(v.round() as i64) } else { ParamValue::Float(v) };
+                        state.write().set_param_override(name.clone(), v);
+                    }
+                }
+            }
+        }
+    };
+
+    let shown = match value {
+        ParamValue::Float(v) => format!("{v:.3}"),
+        other => other.to_string(),
+    };
+
+    rsx! {
+        div { class: "param-row",
+            div { class: "param-label",
+                span { class: "param-name", "{param.name}" }
+                span { class: "param-value", "{shown}" }
+            }
+            {control}
+        }
+    }
+}
@@ -22,6 +22,9 @@ pub fn spawn_preview(mut state: Signal<AppState>) {
         return;
     }
 
+    // Parameter slider values, which the preview reloads on change
+    state.read().write_params_file();
+
     // Mark as previewing and clear errors
     {
         let mut s = state.write();
@@ -38,7 +41,7 @@ pub fn spawn_preview(mut state: Signal<AppState>) {
     }
 
     // Spawn preview process
-    match spawn_preview_process(&temp_path) {
+    match spawn_preview_process(&temp_path, &AppState::params_file()) {
         Ok(child) => {
             state.read().terminal_log(TerminalLevel::Info, "Preview window opened");
 
@@ -115,7 +118,10 @@ async fn wait_for_process_exit(
 }
 
 /// Spawn the preview as a separate window process
-fn spawn_preview_process(script_path: &Path) -> Result<Child, std::io::Error> {
+///
+/// The preview reloads whenever `params_path` changes, so slider moves show
+/// up without restarting it.
+fn spawn_preview_process(script_path: &Path, params_path: &Path) -> Result<Child, std::io::Error> {
     // Try to find the soyuz-preview binary next to the main executable
     let exe_path = std::env::current_exe().ok();
     let preview_path = exe_path
@@ -128,6 +134,8 @@ fn spawn_preview_process(script_path: &Path) -> Result<Child, std::io::Error> {
         Command::new(&preview_bin)
             .arg("--script")
             .arg(script_path)
+            .arg("--params")
+            .arg(params_path)
             .spawn()
     } else {
         // Fallback: try cargo run (for development)
@@ -141,6 +149,8 @@ fn spawn_preview_process(script_path: &Path) -> Result<Child, std::io::Error> {
             .arg("--")
             .arg("--script")
             .arg(script_path)
+            .arg("--params")
+            .arg(params_path)
             .spawn()
     }
 }
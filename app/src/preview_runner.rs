@@ -3,24 +3,32 @@
 //! This binary is spawned by the main Soyuz Studio app to run previews
 //! in a separate process for isolation. It uses the Engine API to load
 //! scripts and display the preview window.
+//!
+//! With `--params <file>`, the script's parameters are overridden with the
+//! `name=value` lines of that file, and the preview re-evaluates the script
+//! whenever the file changes. Studio's parameter sliders write this file.
 
-use soyuz_engine::{Engine, PreviewOptions};
+use soyuz_engine::{Engine, ParamValue, PreviewOptions, parse_param_override, run_watch_preview};
 use std::env;
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
 fn main() -> ExitCode {
-    // Parse command line args: soyuz-preview --script <path>
+    // Parse command line args: soyuz-preview --script <path> [--params <path>]
     let args: Vec<String> = env::args().collect();
 
-    let script_path = parse_args(&args);
+    let (script_path, params_path) = parse_args(&args);
     let Some(path) = script_path else {
-        eprintln!("Usage: soyuz-preview --script <path>");
+        eprintln!("Usage: soyuz-preview --script <path> [--params <path>]");
         return ExitCode::FAILURE;
     };
 
     // Run the preview
-    if let Err(e) = run_preview(&path) {
+    let result = match params_path {
+        Some(params) => run_live_preview(&path, params),
+        None => run_preview(&path),
+    };
+    if let Err(e) = result {
         eprintln!("Preview error: {e}");
         return ExitCode::FAILURE;
     }
@@ -28,16 +36,20 @@ fn main() -> ExitCode {
     ExitCode::SUCCESS
 }
 
-fn parse_args(args: &[String]) -> Option<PathBuf> {
+fn parse_args(args: &[String]) -> (Option<PathBuf>, Option<PathBuf>) {
     let mut iter = args.iter().skip(1); // Skip program name
+    let mut script = None;
+    let mut params = None;
 
     while let Some(arg) = iter.next() {
         if arg == "--script" || arg == "-s" {
-            return iter.next().map(PathBuf::from);
+            script = iter.next().map(PathBuf::from);
+        } else if arg == "--params" {
+            params = iter.next().map(PathBuf::from);
         }
     }
 
-    None
+    (script, params)
 }
 
 fn run_preview(path: &Path) -> anyhow::Result<()> {
@@ -53,3 +65,29 @@ fn run_preview(path: &Path) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Preview that reloads when the script or its parameter file changes
+fn run_live_preview(path: &Path, params: PathBuf) -> anyhow::Result<()> {
+    let options = PreviewOptions::default().with_title("Soyuz Preview");
+    let params_file = params.clone();
+
+    run_watch_preview(path, vec![params], options, move |script| {
+        let mut engine = Engine::new();
+        for (name, value) in read_params(&params_file)? {
+            engine.set_param(name, value);
+        }
+        Ok(engine.load_script(script)?.sdf.clone())
+    })
+}
+
+/// Read `name=value` overrides, one per line; a missing file means none
+fn read_params(path: &Path) -> anyhow::Result<Vec<(String, ParamValue)>> {
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return Ok(Vec::new());
+    };
+
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse_param_override(line).map_err(|e| anyhow::anyhow!(e)))
+        .collect()
+}
@@ -4,8 +4,9 @@
 
 use std::path::PathBuf;
 
-// Re-export ExportFormat from soyuz-core for convenience
+// Re-export ExportFormat and MeshAlgorithm from soyuz-core for convenience
 pub use soyuz_core::export::ExportFormat;
+pub use soyuz_core::mesh::MeshAlgorithm;
 
 /// Export settings for mesh generation
 #[derive(Clone)]
@@ -14,6 +15,8 @@ pub struct ExportSettings {
     pub format: ExportFormat,
     /// Mesh resolution
     pub resolution: u32,
+    /// Surface extraction algorithm
+    pub algorithm: MeshAlgorithm,
     /// Whether to optimize mesh
     pub optimize: bool,
     /// Last used export directory (remembered across sessions)
@@ -27,6 +30,7 @@ impl Default for ExportSettings {
         Self {
             format: ExportFormat::Glb,
             resolution: 128,
+            algorithm: MeshAlgorithm::MarchingCubes,
             optimize: false,
             last_export_dir: None,
             close_after_export: true,
@@ -27,12 +27,14 @@ mod undo;
 
 // Re-export all public types
 pub use editor::{EditorPane, EditorTab, MarkdownDoc, PaneId, SplitDirection, TabId};
-pub use export::{ExportFormat, ExportSettings};
+pub use export::{ExportFormat, ExportSettings, MeshAlgorithm};
 pub use preview::PreviewState;
 pub use terminal::{TerminalBuffer, TerminalEntry, TerminalFilter, TerminalLevel};
 pub use undo::UndoHistory;
 
 use parking_lot::Mutex;
+use soyuz_script::ParamValue;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tracing::warn;
@@ -75,6 +77,8 @@ pub struct AppState {
     pub terminal_height: f32,
     /// Terminal output filter settings
     pub terminal_filter: TerminalFilter,
+    /// Script parameter values set with the parameter sliders
+    pub param_overrides: BTreeMap<String, ParamValue>,
 }
 
 impl AppState {
@@ -103,6 +107,7 @@ impl AppState {
             terminal_visible: false,
             terminal_height: 200.0,
             terminal_filter: TerminalFilter::default(),
+            param_overrides: BTreeMap::new(),
         }
     }
 
@@ -160,6 +165,52 @@ impl AppState {
         self.workspace = None;
     }
 
+    // ========================================================================
+    // Parameter Methods
+    // ========================================================================
+
+    /// File the preview process reads parameter overrides from
+    ///
+    /// Named after this process, so each open Studio keeps its own sliders.
+    pub fn params_file() -> PathBuf {
+        std::env::temp_dir().join(format!("soyuz_preview_{}.params", std::process::id()))
+    }
+
+    /// Delete the parameters file, which nothing else cleans up since it's
+    /// named after this process
+    pub fn remove_params_file() {
+        match std::fs::remove_file(Self::params_file()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                warn!("Failed to remove parameter overrides: {e}");
+            }
+            _ => {}
+        }
+    }
+
+    /// Set a parameter from its slider; a running preview picks it up
+    pub fn set_param_override(&mut self, name: String, value: ParamValue) {
+        self.param_overrides.insert(name, value);
+        self.write_params_file();
+    }
+
+    /// Go back to the script's defaults for every parameter
+    pub fn reset_param_overrides(&mut self) {
+        self.param_overrides.clear();
+        self.write_params_file();
+    }
+
+    /// Write the overrides as `name=value` lines for the preview process
+    pub fn write_params_file(&self) {
+        let text: String = self
+            .param_overrides
+            .iter()
+            .map(|(name, value)| format!("{name}={value}\n"))
+            .collect();
+        if let Err(e) = std::fs::write(Self::params_file(), text) {
+            warn!("Failed to write parameter overrides: {e}");
+        }
+    }
+
     /// Stop the preview process if running
     pub fn stop_preview(&mut self) {
         if let Some(ref mut process) = *self.preview_process.lock() {
@@ -0,0 +1,168 @@
+//! Resolution of command-line inputs to script paths
+//!
+//! Each input may be a script or saved scene file, a directory (all `.rhai`
+//! and `.soyuz.json` files directly inside it), or a glob pattern such as
+//! `props/**/*.rhai`. Shells usually
+//! expand globs before we see them, but quoted patterns and Windows shells
+//! don't, so we expand them ourselves.
+
+use soyuz_engine::scene::Scene;
+use std::path::{Path, PathBuf};
+
+/// An input that could not be resolved to any script
+#[derive(Debug, Clone)]
+pub struct InputError {
+    /// The input as given on the command line
+    pub input: String,
+    /// Why it could not be resolved
+    pub message: String,
+}
+
+/// Expand command-line inputs into a deduplicated, ordered list of scripts
+///
+/// Inputs that match nothing are returned as errors rather than silently
+/// ignored, so a typo in a build pipeline fails loudly.
+pub fn expand_inputs(inputs: &[String]) -> (Vec<PathBuf>, Vec<InputError>) {
+    let mut scripts: Vec<PathBuf> = Vec::new();
+    let mut errors = Vec::new();
+
+    for input in inputs {
+        match expand_input(input) {
+            Ok(paths) if paths.is_empty() => errors.push(InputError {
+                input: input.clone(),
+                message: "no .rhai scripts or .soyuz.json scenes matched".to_string(),
+            }),
+            Ok(paths) => {
+                for path in paths {
+                    if !scripts.contains(&path) {
+                        scripts.push(path);
+                    }
+                }
+            }
+            Err(message) => errors.push(InputError {
+                input: input.clone(),
+                message,
+            }),
+        }
+    }
+
+    (scripts, errors)
+}
+
+/// Expand a single input
+fn expand_input(input: &str) -> Result<Vec<PathBuf>, String> {
+    let path = Path::new(input);
+
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    if path.is_dir() {
+        return scripts_in_dir(path);
+    }
+
+    if !is_glob_pattern(input) {
+        return Err("file not found".to_string());
+    }
+
+    let entries = glob::glob(input).map_err(|e| format!("invalid glob pattern: {e}"))?;
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(std::result::Result::ok)
+        .filter(|p| p.is_file() && is_script(p))
+        .collect();
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// List all scripts and saved scenes directly inside a directory, sorted by name
+fn scripts_in_dir(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("failed to read directory: {e}"))?;
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(std::result::Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && is_script(p))
+        .collect();
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Check whether a path is a `.rhai` script or a `.soyuz.json` scene
+fn is_script(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "rhai") || Scene::is_scene_file(path)
+}
+
+/// Check whether an input contains glob metacharacters
+fn is_glob_pattern(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("soyuz_cli_test_{}_{name}", std::process::id()));
+        std::fs::create_dir_all(&dir).ok();
+        dir
+    }
+
+    #[test]
+    fn test_expand_directory() {
+        let dir = temp_dir("dir");
+        std::fs::write(dir.join("b.rhai"), "sphere(0.5)").ok();
+        std::fs::write(dir.join("a.rhai"), "sphere(0.5)").ok();
+        std::fs::write(dir.join("c.soyuz.json"), "{}").ok();
+        std::fs::write(dir.join("notes.txt"), "").ok();
+        std::fs::write(dir.join("data.json"), "{}").ok();
+
+        let (scripts, errors) = expand_inputs(&[dir.to_string_lossy().to_string()]);
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            scripts,
+            vec![
+                dir.join("a.rhai"),
+                dir.join("b.rhai"),
+                dir.join("c.soyuz.json")
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_expand_glob_deduplicates() {
+        let dir = temp_dir("glob");
+        std::fs::write(dir.join("crate.rhai"), "cube(1.0)").ok();
+
+        let pattern = dir.join("*.rhai").to_string_lossy().to_string();
+        let file = dir.join("crate.rhai").to_string_lossy().to_string();
+        let (scripts, errors) = expand_inputs(&[pattern, file]);
+
+        assert!(errors.is_empty());
+        assert_eq!(scripts.len(), 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_missing_inputs_are_errors() {
+        let (scripts, errors) = expand_inputs(&[
+            "does/not/exist.rhai".to_string(),
+            "does/not/exist/*.rhai".to_string(),
+        ]);
+
+        assert!(scripts.is_empty());
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].message, "file not found");
+        assert_eq!(
+            errors[1].message,
+            "no .rhai scripts or .soyuz.json scenes matched"
+        );
+    }
+}
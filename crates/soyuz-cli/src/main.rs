@@ -0,0 +1,675 @@
+//! Soyuz CLI - Headless batch export of .rhai scripts
+//!
+//! Evaluates Soyuz scripts and exports the resulting meshes without opening
+//! a window or touching the GPU, so it can run in build pipelines. Scenes
+//! saved as `.soyuz.json` can be exported the same way, without the scripts
+//! that made them. Thumbnails render on the GPU when there is one and on the
+//! CPU otherwise.
+//!
+//! ## Usage
+//!
+//! ```bash
+//! # Export every example as GLB at resolution 128
+//! soyuz export examples/*.rhai --resolution 128 --out-dir build/assets
+//!
+//! # Quoted globs and directories are expanded by soyuz itself
+//! soyuz export "props/**/*.rhai" weapons/ --format obj
+//!
+//! # Keep the sharp edges of hard-surface props
+//! soyuz export props/ --mesher dc
+//!
+//! # Mesh only near the surface for fast high-resolution exports
+//! soyuz export scenes/ --mesher adaptive --resolution 512
+//!
+//! # Scripts can import shared parts from a library directory
+//! soyuz export props/ --lib parts/
+//!
+//! # Override parameters declared with param()
+//! soyuz export barrel.rhai --param radius=0.7 --param staves=16
+//!
+//! # Export ten variants of a procedural rock: rock_seed0.glb .. rock_seed9.glb
+//! soyuz export rock.rhai --variants 10
+//!
+//! # Render 256x256 PNG thumbnails, on the CPU even if a GPU is available
+//! soyuz render props/ --size 256x256 --backend cpu --out-dir thumbs
+//!
+//! # 360° turntable GIF, or the same orbit as a sprite sheet
+//! soyuz render crate.rhai --turntable --frames 36
+//! soyuz render crate.rhai --turntable --format sheet --columns 6
+//!
+//! # Scripts that read `time` render as animations: 48 frames at 24 fps
+//! soyuz render flag.rhai --format apng --frames 48 --fps 24
+//!
+//! # Save evaluated scenes, then export them elsewhere without Rhai
+//! soyuz save props/ --out-dir baked
+//! soyuz export baked/ --format obj
+//! ```
+//!
+//! The exit code is non-zero if any script fails to evaluate or export.
+//! Every failure is listed in a report on stderr.
+
+mod inputs;
+
+use clap::{Args, Parser, Subcommand};
+use soyuz_engine::{
+    AnimationFormat, AnimationOptions, Engine, ExportFormat, ExportOptions, ExportResult,
+    FrameRange, MeshAlgorithm, ParamValue, RenderBackend, RenderOptions, SCENE_EXTENSION,
+    parse_param_override, scene::Scene,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use crate::inputs::expand_inputs;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "soyuz",
+    version,
+    about = "Procedural asset generation through code"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Export one or more scripts to mesh files
+    Export(ExportArgs),
+    /// Evaluate scripts and save their scenes as .soyuz.json files
+    Save(SaveArgs),
+    /// Render scripts or saved scenes to PNG thumbnails
+    Render(RenderArgs),
+}
+
+#[derive(Debug, Args)]
+struct ExportArgs {
+    /// Scripts, saved scenes, directories or glob patterns to export
+    #[arg(required = true, value_name = "SCRIPT")]
+    inputs: Vec<String>,
+
+    /// Output format: glb, gltf, obj or stl
+    #[arg(short, long, default_value = "glb", value_parser = parse_format)]
+    format: ExportFormat,
+
+    /// Mesh resolution (32 = fast, 64 = default, 128 = high, 256 = very high)
+    #[arg(short, long, default_value_t = 64)]
+    resolution: u32,
+
+    /// Target cell size in world units, overriding --resolution
+    #[arg(short, long)]
+    cell_size: Option<f32>,
+
+    /// Mesher: mc (marching cubes), dc (dual contouring, keeps sharp edges) or
+    /// adaptive (octree dual contouring, fast at high resolutions)
+    #[arg(short, long, default_value = "mc", value_parser = parse_mesher)]
+    mesher: MeshAlgorithm,
+
+    /// Directory to write exported files into (created if missing)
+    #[arg(short, long, default_value = ".")]
+    out_dir: PathBuf,
+
+    /// Skip mesh optimization (vertex welding and cleanup)
+    #[arg(long)]
+    no_optimize: bool,
+
+    /// Export N variants of each script, seeded from --seed upward, as
+    /// <name>_seed<SEED>.<ext>
+    #[arg(long, value_name = "N")]
+    variants: Option<u64>,
+
+    #[command(flatten)]
+    script: ScriptOptions,
+}
+
+#[derive(Debug, Args)]
+struct SaveArgs {
+    /// Scripts, directories or glob patterns to save
+    #[arg(required = true, value_name = "SCRIPT")]
+    inputs: Vec<String>,
+
+    /// Directory to write scene files into (created if missing)
+    #[arg(short, long, default_value = ".")]
+    out_dir: PathBuf,
+
+    #[command(flatten)]
+    script: ScriptOptions,
+}
+
+#[derive(Debug, Args)]
+struct RenderArgs {
+    /// Scripts, saved scenes, directories or glob patterns to render
+    #[arg(required = true, value_name = "SCRIPT")]
+    inputs: Vec<String>,
+
+    /// Image size as WIDTHxHEIGHT
+    #[arg(short = 'S', long, default_value = "512x512", value_parser = parse_size)]
+    size: (u32, u32),
+
+    /// Renderer: auto (GPU if available, otherwise CPU), gpu or cpu
+    #[arg(short, long, default_value = "auto", value_parser = parse_backend)]
+    backend: RenderBackend,
+
+    /// Directory to write images into (created if missing)
+    #[arg(short, long, default_value = ".")]
+    out_dir: PathBuf,
+
+    /// Render an animation instead of a still: sequence (numbered PNGs),
+    /// gif, apng or sheet (sprite sheet)
+    #[arg(short, long, value_parser = parse_animation_format)]
+    format: Option<AnimationFormat>,
+
+    /// Frames to animate, as COUNT or START..END
+    #[arg(long, default_value = "36", value_parser = parse_frames)]
+    frames: (i64, i64),
+
+    /// Frames per second; frame N is at time N / FPS
+    #[arg(long, default_value_t = 12.0)]
+    fps: f64,
+
+    /// Orbit the camera a full turn over the frames (a GIF unless --format
+    /// says otherwise)
+    #[arg(short, long)]
+    turntable: bool,
+
+    /// Sprite sheet columns (default: as square as possible)
+    #[arg(long)]
+    columns: Option<u32>,
+
+    #[command(flatten)]
+    script: ScriptOptions,
+}
+
+/// How scripts are evaluated, shared by every command
+#[derive(Debug, Args)]
+struct ScriptOptions {
+    /// Extra directory to search for imported scripts (repeatable)
+    #[arg(short = 'L', long = "lib", value_name = "DIR")]
+    library_paths: Vec<PathBuf>,
+
+    /// Override a parameter declared with param() (repeatable)
+    #[arg(short, long = "param", value_name = "NAME=VALUE", value_parser = parse_param_override)]
+    params: Vec<(String, ParamValue)>,
+
+    /// Seed for the random numbers scripts draw with rand()
+    #[arg(short, long, default_value_t = 0)]
+    seed: u64,
+}
+
+/// A script (or input) that failed, with the reason
+struct Failure {
+    source: String,
+    message: String,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Export(args) => run_export(&args),
+        Command::Save(args) => run_save(&args),
+        Command::Render(args) => run_render(&args),
+    }
+}
+
+fn parse_format(s: &str) -> Result<ExportFormat, String> {
+    ExportFormat::from_extension(s)
+        .ok_or_else(|| format!("unknown format '{s}' (expected glb, gltf, obj or stl)"))
+}
+
+fn run_export(args: &ExportArgs) -> ExitCode {
+    run_batch(
+        &args.inputs,
+        &args.script,
+        &args.out_dir,
+        &format!(".{}", args.format.extension()),
+        "Exported",
+        |engine, script, output| export_script(engine, script, output, args),
+    )
+}
+
+fn run_save(args: &SaveArgs) -> ExitCode {
+    run_batch(
+        &args.inputs,
+        &args.script,
+        &args.out_dir,
+        SCENE_EXTENSION,
+        "Saved",
+        |engine, script, output| {
+            engine.load(script)?;
+            engine.save_scene(&output)?;
+            Ok(output.display().to_string())
+        },
+    )
+}
+
+fn run_render(args: &RenderArgs) -> ExitCode {
+    let (width, height) = args.size;
+    let options = RenderOptions::new(width, height).with_backend(args.backend);
+    let animation = args
+        .format
+        .or(args.turntable.then_some(AnimationFormat::Gif));
+    let extension = animation.map_or("png", |format| format.extension());
+
+    run_batch(
+        &args.inputs,
+        &args.script,
+        &args.out_dir,
+        &format!(".{extension}"),
+        "Rendered",
+        |engine, script, output| {
+            if let Some(format) = animation {
+                let (start, end) = args.frames;
+                let mut animation = AnimationOptions::new(output)
+                    .with_format(format)
+                    .with_frames(FrameRange::new(start, end, args.fps))
+                    .with_render(options.clone())
+                    .with_turntable(args.turntable);
+                animation.columns = args.columns;
+                return Ok(engine.render_animation(script, &animation)?.to_string());
+            }
+
+            engine.load(script)?;
+            let result = engine.render_to_file(&output, &options)?;
+            Ok(format!(
+                "Rendered {} ({width}x{height}, {})",
+                output.display(),
+                result.backend
+            ))
+        },
+    )
+}
+
+/// Run `process` on every input, writing `<out_dir>/<stem><extension>`
+///
+/// `process` returns a one-line description of what it wrote. Failures are
+/// collected and reported together at the end. Imports are looked up next
+/// to each script, then in the library paths, and every script gets the
+/// same parameter overrides and seed.
+fn run_batch(
+    inputs: &[String],
+    options: &ScriptOptions,
+    out_dir: &Path,
+    extension: &str,
+    verb: &str,
+    mut process: impl FnMut(&mut Engine, &Path, PathBuf) -> anyhow::Result<String>,
+) -> ExitCode {
+    let (scripts, input_errors) = expand_inputs(inputs);
+
+    let mut failures: Vec<Failure> = input_errors
+        .into_iter()
+        .map(|e| Failure {
+            source: e.input,
+            message: e.message,
+        })
+        .collect();
+
+    if let Err(e) = std::fs::create_dir_all(out_dir) {
+        eprintln!(
+            "error: failed to create output directory {}: {e}",
+            out_dir.display()
+        );
+        return ExitCode::FAILURE;
+    }
+
+    let mut engine = Engine::new();
+    for dir in &options.library_paths {
+        engine.scripting_mut().add_library_path(dir);
+    }
+    for (name, value) in &options.params {
+        engine.set_param(name.clone(), *value);
+    }
+    engine.set_seed(options.seed);
+    let mut outputs: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut done = 0usize;
+
+    for script in &scripts {
+        let output = output_path(out_dir, script, extension);
+
+        // Two scripts with the same name in different directories would
+        // silently overwrite each other - report it instead
+        if let Some(previous) = outputs.get(&output) {
+            failures.push(Failure {
+                source: script.display().to_string(),
+                message: format!(
+                    "output {} already written by {}",
+                    output.display(),
+                    previous.display()
+                ),
+            });
+            continue;
+        }
+        outputs.insert(output.clone(), script.clone());
+
+        match process(&mut engine, script, output) {
+            Ok(result) => {
+                done += 1;
+                println!("{} -> {}", script.display(), result);
+            }
+            Err(e) => {
+                eprintln!("{}: failed", script.display());
+                failures.push(Failure {
+                    source: script.display().to_string(),
+                    message: format!("{e:#}"),
+                });
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        println!("{verb} {done} script(s) to {}", out_dir.display());
+        return ExitCode::SUCCESS;
+    }
+
+    eprintln!();
+    eprintln!(
+        "{} failure(s), {done} of {} script(s) {}:",
+        failures.len(),
+        scripts.len(),
+        verb.to_lowercase()
+    );
+    for failure in &failures {
+        eprintln!();
+        eprintln!("  {}", failure.source);
+        for line in failure.message.lines() {
+            eprintln!("    {line}");
+        }
+    }
+
+    ExitCode::FAILURE
+}
+
+fn parse_size(s: &str) -> Result<(u32, u32), String> {
+    let size = s
+        .split_once(['x', 'X'])
+        .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)));
+    match size {
+        Some((width, height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(format!(
+            "invalid size '{s}' (expected WIDTHxHEIGHT, like 512x512)"
+        )),
+    }
+}
+
+fn parse_backend(s: &str) -> Result<RenderBackend, String> {
+    RenderBackend::from_name(s)
+        .ok_or_else(|| format!("unknown backend '{s}' (expected auto, gpu or cpu)"))
+}
+
+fn parse_animation_format(s: &str) -> Result<AnimationFormat, String> {
+    AnimationFormat::from_name(s)
+        .ok_or_else(|| format!("unknown format '{s}' (expected sequence, gif, apng or sheet)"))
+}
+
+/// Frame range as `COUNT` (frames `0..COUNT`) or `START..END`
+fn parse_frames(s: &str) -> Result<(i64, i64), String> {
+    let range = match s.split_once("..") {
+        Some((start, end)) => start.trim().parse().ok().zip(end.trim().parse().ok()),
+        None => s.trim().parse().ok().map(|count| (0, count)),
+    };
+    match range {
+        Some((start, end)) if end > start => Ok((start, end)),
+        _ => Err(format!(
+            "invalid frames '{s}' (expected a count like 36 or a range like 12..48)"
+        )),
+    }
+}
+
+fn parse_mesher(s: &str) -> Result<MeshAlgorithm, String> {
+    MeshAlgorithm::from_name(s)
+        .ok_or_else(|| format!("unknown mesher '{s}' (expected mc, dc or adaptive)"))
+}
+
+/// Evaluate a single script (or load a saved scene) and export it to
+/// `output`, or export its seeded variants next to `output`
+fn export_script(
+    engine: &mut Engine,
+    script: &Path,
+    output: PathBuf,
+    args: &ExportArgs,
+) -> anyhow::Result<String> {
+    let mut options = ExportOptions::new(output)
+        .with_format(args.format)
+        .with_resolution(args.resolution)
+        .with_algorithm(args.mesher)
+        .with_optimize(!args.no_optimize);
+    options.cell_size = args.cell_size;
+
+    let Some(count) = args.variants else {
+        engine.load(script)?;
+        return Ok(engine.export(&options)?.to_string());
+    };
+
+    if Scene::is_scene_file(script) {
+        anyhow::bail!("saved scenes have no seed to vary; --variants needs a script");
+    }
+    let first = args.script.seed;
+    let results = engine.export_variants(script, first..first.saturating_add(count), &options)?;
+    let lines: Vec<String> = results.iter().map(ExportResult::to_string).collect();
+    Ok(format!("{count} variant(s)\n  {}", lines.join("\n  ")))
+}
+
+/// Output file for a script: `<out_dir>/<script stem><extension>`
+///
+/// The stem of a saved scene drops the whole `.soyuz.json`.
+fn output_path(out_dir: &Path, script: &Path, extension: &str) -> PathBuf {
+    let name = script
+        .file_name()
+        .map_or_else(|| "scene".into(), |s| s.to_string_lossy());
+    let stem = name.strip_suffix(SCENE_EXTENSION).unwrap_or_else(|| {
+        script
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("scene")
+    });
+
+    out_dir.join(format!("{stem}{extension}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(parse_format("glb"), Ok(ExportFormat::Glb));
+        assert_eq!(parse_format("OBJ"), Ok(ExportFormat::Obj));
+        assert!(parse_format("fbx").is_err());
+    }
+
+    #[test]
+    fn test_parse_mesher() {
+        assert_eq!(parse_mesher("dc"), Ok(MeshAlgorithm::DualContouring));
+        assert_eq!(
+            parse_mesher("marching-cubes"),
+            Ok(MeshAlgorithm::MarchingCubes)
+        );
+        assert_eq!(parse_mesher("octree"), Ok(MeshAlgorithm::Adaptive));
+        assert!(parse_mesher("voxel").is_err());
+    }
+
+    #[test]
+    fn test_output_path() {
+        let out = output_path(
+            Path::new("build"),
+            Path::new("examples/barrel.rhai"),
+            ".stl",
+        );
+        assert_eq!(out, PathBuf::from("build/barrel.stl"));
+
+        let out = output_path(
+            Path::new("build"),
+            Path::new("baked/barrel.soyuz.json"),
+            ".glb",
+        );
+        assert_eq!(out, PathBuf::from("build/barrel.glb"));
+
+        let out = output_path(
+            Path::new("baked"),
+            Path::new("barrel.rhai"),
+            SCENE_EXTENSION,
+        );
+        assert_eq!(out, PathBuf::from("baked/barrel.soyuz.json"));
+    }
+
+    #[test]
+    fn test_cli_parses_export() {
+        let cli = Cli::try_parse_from([
+            "soyuz", "export", "a.rhai", "b/*.rhai", "-f", "obj", "-r", "128", "-o", "out",
+        ]);
+        let Ok(Cli {
+            command: Command::Export(args),
+        }) = cli
+        else {
+            panic!("export arguments should parse");
+        };
+
+        assert_eq!(args.inputs, vec!["a.rhai", "b/*.rhai"]);
+        assert_eq!(args.format, ExportFormat::Obj);
+        assert_eq!(args.resolution, 128);
+        assert_eq!(args.cell_size, None);
+        assert_eq!(args.mesher, MeshAlgorithm::MarchingCubes);
+        assert_eq!(args.out_dir, PathBuf::from("out"));
+        assert!(!args.no_optimize);
+        assert!(args.script.library_paths.is_empty());
+        assert!(args.script.params.is_empty());
+        assert_eq!(args.script.seed, 0);
+        assert_eq!(args.variants, None);
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("256x128"), Ok((256, 128)));
+        assert_eq!(parse_size("64X64"), Ok((64, 64)));
+        assert!(parse_size("256").is_err());
+        assert!(parse_size("0x64").is_err());
+    }
+
+    #[test]
+    fn test_cli_parses_render() {
+        let cli = Cli::try_parse_from([
+            "soyuz", "render", "props/", "--size", "256x128", "-b", "cpu", "-o", "thumbs",
+        ]);
+        let Ok(Cli {
+            command: Command::Render(args),
+        }) = cli
+        else {
+            panic!("render arguments should parse");
+        };
+
+        assert_eq!(args.size, (256, 128));
+        assert_eq!(args.backend, RenderBackend::Cpu);
+        assert_eq!(args.out_dir, PathBuf::from("thumbs"));
+    }
+
+    #[test]
+    fn test_parse_frames() {
+        assert_eq!(parse_frames("36"), Ok((0, 36)));
+        assert_eq!(parse_frames("12..48"), Ok((12, 48)));
+        assert_eq!(parse_frames("-5..5"), Ok((-5, 5)));
+        assert!(parse_frames("0").is_err());
+        assert!(parse_frames("10..10").is_err());
+        assert!(parse_frames("a..b").is_err());
+    }
+
+    #[test]
+    fn test_cli_parses_turntable() {
+        let cli = Cli::try_parse_from([
+            "soyuz",
+            "render",
+            "crate.rhai",
+            "--turntable",
+            "--format",
+            "sheet",
+            "--frames",
+            "24",
+            "--columns",
+            "6",
+        ]);
+        let Ok(Cli {
+            command: Command::Render(args),
+        }) = cli
+        else {
+            panic!("render arguments should parse");
+        };
+
+        assert!(args.turntable);
+        assert_eq!(args.format, Some(AnimationFormat::SpriteSheet));
+        assert_eq!(args.frames, (0, 24));
+        assert_eq!(args.columns, Some(6));
+        assert!((args.fps - 12.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_cli_parses_variants() {
+        let cli = Cli::try_parse_from([
+            "soyuz",
+            "export",
+            "rock.rhai",
+            "--variants",
+            "10",
+            "--seed",
+            "100",
+        ]);
+        let Ok(Cli {
+            command: Command::Export(args),
+        }) = cli
+        else {
+            panic!("export arguments should parse");
+        };
+        assert_eq!(args.variants, Some(10));
+        assert_eq!(args.script.seed, 100);
+
+        assert!(Cli::try_parse_from(["soyuz", "save", "rock.rhai", "--variants", "3"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parses_params() {
+        let cli = Cli::try_parse_from([
+            "soyuz",
+            "export",
+            "barrel.rhai",
+            "-p",
+            "radius=0.7",
+            "--param",
+            "staves=16",
+        ]);
+        let Ok(Cli {
+            command: Command::Export(args),
+        }) = cli
+        else {
+            panic!("export arguments should parse");
+        };
+
+        assert_eq!(
+            args.script.params,
+            vec![
+                ("radius".to_string(), ParamValue::Float(0.7)),
+                ("staves".to_string(), ParamValue::Int(16)),
+            ]
+        );
+
+        assert!(Cli::try_parse_from(["soyuz", "export", "a.rhai", "-p", "radius"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parses_save() {
+        let cli = Cli::try_parse_from([
+            "soyuz", "save", "props/", "-o", "baked", "-L", "parts", "--lib", "shared",
+        ]);
+        let Ok(Cli {
+            command: Command::Save(args),
+        }) = cli
+        else {
+            panic!("save arguments should parse");
+        };
+
+        assert_eq!(args.inputs, vec!["props/"]);
+        assert_eq!(args.out_dir, PathBuf::from("baked"));
+        assert_eq!(
+            args.script.library_paths,
+            vec![PathBuf::from("parts"), PathBuf::from("shared")]
+        );
+    }
+}
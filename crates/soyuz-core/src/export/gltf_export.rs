@@ -30,7 +30,7 @@ macro_rules! writeln_str {
 }
 use crate::material::{Material, MeshWithMaterial, RasterizedMaterial};
 use crate::mesh::Mesh;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Export options for GLTF
 #[derive(Debug, Clone)]
@@ -68,6 +68,10 @@ pub fn export_gltf_with_material(mesh_mat: &MeshWithMaterial, path: &Path) -> Re
     )
 }
 
+/// Most textures a glTF export writes: albedo, metallic-roughness, normal
+/// and emissive
+pub const MAX_GLTF_TEXTURES: usize = 4;
+
 /// Export a mesh with optional material and custom options
 pub fn export_gltf_with_options(
     mesh: &Mesh,
@@ -75,6 +79,17 @@ pub fn export_gltf_with_options(
     path: &Path,
     options: &GltfExportOptions,
 ) -> Result<()> {
+    export_gltf_files(mesh, material, path, options).map(|_| ())
+}
+
+/// Export a mesh like [`export_gltf_with_options`], returning every file
+/// written: `path` first, then a `.gltf`'s buffer and textures
+pub fn export_gltf_files(
+    mesh: &Mesh,
+    material: Option<&Material>,
+    path: &Path,
+    options: &GltfExportOptions,
+) -> Result<Vec<PathBuf>> {
     let is_glb = path.extension().is_some_and(|ext| ext == "glb");
 
     // Rasterize material if present
@@ -85,15 +100,26 @@ pub fn export_gltf_with_options(
     };
 
     // Build the GLTF structure
-    let gltf_data = build_gltf_data(mesh, material, rasterized.as_ref(), is_glb, options)?;
+    let gltf_data = build_gltf_data(mesh, material, rasterized.as_ref(), path, options)?;
 
     if is_glb {
         write_glb(path, &gltf_data)?;
+        Ok(vec![path.to_path_buf()])
     } else {
-        write_gltf_separate(path, &gltf_data)?;
+        write_gltf_separate(path, &gltf_data)
     }
+}
 
-    Ok(())
+/// Files a `.gltf` export at `path` writes next to it: the buffer, then
+/// `texture_count` textures
+///
+/// Pass [`MAX_GLTF_TEXTURES`] for every file an export could write.
+pub fn gltf_companion_files(path: &Path, texture_count: usize) -> Vec<PathBuf> {
+    let external = ExternalFiles::for_path(path, texture_count);
+    std::iter::once(external.bin)
+        .chain(external.textures)
+        .map(|name| path.with_file_name(name))
+        .collect()
 }
 
 /// All data needed for GLTF export
@@ -101,22 +127,82 @@ struct GltfData {
     json: String,
     mesh_buffer: Vec<u8>,
     texture_buffers: Vec<Vec<u8>>,
-    #[allow(dead_code)]
-    external_bin_uri: Option<String>,
+    /// Companion files of a `.gltf`; `None` when everything is embedded in a GLB
+    external: Option<ExternalFiles>,
+}
+
+/// File names of the buffer and textures written next to a `.gltf`
+///
+/// Names derive from the output file, so several exports into one directory
+/// keep their own companions.
+struct ExternalFiles {
+    bin: String,
+    textures: Vec<String>,
+}
+
+impl ExternalFiles {
+    fn for_path(path: &Path, texture_count: usize) -> Self {
+        let bin = path.with_extension("bin").file_name().map_or_else(
+            || "mesh.bin".to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        );
+        let stem = path
+            .file_stem()
+            .map_or_else(|| "mesh".to_string(), |s| s.to_string_lossy().into_owned());
+        let textures = (0..texture_count)
+            .map(|i| format!("{}_texture_{}.png", stem, i))
+            .collect();
+        Self { bin, textures }
+    }
+}
+
+/// Percent-encode a file name for use as a relative glTF URI
+fn uri_escape(name: &str) -> String {
+    use std::fmt::Write;
+
+    let mut out = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            out.push(byte as char);
+        } else {
+            write_str!(out, "%{:02X}", byte);
+        }
+    }
+    out
+}
+
+/// Byte layout of the mesh buffer
+///
+/// Buffer views map 1:1 to accessors: POSITION, NORMAL, TEXCOORD_0, optional
+/// COLOR_0, then one index view per primitive.
+struct MeshLayout {
+    vertex_count: usize,
+    min: [f32; 3],
+    max: [f32; 3],
+    /// (byte offset, byte length) of each buffer view
+    views: Vec<(usize, usize)>,
+    has_colors: bool,
+    /// Whether primitives are split by material ID
+    split_materials: bool,
+    /// (material ID, index count) of each primitive
+    primitives: Vec<(u32, usize)>,
+}
+
+impl MeshLayout {
+    /// Number of vertex attribute accessors before the index accessors
+    fn attribute_count(&self) -> usize {
+        if self.has_colors { 4 } else { 3 }
+    }
 }
 
 fn build_gltf_data(
     mesh: &Mesh,
     material: Option<&Material>,
     rasterized: Option<&RasterizedMaterial>,
-    is_glb: bool,
+    path: &Path,
     _options: &GltfExportOptions,
 ) -> Result<GltfData> {
-    // Calculate buffer sizes
-    let positions_size = mesh.vertices.len() * 12;
-    let normals_size = mesh.vertices.len() * 12;
-    let uvs_size = mesh.vertices.len() * 8;
-    let indices_size = mesh.indices.len() * 4;
+    let is_glb = path.extension().is_some_and(|ext| ext == "glb");
 
     // Calculate bounds
     let mut min = [f32::MAX; 3];
@@ -128,20 +214,41 @@ fn build_gltf_data(
         }
     }
 
-    // Build mesh buffer
-    let mut mesh_buffer =
-        Vec::with_capacity(positions_size + normals_size + uvs_size + indices_size);
+    // Build mesh buffer: vertex attributes, then indices for each primitive
+    let mut mesh_buffer = Vec::new();
+    let mut views = Vec::new();
+    let mut push_view = |bytes: &[u8]| {
+        views.push((mesh_buffer.len(), bytes.len()));
+        mesh_buffer.extend_from_slice(bytes);
+    };
 
-    for v in &mesh.vertices {
-        mesh_buffer.extend_from_slice(bytemuck::cast_slice(&v.position));
+    let positions: Vec<[f32; 3]> = mesh.vertices.iter().map(|v| v.position).collect();
+    let normals: Vec<[f32; 3]> = mesh.vertices.iter().map(|v| v.normal).collect();
+    let uvs: Vec<[f32; 2]> = mesh.vertices.iter().map(|v| v.uv).collect();
+    push_view(bytemuck::cast_slice(&positions));
+    push_view(bytemuck::cast_slice(&normals));
+    push_view(bytemuck::cast_slice(&uvs));
+    if let Some(colors) = &mesh.colors {
+        push_view(bytemuck::cast_slice(colors));
     }
-    for v in &mesh.vertices {
-        mesh_buffer.extend_from_slice(bytemuck::cast_slice(&v.normal));
-    }
-    for v in &mesh.vertices {
-        mesh_buffer.extend_from_slice(bytemuck::cast_slice(&v.uv));
+
+    let groups = mesh.split_by_material();
+    for (_, indices) in &groups {
+        push_view(bytemuck::cast_slice(indices));
     }
-    mesh_buffer.extend_from_slice(bytemuck::cast_slice(&mesh.indices));
+
+    let layout = MeshLayout {
+        vertex_count: mesh.vertices.len(),
+        min,
+        max,
+        views,
+        has_colors: mesh.colors.is_some(),
+        split_materials: mesh.material_ids.is_some(),
+        primitives: groups
+            .iter()
+            .map(|(id, indices)| (*id, indices.len()))
+            .collect(),
+    };
 
     // Build texture buffers if we have a rasterized material
     let mut texture_buffers = Vec::new();
@@ -171,31 +278,23 @@ fn build_gltf_data(
         }
     }
 
+    let external = (!is_glb).then(|| ExternalFiles::for_path(path, texture_buffers.len()));
+
     // Build JSON
     let json = build_gltf_json_with_material(
-        mesh.vertices.len(),
-        mesh.indices.len(),
+        &layout,
         mesh_buffer.len(),
-        &min,
-        &max,
-        positions_size,
-        normals_size,
-        uvs_size,
         material,
         &texture_info,
         &texture_buffers,
-        is_glb,
+        external.as_ref(),
     );
 
     Ok(GltfData {
         json,
         mesh_buffer,
         texture_buffers,
-        external_bin_uri: if is_glb {
-            None
-        } else {
-            Some("mesh.bin".to_string())
-        },
+        external,
     })
 }
 
@@ -251,53 +350,56 @@ fn write_glb(path: &Path, data: &GltfData) -> Result<()> {
     Ok(())
 }
 
-fn write_gltf_separate(path: &Path, data: &GltfData) -> Result<()> {
+/// Write a `.gltf` and its companion files, returning every file written
+fn write_gltf_separate(path: &Path, data: &GltfData) -> Result<Vec<PathBuf>> {
+    let external = data
+        .external
+        .as_ref()
+        .expect("separate glTF data names its companion files");
+
     // Write JSON file
     std::fs::write(path, &data.json)?;
 
+    let mut files = vec![path.to_path_buf()];
+
     // Write binary file
-    let bin_path = path.with_extension("bin");
+    let bin_path = path.with_file_name(&external.bin);
     std::fs::write(&bin_path, &data.mesh_buffer)?;
+    files.push(bin_path);
 
     // Write texture files
-    let parent = path.parent().unwrap_or(Path::new("."));
-    for (i, tex) in data.texture_buffers.iter().enumerate() {
-        let tex_path = parent.join(format!("texture_{}.png", i));
+    for (name, tex) in external.textures.iter().zip(&data.texture_buffers) {
+        let tex_path = path.with_file_name(name);
         std::fs::write(&tex_path, tex)?;
+        files.push(tex_path);
     }
 
-    Ok(())
+    Ok(files)
 }
 
 #[allow(clippy::needless_raw_string_hashes)] // Raw strings are more readable for JSON templates
 fn build_gltf_json_with_material(
-    vertex_count: usize,
-    index_count: usize,
+    layout: &MeshLayout,
     mesh_buffer_size: usize,
-    min: &[f32; 3],
-    max: &[f32; 3],
-    positions_size: usize,
-    normals_size: usize,
-    uvs_size: usize,
     material: Option<&Material>,
     texture_info: &[(&str, usize)],
     texture_buffers: &[Vec<u8>],
-    is_glb: bool,
+    external: Option<&ExternalFiles>,
 ) -> String {
     use std::fmt::Write;
 
     let mut json = String::new();
 
-    let positions_offset = 0;
-    let normals_offset = positions_size;
-    let uvs_offset = normals_offset + normals_size;
-    let indices_offset = uvs_offset + uvs_size;
-    let indices_size = index_count * 4;
-
-    // Calculate texture buffer offsets
+    // Calculate texture buffer offsets; external textures are separate
+    // files, so only a GLB stores them after the mesh data
+    let embedded_textures = if external.is_none() {
+        texture_buffers
+    } else {
+        &[]
+    };
     let mut texture_offsets = Vec::new();
     let mut current_offset = mesh_buffer_size;
-    for tex in texture_buffers {
+    for tex in embedded_textures {
         let padding = (4 - (current_offset % 4)) % 4;
         current_offset += padding;
         texture_offsets.push(current_offset);
@@ -305,6 +407,7 @@ fn build_gltf_json_with_material(
     }
 
     let total_buffer_size = current_offset;
+    let attribute_count = layout.attribute_count();
 
     // Start JSON
     writeln_str!(json, "{{");
@@ -316,81 +419,104 @@ fn build_gltf_json_with_material(
     writeln_str!(json, r#"  "scenes": [{{ "nodes": [0] }}],"#);
     writeln_str!(json, r#"  "nodes": [{{ "mesh": 0 }}],"#);
 
-    // Meshes
-    let material_idx = if material.is_some() {
-        r#", "material": 0"#
+    // Meshes: one primitive per material, all sharing the vertex attributes
+    let color_attribute = if layout.has_colors {
+        r#", "COLOR_0": 3"#
     } else {
         ""
     };
     writeln_str!(json, r#"  "meshes": [{{"#);
-    writeln_str!(json, r#"    "primitives": [{{"#);
-    writeln_str!(
-        json,
-        r#"      "attributes": {{ "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 }},"#
-    );
-    writeln_str!(json, r#"      "indices": 3{}"#, material_idx);
-    writeln_str!(json, r#"    }}]"#);
+    writeln_str!(json, r#"    "primitives": ["#);
+    for (i, _) in layout.primitives.iter().enumerate() {
+        let material_idx = if layout.split_materials {
+            format!(r#", "material": {}"#, i)
+        } else if material.is_some() {
+            r#", "material": 0"#.to_string()
+        } else {
+            String::new()
+        };
+        if i > 0 {
+            writeln_str!(json, ",");
+        }
+        writeln_str!(json, r#"      {{"#);
+        writeln_str!(
+            json,
+            r#"        "attributes": {{ "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2{} }},"#,
+            color_attribute
+        );
+        writeln_str!(
+            json,
+            r#"        "indices": {}{}"#,
+            attribute_count + i,
+            material_idx
+        );
+        write_str!(json, r#"      }}"#);
+    }
+    writeln_str!(json);
+    writeln_str!(json, r#"    ]"#);
     writeln_str!(json, r#"  }}],"#);
 
     // Accessors
     writeln_str!(json, r#"  "accessors": ["#);
-    writeln_str!(
-        json,
-        r#"    {{ "bufferView": 0, "componentType": 5126, "count": {}, "type": "VEC3", "min": [{}, {}, {}], "max": [{}, {}, {}] }},"#,
-        vertex_count,
-        min[0],
-        min[1],
-        min[2],
-        max[0],
-        max[1],
-        max[2]
-    );
-    writeln_str!(
+    write_str!(
         json,
-        r#"    {{ "bufferView": 1, "componentType": 5126, "count": {}, "type": "VEC3" }},"#,
-        vertex_count
+        r#"    {{ "bufferView": 0, "componentType": 5126, "count": {}, "type": "VEC3", "min": [{}, {}, {}], "max": [{}, {}, {}] }}"#,
+        layout.vertex_count,
+        layout.min[0],
+        layout.min[1],
+        layout.min[2],
+        layout.max[0],
+        layout.max[1],
+        layout.max[2]
     );
-    writeln_str!(
+    writeln_str!(json, ",");
+    write_str!(
         json,
-        r#"    {{ "bufferView": 2, "componentType": 5126, "count": {}, "type": "VEC2" }},"#,
-        vertex_count
+        r#"    {{ "bufferView": 1, "componentType": 5126, "count": {}, "type": "VEC3" }}"#,
+        layout.vertex_count
     );
-    writeln_str!(
+    writeln_str!(json, ",");
+    write_str!(
         json,
-        r#"    {{ "bufferView": 3, "componentType": 5125, "count": {}, "type": "SCALAR" }}"#,
-        index_count
+        r#"    {{ "bufferView": 2, "componentType": 5126, "count": {}, "type": "VEC2" }}"#,
+        layout.vertex_count
     );
+    if layout.has_colors {
+        writeln_str!(json, ",");
+        write_str!(
+            json,
+            r#"    {{ "bufferView": 3, "componentType": 5126, "count": {}, "type": "VEC4" }}"#,
+            layout.vertex_count
+        );
+    }
+    for (i, (_, index_count)) in layout.primitives.iter().enumerate() {
+        writeln_str!(json, ",");
+        write_str!(
+            json,
+            r#"    {{ "bufferView": {}, "componentType": 5125, "count": {}, "type": "SCALAR" }}"#,
+            attribute_count + i,
+            index_count
+        );
+    }
+    writeln_str!(json);
     writeln_str!(json, r#"  ],"#);
 
     // Buffer views
     writeln_str!(json, r#"  "bufferViews": ["#);
-    writeln_str!(
-        json,
-        r#"    {{ "buffer": 0, "byteOffset": {}, "byteLength": {} }},"#,
-        positions_offset,
-        positions_size
-    );
-    writeln_str!(
-        json,
-        r#"    {{ "buffer": 0, "byteOffset": {}, "byteLength": {} }},"#,
-        normals_offset,
-        normals_size
-    );
-    writeln_str!(
-        json,
-        r#"    {{ "buffer": 0, "byteOffset": {}, "byteLength": {} }},"#,
-        uvs_offset,
-        uvs_size
-    );
-    write_str!(
-        json,
-        r#"    {{ "buffer": 0, "byteOffset": {}, "byteLength": {} }}"#,
-        indices_offset,
-        indices_size
-    );
+    for (i, (offset, length)) in layout.views.iter().enumerate() {
+        if i > 0 {
+            writeln_str!(json, ",");
+        }
+        write_str!(
+            json,
+            r#"    {{ "buffer": 0, "byteOffset": {}, "byteLength": {} }}"#,
+            offset,
+            length
+        );
+    }
 
-    // Add buffer views for textures
-    for (offset, tex) in texture_offsets.iter().zip(texture_buffers.iter()) {
+    // Add buffer views for embedded textures
+    for (offset, tex) in texture_offsets.iter().zip(embedded_textures) {
         writeln_str!(json, ",");
         write_str!(
             json,
@@ -403,81 +529,26 @@ fn build_gltf_json_with_material(
     writeln_str!(json, r#"  ],"#);
 
     // Materials
-    if let Some(mat) = material {
-        writeln_str!(json, r#"  "materials": [{{"#);
-        writeln_str!(json, r#"    "pbrMetallicRoughness": {{"#);
-
-        // Base color
-        let base_color = mat.base_color_factor();
-        write_str!(
-            json,
-            r#"      "baseColorFactor": [{}, {}, {}, {}]"#,
-            base_color[0],
-            base_color[1],
-            base_color[2],
-            base_color[3]
-        );
-
-        // Base color texture
-        if texture_info
-            .iter()
-            .any(|(name, _)| *name == "baseColorTexture")
-        {
-            writeln_str!(json, ",");
-            write_str!(json, r#"      "baseColorTexture": {{ "index": 0 }}"#);
-        }
-
-        // Metallic-roughness texture
-        if texture_info
-            .iter()
-            .any(|(name, _)| *name == "metallicRoughnessTexture")
-        {
-            writeln_str!(json, ",");
-            write_str!(
-                json,
-                r#"      "metallicRoughnessTexture": {{ "index": 1 }}"#
+    if layout.split_materials {
+        writeln_str!(json, r#"  "materials": ["#);
+        for (i, (id, _)) in layout.primitives.iter().enumerate() {
+            if i > 0 {
+                writeln_str!(json, ",");
+            }
+            write_material_json(
+                &mut json,
+                Some(&format!("material_{}", id)),
+                material,
+                texture_info,
             );
         }
-
-        writeln_str!(json, ",");
-        writeln_str!(
-            json,
-            r#"      "metallicFactor": {},"#,
-            mat.metallic_factor()
-        );
-        writeln_str!(
-            json,
-            r#"      "roughnessFactor": {}"#,
-            mat.roughness_factor()
-        );
-        writeln_str!(json, r#"    }}"#);
-
-        // Normal texture
-        if texture_info
-            .iter()
-            .any(|(name, _)| *name == "normalTexture")
-        {
-            let idx = texture_info
-                .iter()
-                .position(|(name, _)| *name == "normalTexture")
-                .expect("normalTexture should exist after any() check");
-            writeln_str!(json, r#"    ,"normalTexture": {{ "index": {} }}"#, idx);
-        }
-
-        // Emissive
-        if texture_info
-            .iter()
-            .any(|(name, _)| *name == "emissiveTexture")
-        {
-            let idx = texture_info
-                .iter()
-                .position(|(name, _)| *name == "emissiveTexture")
-                .expect("emissiveTexture should exist after any() check");
-            writeln_str!(json, r#"    ,"emissiveTexture": {{ "index": {} }}"#, idx);
-            writeln_str!(json, r#"    ,"emissiveFactor": [1.0, 1.0, 1.0]"#);
-        }
-
-        writeln_str!(json, r#"  }}],"#);
+        writeln_str!(json);
+        writeln_str!(json, r#"  ],"#);
+    } else if material.is_some() {
+        writeln_str!(json, r#"  "materials": ["#);
+        write_material_json(&mut json, None, material, texture_info);
+        writeln_str!(json);
+        writeln_str!(json, r#"  ],"#);
     }
 
     // Textures and images
@@ -504,14 +575,18 @@ fn build_gltf_json_with_material(
             if i > 0 {
                 writeln_str!(json, ",");
             }
-            if is_glb {
+            if let Some(external) = external {
                 write_str!(
                     json,
-                    r#"    {{ "bufferView": {}, "mimeType": "image/png" }}"#,
-                    4 + i
+                    r#"    {{ "uri": "{}" }}"#,
+                    uri_escape(&external.textures[i])
                 );
             } else {
-                write_str!(json, r#"    {{ "uri": "texture_{}.png" }}"#, i);
+                write_str!(
+                    json,
+                    r#"    {{ "bufferView": {}, "mimeType": "image/png" }}"#,
+                    layout.views.len() + i
+                );
             }
         }
         writeln_str!(json);
@@ -519,17 +594,18 @@ fn build_gltf_json_with_material(
     }
 
     // Buffer
-    if is_glb {
+    if let Some(external) = external {
         writeln_str!(
             json,
-            r#"  "buffers": [{{ "byteLength": {} }}]"#,
-            total_buffer_size
+            r#"  "buffers": [{{ "uri": "{}", "byteLength": {} }}]"#,
+            uri_escape(&external.bin),
+            mesh_buffer_size
         );
     } else {
         writeln_str!(
             json,
-            r#"  "buffers": [{{ "uri": "mesh.bin", "byteLength": {} }}]"#,
-            mesh_buffer_size
+            r#"  "buffers": [{{ "byteLength": {} }}]"#,
+            total_buffer_size
         );
     }
 
@@ -538,6 +614,99 @@ fn build_gltf_json_with_material(
     json
 }
 
+/// Write a single material object (without trailing comma or newline)
+///
+/// Without a PBR material this is a plain white material, so vertex colors
+/// show through unchanged.
+#[allow(clippy::needless_raw_string_hashes)] // Raw strings are more readable for JSON templates
+fn write_material_json(
+    json: &mut String,
+    name: Option<&str>,
+    material: Option<&Material>,
+    texture_info: &[(&str, usize)],
+) {
+    use std::fmt::Write;
+
+    writeln_str!(json, r#"  {{"#);
+    if let Some(name) = name {
+        writeln_str!(json, r#"    "name": "{}","#, name);
+    }
+    writeln_str!(json, r#"    "pbrMetallicRoughness": {{"#);
+
+    let Some(mat) = material else {
+        writeln_str!(json, r#"      "baseColorFactor": [1.0, 1.0, 1.0, 1.0],"#);
+        writeln_str!(json, r#"      "metallicFactor": 0.0,"#);
+        writeln_str!(json, r#"      "roughnessFactor": 0.5"#);
+        writeln_str!(json, r#"    }}"#);
+        write_str!(json, r#"  }}"#);
+        return;
+    };
+
+    // Base color
+    let base_color = mat.base_color_factor();
+    write_str!(
+        json,
+        r#"      "baseColorFactor": [{}, {}, {}, {}]"#,
+        base_color[0],
+        base_color[1],
+        base_color[2],
+        base_color[3]
+    );
+
+    // Base color texture
+    if texture_info
+        .iter()
+        .any(|(name, _)| *name == "baseColorTexture")
+    {
+        writeln_str!(json, ",");
+        write_str!(json, r#"      "baseColorTexture": {{ "index": 0 }}"#);
+    }
+
+    // Metallic-roughness texture
+    if texture_info
+        .iter()
+        .any(|(name, _)| *name == "metallicRoughnessTexture")
+    {
+        writeln_str!(json, ",");
+        write_str!(
+            json,
+            r#"      "metallicRoughnessTexture": {{ "index": 1 }}"#
+        );
+    }
+
+    writeln_str!(json, ",");
+    writeln_str!(
+        json,
+        r#"      "metallicFactor": {},"#,
+        mat.metallic_factor()
+    );
+    writeln_str!(
+        json,
+        r#"      "roughnessFactor": {}"#,
+        mat.roughness_factor()
+    );
+    writeln_str!(json, r#"    }}"#);
+
+    // Normal texture
+    if let Some(idx) = texture_info
+        .iter()
+        .position(|(name, _)| *name == "normalTexture")
+    {
+        writeln_str!(json, r#"    ,"normalTexture": {{ "index": {} }}"#, idx);
+    }
+
+    // Emissive
+    if let Some(idx) = texture_info
+        .iter()
+        .position(|(name, _)| *name == "emissiveTexture")
+    {
+        writeln_str!(json, r#"    ,"emissiveTexture": {{ "index": {} }}"#, idx);
+        writeln_str!(json, r#"    ,"emissiveFactor": [1.0, 1.0, 1.0]"#);
+    }
+
+    write_str!(json, r#"  }}"#);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -552,6 +721,7 @@ mod tests {
                 Vertex::new(Vec3::new(0.0, 1.0, 0.0), Vec3::Y, Vec2::new(0.0, 1.0)),
             ],
             indices: vec![0, 1, 2],
+            ..Mesh::default()
         }
     }
 
@@ -580,4 +750,92 @@ mod tests {
         assert!(temp_path.exists());
         std::fs::remove_file(&temp_path).ok();
     }
+
+    #[test]
+    fn test_material_groups_become_primitives() {
+        let mut mesh = create_test_mesh();
+        mesh.vertices.extend_from_within(..);
+        mesh.indices.extend_from_slice(&[3, 4, 5]);
+        mesh.colors = Some(vec![[1.0, 0.0, 0.0, 1.0]; 6]);
+        mesh.material_ids = Some(vec![1, 1, 1, 2, 2, 2]);
+
+        let data = build_gltf_data(
+            &mesh,
+            None,
+            None,
+            Path::new("test.glb"),
+            &GltfExportOptions::default(),
+        )
+        .expect("gltf data");
+
+        assert!(data.json.contains(r#""COLOR_0": 3"#));
+        assert!(data.json.contains(r#""name": "material_1""#));
+        assert!(data.json.contains(r#""name": "material_2""#));
+        assert!(data.json.contains(r#""indices": 5, "material": 1"#));
+        // positions + normals + uvs + colors + 3 indices per primitive
+        assert_eq!(data.mesh_buffer.len(), 6 * (12 + 12 + 8 + 16) + 2 * 3 * 4);
+    }
+
+    #[test]
+    fn test_gltf_companion_files_match_uris() {
+        let dir = std::env::temp_dir().join(format!("soyuz_gltf_uris_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+
+        let material = Material::pbr().albedo_color(0.8, 0.2, 0.2);
+        let mesh_mat = MeshWithMaterial::new(create_test_mesh(), material);
+        let options = GltfExportOptions {
+            texture_size: 4,
+            ..GltfExportOptions::default()
+        };
+        for name in ["barrel.gltf", "crate.gltf"] {
+            let path = dir.join(name);
+            let files =
+                export_gltf_files(&mesh_mat.mesh, Some(&mesh_mat.material), &path, &options)
+                    .expect("export gltf");
+            assert_eq!(files[0], path);
+            // Albedo and metallic-roughness textures
+            assert_eq!(files[1..], gltf_companion_files(&path, 2));
+        }
+
+        for (name, stem) in [("barrel.gltf", "barrel"), ("crate.gltf", "crate")] {
+            let gltf = gltf::Gltf::open(dir.join(name)).expect("parse gltf");
+
+            let buffer = gltf.buffers().next().expect("buffer");
+            let gltf::buffer::Source::Uri(uri) = buffer.source() else {
+                panic!("buffer should be external");
+            };
+            assert_eq!(uri, format!("{stem}.bin"));
+            assert!(dir.join(uri).exists());
+
+            let image_uris: Vec<_> = gltf
+                .images()
+                .map(|image| match image.source() {
+                    gltf::image::Source::Uri { uri, .. } => uri.to_string(),
+                    gltf::image::Source::View { .. } => panic!("image should be external"),
+                })
+                .collect();
+            assert!(!image_uris.is_empty());
+
+            // Every view fits its buffer; textures live in their own files
+            let buffer_len = buffer.length();
+            assert_eq!(gltf.views().count(), gltf.accessors().count());
+            for view in gltf.views() {
+                assert_eq!(view.buffer().index(), 0);
+                assert!(view.offset() + view.length() <= buffer_len);
+            }
+            for uri in image_uris {
+                assert!(uri.starts_with(&format!("{stem}_texture_")), "{uri}");
+                assert!(dir.join(&uri).exists(), "{uri}");
+            }
+        }
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_uri_escape() {
+        assert_eq!(uri_escape("barrel_v2.bin"), "barrel_v2.bin");
+        assert_eq!(uri_escape("my barrel.bin"), "my%20barrel.bin");
+        assert_eq!(uri_escape("a\"b.bin"), "a%22b.bin");
+    }
 }
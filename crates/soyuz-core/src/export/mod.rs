@@ -11,7 +11,8 @@ use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 pub use gltf_export::{
-    GltfExportOptions, export_gltf, export_gltf_with_material, export_gltf_with_options,
+    GltfExportOptions, MAX_GLTF_TEXTURES, export_gltf, export_gltf_files,
+    export_gltf_with_material, export_gltf_with_options, gltf_companion_files,
 };
 pub use obj::export_obj;
 pub use stl::export_stl;
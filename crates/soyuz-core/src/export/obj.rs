@@ -1,4 +1,8 @@
 //! OBJ file export
+//!
+//! Vertex colors are written with the common `v x y z r g b` extension.
+//! Meshes with material IDs also get a `.mtl` library next to the OBJ, with
+//! one `usemtl` group per material.
 
 #![allow(clippy::uninlined_format_args)]
 
@@ -8,6 +12,9 @@ use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::Path;
 
+/// Diffuse color used for material groups without vertex colors
+const DEFAULT_DIFFUSE: [f32; 3] = [0.8, 0.8, 0.8];
+
 /// Export a mesh to OBJ format
 pub fn export_obj(mesh: &Mesh, path: &Path) -> Result<()> {
     let file = File::create(path)?;
@@ -19,13 +26,31 @@ pub fn export_obj(mesh: &Mesh, path: &Path) -> Result<()> {
     writeln!(writer, "# Triangles: {}", mesh.indices.len() / 3)?;
     writeln!(writer)?;
 
+    // Material library
+    if mesh.material_ids.is_some() {
+        let mtl_path = path.with_extension("mtl");
+        export_mtl(mesh, &mtl_path)?;
+
+        if let Some(name) = mtl_path.file_name() {
+            writeln!(writer, "mtllib {}", name.to_string_lossy())?;
+            writeln!(writer)?;
+        }
+    }
+
     // Vertices
-    for v in &mesh.vertices {
-        writeln!(
-            writer,
-            "v {} {} {}",
-            v.position[0], v.position[1], v.position[2]
-        )?;
+    for (i, v) in mesh.vertices.iter().enumerate() {
+        match mesh.colors.as_ref().and_then(|c| c.get(i)) {
+            Some(c) => writeln!(
+                writer,
+                "v {} {} {} {} {} {}",
+                v.position[0], v.position[1], v.position[2], c[0], c[1], c[2]
+            )?,
+            None => writeln!(
+                writer,
+                "v {} {} {}",
+                v.position[0], v.position[1], v.position[2]
+            )?,
+        }
     }
     writeln!(writer)?;
 
@@ -41,18 +66,99 @@ pub fn export_obj(mesh: &Mesh, path: &Path) -> Result<()> {
     }
     writeln!(writer)?;
 
-    // Faces (OBJ uses 1-based indexing)
-    for tri in mesh.indices.chunks(3) {
-        let i0 = tri[0] + 1;
-        let i1 = tri[1] + 1;
-        let i2 = tri[2] + 1;
-        writeln!(
-            writer,
-            "f {}/{}/{} {}/{}/{} {}/{}/{}",
-            i0, i0, i0, i1, i1, i1, i2, i2, i2
-        )?;
+    // Faces (OBJ uses 1-based indexing), grouped by material
+    for (id, indices) in mesh.split_by_material() {
+        if mesh.material_ids.is_some() {
+            writeln!(writer, "usemtl material_{}", id)?;
+        }
+
+        for tri in indices.chunks(3) {
+            let i0 = tri[0] + 1;
+            let i1 = tri[1] + 1;
+            let i2 = tri[2] + 1;
+            writeln!(
+                writer,
+                "f {}/{}/{} {}/{}/{} {}/{}/{}",
+                i0, i0, i0, i1, i1, i1, i2, i2, i2
+            )?;
+        }
     }
 
     writer.flush()?;
     Ok(())
 }
+
+/// Write the material library referenced by an OBJ with material groups
+fn export_mtl(mesh: &Mesh, path: &Path) -> Result<()> {
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+
+    writeln!(writer, "# Soyuz MTL Export")?;
+
+    let colors = mesh.material_colors();
+    for (id, _) in mesh.split_by_material() {
+        let diffuse = colors
+            .iter()
+            .find(|(color_id, _)| *color_id == id)
+            .map_or(DEFAULT_DIFFUSE, |(_, c)| [c[0], c[1], c[2]]);
+
+        writeln!(writer)?;
+        writeln!(writer, "newmtl material_{}", id)?;
+        writeln!(writer, "Kd {} {} {}", diffuse[0], diffuse[1], diffuse[2])?;
+        writeln!(writer, "Ka 0 0 0")?;
+        writeln!(writer, "Ks 0 0 0")?;
+        writeln!(writer, "d 1")?;
+        writeln!(writer, "illum 1")?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::mesh::Vertex;
+    use glam::{Vec2, Vec3};
+
+    #[test]
+    fn test_export_materials_writes_mtl() {
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let blue = [0.0, 0.0, 1.0, 1.0];
+        let mesh = Mesh {
+            vertices: [
+                Vec3::ZERO,
+                Vec3::X,
+                Vec3::Y,
+                Vec3::Z,
+                Vec3::ONE,
+                Vec3::NEG_X,
+            ]
+            .into_iter()
+            .map(|p| Vertex::new(p, Vec3::Z, Vec2::ZERO))
+            .collect(),
+            indices: vec![0, 1, 2, 3, 4, 5],
+            colors: Some(vec![red, red, red, blue, blue, blue]),
+            material_ids: Some(vec![1, 1, 1, 2, 2, 2]),
+        };
+
+        let dir = std::env::temp_dir().join(format!("soyuz_obj_mtl_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let obj_path = dir.join("colored.obj");
+        export_obj(&mesh, &obj_path).unwrap();
+
+        let obj = std::fs::read_to_string(&obj_path).unwrap();
+        assert!(obj.contains("mtllib colored.mtl"));
+        assert!(obj.contains("usemtl material_1"));
+        assert!(obj.contains("usemtl material_2"));
+        assert!(obj.contains("v 0 0 0 1 0 0"));
+
+        let mtl = std::fs::read_to_string(dir.join("colored.mtl")).unwrap();
+        assert!(mtl.contains("newmtl material_1"));
+        assert!(mtl.contains("Kd 1 0 0"));
+        assert!(mtl.contains("newmtl material_2"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
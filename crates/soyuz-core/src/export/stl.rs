@@ -100,6 +100,7 @@ pub fn export_stl(mesh: &Mesh, path: &Path) -> Result<()> {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
     use crate::mesh::Vertex;
@@ -130,6 +131,7 @@ mod tests {
                 },
             ],
             indices: vec![0, 1, 2],
+            ..Mesh::default()
         };
 
         let path = temp_path("triangle.stl");
@@ -175,6 +177,7 @@ mod tests {
                 // Bottom
                 4, 5, 1, 4, 1, 0,
             ],
+            ..Mesh::default()
         };
 
         let path = temp_path("cube.stl");
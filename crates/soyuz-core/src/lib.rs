@@ -27,6 +27,7 @@
 //! - **Precision**: All SDF operations use `f32` for GPU compatibility
 //! - **Coordinate system**: Right-handed, Y-up
 
+pub mod camera;
 pub mod export;
 pub mod material;
 pub mod mesh;
@@ -46,7 +47,9 @@ pub mod prelude {
     pub use crate::texture::{Texture, TextureExt, noise::*, pattern::*};
 
     // Mesh generation
-    pub use crate::mesh::{LodConfig, LodMesh, Mesh, MeshConfig, OptimizeConfig, Vertex};
+    pub use crate::mesh::{
+        LodConfig, LodMesh, Mesh, MeshAlgorithm, MeshConfig, OptimizeConfig, Vertex,
+    };
 
     // Materials
     pub use crate::material::{Material, MeshWithMaterial, PbrMaterial, RasterizedMaterial};
@@ -45,6 +45,20 @@ pub struct Material {
     pub emissive_strength: f32,
 }
 
+impl std::fmt::Debug for Material {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Material")
+            .field("albedo", &self.albedo)
+            .field("roughness", &self.roughness)
+            .field("metallic", &self.metallic)
+            .field("normal", &self.normal.as_ref().map(|_| "Texture"))
+            .field("ao", &self.ao)
+            .field("emissive", &self.emissive)
+            .field("emissive_strength", &self.emissive_strength)
+            .finish()
+    }
+}
+
 impl Default for Material {
     fn default() -> Self {
         Self {
@@ -247,6 +261,16 @@ pub enum MaterialChannel {
     Texture(Arc<dyn Texture>),
 }
 
+impl std::fmt::Debug for MaterialChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaterialChannel::Value(v) => f.debug_tuple("Value").field(v).finish(),
+            MaterialChannel::Color(c) => f.debug_tuple("Color").field(c).finish(),
+            MaterialChannel::Texture(_) => f.write_str("Texture"),
+        }
+    }
+}
+
 impl MaterialChannel {
     /// Sample this channel at given UV
     pub fn sample(&self, uv: glam::Vec2) -> [f32; 4] {
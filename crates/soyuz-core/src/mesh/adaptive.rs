@@ -0,0 +1,672 @@
+//! Adaptive octree mesher
+//!
+//! Dual contouring on an octree instead of a dense grid. Each node samples
+//! the SDF at its center: if the distance is larger than half the node's
+//! diagonal the surface can't pass through it, so the whole node is skipped
+//! as empty or solid without sampling anything inside. Only nodes near the
+//! surface are subdivided, down to a depth set by the mesh resolution, so the
+//! number of samples grows with the surface area rather than the volume.
+//!
+//! Leaves are then merged bottom-up wherever one vertex fits the tangent
+//! planes of all eight children within a tolerance, which turns flat and
+//! gently curved regions into a few large cells. Contouring follows the
+//! octree topology (Ju et al., "Dual Contouring of Hermite Data"): every
+//! minimal edge with a sign change becomes a quad joining the leaves around
+//! it, whatever their size, so the mesh stays closed across level changes.
+
+// Grid coordinates keep their usual one-letter names
+#![allow(clippy::many_single_char_names)]
+
+use super::dual_contouring::{Qef, cell_qef};
+use super::{Mesh, MeshConfig, Vertex, compute_gradient};
+use crate::sdf::Sdf;
+use crate::sdf::bounds::DISTANCE_MARGIN;
+use glam::{UVec3, Vec2, Vec3};
+use rayon::prelude::*;
+
+/// Nodes shallower than this build their children in parallel
+const PARALLEL_DEPTH: u32 = 4;
+
+/// Deepest octree allowed, i.e. at most 4096 cells along each axis
+const MAX_DEPTH: u32 = 12;
+
+/// An octree node
+///
+/// Children and corners are numbered `x << 2 | y << 1 | z`.
+enum Node {
+    /// No surface passes through the node
+    Empty { inside: bool },
+    /// A cell with a surface vertex
+    Leaf(Box<Leaf>),
+    /// Eight children
+    Internal(Vec<Node>),
+}
+
+struct Leaf {
+    /// Bit `i` is set when corner `i` is inside the surface
+    corners: u8,
+    depth: u32,
+    qef: Qef,
+    position: Vec3,
+    /// Index into the mesh's vertex buffer
+    index: u32,
+}
+
+impl Node {
+    fn is_empty(&self) -> bool {
+        matches!(self, Node::Empty { .. })
+    }
+
+    fn leaf(&self) -> Option<&Leaf> {
+        match self {
+            Node::Leaf(leaf) => Some(leaf),
+            _ => None,
+        }
+    }
+
+    /// Child `i`, or the node itself if it isn't subdivided
+    fn child(&self, i: usize) -> &Node {
+        match self {
+            Node::Internal(children) => &children[i],
+            _ => self,
+        }
+    }
+
+    /// Whether corner `i` of the node is inside the surface
+    ///
+    /// Only called on nodes that aren't subdivided.
+    fn corner_inside(&self, i: usize) -> bool {
+        match self {
+            Node::Empty { inside } => *inside,
+            Node::Leaf(leaf) => leaf.corners & (1 << i) != 0,
+            Node::Internal(children) => children[i].corner_inside(i),
+        }
+    }
+}
+
+/// Offset of corner or child `i`, in units of the node size
+fn corner_grid(i: usize) -> UVec3 {
+    UVec3::new(((i >> 2) & 1) as u32, ((i >> 1) & 1) as u32, (i & 1) as u32)
+}
+
+/// Octree depth giving at least `resolution` cells along an axis
+pub(super) fn depth_for_resolution(resolution: u32) -> u32 {
+    resolution
+        .max(2)
+        .next_power_of_two()
+        .trailing_zeros()
+        .min(MAX_DEPTH)
+}
+
+/// Generate a mesh from an SDF using an adaptive octree
+pub(super) fn generate<S: Sdf + ?Sized + Sync>(sdf: &S, config: &MeshConfig) -> Mesh {
+    let mut root = build_octree(sdf, config);
+
+    // === Vertices: one per leaf ===
+    let mut positions = Vec::new();
+    assign_indices(&mut root, &mut positions);
+
+    let mut mesh = Mesh::new();
+    mesh.vertices = positions
+        .par_iter()
+        .map(|&pos| {
+            let normal = if config.compute_normals {
+                compute_gradient(sdf, pos, 0.001)
+            } else {
+                Vec3::Y
+            };
+            Vertex::new(pos, normal, Vec2::ZERO)
+        })
+        .collect();
+
+    // === Faces: one quad per minimal edge with a sign change ===
+    cell_proc(&root, &mut mesh.indices);
+
+    mesh.generate_uvs_triplanar(1.0);
+
+    mesh
+}
+
+/// Build and simplify the octree for the configured bounds and resolution
+fn build_octree<S: Sdf + ?Sized + Sync>(sdf: &S, config: &MeshConfig) -> Node {
+    // The finest cells match the dense grid. The octree grows to the next
+    // power of two along the longest axis, so it also covers empty space
+    // past the far side of the bounds, which the distance test skips cheaply.
+    // Past the depth limit the cells grow instead.
+    let cells = config.grid_resolution();
+    let max_depth = depth_for_resolution(cells.max_element());
+    let excess = (cells.max_element() as f32 / (1u32 << max_depth) as f32).max(1.0);
+    let finest_cell = config.bounds.size() / cells.as_vec3() * excess;
+    let tolerance = f64::from(config.adaptive_tolerance * finest_cell.min_element());
+
+    let builder = Builder {
+        sdf,
+        iso: config.iso_level,
+        origin: config.bounds.min,
+        finest_cell,
+        max_depth,
+        max_error: tolerance * tolerance,
+    };
+    builder.build(UVec3::ZERO, 0)
+}
+
+struct Builder<'a, S: ?Sized> {
+    sdf: &'a S,
+    iso: f32,
+    origin: Vec3,
+    finest_cell: Vec3,
+    max_depth: u32,
+    /// Mean squared plane distance allowed when merging leaves
+    max_error: f64,
+}
+
+impl<S: Sdf + ?Sized + Sync> Builder<'_, S> {
+    /// Position of a point on the finest grid
+    ///
+    /// Working in grid coordinates gives neighboring leaves bit-identical
+    /// shared corners, so they always agree on the signs there.
+    fn point(&self, grid: UVec3) -> Vec3 {
+        self.origin + grid.as_vec3() * self.finest_cell
+    }
+
+    /// Build the subtree for the node whose minimum corner is `grid`
+    fn build(&self, grid: UVec3, depth: u32) -> Node {
+        let cells = 1 << (self.max_depth - depth);
+        let min = self.point(grid);
+        let size = self.finest_cell * cells as f32;
+
+        // A distance well beyond the half diagonal means the surface can't
+        // reach the node
+        let d = self.sdf.distance(min + size * 0.5) - self.iso;
+        if d.abs() > size.length() * 0.5 * DISTANCE_MARGIN {
+            return Node::Empty { inside: d < 0.0 };
+        }
+
+        if depth == self.max_depth {
+            return self.leaf(grid, depth);
+        }
+
+        let half = cells / 2;
+        let child = |i: usize| self.build(grid + corner_grid(i) * half, depth + 1);
+        let children: Vec<Node> = if depth < PARALLEL_DEPTH {
+            (0..8).into_par_iter().map(child).collect()
+        } else {
+            (0..8).map(child).collect()
+        };
+
+        self.simplify(children, min, size, depth)
+    }
+
+    /// Sample a cell at the maximum depth
+    fn leaf(&self, grid: UVec3, depth: u32) -> Node {
+        let values: [f32; 8] =
+            std::array::from_fn(|i| self.sdf.distance(self.point(grid + corner_grid(i))));
+
+        let mut corners = 0u8;
+        for (i, &v) in values.iter().enumerate() {
+            if v < self.iso {
+                corners |= 1 << i;
+            }
+        }
+        if corners == 0 || corners == 0xff {
+            return Node::Empty {
+                inside: corners != 0,
+            };
+        }
+
+        // The dual contouring helpers number corners `z << 2 | y << 1 | x`
+        let swapped: [f32; 8] = std::array::from_fn(|i| values[swap_xz(i)]);
+        let min = self.point(grid);
+        let qef = cell_qef(self.sdf, min, self.finest_cell, &swapped, self.iso);
+        let position = qef.solve(min, min + self.finest_cell);
+
+        Node::Leaf(Box::new(Leaf {
+            corners,
+            depth,
+            qef,
+            position,
+            index: 0,
+        }))
+    }
+
+    /// Collapse a node whose children are all empty, or all leaves that one
+    /// vertex can replace
+    fn simplify(&self, children: Vec<Node>, min: Vec3, size: Vec3, depth: u32) -> Node {
+        if children.iter().any(|c| matches!(c, Node::Internal(_))) {
+            return Node::Internal(children);
+        }
+
+        // Signs on the 3x3x3 lattice of child corners
+        let sign = |x: usize, y: usize, z: usize| {
+            let (cx, cy, cz) = (x.min(1), y.min(1), z.min(1));
+            let corner = ((x - cx) << 2) | ((y - cy) << 1) | (z - cz);
+            children[(cx << 2) | (cy << 1) | cz].corner_inside(corner)
+        };
+
+        let mut corners = 0u8;
+        for i in 0..8 {
+            if sign(2 * (i >> 2), 2 * ((i >> 1) & 1), 2 * (i & 1)) {
+                corners |= 1 << i;
+            }
+        }
+
+        if children.iter().all(Node::is_empty) {
+            if corners == 0 || corners == 0xff {
+                return Node::Empty {
+                    inside: corners != 0,
+                };
+            }
+            return Node::Internal(children);
+        }
+
+        // Merging a node whose corners agree would hide the surface inside
+        // it, and merging across an extra sign change on an edge or face
+        // would change the topology
+        if corners == 0 || corners == 0xff || !topology_preserved(sign) {
+            return Node::Internal(children);
+        }
+
+        let mut qef = Qef::default();
+        for leaf in children.iter().filter_map(Node::leaf) {
+            qef.merge(&leaf.qef);
+        }
+        let position = qef.solve(min, min + size);
+        if qef.error(position) > self.max_error * f64::from(qef.count()) {
+            return Node::Internal(children);
+        }
+
+        Node::Leaf(Box::new(Leaf {
+            corners,
+            depth,
+            qef,
+            position,
+            index: 0,
+        }))
+    }
+}
+
+/// Check that the child corner signs add no crossings the merged node lacks
+///
+/// Every edge whose endpoints agree must agree at its midpoint, and every
+/// face whose corners agree must agree at its center.
+fn topology_preserved(sign: impl Fn(usize, usize, usize) -> bool) -> bool {
+    // Lattice point with `u` and `v` on the two axes other than `axis`
+    let point = |axis: usize, w: usize, u: usize, v: usize| match axis {
+        0 => sign(w, u, v),
+        1 => sign(v, w, u),
+        _ => sign(u, v, w),
+    };
+
+    for axis in 0..3 {
+        // Edges along `axis`
+        for (u, v) in [(0, 0), (0, 2), (2, 0), (2, 2)] {
+            let start = point(axis, 0, u, v);
+            if start == point(axis, 2, u, v) && start != point(axis, 1, u, v) {
+                return false;
+            }
+        }
+
+        // Faces perpendicular to `axis`
+        for w in [0, 2] {
+            let first = point(axis, w, 0, 0);
+            let uniform = [(0, 2), (2, 0), (2, 2)]
+                .iter()
+                .all(|&(u, v)| point(axis, w, u, v) == first);
+            if uniform && point(axis, w, 1, 1) != first {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Swap bits 0 and 2 of a corner index
+fn swap_xz(i: usize) -> usize {
+    ((i & 1) << 2) | (i & 2) | ((i >> 2) & 1)
+}
+
+/// Give every leaf a vertex index, in depth-first order
+fn assign_indices(node: &mut Node, positions: &mut Vec<Vec3>) {
+    match node {
+        Node::Empty { .. } => {}
+        Node::Leaf(leaf) => {
+            leaf.index = positions.len() as u32;
+            positions.push(leaf.position);
+        }
+        Node::Internal(children) => {
+            for child in children {
+                assign_indices(child, positions);
+            }
+        }
+    }
+}
+
+// === Octree contouring ===
+//
+// The tables follow the reference implementation of octree dual contouring.
+// Edges 0-3 run along x, 4-7 along y and 8-11 along z.
+
+/// Corner pairs of the 12 cell edges
+const EDGE_CORNERS: [[usize; 2]; 12] = [
+    [0, 4],
+    [1, 5],
+    [2, 6],
+    [3, 7],
+    [0, 2],
+    [1, 3],
+    [4, 6],
+    [5, 7],
+    [0, 1],
+    [2, 3],
+    [4, 5],
+    [6, 7],
+];
+
+/// Child pairs sharing an internal face, with the face direction
+const CELL_PROC_FACES: [[usize; 3]; 12] = [
+    [0, 4, 0],
+    [1, 5, 0],
+    [2, 6, 0],
+    [3, 7, 0],
+    [0, 2, 1],
+    [4, 6, 1],
+    [1, 3, 1],
+    [5, 7, 1],
+    [0, 1, 2],
+    [2, 3, 2],
+    [4, 5, 2],
+    [6, 7, 2],
+];
+
+/// Child quadruples sharing an internal edge, with the edge direction
+const CELL_PROC_EDGES: [[usize; 5]; 6] = [
+    [0, 1, 2, 3, 0],
+    [4, 5, 6, 7, 0],
+    [0, 4, 1, 5, 1],
+    [2, 6, 3, 7, 1],
+    [0, 2, 4, 6, 2],
+    [1, 3, 5, 7, 2],
+];
+
+/// Child pairs across a face of each direction
+const FACE_PROC_FACES: [[[usize; 3]; 4]; 3] = [
+    [[4, 0, 0], [5, 1, 0], [6, 2, 0], [7, 3, 0]],
+    [[2, 0, 1], [6, 4, 1], [3, 1, 1], [7, 5, 1]],
+    [[1, 0, 2], [3, 2, 2], [5, 4, 2], [7, 6, 2]],
+];
+
+/// Edges inside a face: node order, four children and edge direction
+const FACE_PROC_EDGES: [[[usize; 6]; 4]; 3] = [
+    [
+        [1, 4, 0, 5, 1, 1],
+        [1, 6, 2, 7, 3, 1],
+        [0, 4, 6, 0, 2, 2],
+        [0, 5, 7, 1, 3, 2],
+    ],
+    [
+        [0, 2, 3, 0, 1, 0],
+        [0, 6, 7, 4, 5, 0],
+        [1, 2, 0, 6, 4, 2],
+        [1, 3, 1, 7, 5, 2],
+    ],
+    [
+        [1, 1, 0, 3, 2, 0],
+        [1, 5, 4, 7, 6, 0],
+        [0, 1, 5, 0, 4, 1],
+        [0, 3, 7, 2, 6, 1],
+    ],
+];
+
+/// Which of the four nodes around an edge each face edge belongs to
+const FACE_EDGE_ORDERS: [[usize; 4]; 2] = [[0, 0, 1, 1], [0, 1, 0, 1]];
+
+/// Children along the two halves of an edge, with its direction
+const EDGE_PROC_EDGES: [[[usize; 5]; 2]; 3] = [
+    [[3, 2, 1, 0, 0], [7, 6, 5, 4, 0]],
+    [[5, 1, 4, 0, 1], [7, 3, 6, 2, 1]],
+    [[6, 4, 2, 0, 2], [7, 5, 3, 1, 2]],
+];
+
+/// The edge each of the four nodes around an edge shares, per direction
+const PROCESS_EDGES: [[usize; 4]; 3] = [[3, 2, 1, 0], [7, 5, 6, 4], [11, 10, 9, 8]];
+
+fn cell_proc(node: &Node, indices: &mut Vec<u32>) {
+    let Node::Internal(children) = node else {
+        return;
+    };
+
+    for child in children {
+        cell_proc(child, indices);
+    }
+    for [a, b, dir] in CELL_PROC_FACES {
+        face_proc([&children[a], &children[b]], dir, indices);
+    }
+    for [a, b, c, d, dir] in CELL_PROC_EDGES {
+        edge_proc(
+            [&children[a], &children[b], &children[c], &children[d]],
+            dir,
+            indices,
+        );
+    }
+}
+
+fn face_proc(nodes: [&Node; 2], dir: usize, indices: &mut Vec<u32>) {
+    if nodes.iter().any(|n| n.is_empty()) {
+        return;
+    }
+    if nodes.iter().all(|n| n.leaf().is_some()) {
+        return;
+    }
+
+    for [a, b, next_dir] in FACE_PROC_FACES[dir] {
+        face_proc([nodes[0].child(a), nodes[1].child(b)], next_dir, indices);
+    }
+    for [order, a, b, c, d, next_dir] in FACE_PROC_EDGES[dir] {
+        let order = FACE_EDGE_ORDERS[order];
+        let edge_nodes = [
+            nodes[order[0]].child(a),
+            nodes[order[1]].child(b),
+            nodes[order[2]].child(c),
+            nodes[order[3]].child(d),
+        ];
+        edge_proc(edge_nodes, next_dir, indices);
+    }
+}
+
+fn edge_proc(nodes: [&Node; 4], dir: usize, indices: &mut Vec<u32>) {
+    if nodes.iter().any(|n| n.is_empty()) {
+        return;
+    }
+
+    if let [Some(a), Some(b), Some(c), Some(d)] = nodes.map(Node::leaf) {
+        process_edge([a, b, c, d], dir, indices);
+        return;
+    }
+
+    for [a, b, c, d, next_dir] in EDGE_PROC_EDGES[dir] {
+        let edge_nodes = [
+            nodes[0].child(a),
+            nodes[1].child(b),
+            nodes[2].child(c),
+            nodes[3].child(d),
+        ];
+        edge_proc(edge_nodes, next_dir, indices);
+    }
+}
+
+/// Emit the quad around an edge shared by four leaves
+///
+/// The signs come from the deepest leaf, whose edge is the minimal one.
+fn process_edge(leaves: [&Leaf; 4], dir: usize, indices: &mut Vec<u32>) {
+    let mut deepest = 0;
+    for (i, leaf) in leaves.iter().enumerate() {
+        if leaf.depth > leaves[deepest].depth {
+            deepest = i;
+        }
+    }
+
+    let [c1, c2] = EDGE_CORNERS[PROCESS_EDGES[dir][deepest]];
+    let corners = leaves[deepest].corners;
+    let start_inside = corners & (1 << c1) != 0;
+    if start_inside == (corners & (1 << c2) != 0) {
+        return;
+    }
+
+    // Leaves sit at (-,-), (-,+), (+,-), (+,+) in the plane across the edge,
+    // so this ring faces along the edge when the surface is crossed from
+    // inside to out
+    let [a, b, c, d] = leaves.map(|leaf| leaf.index);
+    let triangles = if start_inside {
+        [[a, d, b], [a, c, d]]
+    } else {
+        [[a, b, d], [a, d, c]]
+    };
+    for tri in triangles {
+        if tri[0] != tri[1] && tri[1] != tri[2] && tri[2] != tri[0] {
+            indices.extend(tri);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::mesh::{MeshAlgorithm, SdfToMesh};
+    use crate::sdf::primitives::{box3, sphere};
+    use crate::sdf::{Aabb, SdfExt};
+    use std::collections::{HashMap, HashSet};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn adaptive(resolution: u32) -> MeshConfig {
+        MeshConfig::default()
+            .with_resolution(resolution)
+            .with_bounds(Aabb::cube(1.0))
+            .with_algorithm(MeshAlgorithm::Adaptive)
+    }
+
+    /// Check that every edge is used once in each direction
+    fn assert_closed(mesh: &Mesh) {
+        let mut edges: HashMap<(u32, u32), i32> = HashMap::new();
+        for tri in mesh.indices.chunks(3) {
+            for k in 0..3 {
+                let (a, b) = (tri[k], tri[(k + 1) % 3]);
+                *edges.entry((a.min(b), a.max(b))).or_default() += if a < b { 1 } else { -1 };
+            }
+        }
+        assert!(edges.values().all(|&balance| balance == 0));
+    }
+
+    /// SDF wrapper counting its evaluations
+    struct Counted<S> {
+        inner: S,
+        calls: AtomicUsize,
+    }
+
+    impl<S: Sdf> Sdf for Counted<S> {
+        fn distance(&self, p: Vec3) -> f32 {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            self.inner.distance(p)
+        }
+
+        fn bounds(&self) -> Aabb {
+            self.inner.bounds()
+        }
+    }
+
+    #[test]
+    fn test_depth_for_resolution() {
+        assert_eq!(depth_for_resolution(64), 6);
+        assert_eq!(depth_for_resolution(100), 7);
+        assert_eq!(depth_for_resolution(1), 1);
+        assert_eq!(depth_for_resolution(100_000), MAX_DEPTH);
+    }
+
+    #[test]
+    fn test_sphere_is_closed_and_outward() {
+        let mesh = sphere(0.6)
+            .to_mesh(adaptive(32))
+            .expect("mesh generation should succeed");
+
+        assert!(mesh.triangle_count() > 0);
+        assert_closed(&mesh);
+        for tri in mesh.indices.chunks(3) {
+            let p: Vec<Vec3> = tri
+                .iter()
+                .map(|&i| Vec3::from_array(mesh.vertices[i as usize].position))
+                .collect();
+            let normal = (p[1] - p[0]).cross(p[2] - p[0]);
+            assert!(normal.dot(p[0] + p[1] + p[2]) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_flat_faces_use_fewer_triangles() {
+        let shape = box3(Vec3::new(0.6, 0.35, 0.45));
+        let dense = shape
+            .to_mesh(adaptive(64).with_algorithm(MeshAlgorithm::DualContouring))
+            .expect("mesh generation should succeed");
+        let mesh = shape
+            .to_mesh(adaptive(64))
+            .expect("mesh generation should succeed");
+
+        assert_closed(&mesh);
+        assert!(
+            mesh.triangle_count() * 4 < dense.triangle_count(),
+            "{} adaptive vs {} dense triangles",
+            mesh.triangle_count(),
+            dense.triangle_count()
+        );
+        for v in &mesh.vertices {
+            let d = shape.distance(Vec3::from_array(v.position));
+            assert!(d.abs() < 0.01, "vertex off the surface by {d}");
+        }
+    }
+
+    #[test]
+    fn test_level_transitions_are_crack_free() {
+        // A sphere on a box mixes large merged cells on the flat faces with
+        // fine cells along the curved seam
+        let shape = box3(Vec3::new(0.6, 0.2, 0.6)).union(sphere(0.35).translate(0.1, 0.25, 0.0));
+        let mesh = shape
+            .to_mesh(adaptive(64))
+            .expect("mesh generation should succeed");
+
+        let mut depths = HashSet::new();
+        collect_leaf_depths(&build_octree(&shape, &adaptive(64)), &mut depths);
+        assert!(depths.len() > 1, "expected leaves at several depths");
+
+        assert!(mesh.triangle_count() > 0);
+        assert_closed(&mesh);
+    }
+
+    fn collect_leaf_depths(node: &Node, depths: &mut HashSet<u32>) {
+        match node {
+            Node::Empty { .. } => {}
+            Node::Leaf(leaf) => {
+                depths.insert(leaf.depth);
+            }
+            Node::Internal(children) => {
+                for child in children {
+                    collect_leaf_depths(child, depths);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_samples_only_near_the_surface() {
+        let shape = Counted {
+            inner: sphere(0.5),
+            calls: AtomicUsize::new(0),
+        };
+        let mesh = shape
+            .to_mesh(adaptive(128))
+            .expect("mesh generation should succeed");
+        assert!(mesh.triangle_count() > 0);
+
+        // A dense grid would sample 129³ ≈ 2.1M points
+        let calls = shape.calls.load(Ordering::Relaxed);
+        assert!(calls < 1_000_000, "{calls} SDF evaluations");
+    }
+}
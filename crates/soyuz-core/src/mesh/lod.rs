@@ -208,7 +208,11 @@ mod tests {
             }
         }
 
-        Mesh { vertices, indices }
+        Mesh {
+            vertices,
+            indices,
+            ..Mesh::default()
+        }
     }
 
     #[test]
@@ -1,7 +1,233 @@
-//! Marching Cubes lookup tables
+//! Marching cubes mesher
 //!
-//! These tables are standard and define how to triangulate the 256 possible
-//! configurations of a cube's corners being inside/outside the isosurface.
+//! Places a vertex on every grid edge where the SDF changes sign and
+//! triangulates each cell from the standard lookup tables, which define how
+//! to connect those vertices for the 256 possible configurations of a cube's
+//! corners being inside/outside the isosurface.
+//!
+//! Each edge vertex is created once and shared by the (up to four) cells
+//! around the edge, so the output is an indexed, watertight mesh without a
+//! welding pass. The work is split into slabs one grid layer thick: vertices
+//! are numbered layer by layer, so any slab can work out the index of a
+//! vertex on its top or bottom layer without talking to its neighbours.
+
+use super::{Mesh, MeshConfig, Vertex, compute_gradient, grid_index, sample_grid};
+use crate::sdf::Sdf;
+use glam::{UVec3, Vec2, Vec3};
+use rayon::prelude::*;
+
+/// Marker for grid edges without a vertex
+const NO_VERTEX: u32 = u32::MAX;
+
+/// Cell edges as the grid edge they lie on: the offset of the edge's lower
+/// end from the cell's minimum corner, and the edge's axis
+///
+/// Matches the edge numbering of [`EDGE_TABLE`] and [`TRI_TABLE`], whose
+/// corners are numbered counter-clockwise around the bottom face, then the
+/// top face.
+const CELL_EDGES: [([usize; 3], usize); 12] = [
+    ([0, 0, 0], 0),
+    ([1, 0, 0], 1),
+    ([0, 1, 0], 0),
+    ([0, 0, 0], 1),
+    ([0, 0, 1], 0),
+    ([1, 0, 1], 1),
+    ([0, 1, 1], 0),
+    ([0, 0, 1], 1),
+    ([0, 0, 0], 2),
+    ([1, 0, 0], 2),
+    ([1, 1, 0], 2),
+    ([0, 1, 0], 2),
+];
+
+/// Cell corners in table order, as offsets from the cell's minimum corner
+const CELL_CORNERS: [[usize; 3]; 8] = [
+    [0, 0, 0],
+    [1, 0, 0],
+    [1, 1, 0],
+    [0, 1, 0],
+    [0, 0, 1],
+    [1, 0, 1],
+    [1, 1, 1],
+    [0, 1, 1],
+];
+
+/// Generate a mesh from an SDF using marching cubes
+pub(super) fn generate<S: Sdf + ?Sized + Sync>(sdf: &S, config: &MeshConfig) -> Mesh {
+    let cells = config.grid_resolution();
+    let grid = Grid {
+        values: sample_grid(sdf, &config.bounds, cells),
+        cells,
+        iso: config.iso_level,
+    };
+    let nz = cells.z as usize;
+
+    // === Phase 1: vertices on crossed edges, one grid layer per task ===
+    let layers: Vec<Vec<Vertex>> = (0..=nz)
+        .into_par_iter()
+        .map(|z| layer_vertices(sdf, &grid, config, z))
+        .collect();
+
+    // Vertices are numbered layer by layer, in the order of each layer's
+    // crossings
+    let mut first_vertex = Vec::with_capacity(layers.len());
+    let mut mesh = Mesh::new();
+    mesh.vertices.reserve(layers.iter().map(Vec::len).sum());
+    for layer in layers {
+        first_vertex.push(mesh.vertices.len() as u32);
+        mesh.vertices.extend(layer);
+    }
+
+    // === Phase 2: triangles, one slab of cells per task ===
+    let slabs: Vec<Vec<u32>> = (0..nz)
+        .into_par_iter()
+        .map(|z| {
+            let below = grid.edge_vertices(z, first_vertex[z]);
+            let above = grid.edge_vertices(z + 1, first_vertex[z + 1]);
+            slab_triangles(&grid, z, [&below, &above])
+        })
+        .collect();
+
+    mesh.indices.reserve(slabs.iter().map(Vec::len).sum());
+    for slab in slabs {
+        mesh.indices.extend(slab);
+    }
+
+    mesh.generate_uvs_triplanar(1.0);
+
+    mesh
+}
+
+/// Sampled SDF values with the grid they were sampled on
+struct Grid {
+    values: Vec<f32>,
+    cells: UVec3,
+    iso: f32,
+}
+
+impl Grid {
+    fn value(&self, [x, y, z]: [usize; 3]) -> f32 {
+        self.values[grid_index(self.cells, x, y, z)]
+    }
+
+    /// Visit the crossed edges owned by grid layer `z`, in vertex order
+    ///
+    /// Each grid point owns the edges leaving it along +X, +Y and +Z, so
+    /// layer `z` owns its in-plane edges and those rising to layer `z + 1`.
+    fn for_each_crossing(&self, z: usize, mut f: impl FnMut([usize; 3], usize)) {
+        let [nx, ny, nz] = self.cells.to_array().map(|n| n as usize);
+        let limits = [nx, ny, nz];
+        for y in 0..=ny {
+            for x in 0..=nx {
+                let point = [x, y, z];
+                let inside = self.value(point) < self.iso;
+                for axis in 0..3 {
+                    if point[axis] == limits[axis] {
+                        continue;
+                    }
+                    let mut end = point;
+                    end[axis] += 1;
+                    if (self.value(end) < self.iso) != inside {
+                        f(point, axis);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Vertex index of every edge owned by layer `z`, or [`NO_VERTEX`]
+    ///
+    /// Indexed by `(y * (nx + 1) + x) * 3 + axis`; `first` is the index of
+    /// the layer's first vertex.
+    fn edge_vertices(&self, z: usize, first: u32) -> Vec<u32> {
+        let (px, py) = (self.cells.x as usize + 1, self.cells.y as usize + 1);
+        let mut ids = vec![NO_VERTEX; px * py * 3];
+        let mut next = first;
+        self.for_each_crossing(z, |[x, y, _], axis| {
+            ids[(y * px + x) * 3 + axis] = next;
+            next += 1;
+        });
+        ids
+    }
+}
+
+/// Vertices on the crossed edges owned by grid layer `z`
+fn layer_vertices<S: Sdf + ?Sized>(
+    sdf: &S,
+    grid: &Grid,
+    config: &MeshConfig,
+    z: usize,
+) -> Vec<Vertex> {
+    let bounds = config.bounds;
+    let step = bounds.size() / grid.cells.as_vec3();
+    let mut vertices = Vec::new();
+
+    grid.for_each_crossing(z, |point, axis| {
+        let mut end = point;
+        end[axis] += 1;
+        let (v0, v1) = (grid.value(point), grid.value(end));
+
+        let t = if (v1 - v0).abs() > 0.00001 {
+            (grid.iso - v0) / (v1 - v0)
+        } else {
+            0.5
+        };
+
+        let mut offset = Vec3::from_array(point.map(|n| n as f32));
+        offset[axis] += t;
+        let pos = bounds.min + offset * step;
+
+        // Compute normal from SDF gradient
+        let normal = if config.compute_normals {
+            compute_gradient(sdf, pos, 0.001)
+        } else {
+            Vec3::Y
+        };
+        vertices.push(Vertex::new(pos, normal, Vec2::ZERO));
+    });
+
+    vertices
+}
+
+/// Triangles of the cells between grid layers `z` and `z + 1`
+///
+/// `layers` holds the edge vertex indices of those two layers, as returned
+/// by [`Grid::edge_vertices`].
+fn slab_triangles(grid: &Grid, z: usize, layers: [&[u32]; 2]) -> Vec<u32> {
+    let (nx, ny) = (grid.cells.x as usize, grid.cells.y as usize);
+    let px = nx + 1;
+    let mut indices = Vec::new();
+
+    for y in 0..ny {
+        for x in 0..nx {
+            // Determine cube index
+            let mut cube_index = 0usize;
+            for (i, [dx, dy, dz]) in CELL_CORNERS.into_iter().enumerate() {
+                if grid.value([x + dx, y + dy, z + dz]) < grid.iso {
+                    cube_index |= 1 << i;
+                }
+            }
+
+            // Every listed edge is crossed, so it has a vertex in its layer
+            let vertex = |edge: i8| {
+                let ([dx, dy, dz], axis) = CELL_EDGES[edge as usize];
+                let id = layers[dz][((y + dy) * px + x + dx) * 3 + axis];
+                debug_assert_ne!(id, NO_VERTEX, "edge {edge} of cell {x},{y},{z}");
+                id
+            };
+
+            // The table winds triangles clockwise seen from outside; reverse
+            // them to match the other meshers
+            let edges = &TRI_TABLE[cube_index];
+            let count = edges.iter().take_while(|&&e| e >= 0).count();
+            for tri in edges[..count].chunks_exact(3) {
+                indices.extend([vertex(tri[0]), vertex(tri[2]), vertex(tri[1])]);
+            }
+        }
+    }
+
+    indices
+}
 
 /// Edge table - which edges are intersected for each cube configuration
 pub const EDGE_TABLE: [u16; 256] = [
@@ -291,3 +517,78 @@ pub const TRI_TABLE: [[i8; 16]; 256] = [
         -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
     ],
 ];
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::mesh::{OptimizeConfig, SdfToMesh};
+    use crate::sdf::Aabb;
+    use crate::sdf::SdfExt;
+    use crate::sdf::primitives::{sphere, torus};
+    use std::collections::HashMap;
+
+    fn marching_cubes(resolution: u32) -> MeshConfig {
+        MeshConfig::default()
+            .with_resolution(resolution)
+            .with_bounds(Aabb::cube(1.0))
+    }
+
+    #[test]
+    fn test_sphere_is_closed_and_outward() {
+        let mesh = sphere(0.6)
+            .to_mesh(marching_cubes(16))
+            .expect("mesh generation should succeed");
+
+        // Every edge of a closed, consistently wound mesh is used once in
+        // each direction
+        let mut edges: HashMap<(u32, u32), i32> = HashMap::new();
+        for tri in mesh.indices.chunks(3) {
+            for k in 0..3 {
+                let (a, b) = (tri[k], tri[(k + 1) % 3]);
+                *edges.entry((a.min(b), a.max(b))).or_default() += if a < b { 1 } else { -1 };
+            }
+        }
+        assert!(edges.values().all(|&balance| balance == 0));
+
+        // Faces point away from the center
+        for tri in mesh.indices.chunks(3) {
+            let p: Vec<Vec3> = tri
+                .iter()
+                .map(|&i| Vec3::from_array(mesh.vertices[i as usize].position))
+                .collect();
+            let normal = (p[1] - p[0]).cross(p[2] - p[0]);
+            assert!(normal.dot(p[0] + p[1] + p[2]) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_vertices_are_shared() {
+        let mut mesh = torus(0.5, 0.2)
+            .rotate_x(0.4)
+            .to_mesh(marching_cubes(32))
+            .expect("mesh generation should succeed");
+
+        // A closed genus-1 surface has exactly half as many vertices as
+        // triangles (Euler characteristic 0)
+        assert_eq!(mesh.vertex_count() * 2, mesh.triangle_count());
+
+        // Nothing is left for welding to merge
+        let vertex_count = mesh.vertex_count();
+        mesh.weld_vertices(OptimizeConfig::default().weld_threshold);
+        assert_eq!(mesh.vertex_count(), vertex_count);
+    }
+
+    #[test]
+    fn test_normals_follow_gradient() {
+        let mesh = sphere(0.6)
+            .to_mesh(marching_cubes(16))
+            .expect("mesh generation should succeed");
+
+        for v in &mesh.vertices {
+            let p = Vec3::from_array(v.position);
+            let n = Vec3::from_array(v.normal);
+            assert!(n.dot(p.normalize()) > 0.999, "normal {n} at {p}");
+        }
+    }
+}
@@ -1,21 +1,22 @@
-//! Mesh generation from SDFs using Marching Cubes
+//! Mesh generation from SDFs using Marching Cubes, Dual Contouring or an
+//! adaptive octree
 //!
 //! Uses Rayon for parallel processing of voxel grids.
 
 // Builder pattern methods intentionally return Self without #[must_use]
 // Config is small and passed by value for ergonomics
-// Range loop is clearer for marching cubes corner indexing
 #![allow(clippy::return_self_not_must_use)]
 #![allow(clippy::needless_pass_by_value)]
-#![allow(clippy::needless_range_loop)]
 
+mod adaptive;
+mod dual_contouring;
 mod lod;
 mod marching_cubes;
 mod optimize;
 
-use crate::Result;
 use crate::sdf::{Aabb, Sdf};
-use glam::{Vec2, Vec3};
+use crate::{Error, Result};
+use glam::{UVec3, Vec2, Vec3};
 use rayon::prelude::*;
 
 pub use lod::{LodConfig, LodLevel, LodMesh};
@@ -43,10 +44,18 @@ impl Vertex {
 }
 
 /// A triangle mesh
+///
+/// Vertex colors and material IDs are optional per-vertex attributes stored
+/// parallel to `vertices`. They are filled in by [`Mesh::apply_vertex_materials`]
+/// for material-tagged SDFs and kept in sync by welding and decimation.
 #[derive(Debug, Clone, Default)]
 pub struct Mesh {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
+    /// Per-vertex linear RGBA colors
+    pub colors: Option<Vec<[f32; 4]>>,
+    /// Per-vertex material IDs (0 = default material)
+    pub material_ids: Option<Vec<u32>>,
 }
 
 impl Mesh {
@@ -64,6 +73,100 @@ impl Mesh {
         self.vertices.len()
     }
 
+    /// Check whether the mesh carries per-vertex colors
+    pub fn has_colors(&self) -> bool {
+        self.colors.is_some()
+    }
+
+    /// Assign a material ID and color to every vertex
+    ///
+    /// `sample` is called with each vertex position and returns the material
+    /// ID and linear RGBA color there. Sampling runs in parallel.
+    pub fn apply_vertex_materials<F>(&mut self, sample: F)
+    where
+        F: Fn(Vec3) -> (u32, [f32; 4]) + Sync,
+    {
+        let (ids, colors): (Vec<u32>, Vec<[f32; 4]>) = self
+            .vertices
+            .par_iter()
+            .map(|v| sample(Vec3::from_array(v.position)))
+            .unzip();
+
+        self.material_ids = Some(ids);
+        self.colors = Some(colors);
+    }
+
+    /// Material ID of a triangle
+    ///
+    /// Vertices on a material boundary can disagree, so the ID shared by at
+    /// least two corners wins (falling back to the first corner).
+    pub fn triangle_material(&self, triangle: usize) -> u32 {
+        let Some(ids) = &self.material_ids else {
+            return 0;
+        };
+        let tri = &self.indices[triangle * 3..triangle * 3 + 3];
+        let (a, b, c) = (
+            ids[tri[0] as usize],
+            ids[tri[1] as usize],
+            ids[tri[2] as usize],
+        );
+        if b == c { b } else { a }
+    }
+
+    /// Group triangle indices by material ID, sorted by ID
+    ///
+    /// Meshes without material IDs return a single group for material 0.
+    pub fn split_by_material(&self) -> Vec<(u32, Vec<u32>)> {
+        if self.material_ids.is_none() {
+            return vec![(0, self.indices.clone())];
+        }
+
+        let mut groups: std::collections::BTreeMap<u32, Vec<u32>> =
+            std::collections::BTreeMap::new();
+        for (t, tri) in self.indices.chunks(3).enumerate() {
+            groups
+                .entry(self.triangle_material(t))
+                .or_default()
+                .extend_from_slice(tri);
+        }
+        groups.into_iter().collect()
+    }
+
+    /// Average vertex color of each material, sorted by ID
+    ///
+    /// Used by formats that only support one color per material.
+    pub fn material_colors(&self) -> Vec<(u32, [f32; 4])> {
+        let Some(colors) = &self.colors else {
+            return Vec::new();
+        };
+
+        self.split_by_material()
+            .into_iter()
+            .map(|(id, indices)| {
+                let mut sum = [0.0f32; 4];
+                for &i in &indices {
+                    for (s, c) in sum.iter_mut().zip(colors[i as usize]) {
+                        *s += c;
+                    }
+                }
+                let n = indices.len().max(1) as f32;
+                (id, sum.map(|s| s / n))
+            })
+            .collect()
+    }
+
+    /// Keep only the per-vertex attributes of the given old vertex indices
+    ///
+    /// Called after vertices are merged or removed, with `kept[new] = old`.
+    pub(crate) fn retain_vertex_attributes(&mut self, kept: &[usize]) {
+        if let Some(colors) = &mut self.colors {
+            *colors = kept.iter().map(|&i| colors[i]).collect();
+        }
+        if let Some(ids) = &mut self.material_ids {
+            *ids = kept.iter().map(|&i| ids[i]).collect();
+        }
+    }
+
     /// Calculate face normals and smooth them
     pub fn recalculate_normals(&mut self) {
         // Reset normals
@@ -221,26 +324,74 @@ impl Mesh {
     }
 }
 
+/// Surface extraction algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MeshAlgorithm {
+    /// Marching cubes: fast and robust, but rounds off sharp edges and corners
+    #[default]
+    MarchingCubes,
+    /// Dual contouring: places vertices on sharp features using SDF gradients,
+    /// for hard-surface models
+    DualContouring,
+    /// Adaptive octree: dual contouring that samples only near the surface
+    /// and merges flat regions into larger cells, for fast high-detail exports
+    Adaptive,
+}
+
+impl MeshAlgorithm {
+    /// Short lowercase name, e.g. for command-line flags
+    pub fn name(&self) -> &'static str {
+        match self {
+            MeshAlgorithm::MarchingCubes => "marching-cubes",
+            MeshAlgorithm::DualContouring => "dual-contouring",
+            MeshAlgorithm::Adaptive => "adaptive",
+        }
+    }
+
+    /// Parse an algorithm from its name or abbreviation (`mc`, `dc`, `octree`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "marching-cubes" | "mc" => Some(MeshAlgorithm::MarchingCubes),
+            "dual-contouring" | "dc" => Some(MeshAlgorithm::DualContouring),
+            "adaptive" | "octree" => Some(MeshAlgorithm::Adaptive),
+            _ => None,
+        }
+    }
+}
+
 /// Configuration for mesh generation
 #[derive(Debug, Clone)]
 pub struct MeshConfig {
-    /// Grid resolution (number of cells along each axis)
+    /// Grid resolution (number of cells along the longest axis of the bounds)
+    ///
+    /// Shorter axes get proportionally fewer cells so cells stay cubic; see
+    /// [`MeshConfig::grid_resolution`].
     pub resolution: u32,
+    /// Target cell size in world units, overriding `resolution` when set
+    pub cell_size: Option<f32>,
     /// Bounding box to sample within
     pub bounds: Aabb,
     /// ISO level (distance value for surface extraction)
     pub iso_level: f32,
     /// Whether to compute normals from the SDF gradient
     pub compute_normals: bool,
+    /// Surface extraction algorithm
+    pub algorithm: MeshAlgorithm,
+    /// Largest RMS deviation, as a fraction of the finest cell size, allowed
+    /// when the adaptive mesher merges cells (0 merges only exactly flat ones)
+    pub adaptive_tolerance: f32,
 }
 
 impl Default for MeshConfig {
     fn default() -> Self {
         Self {
             resolution: 64,
+            cell_size: None,
             bounds: Aabb::cube(2.0),
             iso_level: 0.0,
             compute_normals: true,
+            algorithm: MeshAlgorithm::MarchingCubes,
+            adaptive_tolerance: 0.1,
         }
     }
 }
@@ -251,6 +402,11 @@ impl MeshConfig {
         self
     }
 
+    pub fn with_cell_size(mut self, cell_size: f32) -> Self {
+        self.cell_size = Some(cell_size);
+        self
+    }
+
     pub fn with_bounds(mut self, bounds: Aabb) -> Self {
         self.bounds = bounds;
         self
@@ -260,8 +416,58 @@ impl MeshConfig {
         self.iso_level = iso_level;
         self
     }
+
+    pub fn with_algorithm(mut self, algorithm: MeshAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn with_adaptive_tolerance(mut self, tolerance: f32) -> Self {
+        self.adaptive_tolerance = tolerance;
+        self
+    }
+
+    /// Number of cells along each axis of the bounds
+    ///
+    /// Derived from the target cell size (the longest axis divided by
+    /// `resolution` unless `cell_size` is set), rounded up per axis so every
+    /// axis has at least one cell. A long thin shape gets many cells along
+    /// its length and few across, all roughly cubic.
+    pub fn grid_resolution(&self) -> UVec3 {
+        let size = self.bounds.size();
+        let cell = self
+            .cell_size
+            .unwrap_or(size.max_element() / self.resolution.max(1) as f32);
+        if cell <= 0.0 || !cell.is_finite() {
+            return UVec3::splat(self.resolution.max(1));
+        }
+        (size / cell).ceil().max(Vec3::ONE).as_uvec3()
+    }
+
+    /// Check that the grid stays within [`MAX_GRID_CELLS`]
+    ///
+    /// A tiny `cell_size` or a huge `resolution` would otherwise allocate
+    /// samples for billions of cells before any meshing starts.
+    pub fn validate(&self) -> Result<()> {
+        let cells = self.grid_resolution();
+        let total = u64::from(cells.x)
+            .checked_mul(u64::from(cells.y))
+            .and_then(|n| n.checked_mul(u64::from(cells.z)));
+        match total {
+            Some(total) if total <= MAX_GRID_CELLS => Ok(()),
+            _ => Err(Error::InvalidParameter(format!(
+                "a {}x{}x{} mesh grid is over the limit of {MAX_GRID_CELLS} cells; \
+                 lower the resolution or use a larger cell size",
+                cells.x, cells.y, cells.z
+            ))),
+        }
+    }
 }
 
+/// Most cells a mesh grid may have: about 645 along each side of a cube,
+/// with around a gigabyte of distance samples
+pub const MAX_GRID_CELLS: u64 = 1 << 28;
+
 /// Extension trait to generate meshes from SDFs
 pub trait SdfToMesh: Sdf + Sync {
     /// Generate a mesh from this SDF
@@ -279,201 +485,133 @@ pub trait SdfToMesh: Sdf + Sync {
 
 impl<T: Sdf + Sync> SdfToMesh for T {}
 
-/// Generate a mesh from an SDF using marching cubes
+/// Generate a mesh from an SDF using the configured algorithm
 ///
 /// Uses Rayon for parallel processing:
 /// - Parallel SDF sampling to build the distance field
-/// - Parallel cell processing for marching cubes triangulation
+/// - Parallel vertex placement and triangulation in slabs of the grid
 pub fn generate_mesh<S: Sdf + ?Sized + Sync>(sdf: &S, config: MeshConfig) -> Result<Mesh> {
-    let res = config.resolution;
-    let bounds = config.bounds;
-    let size = bounds.size();
-    let step = size / res as f32;
-    let grid_size = (res + 1) as usize;
-
-    // === Phase 1: Parallel SDF sampling ===
-    // Create all grid point indices
-    let total_points = grid_size * grid_size * grid_size;
-    let values: Vec<f32> = (0..total_points)
+    config.validate()?;
+    match config.algorithm {
+        MeshAlgorithm::MarchingCubes => Ok(marching_cubes::generate(sdf, &config)),
+        MeshAlgorithm::DualContouring => Ok(dual_contouring::generate(sdf, &config)),
+        MeshAlgorithm::Adaptive => Ok(adaptive::generate(sdf, &config)),
+    }
+}
+
+/// Sample the SDF at every point of a grid with `cells` cells spanning `bounds`
+///
+/// The grid has `cells + 1` points along each axis. Values are stored
+/// x-fastest; use [`grid_index`] to look them up.
+fn sample_grid<S: Sdf + ?Sized + Sync>(sdf: &S, bounds: &Aabb, cells: UVec3) -> Vec<f32> {
+    let step = bounds.size() / cells.as_vec3();
+    let [px, py, pz] = (cells + 1).to_array().map(|n| n as usize);
+
+    (0..px * py * pz)
         .into_par_iter()
         .map(|idx| {
-            let x = idx % grid_size;
-            let y = (idx / grid_size) % grid_size;
-            let z = idx / (grid_size * grid_size);
+            let x = idx % px;
+            let y = (idx / px) % py;
+            let z = idx / (px * py);
             let p = bounds.min + Vec3::new(x as f32, y as f32, z as f32) * step;
             sdf.distance(p)
         })
-        .collect();
-
-    // === Phase 2: Parallel marching cubes ===
-    // Process cells in parallel, each cell produces local triangles
-    let total_cells = (res * res * res) as usize;
-
-    let cell_results: Vec<CellTriangles> = (0..total_cells)
-        .into_par_iter()
-        .filter_map(|cell_idx| {
-            let x = (cell_idx % res as usize) as u32;
-            let y = ((cell_idx / res as usize) % res as usize) as u32;
-            let z = (cell_idx / (res * res) as usize) as u32;
-
-            process_cell(x, y, z, &values, grid_size, &bounds, step, &config, sdf)
-        })
-        .collect();
-
-    // === Phase 3: Merge results ===
-    let mut mesh = Mesh::new();
-
-    // Pre-calculate total size for efficiency
-    let total_verts: usize = cell_results.iter().map(|c| c.vertices.len()).sum();
-    let total_indices: usize = cell_results.iter().map(|c| c.indices.len()).sum();
-
-    mesh.vertices.reserve(total_verts);
-    mesh.indices.reserve(total_indices);
-
-    for cell in cell_results {
-        let base_idx = mesh.vertices.len() as u32;
-        mesh.vertices.extend(cell.vertices);
-        mesh.indices
-            .extend(cell.indices.iter().map(|&i| i + base_idx));
-    }
-
-    // Generate UVs
-    mesh.generate_uvs_triplanar(1.0);
-
-    Ok(mesh)
+        .collect()
 }
 
-/// Triangles generated by a single cell
-struct CellTriangles {
-    vertices: Vec<Vertex>,
-    indices: Vec<u32>,
+/// Index of grid point `(x, y, z)` in the output of [`sample_grid`]
+fn grid_index(cells: UVec3, x: usize, y: usize, z: usize) -> usize {
+    let (px, py) = (cells.x as usize + 1, cells.y as usize + 1);
+    (z * py + y) * px + x
 }
 
-/// Process a single marching cubes cell
-fn process_cell<S: Sdf + ?Sized>(
-    x: u32,
-    y: u32,
-    z: u32,
-    values: &[f32],
-    grid_size: usize,
-    bounds: &Aabb,
-    step: Vec3,
-    config: &MeshConfig,
-    sdf: &S,
-) -> Option<CellTriangles> {
-    let corner_positions = [
-        bounds.min + Vec3::new(x as f32, y as f32, z as f32) * step,
-        bounds.min + Vec3::new((x + 1) as f32, y as f32, z as f32) * step,
-        bounds.min + Vec3::new((x + 1) as f32, (y + 1) as f32, z as f32) * step,
-        bounds.min + Vec3::new(x as f32, (y + 1) as f32, z as f32) * step,
-        bounds.min + Vec3::new(x as f32, y as f32, (z + 1) as f32) * step,
-        bounds.min + Vec3::new((x + 1) as f32, y as f32, (z + 1) as f32) * step,
-        bounds.min + Vec3::new((x + 1) as f32, (y + 1) as f32, (z + 1) as f32) * step,
-        bounds.min + Vec3::new(x as f32, (y + 1) as f32, (z + 1) as f32) * step,
-    ];
-
-    let corner_values = [
-        values[(z as usize * grid_size + y as usize) * grid_size + x as usize],
-        values[(z as usize * grid_size + y as usize) * grid_size + (x + 1) as usize],
-        values[(z as usize * grid_size + (y + 1) as usize) * grid_size + (x + 1) as usize],
-        values[(z as usize * grid_size + (y + 1) as usize) * grid_size + x as usize],
-        values[((z + 1) as usize * grid_size + y as usize) * grid_size + x as usize],
-        values[((z + 1) as usize * grid_size + y as usize) * grid_size + (x + 1) as usize],
-        values[((z + 1) as usize * grid_size + (y + 1) as usize) * grid_size + (x + 1) as usize],
-        values[((z + 1) as usize * grid_size + (y + 1) as usize) * grid_size + x as usize],
-    ];
-
-    // Determine cube index
-    let mut cube_index = 0u8;
-    for i in 0..8 {
-        if corner_values[i] < config.iso_level {
-            cube_index |= 1 << i;
-        }
-    }
+/// Compute the gradient (normal) of an SDF at a point
+fn compute_gradient<S: Sdf + ?Sized>(sdf: &S, p: Vec3, eps: f32) -> Vec3 {
+    let dx = sdf.distance(p + Vec3::X * eps) - sdf.distance(p - Vec3::X * eps);
+    let dy = sdf.distance(p + Vec3::Y * eps) - sdf.distance(p - Vec3::Y * eps);
+    let dz = sdf.distance(p + Vec3::Z * eps) - sdf.distance(p - Vec3::Z * eps);
+    Vec3::new(dx, dy, dz).normalize_or_zero()
+}
 
-    // Skip if entirely inside or outside
-    if cube_index == 0 || cube_index == 255 {
-        return None;
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::sdf::primitives::box3;
+
+    #[test]
+    fn test_grid_resolution_keeps_cells_cubic() {
+        let config = MeshConfig::default()
+            .with_resolution(100)
+            .with_bounds(Aabb::new(Vec3::ZERO, Vec3::new(4.0, 0.5, 1.0)));
+        assert_eq!(config.grid_resolution(), UVec3::new(100, 13, 25));
+
+        let config = config.with_cell_size(0.1);
+        assert_eq!(config.grid_resolution(), UVec3::new(40, 5, 10));
+
+        // Flat bounds still get one cell across
+        let config =
+            MeshConfig::default().with_bounds(Aabb::new(Vec3::ZERO, Vec3::new(1.0, 0.0, 1.0)));
+        assert_eq!(config.grid_resolution().y, 1);
     }
 
-    // Get edge flags
-    let edge_flags = EDGE_TABLE[cube_index as usize];
-
-    // Interpolate vertices on edges
-    let mut edge_vertices = [Vec3::ZERO; 12];
-
-    for edge in 0..12 {
-        if (edge_flags & (1 << edge)) != 0 {
-            let (v0, v1) = EDGE_CORNERS[edge];
-            let p0 = corner_positions[v0];
-            let p1 = corner_positions[v1];
-            let val0 = corner_values[v0];
-            let val1 = corner_values[v1];
-
-            let t = if (val1 - val0).abs() > 0.00001 {
-                (config.iso_level - val0) / (val1 - val0)
-            } else {
-                0.5
-            };
-
-            edge_vertices[edge] = p0.lerp(p1, t);
+    #[test]
+    fn test_oversized_grids_are_rejected() {
+        let shape = box3(Vec3::splat(0.5));
+        assert!(
+            MeshConfig::default()
+                .with_resolution(512)
+                .validate()
+                .is_ok()
+        );
+
+        for config in [
+            MeshConfig::default().with_cell_size(1e-6),
+            MeshConfig::default().with_resolution(u32::MAX),
+        ] {
+            let err = shape.to_mesh(config).expect_err("grid should be too large");
+            assert!(
+                err.to_string().contains(&MAX_GRID_CELLS.to_string()),
+                "{err}"
+            );
         }
     }
 
-    // Generate triangles for this cell
-    let mut vertices = Vec::new();
-    let mut indices = Vec::new();
-
-    let tri_indices = &TRI_TABLE[cube_index as usize];
-    let mut i = 0;
-    while tri_indices[i] != -1 {
-        let base_idx = vertices.len() as u32;
-
-        for j in 0..3 {
-            let edge_idx = tri_indices[i + j] as usize;
-            let pos = edge_vertices[edge_idx];
-
-            // Compute normal from SDF gradient
-            let normal = if config.compute_normals {
-                compute_gradient(sdf, pos, 0.001)
-            } else {
-                Vec3::Y
-            };
-
-            vertices.push(Vertex::new(pos, normal, Vec2::ZERO));
+    #[test]
+    fn test_long_shapes_mesh_with_every_algorithm() {
+        let shape = box3(Vec3::new(1.5, 0.1, 0.1));
+        let bounds = Aabb::new(Vec3::new(-1.6, -0.2, -0.2), Vec3::new(1.6, 0.2, 0.2));
+
+        for algorithm in [
+            MeshAlgorithm::MarchingCubes,
+            MeshAlgorithm::DualContouring,
+            MeshAlgorithm::Adaptive,
+        ] {
+            let mesh = shape
+                .to_mesh(
+                    MeshConfig::default()
+                        .with_resolution(64)
+                        .with_bounds(bounds)
+                        .with_algorithm(algorithm),
+                )
+                .expect("mesh generation should succeed");
+
+            // Cubic cells of 0.05 resolve the thin sides as well as the long one
+            let (min, max) = mesh.vertices.iter().fold(
+                (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+                |(min, max), v| {
+                    let p = Vec3::from_array(v.position);
+                    (min.min(p), max.max(p))
+                },
+            );
+            assert!(
+                min.abs_diff_eq(Vec3::new(-1.5, -0.1, -0.1), 0.03),
+                "{algorithm:?}: {min}"
+            );
+            assert!(
+                max.abs_diff_eq(Vec3::new(1.5, 0.1, 0.1), 0.03),
+                "{algorithm:?}: {max}"
+            );
         }
-
-        // Wind triangles correctly
-        indices.push(base_idx);
-        indices.push(base_idx + 1);
-        indices.push(base_idx + 2);
-
-        i += 3;
     }
-
-    Some(CellTriangles { vertices, indices })
-}
-
-/// Compute the gradient (normal) of an SDF at a point
-fn compute_gradient<S: Sdf + ?Sized>(sdf: &S, p: Vec3, eps: f32) -> Vec3 {
-    let dx = sdf.distance(p + Vec3::X * eps) - sdf.distance(p - Vec3::X * eps);
-    let dy = sdf.distance(p + Vec3::Y * eps) - sdf.distance(p - Vec3::Y * eps);
-    let dz = sdf.distance(p + Vec3::Z * eps) - sdf.distance(p - Vec3::Z * eps);
-    Vec3::new(dx, dy, dz).normalize_or_zero()
 }
-
-/// Edge to corner vertex mapping for marching cubes
-const EDGE_CORNERS: [(usize, usize); 12] = [
-    (0, 1),
-    (1, 2),
-    (2, 3),
-    (3, 0),
-    (4, 5),
-    (5, 6),
-    (6, 7),
-    (7, 4),
-    (0, 4),
-    (1, 5),
-    (2, 6),
-    (3, 7),
-];
@@ -105,6 +105,7 @@ impl Mesh {
         // Map from old index to new index
         let mut index_map: Vec<u32> = (0..self.vertices.len() as u32).collect();
         let mut new_vertices: Vec<Vertex> = Vec::with_capacity(self.vertices.len());
+        let mut kept: Vec<usize> = Vec::with_capacity(self.vertices.len());
         let mut vertex_remap: Vec<Option<u32>> = vec![None; self.vertices.len()];
 
         for i in 0..self.vertices.len() {
@@ -151,6 +152,7 @@ impl Mesh {
             } else {
                 let new_idx = new_vertices.len() as u32;
                 new_vertices.push(*v);
+                kept.push(i);
                 vertex_remap[i] = Some(new_idx);
                 index_map[i] = new_idx;
             }
@@ -171,6 +173,7 @@ impl Mesh {
 
         self.vertices = new_vertices;
         self.indices = new_indices;
+        self.retain_vertex_attributes(&kept);
     }
 
     /// Decimate mesh using edge collapse with quadric error metric
@@ -367,12 +370,14 @@ impl Mesh {
         }
 
         let mut new_vertices = Vec::new();
+        let mut kept = Vec::new();
         let mut index_map = vec![0u32; self.vertices.len()];
 
         for (i, v) in self.vertices.iter().enumerate() {
             if used[i] {
                 index_map[i] = new_vertices.len() as u32;
                 new_vertices.push(*v);
+                kept.push(i);
             }
         }
 
@@ -381,6 +386,7 @@ impl Mesh {
         }
 
         self.vertices = new_vertices;
+        self.retain_vertex_attributes(&kept);
     }
 
     /// Smooth normals based on angle threshold
@@ -454,6 +460,7 @@ mod tests {
                 Vertex::new(Vec3::new(1.0, 0.0, 0.0), Vec3::Y, Vec2::ZERO),
             ],
             indices: vec![0, 1, 2],
+            ..Mesh::default()
         };
 
         mesh.weld_vertices(0.001);
@@ -461,6 +468,27 @@ mod tests {
         assert_eq!(mesh.vertices.len(), 2); // Two unique vertices
     }
 
+    #[test]
+    fn test_weld_keeps_vertex_attributes() {
+        let mut mesh = Mesh {
+            vertices: vec![
+                Vertex::new(Vec3::new(0.0, 0.0, 0.0), Vec3::Y, Vec2::ZERO),
+                Vertex::new(Vec3::new(1.0, 0.0, 0.0), Vec3::Y, Vec2::ZERO),
+                Vertex::new(Vec3::new(0.00001, 0.0, 0.0), Vec3::Y, Vec2::ZERO), // Should weld
+                Vertex::new(Vec3::new(0.0, 1.0, 0.0), Vec3::Y, Vec2::ZERO),
+            ],
+            indices: vec![0, 1, 3, 2, 1, 3],
+            colors: Some(vec![[1.0, 0.0, 0.0, 1.0]; 4]),
+            material_ids: Some(vec![1, 2, 1, 3]),
+        };
+
+        mesh.weld_vertices(0.001);
+
+        assert_eq!(mesh.vertices.len(), 3);
+        assert_eq!(mesh.material_ids, Some(vec![1, 2, 3]));
+        assert_eq!(mesh.colors.as_ref().map(Vec::len), Some(3));
+    }
+
     #[test]
     fn test_remove_unused() {
         let mut mesh = Mesh {
@@ -471,6 +499,7 @@ mod tests {
                 Vertex::new(Vec3::new(99.0, 99.0, 99.0), Vec3::Y, Vec2::ZERO), // Unused
             ],
             indices: vec![0, 1, 2],
+            ..Mesh::default()
         };
 
         mesh.remove_unused_vertices();
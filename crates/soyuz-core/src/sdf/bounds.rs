@@ -0,0 +1,114 @@
+//! Sampling-based bounds refinement
+//!
+//! Analytic bounds have to be conservative, so rotations, twists and smooth
+//! blends leave them noticeably larger than the shape. [`refine_bounds`]
+//! shrinks such a box to the region the surface actually passes through by
+//! sampling the SDF on a sparse octree: a cell whose center distance exceeds
+//! its half diagonal can't contain the surface, so only cells near the
+//! surface are subdivided.
+
+use super::{Aabb, Sdf};
+use glam::Vec3;
+
+/// How far beyond a cell's half diagonal the center distance must be before
+/// the cell is treated as not containing the surface
+///
+/// Exact SDFs would allow 1.0, but twists, bends and displacements
+/// overestimate distances, and skipping a cell they reach would clip the
+/// surface.
+pub(crate) const DISTANCE_MARGIN: f32 = 1.5;
+
+/// Subdivision depth of the refinement octree (128 cells on the longest axis)
+const REFINE_DEPTH: u32 = 7;
+
+/// Shrink `bounds` to the cells the surface of `sdf` can pass through
+///
+/// The result stays inside `bounds` and is at most a couple of cells
+/// (1/128 of the longest axis each) larger than the zero-set. If no part of
+/// the surface lies inside `bounds`, they are returned unchanged.
+pub fn refine_bounds<S: Sdf + ?Sized>(sdf: &S, bounds: Aabb) -> Aabb {
+    // Cubic cells keep the distance test equally sharp along every axis
+    let root = Aabb::new(
+        bounds.min,
+        bounds.min + Vec3::splat(bounds.size().max_element()),
+    );
+
+    let mut found = None;
+    refine_node(sdf, &bounds, root, REFINE_DEPTH, &mut found);
+    found.map_or(bounds, |b: Aabb| b.intersection(&bounds))
+}
+
+fn refine_node<S: Sdf + ?Sized>(
+    sdf: &S,
+    bounds: &Aabb,
+    node: Aabb,
+    depth: u32,
+    found: &mut Option<Aabb>,
+) {
+    // Nothing to learn from cells outside the bounds or inside what the
+    // surface already covers
+    if node.intersection(bounds).size().min_element() < 0.0
+        || found.is_some_and(|f| f.contains(&node))
+    {
+        return;
+    }
+
+    let d = sdf.distance(node.center());
+    if d.abs() > node.size().length() * 0.5 * DISTANCE_MARGIN {
+        return;
+    }
+
+    if depth == 0 {
+        *found = Some(found.map_or(node, |f| f.union(&node)));
+        return;
+    }
+
+    let half = node.size() * 0.5;
+    // The corners of the first octant are the minimum corners of all eight
+    for child_min in Aabb::new(node.min, node.min + half).corners() {
+        refine_node(
+            sdf,
+            bounds,
+            Aabb::new(child_min, child_min + half),
+            depth - 1,
+            found,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdf::SdfExt;
+    use crate::sdf::primitives::{box3, sphere};
+
+    #[test]
+    fn test_refine_shrinks_loose_bounds() {
+        let shape = sphere(0.5).translate(1.0, 0.0, 0.0);
+        let refined = refine_bounds(&shape, Aabb::cube(4.0));
+
+        let cell = 8.0 / 128.0;
+        let expected = Aabb::new(Vec3::new(0.5, -0.5, -0.5), Vec3::new(1.5, 0.5, 0.5));
+        assert!(refined.contains(&expected), "{refined:?} clips the sphere");
+        assert!(
+            expected.expand(3.0 * cell).contains(&refined),
+            "{refined:?} is loose"
+        );
+    }
+
+    #[test]
+    fn test_refine_stays_inside_bounds() {
+        let bounds = Aabb::new(Vec3::new(-0.2, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let refined = refine_bounds(&box3(Vec3::splat(0.5)), bounds);
+        assert!(bounds.contains(&refined));
+        assert!((refined.min.x + 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_refine_without_surface_keeps_bounds() {
+        let bounds = Aabb::cube(1.0);
+        let refined = refine_bounds(&sphere(0.5).translate(10.0, 0.0, 0.0), bounds);
+        assert_eq!(refined.min, bounds.min);
+        assert_eq!(refined.max, bounds.max);
+    }
+}
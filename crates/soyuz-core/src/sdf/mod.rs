@@ -21,6 +21,7 @@
 // Aabb methods return modified copy, not Self builder pattern
 #![allow(clippy::return_self_not_must_use)]
 
+pub mod bounds;
 pub mod operations;
 pub mod primitives;
 pub mod transforms;
@@ -66,6 +67,30 @@ impl Aabb {
         Self::new(center - half_extents, center + half_extents)
     }
 
+    /// A box covering all of space, for unbounded shapes like planes
+    pub fn infinite() -> Self {
+        Self::new(Vec3::NEG_INFINITY, Vec3::INFINITY)
+    }
+
+    /// Smallest box containing all the points
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self {
+        let empty = Self::new(Vec3::INFINITY, Vec3::NEG_INFINITY);
+        points
+            .into_iter()
+            .fold(empty, |b, p| Self::new(b.min.min(p), b.max.max(p)))
+    }
+
+    /// The 8 corners, corner `i` taking `max` on axis `k` when bit `k` is set
+    pub fn corners(&self) -> [Vec3; 8] {
+        std::array::from_fn(|i| {
+            Vec3::select(
+                glam::BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0),
+                self.max,
+                self.min,
+            )
+        })
+    }
+
     /// Expand the bounding box by a margin
     pub fn expand(&self, margin: f32) -> Self {
         Self::new(
@@ -79,6 +104,23 @@ impl Aabb {
         Self::new(self.min.min(other.min), self.max.max(other.max))
     }
 
+    /// Overlap of two bounding boxes
+    ///
+    /// Disjoint boxes give an empty box with `min > max` on some axis.
+    pub fn intersection(&self, other: &Aabb) -> Self {
+        Self::new(self.min.max(other.min), self.max.min(other.max))
+    }
+
+    /// Whether every coordinate is finite
+    pub fn is_finite(&self) -> bool {
+        self.min.is_finite() && self.max.is_finite()
+    }
+
+    /// Whether `other` lies entirely inside this box
+    pub fn contains(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.min).all() && self.max.cmpge(other.max).all()
+    }
+
     /// Get the size of the bounding box
     pub fn size(&self) -> Vec3 {
         self.max - self.min
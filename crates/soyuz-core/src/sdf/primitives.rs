@@ -526,37 +526,7 @@ pub fn mandelbulb(power: f32, iterations: u32) -> Mandelbulb {
 
 impl Sdf for Mandelbulb {
     fn distance(&self, p: Vec3) -> f32 {
-        let mut z = p;
-        let mut dr = 1.0f32;
-        let mut r = 0.0f32;
-
-        for _ in 0..self.iterations {
-            r = z.length();
-            if r > 2.0 {
-                break;
-            }
-
-            // Convert to spherical coordinates
-            let theta = (z.z / r).acos();
-            let phi = z.y.atan2(z.x);
-            dr = r.powf(self.power - 1.0) * self.power * dr + 1.0;
-
-            // Scale and rotate
-            let zr = r.powf(self.power);
-            let theta = theta * self.power;
-            let phi = phi * self.power;
-
-            // Convert back to cartesian
-            z = zr
-                * Vec3::new(
-                    theta.sin() * phi.cos(),
-                    theta.sin() * phi.sin(),
-                    theta.cos(),
-                );
-            z += p;
-        }
-
-        0.5 * r.ln() * r / dr
+        soyuz_math::sd_mandelbulb(p, self.power, self.iterations as f32)
     }
 
     fn bounds(&self) -> Aabb {
@@ -584,19 +554,7 @@ pub fn menger(iterations: u32) -> MengerSponge {
 
 impl Sdf for MengerSponge {
     fn distance(&self, p: Vec3) -> f32 {
-        let mut d = Box3::new(Vec3::splat(self.size)).distance(p);
-        let mut s = 1.0f32;
-
-        for _ in 0..self.iterations {
-            let a = (p * s).rem_euclid(Vec3::splat(2.0)) - Vec3::ONE;
-            s *= 3.0;
-            let r = Vec3::ONE - a.abs() * 3.0;
-
-            let c = cross_sdf(r) / s;
-            d = d.max(c);
-        }
-
-        d
+        soyuz_math::sd_menger_sponge(p, self.size, self.iterations as f32)
     }
 
     fn bounds(&self) -> Aabb {
@@ -604,14 +562,6 @@ impl Sdf for MengerSponge {
     }
 }
 
-// Helper for Menger sponge
-fn cross_sdf(p: Vec3) -> f32 {
-    let inf_cross_x = p.y.abs().max(p.z.abs());
-    let inf_cross_y = p.x.abs().max(p.z.abs());
-    let inf_cross_z = p.x.abs().max(p.y.abs());
-    inf_cross_x.min(inf_cross_y).min(inf_cross_z)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,6 +81,11 @@ pub trait TextureExt: Texture + Sized + 'static {
         ops::Warp::new(self, warper, amount)
     }
 
+    /// Map values onto a color ramp from `low` (at 0) to `high` (at 1)
+    fn colorize(self, low: [f32; 3], high: [f32; 3]) -> ops::Colorize<Self> {
+        ops::Colorize::new(self, low, high)
+    }
+
     /// Generate an image
     fn to_image(&self, size: u32) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
         let mut img = ImageBuffer::new(size, size);
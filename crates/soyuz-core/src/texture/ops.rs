@@ -355,3 +355,32 @@ impl<T: Texture> Texture for Abs<T> {
         self.inner.sample(uv).abs()
     }
 }
+
+/// Map grayscale values onto a color ramp between two colors
+pub struct Colorize<T: Texture> {
+    inner: T,
+    low: [f32; 3],
+    high: [f32; 3],
+}
+
+impl<T: Texture> Colorize<T> {
+    pub fn new(inner: T, low: [f32; 3], high: [f32; 3]) -> Self {
+        Self { inner, low, high }
+    }
+}
+
+impl<T: Texture> Texture for Colorize<T> {
+    fn sample(&self, uv: Vec2) -> f32 {
+        self.inner.sample(uv)
+    }
+
+    fn sample_color(&self, uv: Vec2) -> [f32; 4] {
+        let t = self.inner.sample(uv).clamp(0.0, 1.0);
+        [
+            self.low[0] + (self.high[0] - self.low[0]) * t,
+            self.low[1] + (self.high[1] - self.low[1]) * t,
+            self.low[2] + (self.high[2] - self.low[2]) * t,
+            1.0,
+        ]
+    }
+}
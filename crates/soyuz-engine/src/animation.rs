@@ -0,0 +1,677 @@
+//! Rendering animations and turntables
+//!
+//! Scripts read the `time` and `frame` variables to change shape over an
+//! animation. The engine evaluates a script once for each frame of a
+//! [`FrameRange`] (only once if it never reads the time) and renders every
+//! frame. Turntables also orbit the camera a full turn around the scene over
+//! the range. Frames are written as a numbered PNG sequence, an animated GIF
+//! or APNG, or a sprite sheet. Each frame is written as soon as it renders,
+//! so only sprite sheets hold the whole animation in memory.
+
+use crate::render::{RenderBackend, RenderOptions, frame_scene, render_views};
+use crate::scene::Scene;
+use anyhow::{Context, Result};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+use soyuz_core::camera::Camera;
+use std::fmt;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// GIF quantizer speed, from 1 (best, slowest) to 30
+const GIF_SPEED: i32 = 10;
+
+/// Most frames a range may have, a bit over an hour at 24 fps
+///
+/// Every frame's scene is kept in memory while an animation is written.
+pub const MAX_FRAMES: usize = 100_000;
+
+/// Most pixels a sprite sheet may have, 256 MiB of RGBA
+///
+/// Sheets are the one format built in memory before they are written.
+pub const MAX_SHEET_PIXELS: u64 = 8192 * 8192;
+
+/// Views of a still scene rendered from one renderer setup at a time
+const RENDER_BATCH: usize = 16;
+
+/// Frames to evaluate, and how fast they play
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameRange {
+    /// First frame
+    pub start: i64,
+
+    /// Frame after the last one
+    pub end: i64,
+
+    /// Frames per second; frame `n` is at `time = n / fps`
+    pub fps: f64,
+}
+
+impl Default for FrameRange {
+    fn default() -> Self {
+        Self::new(0, 36, 12.0)
+    }
+}
+
+impl FrameRange {
+    /// Frames `start..end` at `fps` frames per second
+    pub fn new(start: i64, end: i64, fps: f64) -> Self {
+        Self { start, end, fps }
+    }
+
+    /// Number of frames
+    pub fn len(&self) -> usize {
+        usize::try_from(self.end.saturating_sub(self.start)).unwrap_or(0)
+    }
+
+    /// Whether the range has no frames
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Frame numbers in order
+    pub fn frames(&self) -> std::ops::Range<i64> {
+        self.start..self.end
+    }
+
+    /// Time in seconds of a frame
+    pub fn time(&self, frame: i64) -> f64 {
+        frame as f64 / self.fps
+    }
+
+    /// Check that the range has frames, at most [`MAX_FRAMES`] of them, and a
+    /// usable frame rate
+    ///
+    /// Frame rates so low that a frame would last longer than a
+    /// [`Duration`] can hold are rejected.
+    pub fn validate(&self) -> Result<()> {
+        if self.is_empty() {
+            anyhow::bail!("Frame range {}..{} is empty", self.start, self.end);
+        }
+        if self.len() > MAX_FRAMES {
+            anyhow::bail!(
+                "Frame range {}..{} has {} frames, more than the limit of {MAX_FRAMES}",
+                self.start,
+                self.end,
+                self.len()
+            );
+        }
+        if !(self.fps.is_finite() && self.fps > 0.0) {
+            anyhow::bail!("Frame rate must be positive, got {}", self.fps);
+        }
+        frame_delay(self.fps)?;
+        Ok(())
+    }
+}
+
+/// How the frames of an animation are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnimationFormat {
+    /// One PNG per frame, numbered after the frame
+    Sequence,
+    /// Looping animated GIF
+    #[default]
+    Gif,
+    /// Looping animated PNG
+    Apng,
+    /// All frames in a grid on one PNG, left to right and top to bottom
+    SpriteSheet,
+}
+
+impl AnimationFormat {
+    /// Parse a format name: `sequence`, `gif`, `apng` or `sheet`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "sequence" | "seq" => Some(Self::Sequence),
+            "gif" => Some(Self::Gif),
+            "apng" => Some(Self::Apng),
+            "sheet" | "spritesheet" | "sprite-sheet" => Some(Self::SpriteSheet),
+            _ => None,
+        }
+    }
+
+    /// File extension (without the dot)
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Gif => "gif",
+            Self::Sequence | Self::Apng | Self::SpriteSheet => "png",
+        }
+    }
+}
+
+/// Options for rendering an animation
+#[derive(Debug, Clone)]
+pub struct AnimationOptions {
+    /// Output file; sequences number their files after it
+    /// (`spin.png` becomes `spin_0000.png`, `spin_0001.png`, ...)
+    pub path: PathBuf,
+
+    /// How the frames are written
+    pub format: AnimationFormat,
+
+    /// Frames to render
+    pub frames: FrameRange,
+
+    /// Image size, renderer and camera of each frame; without a camera, the
+    /// first frame's scene is framed and the camera stays there
+    pub render: RenderOptions,
+
+    /// Orbit the camera a full turn around its target over the frames
+    pub turntable: bool,
+
+    /// Sprite sheet columns; `None` makes the grid as square as possible
+    ///
+    /// The whole sheet may have at most [`MAX_SHEET_PIXELS`] pixels.
+    pub columns: Option<u32>,
+}
+
+impl AnimationOptions {
+    /// Create animation options with defaults (36-frame GIF at 12 fps)
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            format: AnimationFormat::default(),
+            frames: FrameRange::default(),
+            render: RenderOptions::default(),
+            turntable: false,
+            columns: None,
+        }
+    }
+
+    /// Set the output format
+    pub fn with_format(mut self, format: AnimationFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the frames to render
+    pub fn with_frames(mut self, frames: FrameRange) -> Self {
+        self.frames = frames;
+        self
+    }
+
+    /// Set the image size, renderer and camera
+    pub fn with_render(mut self, render: RenderOptions) -> Self {
+        self.render = render;
+        self
+    }
+
+    /// Orbit the camera a full turn over the frames
+    pub fn with_turntable(mut self, turntable: bool) -> Self {
+        self.turntable = turntable;
+        self
+    }
+
+    /// Set the number of sprite sheet columns
+    pub fn with_columns(mut self, columns: u32) -> Self {
+        self.columns = Some(columns);
+        self
+    }
+}
+
+/// Result of a successful animation render
+#[derive(Debug, Clone)]
+pub struct AnimationResult {
+    /// Files written: one per frame for sequences, otherwise one
+    pub files: Vec<PathBuf>,
+
+    /// Number of frames rendered
+    pub frame_count: usize,
+
+    /// Size of each frame in pixels
+    pub frame_size: (u32, u32),
+
+    /// Renderer that drew the frames (`Gpu` or `Cpu`)
+    pub backend: RenderBackend,
+}
+
+impl fmt::Display for AnimationResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.files.as_slice() {
+            [file] => write!(f, "Rendered {}", file.display())?,
+            [first, .., last] => write!(f, "Rendered {} .. {}", first.display(), last.display())?,
+            [] => write!(f, "Rendered nothing")?,
+        }
+        let (width, height) = self.frame_size;
+        write!(
+            f,
+            " ({} frames, {width}x{height}, {})",
+            self.frame_count, self.backend
+        )
+    }
+}
+
+/// Camera of each frame
+///
+/// Turntables orbit `base` through a full turn, stopping one step short so
+/// the loop doesn't repeat a frame.
+pub fn orbit_cameras(base: &Camera, count: usize, turntable: bool) -> Vec<Camera> {
+    (0..count)
+        .map(|i| {
+            let mut camera = base.clone();
+            if turntable {
+                camera.orbit(std::f32::consts::TAU * i as f32 / count as f32, 0.0);
+            }
+            camera
+        })
+        .collect()
+}
+
+/// Render one scene per frame and write them as `options.format`
+///
+/// Scenes that don't read the time are rendered in batches from a single
+/// renderer setup; animated ones are set up frame by frame. Each frame is
+/// handed to the writer as soon as it renders.
+pub fn render_animation(scenes: &[Scene], options: &AnimationOptions) -> Result<AnimationResult> {
+    options.frames.validate()?;
+    let Some(first) = scenes.first() else {
+        anyhow::bail!("No frames to render");
+    };
+
+    // Every frame uses the renderer the first one settled on
+    let mut render = options.render.clone();
+    let frame_size = (render.width, render.height);
+    let mut writer = FrameWriter::new(options, scenes.len(), frame_size)?;
+
+    let aspect = render.width as f32 / render.height.max(1) as f32;
+    let base = render
+        .camera
+        .clone()
+        .unwrap_or_else(|| frame_scene(first, aspect));
+    let cameras = orbit_cameras(&base, scenes.len(), options.turntable);
+
+    let batches: Vec<(&Scene, &[Camera])> = if first.animated {
+        scenes.iter().zip(cameras.chunks(1)).collect()
+    } else {
+        cameras
+            .chunks(RENDER_BATCH)
+            .map(|batch| (first, batch))
+            .collect()
+    };
+
+    let mut backend = None;
+    for (scene, batch) in batches {
+        for result in render_views(scene, batch, &render)? {
+            render.backend = result.backend;
+            backend.get_or_insert(result.backend);
+            writer.push(result.image)?;
+        }
+    }
+
+    let frame_count = writer.written;
+    let files = writer.finish()?;
+    Ok(AnimationResult {
+        files,
+        frame_count,
+        frame_size,
+        backend: backend.unwrap_or(render.backend),
+    })
+}
+
+/// Write rendered frames in the chosen format, returning the files written
+///
+/// Fails if `options.frames` isn't a valid range, e.g. with a frame rate of 0,
+/// or if a sprite sheet would have more than [`MAX_SHEET_PIXELS`] pixels.
+pub fn write_frames(images: &[RgbaImage], options: &AnimationOptions) -> Result<Vec<PathBuf>> {
+    let Some(first) = images.first() else {
+        anyhow::bail!("No frames to write");
+    };
+    let mut writer = FrameWriter::new(options, images.len(), first.dimensions())?;
+    for image in images {
+        writer.push(image.clone())?;
+    }
+    writer.finish()
+}
+
+/// Writes frames in an animation format one at a time
+///
+/// Sequences, GIFs and APNGs encode each frame as it arrives; sprite sheets
+/// copy it into a sheet allocated up front.
+struct FrameWriter {
+    path: PathBuf,
+    sink: FrameSink,
+    frame_size: (u32, u32),
+    expected: usize,
+    written: usize,
+}
+
+enum FrameSink {
+    Sequence {
+        frames: std::ops::Range<i64>,
+        files: Vec<PathBuf>,
+    },
+    Gif {
+        encoder: GifEncoder<BufWriter<File>>,
+        delay: Delay,
+    },
+    Apng(png::Writer<BufWriter<File>>),
+    SpriteSheet {
+        sheet: RgbaImage,
+        columns: u32,
+    },
+}
+
+impl FrameWriter {
+    /// Check the options and open the output for `count` frames of
+    /// `frame_size`, before any frame is rendered
+    fn new(options: &AnimationOptions, count: usize, frame_size: (u32, u32)) -> Result<Self> {
+        options.frames.validate()?;
+        let (width, height) = frame_size;
+        if width == 0 || height == 0 {
+            anyhow::bail!("Cannot write {width}x{height} frames");
+        }
+
+        let path = &options.path;
+        let sheet_size = match options.format {
+            AnimationFormat::SpriteSheet => Some(sheet_size(count, options.columns, frame_size)?),
+            _ => None,
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+
+        let fps = options.frames.fps;
+        let sink = match (options.format, sheet_size) {
+            (AnimationFormat::Sequence, _) => FrameSink::Sequence {
+                frames: options.frames.frames(),
+                files: Vec::with_capacity(count),
+            },
+            (AnimationFormat::Gif, _) => open_gif(path, fps)
+                .with_context(|| format!("Failed to write {}", path.display()))?,
+            (AnimationFormat::Apng, _) => open_apng(path, fps, count, frame_size)
+                .with_context(|| format!("Failed to write {}", path.display()))?,
+            (AnimationFormat::SpriteSheet, Some((sheet_width, sheet_height, columns))) => {
+                FrameSink::SpriteSheet {
+                    sheet: RgbaImage::new(sheet_width, sheet_height),
+                    columns,
+                }
+            }
+            (AnimationFormat::SpriteSheet, None) => unreachable!("sheet size is checked above"),
+        };
+
+        Ok(Self {
+            path: path.clone(),
+            sink,
+            frame_size,
+            expected: count,
+            written: 0,
+        })
+    }
+
+    /// Write the next frame
+    fn push(&mut self, image: RgbaImage) -> Result<()> {
+        if self.written == self.expected {
+            anyhow::bail!("More than the {} frames expected", self.expected);
+        }
+        let (width, height) = self.frame_size;
+        if image.dimensions() != self.frame_size {
+            anyhow::bail!(
+                "Frame {} is {}x{}, expected {width}x{height}",
+                self.written,
+                image.width(),
+                image.height()
+            );
+        }
+
+        let index = self.written;
+        let path = &self.path;
+        match &mut self.sink {
+            FrameSink::Sequence { frames, files } => {
+                let frame = frames.next().context("More frames than the frame range")?;
+                let file = sequence_path(path, frame);
+                image
+                    .save_with_format(&file, image::ImageFormat::Png)
+                    .with_context(|| format!("Failed to write {}", file.display()))?;
+                files.push(file);
+            }
+            FrameSink::Gif { encoder, delay } => encoder
+                .encode_frame(Frame::from_parts(image, 0, 0, *delay))
+                .with_context(|| format!("Failed to write {}", path.display()))?,
+            FrameSink::Apng(writer) => writer
+                .write_image_data(image.as_raw())
+                .with_context(|| format!("Failed to write {}", path.display()))?,
+            FrameSink::SpriteSheet { sheet, columns } => {
+                let i = index as u32;
+                let x = i64::from(i % *columns * width);
+                let y = i64::from(i / *columns * height);
+                image::imageops::replace(sheet, &image, x, y);
+            }
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Finish the output, returning the files written
+    fn finish(self) -> Result<Vec<PathBuf>> {
+        if self.written != self.expected {
+            anyhow::bail!("Expected {} frames, got {}", self.expected, self.written);
+        }
+        let path = self.path;
+        match self.sink {
+            FrameSink::Sequence { files, .. } => return Ok(files),
+            // The GIF trailer is written when the encoder drops
+            FrameSink::Gif { encoder, .. } => drop(encoder),
+            FrameSink::Apng(writer) => writer
+                .finish()
+                .with_context(|| format!("Failed to write {}", path.display()))?,
+            FrameSink::SpriteSheet { sheet, .. } => sheet
+                .save_with_format(&path, image::ImageFormat::Png)
+                .with_context(|| format!("Failed to write {}", path.display()))?,
+        }
+        Ok(vec![path])
+    }
+}
+
+/// File of one frame in a sequence: `<stem>_<frame>.<ext>`, with the frame
+/// padded to four digits
+pub fn sequence_path(path: &Path, frame: i64) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "frame".into(), |s| s.to_string_lossy());
+    let extension = path
+        .extension()
+        .map_or_else(|| "png".into(), |e| e.to_string_lossy());
+    path.with_file_name(format!("{stem}_{frame:04}.{extension}"))
+}
+
+/// Columns and rows of a sprite sheet of `count` frames, `columns` wide (or
+/// as square as possible)
+fn sheet_grid(count: u32, columns: Option<u32>) -> (u32, u32) {
+    let columns = columns
+        .unwrap_or_else(|| f64::from(count).sqrt().ceil() as u32)
+        .clamp(1, count.max(1));
+    (columns, count.div_ceil(columns))
+}
+
+/// Width, height and columns of a sprite sheet of `count` frames of
+/// `frame_size`, at most [`MAX_SHEET_PIXELS`] pixels
+fn sheet_size(
+    count: usize,
+    columns: Option<u32>,
+    frame_size: (u32, u32),
+) -> Result<(u32, u32, u32)> {
+    let (width, height) = frame_size;
+    let count = u32::try_from(count).unwrap_or(u32::MAX);
+    let (columns, rows) = sheet_grid(count, columns);
+    let sheet_width = u64::from(width) * u64::from(columns);
+    let sheet_height = u64::from(height) * u64::from(rows);
+    if sheet_width * sheet_height > MAX_SHEET_PIXELS {
+        anyhow::bail!(
+            "A sprite sheet of {count} {width}x{height} frames would be \
+             {sheet_width}x{sheet_height}, more than the limit of {MAX_SHEET_PIXELS} pixels"
+        );
+    }
+    // Both sides fit: their product is within the limit
+    Ok((sheet_width as u32, sheet_height as u32, columns))
+}
+
+/// Pack frames into a grid, `columns` wide (or as square as possible)
+pub fn sprite_sheet(images: &[RgbaImage], columns: Option<u32>) -> RgbaImage {
+    let Some(first) = images.first() else {
+        return RgbaImage::new(0, 0);
+    };
+    let (width, height) = first.dimensions();
+    let count = images.len() as u32;
+    let (columns, rows) = sheet_grid(count, columns);
+
+    let mut sheet = RgbaImage::new(width * columns, height * rows);
+    for (i, image) in (0..count).zip(images) {
+        let x = i64::from(i % columns * width);
+        let y = i64::from(i / columns * height);
+        image::imageops::replace(&mut sheet, image, x, y);
+    }
+    sheet
+}
+
+/// Delay between frames at `fps`, which must be positive
+///
+/// Fails if a frame would last longer than a [`Duration`] can hold.
+fn frame_delay(fps: f64) -> Result<Duration> {
+    Duration::try_from_secs_f64(1.0 / fps)
+        .map_err(|_| anyhow::anyhow!("Frame rate {fps} is too low"))
+}
+
+/// Start a looping animated GIF
+fn open_gif(path: &Path, fps: f64) -> Result<FrameSink> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = GifEncoder::new_with_speed(file, GIF_SPEED);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    let delay = Delay::from_saturating_duration(frame_delay(fps)?);
+    Ok(FrameSink::Gif { encoder, delay })
+}
+
+/// Start a looping animated PNG of `count` frames
+fn open_apng(path: &Path, fps: f64, count: usize, frame_size: (u32, u32)) -> Result<FrameSink> {
+    let (width, height) = frame_size;
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(u32::try_from(count)?, 0)?;
+
+    // Delays are fractions of a second; milliseconds are plenty
+    let millis = frame_delay(fps)?.as_millis().clamp(1, u128::from(u16::MAX)) as u16;
+    encoder.set_frame_delay(millis, 1000)?;
+
+    Ok(FrameSink::Apng(encoder.write_header()?))
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn frames(count: u32) -> Vec<RgbaImage> {
+        (0..count)
+            .map(|i| RgbaImage::from_pixel(4, 2, image::Rgba([i as u8 * 10, 0, 0, 255])))
+            .collect()
+    }
+
+    #[test]
+    fn test_frame_range() {
+        let range = FrameRange::new(10, 34, 24.0);
+        assert_eq!(range.len(), 24);
+        assert!((range.time(12) - 0.5).abs() < 1e-12);
+        assert!(range.validate().is_ok());
+
+        assert!(FrameRange::new(5, 5, 24.0).validate().is_err());
+        assert!(FrameRange::new(5, 0, 24.0).is_empty());
+        assert!(FrameRange::new(0, 5, 0.0).validate().is_err());
+
+        // A frame would outlast any Duration: an error, not a panic later on
+        let err = FrameRange::new(0, 5, 1e-30)
+            .validate()
+            .expect_err("tiny frame rate should be rejected");
+        assert!(err.to_string().contains("too low"), "{err}");
+
+        let err = FrameRange::new(0, i64::MAX, 24.0)
+            .validate()
+            .expect_err("huge range should be rejected");
+        assert!(err.to_string().contains("100000"), "{err}");
+    }
+
+    #[test]
+    fn test_turntable_makes_a_full_turn() {
+        let base = Camera::look_at(glam::Vec3::new(0.0, 0.0, 3.0), glam::Vec3::ZERO);
+        let cameras = orbit_cameras(&base, 4, true);
+        assert!((cameras[0].position - base.position).length() < 1e-5);
+        assert!((cameras[2].position + base.position).length() < 1e-4);
+        assert!(cameras.iter().all(|c| (c.distance() - 3.0).abs() < 1e-4));
+
+        let still = orbit_cameras(&base, 3, false);
+        assert!(still.iter().all(|c| c.position == base.position));
+    }
+
+    #[test]
+    fn test_sprite_sheet_layout() {
+        let sheet = sprite_sheet(&frames(5), None);
+        assert_eq!(sheet.dimensions(), (12, 4));
+        assert_eq!(sheet.get_pixel(4, 0)[0], 10);
+        assert_eq!(sheet.get_pixel(0, 2)[0], 30);
+        // The unused last cell stays transparent
+        assert_eq!(sheet.get_pixel(11, 3)[3], 0);
+
+        assert_eq!(sprite_sheet(&frames(5), Some(5)).dimensions(), (20, 2));
+    }
+
+    #[test]
+    fn test_sprite_sheet_pixel_limit() {
+        assert_eq!(
+            sheet_size(5, None, (4, 2)).expect("small sheet"),
+            (12, 4, 3)
+        );
+
+        // Few frames, but each one is huge
+        let err = sheet_size(4, None, (8192, 8192)).expect_err("huge sheet should be rejected");
+        assert!(err.to_string().contains("16384x16384"), "{err}");
+        assert!(sheet_size(MAX_FRAMES, None, (64, 64)).is_err());
+    }
+
+    #[test]
+    fn test_write_formats() {
+        let dir = std::env::temp_dir().join(format!("soyuz_animation_{}", std::process::id()));
+        let images = frames(3);
+        let options =
+            AnimationOptions::new(dir.join("spin.png")).with_frames(FrameRange::new(0, 3, 10.0));
+
+        let files = write_frames(
+            &images,
+            &options.clone().with_format(AnimationFormat::Sequence),
+        )
+        .expect("sequence should write");
+        assert_eq!(files[2], dir.join("spin_0002.png"));
+        assert!(files.iter().all(|f| f.exists()));
+
+        let gif = AnimationOptions {
+            path: dir.join("spin.gif"),
+            ..options.clone()
+        };
+        write_frames(&images, &gif).expect("GIF should write");
+        let decoded = image::open(&gif.path).expect("GIF should decode");
+        assert_eq!(decoded.width(), 4);
+
+        let apng = options.with_format(AnimationFormat::Apng);
+        write_frames(&images, &apng).expect("APNG should write");
+        let bytes = std::fs::read(&apng.path).expect("APNG should exist");
+        assert!(bytes.windows(4).any(|chunk| chunk == b"acTL"));
+
+        let sheet = AnimationOptions {
+            path: dir.join("sheet.png"),
+            ..gif.clone()
+        }
+        .with_format(AnimationFormat::SpriteSheet);
+        write_frames(&images, &sheet).expect("sheet should write");
+        let decoded = image::open(&sheet.path).expect("sheet should decode");
+        assert_eq!(decoded.width(), 8);
+
+        // A frame rate of 0 is an error, not a panic
+        let stopped = gif.with_frames(FrameRange::new(0, 3, 0.0));
+        assert!(write_frames(&images, &stopped).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,610 @@
+//! CPU raymarching renderer for SDFs
+//!
+//! Sphere-traces the scene on the CPU with the shading model of the preview
+//! shader (`raymarch.wgsl` in `soyuz-sdf`): sun with Blinn-Phong specular,
+//! ambient light, ambient occlusion, soft shadows, distance fog and the sky
+//! gradient, all taken from the scene's [`Environment`]. Images match the
+//! GPU [`Raymarcher`](soyuz_render::Raymarcher) closely, so thumbnails and
+//! previews work on machines without a GPU adapter. Rows are traced in
+//! parallel.
+//!
+//! Besides the shaded image, the CPU renderer draws diagnostic views for
+//! debugging geometry (see [`RenderMode`]) and can use an orthographic
+//! [`Projection`]. The GPU renderer only draws shaded perspective images.
+
+use glam::{Vec2, Vec3};
+use image::RgbaImage;
+use rayon::prelude::*;
+use soyuz_core::camera::Camera;
+use soyuz_core::sdf::Sdf;
+use soyuz_script::CpuSdf;
+use soyuz_sdf::{Environment, SdfOp};
+use std::f32::consts::TAU;
+use std::fmt;
+
+/// Most steps a ray takes before giving up, as in `raymarch.wgsl`
+pub const MAX_STEPS: u32 = 128;
+
+// Constants from `raymarch.wgsl`
+const MAX_DIST: f32 = 100.0;
+const MIN_SURF_DIST: f32 = 0.0001;
+const DIST_SCALE: f32 = 0.0005;
+const SHADOW_STEPS: u32 = 32;
+
+/// Result of tracing one ray
+struct RayHit {
+    /// Distance travelled along the ray
+    dist: f32,
+    /// Hit point
+    pos: Vec3,
+}
+
+/// Outcome of tracing one ray
+struct March {
+    /// Surface hit, if any
+    hit: Option<RayHit>,
+    /// Steps taken
+    steps: u32,
+}
+
+/// Camera projection
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Projection {
+    /// Perspective with the camera's field of view
+    #[default]
+    Perspective,
+    /// Parallel rays along the view direction; `height` is the image height
+    /// in world units
+    Orthographic { height: f32 },
+}
+
+/// A world axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    /// Parse an axis name: `x`, `y` or `z`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "x" => Some(Self::X),
+            "y" => Some(Self::Y),
+            "z" => Some(Self::Z),
+            _ => None,
+        }
+    }
+
+    /// Component of `v` along this axis
+    pub fn component(self, v: Vec3) -> f32 {
+        match self {
+            Self::X => v.x,
+            Self::Y => v.y,
+            Self::Z => v.z,
+        }
+    }
+}
+
+impl fmt::Display for Axis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::X => "x",
+            Self::Y => "y",
+            Self::Z => "z",
+        })
+    }
+}
+
+/// An axis-aligned plane through the scene, for distance-field slices
+///
+/// The plane is seen from the positive end of its axis, as from the
+/// `right`, `top` and `front` preview angles: an X slice has +Y up and -Z
+/// to the right, a Y slice +X to the right and -Z up, a Z slice +X to the
+/// right and +Y up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlicePlane {
+    /// Axis the plane is perpendicular to
+    pub axis: Axis,
+
+    /// Position of the plane along `axis`
+    pub offset: f32,
+
+    /// Point shown at the image center (its `axis` component is ignored)
+    pub center: Vec3,
+
+    /// Image width in world units; the height follows the image's aspect
+    pub size: f32,
+}
+
+impl SlicePlane {
+    /// Distance between contour bands: a twentieth of the image width
+    pub fn contour_spacing(&self) -> f32 {
+        self.size / 20.0
+    }
+
+    /// Point on the plane `right` and `up` world units from the center
+    fn point(&self, right: f32, up: f32) -> Vec3 {
+        let c = self.center;
+        match self.axis {
+            Axis::X => Vec3::new(self.offset, c.y + up, c.z - right),
+            Axis::Y => Vec3::new(c.x + right, self.offset, c.z - up),
+            Axis::Z => Vec3::new(c.x + right, c.y + up, self.offset),
+        }
+    }
+}
+
+/// What the CPU raymarcher draws
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RenderMode {
+    /// Lit surface, as in the preview
+    #[default]
+    Shaded,
+    /// Surface normals: x, y and z mapped from -1..1 to red, green and blue;
+    /// black where rays miss
+    Normals,
+    /// Distance from the camera: the nearest surface white, the farthest
+    /// dark grey, black where rays miss
+    Depth,
+    /// Raymarch steps per pixel, dark blue (few) through green and yellow
+    /// to red ([`MAX_STEPS`], where rays give up). Red on the surface
+    /// usually means a distance bound that isn't a true distance
+    Steps,
+    /// Signed distance on a plane, ignoring the camera: blue inside,
+    /// orange outside, darker near the surface, with contour bands every
+    /// [`SlicePlane::contour_spacing`] and a white line on the surface
+    Slice(SlicePlane),
+}
+
+/// Raymarching renderer that runs on the CPU
+pub struct CpuRaymarcher {
+    sdf: CpuSdf,
+    has_materials: bool,
+    environment: Environment,
+    mode: RenderMode,
+    projection: Projection,
+}
+
+impl CpuRaymarcher {
+    /// Create a renderer for an SDF with environment settings
+    pub fn new(sdf: &SdfOp, environment: Environment) -> Self {
+        let sdf = CpuSdf::new(sdf.clone());
+        Self {
+            has_materials: sdf.has_materials(),
+            sdf,
+            environment,
+            mode: RenderMode::Shaded,
+            projection: Projection::Perspective,
+        }
+    }
+
+    /// Draw a diagnostic view instead of the shaded image
+    pub fn with_mode(mut self, mode: RenderMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the camera projection
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Get the current environment settings
+    pub fn environment(&self) -> &Environment {
+        &self.environment
+    }
+
+    /// Render to an image buffer
+    ///
+    /// The camera's aspect ratio is ignored; like the GPU renderer, the
+    /// image's own width and height set it.
+    pub fn render_to_image(&self, width: u32, height: u32, camera: &Camera) -> RgbaImage {
+        let view = View::new(camera, self.projection, width, height);
+
+        let pixels: Vec<[u8; 4]> = match self.mode {
+            RenderMode::Shaded => per_pixel(width, height, |x, y| {
+                to_rgba8(self.shade_pixel(&view, x, y))
+            }),
+            RenderMode::Normals => per_pixel(width, height, |x, y| {
+                let (ro, rd) = view.ray(view.ndc(x, y));
+                self.raymarch(ro, rd)
+                    .hit
+                    .map_or(BLACK, |hit| to_unorm8(self.normal(hit.pos, ro) * 0.5 + 0.5))
+            }),
+            RenderMode::Depth => {
+                let depths = per_pixel(width, height, |x, y| {
+                    let (ro, rd) = view.ray(view.ndc(x, y));
+                    self.raymarch(ro, rd).hit.map(|hit| hit.dist)
+                });
+                let (near, far) = depths
+                    .iter()
+                    .flatten()
+                    .fold((f32::INFINITY, 0.0_f32), |(near, far), &d| {
+                        (near.min(d), far.max(d))
+                    });
+                let range = (far - near).max(1e-6);
+                depths
+                    .iter()
+                    .map(|depth| {
+                        depth.map_or(BLACK, |d| {
+                            to_unorm8(Vec3::splat(1.0 - 0.8 * (d - near) / range))
+                        })
+                    })
+                    .collect()
+            }
+            RenderMode::Steps => per_pixel(width, height, |x, y| {
+                let (ro, rd) = view.ray(view.ndc(x, y));
+                let steps = self.raymarch(ro, rd).steps;
+                to_unorm8(heat(steps as f32 / MAX_STEPS as f32))
+            }),
+            RenderMode::Slice(plane) => per_pixel(width, height, |x, y| {
+                to_unorm8(self.slice_color(&plane, &view, view.ndc(x, y)))
+            }),
+        };
+
+        RgbaImage::from_raw(width, height, pixels.into_flattened()).unwrap_or_default()
+    }
+
+    /// Color of one pixel before it's written to the (sRGB) target
+    fn shade_pixel(&self, view: &View, x: u32, y: u32) -> Vec3 {
+        let ndc = view.ndc(x, y);
+        let (ro, rd) = view.ray(ndc);
+
+        let mut col = match self.raymarch(ro, rd).hit {
+            Some(hit) => {
+                let n = self.normal(hit.pos, ro);
+                let col = self.light(hit.pos, n, rd) * self.surface_color(hit.pos);
+
+                // Distance fog
+                let fog = (-self.environment.fog_density * hit.dist * hit.dist).exp();
+                self.background(rd).lerp(col, fog)
+            }
+            None => self.background(rd),
+        };
+
+        // Gamma correction and vignette
+        col = col.max(Vec3::ZERO).powf(1.0 / 2.2);
+        col * (1.0 - 0.3 * ndc.length())
+    }
+
+    fn distance(&self, p: Vec3) -> f32 {
+        self.sdf.distance(p)
+    }
+
+    /// Sphere-trace a ray, with a hit threshold that grows with distance
+    fn raymarch(&self, ro: Vec3, rd: Vec3) -> March {
+        let mut dist = 0.0;
+        for step in 1..=MAX_STEPS {
+            let pos = ro + rd * dist;
+            let d = self.distance(pos);
+            if d < MIN_SURF_DIST + DIST_SCALE * dist {
+                return March {
+                    hit: Some(RayHit { dist, pos }),
+                    steps: step,
+                };
+            }
+            if dist > MAX_DIST {
+                return March {
+                    hit: None,
+                    steps: step,
+                };
+            }
+            dist += d;
+        }
+        March {
+            hit: None,
+            steps: MAX_STEPS,
+        }
+    }
+
+    /// Slice view color of the distance under a pixel
+    fn slice_color(&self, plane: &SlicePlane, view: &View, ndc: Vec2) -> Vec3 {
+        let half_width = plane.size * 0.5;
+        let p = plane.point(ndc.x * half_width, ndc.y * half_width / view.aspect);
+        let d = self.distance(p);
+
+        let mut col = if d > 0.0 {
+            Vec3::new(0.9, 0.6, 0.3)
+        } else {
+            Vec3::new(0.35, 0.65, 0.9)
+        };
+        col *= 1.0 - 0.7 * (-12.0 * d.abs() / plane.size).exp();
+        col *= 0.8 + 0.2 * (TAU * d / plane.contour_spacing()).cos();
+
+        // Surface line a pixel wide, fading out over the next pixel
+        let pixel = plane.size / view.resolution.x;
+        let line = 1.0 - ((d.abs() - 0.5 * pixel) / pixel).clamp(0.0, 1.0);
+        col.lerp(Vec3::ONE, line)
+    }
+
+    /// Central-difference normal, with a coarser step for far surfaces
+    fn normal(&self, p: Vec3, camera_pos: Vec3) -> Vec3 {
+        let eps = 0.0001 + 0.0002 * (p - camera_pos).length();
+        let (ex, ey, ez) = (Vec3::X * eps, Vec3::Y * eps, Vec3::Z * eps);
+        Vec3::new(
+            self.distance(p + ex) - self.distance(p - ex),
+            self.distance(p + ey) - self.distance(p - ey),
+            self.distance(p + ez) - self.distance(p - ez),
+        )
+        .normalize_or_zero()
+    }
+
+    /// Ambient occlusion from three samples along the normal
+    fn ambient_occlusion(&self, pos: Vec3, nor: Vec3) -> f32 {
+        let occ = [(0.02, 1.0), (0.06, 0.5), (0.10, 0.25)]
+            .iter()
+            .map(|&(h, weight)| (h - self.distance(pos + h * nor)) * weight)
+            .sum::<f32>();
+        (1.0 - 4.0 * occ).clamp(0.0, 1.0)
+    }
+
+    /// Soft shadow towards the sun, with the improved penumbra estimate
+    fn soft_shadow(&self, ro: Vec3, rd: Vec3, mint: f32, maxt: f32, softness: f32) -> f32 {
+        let mut res: f32 = 1.0;
+        let mut t = mint;
+        let mut previous = 1e10;
+
+        for _ in 0..SHADOW_STEPS {
+            let h = self.distance(ro + rd * t);
+            if h < 0.0005 {
+                return 0.0;
+            }
+            let offset = h * h / (2.0 * previous);
+            let width = (h * h - offset * offset).sqrt();
+            res = res.min(softness * width / (t - offset).max(0.0));
+            previous = h;
+
+            t += h.max(0.02);
+            if t > maxt {
+                break;
+            }
+        }
+        res
+    }
+
+    /// Sun, ambient and fake sky light at a surface point
+    fn light(&self, p: Vec3, n: Vec3, rd: Vec3) -> Vec3 {
+        let env = &self.environment;
+        let sun_dir = Vec3::from_array(env.sun_direction).normalize_or_zero();
+        let sun_color = Vec3::from_array(env.sun_color) * env.sun_intensity;
+        let ambient = Vec3::from_array(env.ambient_color) * env.ambient_intensity;
+
+        // Diffuse and Blinn-Phong specular
+        let diff = n.dot(sun_dir).max(0.0);
+        let half_vec = (sun_dir - rd).normalize_or_zero();
+        let spec = n.dot(half_vec).max(0.0).powf(env.material_shininess);
+
+        let shadow = if env.shadows_enabled {
+            self.soft_shadow(p + n * 0.002, sun_dir, 0.02, 2.5, env.shadow_softness)
+        } else {
+            1.0
+        };
+        let ao = if env.ao_enabled {
+            self.ambient_occlusion(p, n)
+        } else {
+            1.0
+        };
+
+        let mut col = ambient * ao;
+        col += sun_color * diff * shadow;
+        col += sun_color * spec * shadow * env.specular_intensity;
+
+        // Sky reflection (fake)
+        let sky_diff = n.dot(Vec3::Y).max(0.0);
+        let sky_color =
+            Vec3::from_array(env.sky_horizon).lerp(Vec3::from_array(env.sky_zenith), 0.5);
+        col + sky_color * 0.15 * sky_diff * ao
+    }
+
+    /// Material color from the SDF tree, the environment color where untagged
+    fn surface_color(&self, p: Vec3) -> Vec3 {
+        let default = self.environment.material_color;
+        if self.has_materials {
+            Vec3::from_array(self.sdf.material_at(p, default).color)
+        } else {
+            Vec3::from_array(default)
+        }
+    }
+
+    /// Sky gradient with ground fog towards the horizon
+    fn background(&self, rd: Vec3) -> Vec3 {
+        let env = &self.environment;
+        let t = 0.5 * (rd.y + 1.0);
+        let sky = Vec3::from_array(env.sky_horizon).lerp(Vec3::from_array(env.sky_zenith), t);
+        let fog = (-10.0 * rd.y.max(0.0)).exp();
+        sky.lerp(Vec3::from_array(env.fog_color), fog * 0.3)
+    }
+}
+
+/// Camera basis and projection for one image
+struct View {
+    position: Vec3,
+    forward: Vec3,
+    right: Vec3,
+    up: Vec3,
+    fov_tan: f32,
+    /// Half the image height in world units, for orthographic views
+    ortho_half_height: Option<f32>,
+    aspect: f32,
+    resolution: Vec2,
+}
+
+impl View {
+    fn new(camera: &Camera, projection: Projection, width: u32, height: u32) -> Self {
+        let resolution = Vec2::new(width.max(1) as f32, height.max(1) as f32);
+        Self {
+            position: camera.position,
+            forward: camera.forward(),
+            right: camera.right(),
+            up: camera.actual_up(),
+            fov_tan: (camera.fov * 0.5).tan(),
+            ortho_half_height: match projection {
+                Projection::Perspective => None,
+                Projection::Orthographic { height } => Some(height * 0.5),
+            },
+            aspect: resolution.x / resolution.y,
+            resolution,
+        }
+    }
+
+    /// Pixel center in normalized device coordinates, +y up
+    fn ndc(&self, x: u32, y: u32) -> Vec2 {
+        Vec2::new(
+            (x as f32 + 0.5) / self.resolution.x * 2.0 - 1.0,
+            1.0 - (y as f32 + 0.5) / self.resolution.y * 2.0,
+        )
+    }
+
+    /// Origin and direction of the ray through a point on the image
+    fn ray(&self, ndc: Vec2) -> (Vec3, Vec3) {
+        if let Some(half_height) = self.ortho_half_height {
+            let offset = (ndc.x * self.aspect * self.right + ndc.y * self.up) * half_height;
+            return (self.position + offset, self.forward);
+        }
+
+        let rd = self.forward
+            + ndc.x * self.right * self.fov_tan * self.aspect
+            + ndc.y * self.up * self.fov_tan;
+        (self.position, rd.normalize())
+    }
+}
+
+/// Evaluate `f` for every pixel, rows in parallel, in row-major order
+fn per_pixel<T: Send>(width: u32, height: u32, f: impl Fn(u32, u32) -> T + Sync) -> Vec<T> {
+    (0..height)
+        .into_par_iter()
+        .flat_map_iter(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| f(x, y))
+        .collect()
+}
+
+/// Heatmap color for `t` in `0..=1`
+fn heat(t: f32) -> Vec3 {
+    const STOPS: [Vec3; 5] = [
+        Vec3::new(0.05, 0.05, 0.3),
+        Vec3::new(0.0, 0.4, 1.0),
+        Vec3::new(0.0, 0.8, 0.3),
+        Vec3::new(1.0, 0.85, 0.0),
+        Vec3::new(1.0, 0.0, 0.0),
+    ];
+    let scaled = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let i = (scaled as usize).min(STOPS.len() - 2);
+    STOPS[i].lerp(STOPS[i + 1], scaled - i as f32)
+}
+
+/// Opaque black, for rays that miss in diagnostic views
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+/// Store a diagnostic color as is, without sRGB encoding
+fn to_unorm8(color: Vec3) -> [u8; 4] {
+    let encode = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    [encode(color.x), encode(color.y), encode(color.z), 255]
+}
+
+/// Store a shader output color as the GPU does in an `Rgba8UnormSrgb`
+/// target: clamped, then sRGB-encoded
+fn to_rgba8(color: Vec3) -> [u8; 4] {
+    let encode = |c: f32| {
+        let c = c.clamp(0.0, 1.0);
+        let srgb = if c <= 0.003_130_8 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        };
+        (srgb * 255.0).round() as u8
+    };
+    [encode(color.x), encode(color.y), encode(color.z), 255]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(environment: Environment, width: u32, height: u32) -> RgbaImage {
+        let camera = Camera::look_at(Vec3::new(0.0, 0.0, 3.0), Vec3::ZERO);
+        CpuRaymarcher::new(&SdfOp::Sphere { radius: 0.5 }, environment)
+            .render_to_image(width, height, &camera)
+    }
+
+    #[test]
+    fn test_renders_surface_over_sky() {
+        let image = render(Environment::default(), 32, 24);
+        assert_eq!(image.dimensions(), (32, 24));
+        assert!(image.pixels().all(|p| p[3] == 255));
+
+        // The sphere fills the center; the sky is symmetric left to right
+        assert_ne!(image.get_pixel(16, 12), image.get_pixel(0, 0));
+        assert_eq!(image.get_pixel(0, 0), image.get_pixel(31, 0));
+    }
+
+    #[test]
+    fn test_environment_colors_the_surface() {
+        let red = Environment {
+            material_color: [1.0, 0.0, 0.0],
+            fog_density: 0.0,
+            ..Environment::default()
+        };
+        let center = *render(red, 32, 24).get_pixel(16, 12);
+        assert!(
+            center[0] > 0 && center[1] == 0 && center[2] == 0,
+            "{center:?}"
+        );
+    }
+
+    #[test]
+    fn test_empty_image() {
+        assert_eq!(render(Environment::default(), 0, 0).dimensions(), (0, 0));
+    }
+
+    fn render_mode(mode: RenderMode, projection: Projection) -> RgbaImage {
+        let camera = Camera::look_at(Vec3::new(0.0, 0.0, 3.0), Vec3::ZERO);
+        CpuRaymarcher::new(&SdfOp::Sphere { radius: 0.5 }, Environment::default())
+            .with_mode(mode)
+            .with_projection(projection)
+            .render_to_image(32, 32, &camera)
+    }
+
+    #[test]
+    fn test_diagnostic_modes() {
+        // The sphere's center faces the camera: normal +Z is blue
+        let normals = render_mode(RenderMode::Normals, Projection::Perspective);
+        assert!(normals.get_pixel(16, 16)[2] > 250);
+        assert_eq!(*normals.get_pixel(0, 0), image::Rgba(BLACK));
+
+        // The bulge of the sphere is nearer than its rim
+        let depth = render_mode(RenderMode::Depth, Projection::Perspective);
+        assert!(depth.get_pixel(16, 16)[0] > depth.get_pixel(16, 11)[0]);
+
+        // Missing rays march out to the far plane, which takes a few steps
+        let steps = render_mode(RenderMode::Steps, Projection::Perspective);
+        assert_ne!(steps.get_pixel(16, 16), steps.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn test_orthographic_projection() {
+        // A view 2 units tall puts the sphere's edge a quarter in from the sides
+        let ortho = Projection::Orthographic { height: 2.0 };
+        let image = render_mode(RenderMode::Normals, ortho);
+        assert_eq!(*image.get_pixel(6, 16), image::Rgba(BLACK));
+        assert_ne!(*image.get_pixel(10, 16), image::Rgba(BLACK));
+    }
+
+    #[test]
+    fn test_slice_shows_inside_and_outside() {
+        let plane = SlicePlane {
+            axis: Axis::Y,
+            offset: 0.0,
+            center: Vec3::ZERO,
+            size: 2.0,
+        };
+        let image = render_mode(RenderMode::Slice(plane), Projection::Perspective);
+        let inside = image.get_pixel(16, 16);
+        let outside = image.get_pixel(1, 1);
+        assert!(inside[2] > inside[0], "{inside:?}");
+        assert!(outside[0] > outside[2], "{outside:?}");
+        // The surface crosses x = 0.5 on the middle row: a white line
+        assert!(image.get_pixel(24, 16).0[..3].iter().all(|&c| c > 200));
+    }
+}
@@ -5,9 +5,11 @@
 
 use crate::scene::Scene;
 use anyhow::Result;
-use soyuz_core::export::MeshExport;
-use soyuz_core::mesh::{Mesh, MeshConfig, OptimizeConfig, SdfToMesh};
-use soyuz_core::sdf::Sdf;
+use soyuz_core::export::{
+    GltfExportOptions, MAX_GLTF_TEXTURES, MeshExport, export_gltf_files, gltf_companion_files,
+};
+use soyuz_core::mesh::{Mesh, MeshAlgorithm, MeshConfig, OptimizeConfig, SdfToMesh};
+use soyuz_core::sdf::Aabb;
 use soyuz_script::CpuSdf;
 use std::path::{Path, PathBuf};
 
@@ -39,6 +41,18 @@ impl ExportFormat {
         }
     }
 
+    /// Every file an export to `path` in this format may write next to it
+    /// (glTF buffers and textures, OBJ materials)
+    ///
+    /// [`ExportResult::companions`] lists the ones an export actually wrote.
+    pub fn companion_paths(&self, path: &Path) -> Vec<PathBuf> {
+        match self {
+            ExportFormat::Gltf => gltf_companion_files(path, MAX_GLTF_TEXTURES),
+            ExportFormat::Obj => vec![path.with_extension("mtl")],
+            ExportFormat::Glb | ExportFormat::Stl => Vec::new(),
+        }
+    }
+
     /// Parse format from file extension
     pub fn from_extension(ext: &str) -> Option<Self> {
         match ext.to_lowercase().as_str() {
@@ -68,8 +82,18 @@ pub struct ExportOptions {
     pub format: Option<ExportFormat>,
 
     /// Mesh resolution (higher = more detail, slower)
+    ///
+    /// Counts cells along the longest axis of the scene; shorter axes get
+    /// fewer so cells stay cubic.
     pub resolution: u32,
 
+    /// Target cell size in world units, overriding `resolution` when set
+    pub cell_size: Option<f32>,
+
+    /// Surface extraction algorithm (dual contouring keeps sharp edges, the
+    /// adaptive octree is fastest at high resolutions)
+    pub algorithm: MeshAlgorithm,
+
     /// Whether to optimize the mesh (remove duplicates, etc.)
     pub optimize: bool,
 }
@@ -81,6 +105,8 @@ impl ExportOptions {
             path: path.into(),
             format: None,
             resolution: 64,
+            cell_size: None,
+            algorithm: MeshAlgorithm::default(),
             optimize: true,
         }
     }
@@ -97,12 +123,40 @@ impl ExportOptions {
         self
     }
 
+    /// Set a target cell size in world units, overriding the resolution
+    pub fn with_cell_size(mut self, cell_size: f32) -> Self {
+        self.cell_size = Some(cell_size);
+        self
+    }
+
+    /// Set the surface extraction algorithm
+    pub fn with_algorithm(mut self, algorithm: MeshAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
     /// Set whether to optimize the mesh
     pub fn with_optimize(mut self, optimize: bool) -> Self {
         self.optimize = optimize;
         self
     }
 
+    /// The same options for the variant built from `seed`, written to
+    /// `<name>_seed<seed>.<ext>` next to the original path
+    pub fn for_seed(&self, seed: u64) -> Self {
+        let mut name = self.path.file_stem().unwrap_or_default().to_os_string();
+        name.push(format!("_seed{seed}"));
+        if let Some(extension) = self.path.extension() {
+            name.push(".");
+            name.push(extension);
+        }
+
+        Self {
+            path: self.path.with_file_name(name),
+            ..self.clone()
+        }
+    }
+
     /// Get the effective format (explicit or inferred from path)
     pub fn effective_format(&self) -> Option<ExportFormat> {
         self.format.or_else(|| ExportFormat::from_path(&self.path))
@@ -115,6 +169,10 @@ pub struct ExportResult {
     /// Path where the file was written
     pub path: PathBuf,
 
+    /// Other files written next to it (glTF buffers and textures, OBJ
+    /// materials)
+    pub companions: Vec<PathBuf>,
+
     /// Format used for export
     pub format: ExportFormat,
 
@@ -143,11 +201,47 @@ pub fn generate_mesh_from_scene(scene: &Scene, config: MeshConfig) -> Result<Mes
     let cpu_sdf = CpuSdf::new(scene.sdf.clone());
 
     // Generate mesh using marching cubes
-    let mesh = cpu_sdf.to_mesh(config)?;
+    let mut mesh = cpu_sdf.to_mesh(config)?;
+    apply_scene_materials(scene, &cpu_sdf, &mut mesh);
 
     Ok(mesh)
 }
 
+/// Bake material IDs and colors into the mesh vertices
+///
+/// Only scenes that tag objects with `.material()` or `.color()` get vertex
+/// attributes, so untagged exports are unchanged.
+fn apply_scene_materials(scene: &Scene, cpu_sdf: &CpuSdf, mesh: &mut Mesh) {
+    if !cpu_sdf.has_materials() {
+        return;
+    }
+
+    let default_color = scene.environment.material_color;
+    mesh.apply_vertex_materials(|p| {
+        let m = cpu_sdf.material_at(p, default_color);
+        (m.id, [m.color[0], m.color[1], m.color[2], 1.0])
+    });
+}
+
+/// Cells of empty space kept around the surface when meshing
+///
+/// Dual contouring can't place faces on the outermost grid edges, so a
+/// surface touching the bounds would be left open there.
+const MESH_PADDING_CELLS: f32 = 2.0;
+
+/// Bounds to mesh an SDF in, fitted to its surface
+///
+/// Refines the analytic bounds down to the zero-set (see
+/// [`CpuSdf::tight_bounds`]) and pads them by a couple of cells of the grid
+/// that `resolution` or `cell_size` will produce.
+pub fn mesh_bounds(sdf: &CpuSdf, resolution: u32, cell_size: Option<f32>) -> Aabb {
+    let bounds = sdf.tight_bounds();
+    let cell = cell_size
+        .filter(|c| c.is_finite() && *c > 0.0)
+        .unwrap_or_else(|| bounds.size().max_element() / resolution.max(1) as f32);
+    bounds.expand(cell * MESH_PADDING_CELLS)
+}
+
 /// Export a scene to a mesh file
 pub fn export_scene(scene: &Scene, options: &ExportOptions) -> Result<ExportResult> {
     // Determine format
@@ -158,13 +252,14 @@ pub fn export_scene(scene: &Scene, options: &ExportOptions) -> Result<ExportResu
     // Create CPU-evaluable SDF
     let cpu_sdf = CpuSdf::new(scene.sdf.clone());
 
-    // Use the SDF's bounds for mesh generation
-    let bounds = cpu_sdf.bounds();
+    let bounds = mesh_bounds(&cpu_sdf, options.resolution, options.cell_size);
 
     // Configure mesh generation
-    let config = MeshConfig::default()
+    let mut config = MeshConfig::default()
         .with_resolution(options.resolution)
-        .with_bounds(bounds);
+        .with_bounds(bounds)
+        .with_algorithm(options.algorithm);
+    config.cell_size = options.cell_size;
 
     // Generate mesh
     let mut mesh = cpu_sdf.to_mesh(config)?;
@@ -174,6 +269,9 @@ pub fn export_scene(scene: &Scene, options: &ExportOptions) -> Result<ExportResu
         mesh.optimize(&OptimizeConfig::default());
     }
 
+    // Sample materials after welding so every final vertex gets one
+    apply_scene_materials(scene, &cpu_sdf, &mut mesh);
+
     let vertex_count = mesh.vertex_count();
     let triangle_count = mesh.triangle_count();
 
@@ -183,11 +281,36 @@ pub fn export_scene(scene: &Scene, options: &ExportOptions) -> Result<ExportResu
         output_path.set_extension(format.extension());
     }
 
-    // Export to file
-    mesh.export(&output_path)?;
+    // Export to file, embedding the script's PBR material where supported.
+    // The file's extension picks the writer.
+    let companions = match ExportFormat::from_path(&output_path).unwrap_or(format) {
+        ExportFormat::Glb | ExportFormat::Gltf => {
+            let mut files = export_gltf_files(
+                &mesh,
+                scene.material.as_ref(),
+                &output_path,
+                &GltfExportOptions::default(),
+            )?;
+            files.split_off(1)
+        }
+        ExportFormat::Obj => {
+            let writes_mtl = mesh.material_ids.is_some();
+            mesh.export(&output_path)?;
+            if writes_mtl {
+                vec![output_path.with_extension("mtl")]
+            } else {
+                Vec::new()
+            }
+        }
+        ExportFormat::Stl => {
+            mesh.export(&output_path)?;
+            Vec::new()
+        }
+    };
 
     Ok(ExportResult {
         path: output_path,
+        companions,
         format,
         vertex_count,
         triangle_count,
@@ -203,8 +326,10 @@ pub fn quick_export(scene: &Scene, path: impl Into<PathBuf>, resolution: u32) ->
 }
 
 #[cfg(test)]
+#[allow(clippy::expect_used)]
 mod tests {
     use super::*;
+    use soyuz_core::prelude::Vec3;
     use soyuz_sdf::SdfOp;
 
     #[test]
@@ -241,14 +366,53 @@ mod tests {
         let opts = ExportOptions::new("model.glb")
             .with_format(ExportFormat::Glb)
             .with_resolution(128)
+            .with_cell_size(0.01)
+            .with_algorithm(MeshAlgorithm::DualContouring)
             .with_optimize(false);
 
         assert_eq!(opts.path, PathBuf::from("model.glb"));
         assert_eq!(opts.format, Some(ExportFormat::Glb));
         assert_eq!(opts.resolution, 128);
+        assert_eq!(opts.cell_size, Some(0.01));
+        assert_eq!(opts.algorithm, MeshAlgorithm::DualContouring);
         assert!(!opts.optimize);
     }
 
+    #[test]
+    fn test_seed_variant_path() {
+        let opts = ExportOptions::new("out/rock.glb").with_resolution(32);
+        let variant = opts.for_seed(7);
+        assert_eq!(variant.path, PathBuf::from("out/rock_seed7.glb"));
+        assert_eq!(variant.resolution, 32);
+        assert_eq!(
+            ExportOptions::new("rock").for_seed(0).path,
+            PathBuf::from("rock_seed0")
+        );
+    }
+
+    #[test]
+    fn test_mesh_bounds_fit_surface() {
+        // Analytic bounds of a rotated sphere are a rotated cube; the mesh
+        // bounds hug the sphere itself with two cells of padding
+        let op = SdfOp::Translate {
+            inner: std::sync::Arc::new(SdfOp::RotateY {
+                inner: std::sync::Arc::new(SdfOp::Sphere { radius: 0.5 }),
+                angle: 0.7,
+            }),
+            offset: [3.0, 0.0, 0.0],
+        };
+        let sdf = CpuSdf::new(op);
+        let bounds = mesh_bounds(&sdf, 64, None);
+
+        let cell = bounds.size().max_element() / 64.0;
+        let surface = Aabb::new(Vec3::new(2.5, -0.5, -0.5), Vec3::new(3.5, 0.5, 0.5));
+        assert!(bounds.contains(&surface.expand(cell * 1.5)));
+        assert!(surface.expand(cell * 4.0).contains(&bounds));
+
+        let bounds = mesh_bounds(&sdf, 64, Some(0.1));
+        assert!(bounds.contains(&surface.expand(0.2)));
+    }
+
     #[test]
     fn test_generate_mesh() {
         let scene = Scene::new(SdfOp::Sphere { radius: 0.5 }, soyuz_sdf::Environment::default());
@@ -260,5 +424,65 @@ mod tests {
         let mesh = mesh.expect("Failed to generate mesh");
         assert!(mesh.vertex_count() > 0);
         assert!(mesh.triangle_count() > 0);
+        assert!(!mesh.has_colors());
+    }
+
+    #[test]
+    fn test_generate_mesh_with_materials() {
+        let red = SdfOp::Material {
+            inner: std::sync::Arc::new(SdfOp::Sphere { radius: 0.4 }),
+            material: soyuz_sdf::MaterialTag {
+                id: 1,
+                color: Some([1.0, 0.0, 0.0]),
+            },
+        };
+        let scene = Scene::new(red, soyuz_sdf::Environment::default());
+
+        let config = MeshConfig::default().with_resolution(16);
+        let mesh = generate_mesh_from_scene(&scene, config).expect("Failed to generate mesh");
+
+        let colors = mesh.colors.as_ref().expect("mesh should have colors");
+        assert_eq!(colors.len(), mesh.vertex_count());
+        assert!(
+            colors
+                .iter()
+                .all(|c| (c[0] - 1.0).abs() < 1e-6 && c[1] < 1e-6)
+        );
+        assert_eq!(mesh.split_by_material().len(), 1);
+        assert_eq!(mesh.split_by_material()[0].0, 1);
+    }
+
+    #[test]
+    fn test_export_with_pbr_material() {
+        let engine = soyuz_script::ScriptEngine::new();
+        let result = engine
+            .eval_scene("set_material(pbr().albedo(checker(4.0)).roughness(0.3)); sphere(0.5)")
+            .expect("script should evaluate");
+        let scene = Scene::from_scene_result(result);
+        assert!(scene.material.is_some());
+
+        let path = std::env::temp_dir().join(format!("soyuz_pbr_{}.glb", std::process::id()));
+        let options = ExportOptions::new(&path).with_resolution(16);
+        export_scene(&scene, &options).expect("export should succeed");
+
+        let glb = std::fs::read(&path).expect("glb should be written");
+        let json = String::from_utf8_lossy(&glb);
+        assert!(json.contains("baseColorTexture"));
+        assert!(json.contains(r#""roughnessFactor": 0.3"#));
+        std::fs::remove_file(&path).ok();
+
+        // A .gltf reports its buffer and textures, all among the files the
+        // format may write
+        let path = path.with_extension("gltf");
+        let exported = export_scene(&scene, &ExportOptions::new(&path).with_resolution(16))
+            .expect("export should succeed");
+        assert_eq!(exported.companions.len(), 3);
+        let possible = ExportFormat::Gltf.companion_paths(&path);
+        for file in &exported.companions {
+            assert!(file.exists(), "{}", file.display());
+            assert!(possible.contains(file), "{}", file.display());
+            std::fs::remove_file(file).ok();
+        }
+        std::fs::remove_file(&path).ok();
     }
 }
@@ -5,8 +5,8 @@
 //!
 //! ## Example
 //!
-//! ```ignore
-//! use soyuz_engine::Engine;
+//! ```no_run
+//! use soyuz_engine::{Engine, ExportFormat, ExportOptions, MeshAlgorithm, PreviewOptions};
 //!
 //! let mut engine = Engine::new();
 //!
@@ -22,16 +22,22 @@
 //! engine.preview(PreviewOptions::default())?;
 //!
 //! // Export to file
-//! engine.export(ExportOptions {
+//! engine.export(&ExportOptions {
 //!     path: "model.glb".into(),
-//!     format: ExportFormat::Glb,
+//!     format: Some(ExportFormat::Glb),
 //!     resolution: 128,
+//!     cell_size: None,
+//!     algorithm: MeshAlgorithm::DualContouring,
 //!     optimize: true,
 //! })?;
+//! # Ok::<(), anyhow::Error>(())
 //! ```
 
+pub mod animation;
+pub mod cpu_raymarcher;
 pub mod export;
 pub mod preview;
+pub mod render;
 pub mod scene;
 
 #[cfg(feature = "file-watcher")]
@@ -40,19 +46,26 @@ pub mod watch;
 use anyhow::Result;
 use scene::Scene;
 use soyuz_script::ScriptEngine;
+use std::ops::Range;
 use std::path::Path;
 
 // Re-export commonly used types from dependencies
+pub use soyuz_core::camera::Camera;
 pub use soyuz_core::export::MeshExport;
-pub use soyuz_core::mesh::{Mesh, MeshConfig, OptimizeConfig, SdfToMesh};
-pub use soyuz_render::{Camera, WindowConfig, run_preview_with_sdf};
-pub use soyuz_script::{CpuSdf, SceneResult};
+pub use soyuz_core::mesh::{Mesh, MeshAlgorithm, MeshConfig, OptimizeConfig, SdfToMesh};
+pub use soyuz_render::{WindowConfig, run_preview_with_sdf};
+pub use soyuz_script::{CpuSdf, ParamValue, SceneResult, ScriptParam, parse_param_override};
 pub use soyuz_sdf::{Environment, SdfOp};
 
 // Re-export our own types
+pub use animation::{
+    AnimationFormat, AnimationOptions, AnimationResult, FrameRange, MAX_FRAMES, MAX_SHEET_PIXELS,
+};
+pub use cpu_raymarcher::{Axis, CpuRaymarcher, Projection, RenderMode, SlicePlane};
 pub use export::{ExportFormat, ExportOptions, ExportResult};
-pub use preview::PreviewOptions;
-pub use scene::SceneError;
+pub use preview::{PreviewOptions, run_watch_preview};
+pub use render::{RenderBackend, RenderOptions, RenderResult};
+pub use scene::{SCENE_EXTENSION, SCENE_FORMAT_VERSION, SceneError};
 
 #[cfg(feature = "file-watcher")]
 pub use soyuz_script::{ScriptWatcher, WatchEvent};
@@ -99,10 +112,14 @@ impl Engine {
     pub fn load_script(&mut self, path: &Path) -> Result<&Scene> {
         let scene_result = self.scripting.eval_scene_file(path)?;
 
+        #[cfg(feature = "file-watcher")]
+        if let Some(watcher) = &mut self.watcher {
+            watcher.watch_imports(path, &scene_result.imports)?;
+        }
+
         self.current_scene = Some(Scene {
-            sdf: scene_result.sdf,
-            environment: scene_result.environment,
             source_path: Some(path.to_path_buf()),
+            ..Scene::from_scene_result(scene_result)
         });
 
         self.current_scene
@@ -117,22 +134,94 @@ impl Engine {
     pub fn run_script(&mut self, code: &str) -> Result<&Scene> {
         let scene_result = self.scripting.eval_scene(code)?;
 
-        self.current_scene = Some(Scene {
-            sdf: scene_result.sdf,
-            environment: scene_result.environment,
-            source_path: None,
-        });
+        self.current_scene = Some(Scene::from_scene_result(scene_result));
 
         self.current_scene
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("Scene was not stored"))
     }
 
+    /// Load a scene saved as `.soyuz.json`, without running any script
+    ///
+    /// Replaces the current scene, like [`load_script`](Self::load_script).
+    pub fn load_scene(&mut self, path: &Path) -> Result<&Scene> {
+        let scene = Scene::load(path)?;
+        Ok(self.current_scene.insert(scene))
+    }
+
+    /// Load a script or a saved scene, depending on the file name
+    pub fn load(&mut self, path: &Path) -> Result<&Scene> {
+        if Scene::is_scene_file(path) {
+            self.load_scene(path)
+        } else {
+            self.load_script(path)
+        }
+    }
+
+    /// Save the current scene as `.soyuz.json`
+    pub fn save_scene(&self, path: &Path) -> Result<()> {
+        let scene = self
+            .current_scene
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No scene loaded"))?;
+
+        scene.save(path)?;
+        Ok(())
+    }
+
     /// Compile a script to check for syntax errors without executing
     pub fn compile(&self, code: &str) -> Result<()> {
         self.scripting.compile(code)
     }
 
+    /// Override a script parameter declared with `param()`
+    ///
+    /// Applies to scripts run or loaded afterwards; call
+    /// [`reload`](Self::reload) to re-run the current one.
+    pub fn set_param(&mut self, name: impl Into<String>, value: impl Into<ParamValue>) {
+        self.scripting.set_param(name, value);
+    }
+
+    /// Remove all parameter overrides
+    pub fn clear_params(&mut self) {
+        self.scripting.clear_params();
+    }
+
+    /// Seed the random numbers of scripts run or loaded afterwards
+    ///
+    /// A script run with the same seed and parameters always builds the
+    /// same shape, and so exports the same mesh.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.scripting.set_seed(seed);
+    }
+
+    /// Set the `time` (in seconds) and `frame` that scripts run or loaded
+    /// afterwards see
+    pub fn set_time(&mut self, time: f64, frame: i64) {
+        self.scripting.set_time(time, frame);
+    }
+
+    /// Re-run the current scene's source script, picking up new parameter
+    /// overrides
+    ///
+    /// Scenes that weren't loaded from a file are left as they are.
+    pub fn reload(&mut self) -> Result<&Scene> {
+        let source = self
+            .current_scene
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No scene loaded"))?
+            .source_path
+            .clone();
+
+        match source {
+            Some(path) => self.load(&path),
+            None => self
+                .current_scene
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Scene was not stored")),
+        }
+    }
+
     // ========================================================================
     // Scene Access
     // ========================================================================
@@ -178,6 +267,82 @@ impl Engine {
         preview::run_preview(self.current_scene.as_ref(), options)
     }
 
+    // ========================================================================
+    // Rendering
+    // ========================================================================
+
+    /// Render the current scene to an image
+    ///
+    /// Uses the GPU when one is available and the CPU raymarcher otherwise
+    /// (see [`RenderBackend`]).
+    pub fn render(&self, options: &RenderOptions) -> Result<RenderResult> {
+        let scene = self
+            .current_scene
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No scene loaded"))?;
+
+        render::render_scene(scene, options)
+    }
+
+    /// Render the current scene and save it as a PNG
+    pub fn render_to_file(&self, path: &Path, options: &RenderOptions) -> Result<RenderResult> {
+        let result = self.render(options)?;
+        result
+            .image
+            .save_with_format(path, image::ImageFormat::Png)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {e}", path.display()))?;
+        Ok(result)
+    }
+
+    // ========================================================================
+    // Animation
+    // ========================================================================
+
+    /// Evaluate a script at every frame of `frames`, one scene per frame
+    ///
+    /// Each evaluation sees that frame's `time` and `frame`. A script that
+    /// never reads them (or a saved scene) is evaluated only once and its
+    /// scene repeated. The last frame is left as the current scene, and the
+    /// engine's own time is restored afterwards.
+    pub fn eval_frames(&mut self, script: &Path, frames: &FrameRange) -> Result<Vec<Scene>> {
+        frames.validate()?;
+        let previous = (self.scripting.time(), self.scripting.frame());
+
+        let mut scenes: Vec<Scene> = Vec::with_capacity(frames.len());
+        let mut result = Ok(());
+        for frame in frames.frames() {
+            if let Some(last) = scenes.last().filter(|scene| !scene.animated) {
+                scenes.push(last.clone());
+                continue;
+            }
+            self.scripting.set_time(frames.time(frame), frame);
+            match self.load(script) {
+                Ok(scene) => scenes.push(scene.clone()),
+                Err(e) => {
+                    result = Err(e.context(format!("Failed to evaluate frame {frame}")));
+                    break;
+                }
+            }
+        }
+
+        self.scripting.set_time(previous.0, previous.1);
+        result.map(|()| scenes)
+    }
+
+    /// Render a script over a frame range, as an image sequence, animated
+    /// GIF or APNG, or sprite sheet
+    ///
+    /// With `options.turntable`, the camera also orbits the scene once
+    /// over the frames, which makes 360° turntables of still scripts.
+    pub fn render_animation(
+        &mut self,
+        script: &Path,
+        options: &AnimationOptions,
+    ) -> Result<AnimationResult> {
+        let scenes = self.eval_frames(script, &options.frames)?;
+        animation::render_animation(&scenes, options)
+    }
+
     // ========================================================================
     // Export
     // ========================================================================
@@ -194,6 +359,30 @@ impl Engine {
         export::export_scene(scene, options)
     }
 
+    /// Export one variant of a script for each seed in `seeds`
+    ///
+    /// Each variant is written to `options.path` with the seed appended to
+    /// the file name (`rock.glb` becomes `rock_seed0.glb`, `rock_seed1.glb`,
+    /// ...). The last variant is left as the current scene, and the engine's
+    /// own seed is restored afterwards.
+    pub fn export_variants(
+        &mut self,
+        script: &Path,
+        seeds: Range<u64>,
+        options: &ExportOptions,
+    ) -> Result<Vec<ExportResult>> {
+        let previous = self.scripting.seed();
+        let results = seeds
+            .map(|seed| {
+                self.scripting.set_seed(seed);
+                self.load_script(script)?;
+                self.export(&options.for_seed(seed))
+            })
+            .collect();
+        self.scripting.set_seed(previous);
+        results
+    }
+
     /// Generate a mesh from the current scene without saving to file
     ///
     /// Useful for further processing or custom export formats.
@@ -211,15 +400,23 @@ impl Engine {
     // ========================================================================
 
     /// Start watching a file or directory for changes
+    ///
+    /// Files imported by the current scene's script are watched too, and
+    /// so are the imports of scripts loaded later.
     #[cfg(feature = "file-watcher")]
     pub fn watch(&mut self, path: &Path) -> Result<()> {
         // Create watcher if not already created
-        if self.watcher.is_none() {
-            self.watcher = Some(ScriptWatcher::new(None)?);
-        }
+        let watcher = match &mut self.watcher {
+            Some(watcher) => watcher,
+            None => self.watcher.insert(ScriptWatcher::new(None)?),
+        };
 
-        if let Some(watcher) = &mut self.watcher {
-            watcher.watch(path)?;
+        watcher.watch(path)?;
+
+        if let Some(scene) = &self.current_scene
+            && let Some(source) = &scene.source_path
+        {
+            watcher.watch_imports(source, &scene.imports)?;
         }
 
         Ok(())
@@ -267,10 +464,13 @@ impl Engine {
             .as_ref()
             .and_then(|s| s.source_path.as_ref());
 
+        // The watcher reports canonical paths, and reports changes to
+        // imported files as changes to the scripts importing them
         let should_reload = source_path.is_some_and(|source| {
+            let source = source.canonicalize().unwrap_or_else(|_| source.clone());
             events.iter().any(|event| match event {
                 WatchEvent::Modified(p) | WatchEvent::Created(p) | WatchEvent::Deleted(p) => {
-                    p == source
+                    *p == source
                 }
                 WatchEvent::Error(_) => false,
             })
@@ -279,7 +479,7 @@ impl Engine {
         if should_reload
             && let Some(path) = source_path.cloned()
         {
-            self.load_script(&path)?;
+            self.load(&path)?;
             return Ok(true);
         }
 
@@ -308,8 +508,10 @@ impl Default for Engine {
 }
 
 #[cfg(test)]
+#[allow(clippy::expect_used)]
 mod tests {
     use super::*;
+    use std::path::PathBuf;
 
     #[test]
     fn test_engine_creation() {
@@ -334,6 +536,136 @@ mod tests {
         assert!(engine.compile("sphere(").is_err());
     }
 
+    #[test]
+    fn test_save_and_load_scene() {
+        let path = std::env::temp_dir().join(format!(
+            "soyuz_engine_test_{}{SCENE_EXTENSION}",
+            std::process::id()
+        ));
+        let mut engine = Engine::new();
+        engine.run_script("sphere(0.5).union(cube(0.4))").ok();
+        assert!(engine.save_scene(&path).is_ok());
+
+        let mut loaded = Engine::new();
+        assert!(loaded.load(&path).is_ok());
+        assert!(matches!(loaded.sdf(), Some(SdfOp::Union { .. })));
+        assert_eq!(
+            loaded.scene().and_then(|s| s.source_path.clone()),
+            Some(path.clone())
+        );
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_param_overrides() {
+        let script = r#"sphere(param("radius", 0.5, 0.1, 1.0))"#;
+        let mut engine = Engine::new();
+        engine.set_param("radius", 0.75);
+        engine.run_script(script).ok();
+
+        let scene = engine.scene().expect("scene should load");
+        assert!(matches!(scene.sdf, SdfOp::Sphere { radius } if (radius - 0.75).abs() < 1e-6));
+        assert_eq!(scene.params[0].value, ParamValue::Float(0.75));
+
+        engine.clear_params();
+        engine.run_script(script).ok();
+        assert!(
+            matches!(engine.sdf(), Some(SdfOp::Sphere { radius }) if (radius - 0.5).abs() < 1e-6)
+        );
+    }
+
+    #[test]
+    fn test_reload_applies_new_params() {
+        let path =
+            std::env::temp_dir().join(format!("soyuz_engine_params_{}.rhai", std::process::id()));
+        std::fs::write(&path, r#"cube(param("size", 0.5, 0.1, 2.0))"#).expect("write script");
+
+        let mut engine = Engine::new();
+        engine.load_script(&path).expect("script should load");
+        engine.set_param("size", 2_i64);
+        let scene = engine.reload().expect("script should reload");
+        assert_eq!(scene.params[0].value, ParamValue::Float(2.0));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_export_variants_per_seed() {
+        let dir = std::env::temp_dir().join(format!("soyuz_variants_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let script = dir.join("rock.rhai");
+        std::fs::write(
+            &script,
+            "sphere(0.5).smooth_subtract(sphere(rand_range(0.1, 0.3)).translate(rand(), rand(), 0.3), 0.05)",
+        )
+        .expect("write script");
+
+        let mut engine = Engine::new();
+        let options = ExportOptions::new(dir.join("rock.stl")).with_resolution(24);
+        let results = engine
+            .export_variants(&script, 0..3, &options)
+            .expect("variants should export");
+        let paths: Vec<PathBuf> = results.iter().map(|r| r.path.clone()).collect();
+        assert_eq!(
+            paths,
+            ["rock_seed0.stl", "rock_seed1.stl", "rock_seed2.stl"].map(|name| dir.join(name))
+        );
+        assert_eq!(engine.scripting().seed(), 0);
+
+        // Seeds give different shapes, and the same seed the same bytes
+        let first = std::fs::read(&paths[0]).expect("read variant");
+        assert_ne!(first, std::fs::read(&paths[1]).expect("read variant"));
+        engine.set_seed(0);
+        engine.load_script(&script).expect("script should load");
+        let again = dir.join("again.stl");
+        engine
+            .export(&ExportOptions::new(&again).with_resolution(24))
+            .expect("export should succeed");
+        assert_eq!(std::fs::read(&again).expect("read export"), first);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_eval_frames_and_turntable() {
+        let dir = std::env::temp_dir().join(format!("soyuz_frames_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let bob = dir.join("bob.rhai");
+        std::fs::write(&bob, "sphere(0.5).translate_y(time)").expect("write script");
+        let still = dir.join("still.rhai");
+        std::fs::write(&still, "sphere(0.5)").expect("write script");
+
+        let mut engine = Engine::new();
+        let frames = FrameRange::new(0, 3, 2.0);
+        let scenes = engine
+            .eval_frames(&bob, &frames)
+            .expect("frames should evaluate");
+        let heights: Vec<String> = scenes.iter().map(|s| format!("{:?}", s.sdf)).collect();
+        assert!(heights[2].contains("1.0"), "{}", heights[2]);
+        assert_ne!(heights[0], heights[1]);
+        assert_eq!(engine.scripting().frame(), 0);
+
+        let scenes = engine
+            .eval_frames(&still, &frames)
+            .expect("frames should evaluate");
+        assert!(scenes.iter().all(|s| !s.animated));
+
+        let options = AnimationOptions::new(dir.join("still.png"))
+            .with_format(AnimationFormat::SpriteSheet)
+            .with_frames(FrameRange::new(0, 4, 12.0))
+            .with_render(RenderOptions::new(16, 16).with_backend(RenderBackend::Cpu))
+            .with_turntable(true);
+        let result = engine
+            .render_animation(&still, &options)
+            .expect("turntable should render");
+        assert_eq!(result.frame_count, 4);
+        assert_eq!(result.backend, RenderBackend::Cpu);
+        let sheet = image::open(&options.path).expect("sheet should be written");
+        assert_eq!((sheet.width(), sheet.height()), (32, 32));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
     #[test]
     fn test_clear_scene() {
         let mut engine = Engine::new();
@@ -1,11 +1,19 @@
 //! Preview window management for the Soyuz engine
 //!
 //! Provides functions to open a real-time preview window for visualizing
-//! SDF scenes. The preview uses GPU raymarching for interactive rendering.
+//! SDF scenes. The preview uses GPU raymarching for interactive rendering,
+//! which also backs GPU renders to images.
 
 use crate::scene::Scene;
 use anyhow::Result;
-use soyuz_render::{WindowConfig, run_preview_with_sdf};
+use image::RgbaImage;
+use soyuz_core::camera::Camera;
+use soyuz_render::{
+    Raymarcher, WatchWindowConfig, WindowConfig, init_headless, run_preview_with_sdf,
+    run_watch_window, wgpu,
+};
+use soyuz_sdf::SdfOp;
+use std::path::{Path, PathBuf};
 
 /// Options for opening a preview window
 #[derive(Debug, Clone)]
@@ -66,6 +74,60 @@ pub fn run_preview(scene: Option<&Scene>, options: PreviewOptions) -> Result<()>
     run_preview_with_sdf(config, sdf)
 }
 
+/// Run a preview window that re-evaluates a script whenever it changes
+///
+/// `eval` turns the script into a shape; errors are shown in the window
+/// title and on stderr, and the last good shape stays on screen. Changes to
+/// any of `watch_paths` also re-run `eval`, which lets a host push new
+/// parameter overrides through a file. This function blocks until the
+/// preview window is closed.
+pub fn run_watch_preview(
+    script: &Path,
+    watch_paths: Vec<PathBuf>,
+    options: PreviewOptions,
+    eval: impl Fn(&Path) -> Result<SdfOp> + Send + 'static,
+) -> Result<()> {
+    let config = WatchWindowConfig {
+        title: options.title,
+        width: options.width,
+        height: options.height,
+        script_path: Some(script.to_path_buf()),
+        watch_paths,
+    };
+
+    run_watch_window(
+        config,
+        Box::new(move |path| eval(path).map_err(|e| format!("{e:#}"))),
+    )
+}
+
+/// Render a scene to one image per camera on the GPU
+///
+/// The GPU and the scene's shader are set up once and shared by every
+/// camera. Fails when no GPU adapter is available; [`crate::render`] falls
+/// back to the CPU raymarcher then.
+pub(crate) fn render_gpu(
+    scene: &Scene,
+    width: u32,
+    height: u32,
+    cameras: &[Camera],
+) -> Result<Vec<RgbaImage>> {
+    let (device, queue) = pollster::block_on(init_headless())?;
+    let raymarcher = Raymarcher::with_sdf_and_env(
+        device,
+        queue,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        &scene.sdf,
+        scene.environment.clone(),
+    );
+    cameras
+        .iter()
+        .map(|camera| -> Result<RgbaImage> {
+            Ok(raymarcher.render_to_image(width, height, camera, 0.0)?)
+        })
+        .collect()
+}
+
 /// Preview controls help text
 pub fn preview_help() -> &'static str {
     soyuz_render::controls_help()
@@ -0,0 +1,223 @@
+//! Rendering scenes to images
+//!
+//! Renders use the GPU raymarcher when a GPU adapter is available and fall
+//! back to the [`CpuRaymarcher`] otherwise, so thumbnails and previews also
+//! work on CI runners and headless build machines.
+
+use crate::cpu_raymarcher::CpuRaymarcher;
+use crate::scene::Scene;
+use anyhow::Result;
+use image::RgbaImage;
+use soyuz_core::camera::Camera;
+use soyuz_script::CpuSdf;
+use std::fmt;
+
+/// Which renderer draws an image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderBackend {
+    /// GPU if an adapter is available, otherwise CPU
+    #[default]
+    Auto,
+    /// GPU raymarcher only; fails without an adapter
+    Gpu,
+    /// CPU raymarcher only
+    Cpu,
+}
+
+impl RenderBackend {
+    /// Parse a backend name: `auto`, `gpu` or `cpu`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "gpu" => Some(Self::Gpu),
+            "cpu" => Some(Self::Cpu),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RenderBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Auto => "auto",
+            Self::Gpu => "GPU",
+            Self::Cpu => "CPU",
+        })
+    }
+}
+
+/// Options for rendering a scene to an image
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Image width in pixels
+    pub width: u32,
+
+    /// Image height in pixels
+    pub height: u32,
+
+    /// Camera to render from; `None` frames the whole scene from a
+    /// three-quarter view
+    pub camera: Option<Camera>,
+
+    /// Renderer to use
+    pub backend: RenderBackend,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self::new(512, 512)
+    }
+}
+
+impl RenderOptions {
+    /// Create render options for an image size
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            camera: None,
+            backend: RenderBackend::Auto,
+        }
+    }
+
+    /// Render from a specific camera
+    pub fn with_camera(mut self, camera: Camera) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+
+    /// Choose the renderer
+    pub fn with_backend(mut self, backend: RenderBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+}
+
+/// Result of a successful render
+#[derive(Debug, Clone)]
+pub struct RenderResult {
+    /// The rendered image
+    pub image: RgbaImage,
+
+    /// Renderer that drew it (`Gpu` or `Cpu`, never `Auto`)
+    pub backend: RenderBackend,
+}
+
+/// Camera that frames a scene's surface from a three-quarter view
+pub fn frame_scene(scene: &Scene, aspect: f32) -> Camera {
+    let bounds = CpuSdf::new(scene.sdf.clone()).tight_bounds();
+    let mut camera = Camera {
+        aspect,
+        ..Camera::default()
+    };
+    camera.frame_bounds(bounds.min, bounds.max, 0.3);
+    camera
+}
+
+/// Render a scene to an image
+///
+/// With [`RenderBackend::Auto`], a GPU failure (usually no adapter) is
+/// logged and the image is drawn on the CPU instead.
+pub fn render_scene(scene: &Scene, options: &RenderOptions) -> Result<RenderResult> {
+    let aspect = options.width as f32 / options.height.max(1) as f32;
+    let camera = match &options.camera {
+        Some(camera) => camera.clone(),
+        None => frame_scene(scene, aspect),
+    };
+
+    render_views(scene, &[camera], options)?
+        .pop()
+        .ok_or_else(|| anyhow::anyhow!("Nothing was rendered"))
+}
+
+/// Render a scene from several cameras, one image each
+///
+/// The cameras replace `options.camera`. Renderer setup is shared between
+/// the images, and every image is drawn by the same renderer: with
+/// [`RenderBackend::Auto`], a GPU failure sends all of them to the CPU.
+pub fn render_views(
+    scene: &Scene,
+    cameras: &[Camera],
+    options: &RenderOptions,
+) -> Result<Vec<RenderResult>> {
+    let (width, height) = (options.width, options.height);
+    if width == 0 || height == 0 {
+        anyhow::bail!("Cannot render a {width}x{height} image");
+    }
+
+    let aspect = width as f32 / height as f32;
+    let cameras: Vec<Camera> = cameras
+        .iter()
+        .map(|camera| Camera {
+            aspect,
+            ..camera.clone()
+        })
+        .collect();
+
+    let results = |images: Vec<RgbaImage>, backend| {
+        images
+            .into_iter()
+            .map(|image| RenderResult { image, backend })
+            .collect::<Vec<_>>()
+    };
+    let cpu = || {
+        let raymarcher = CpuRaymarcher::new(&scene.sdf, scene.environment.clone());
+        let images = cameras
+            .iter()
+            .map(|camera| raymarcher.render_to_image(width, height, camera))
+            .collect();
+        results(images, RenderBackend::Cpu)
+    };
+    let gpu = || {
+        crate::preview::render_gpu(scene, width, height, &cameras)
+            .map(|images| results(images, RenderBackend::Gpu))
+    };
+
+    match options.backend {
+        RenderBackend::Cpu => Ok(cpu()),
+        RenderBackend::Gpu => gpu(),
+        RenderBackend::Auto => Ok(gpu().unwrap_or_else(|e| {
+            tracing::warn!("GPU rendering unavailable ({e:#}), rendering on the CPU");
+            cpu()
+        })),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use soyuz_sdf::{Environment, SdfOp};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_backend_names() {
+        assert_eq!(RenderBackend::from_name("CPU"), Some(RenderBackend::Cpu));
+        assert_eq!(RenderBackend::from_name("auto"), Some(RenderBackend::Auto));
+        assert_eq!(RenderBackend::from_name("vulkan"), None);
+    }
+
+    #[test]
+    fn test_frame_scene_sees_whole_surface() {
+        let sdf = SdfOp::Translate {
+            inner: Arc::new(SdfOp::Sphere { radius: 2.0 }),
+            offset: [5.0, 0.0, 0.0],
+        };
+        let scene = Scene::new(sdf, Environment::default());
+        let camera = frame_scene(&scene, 1.0);
+        assert!((camera.target.x - 5.0).abs() < 0.1);
+        assert!(camera.distance() > 4.0);
+    }
+
+    #[test]
+    fn test_render_on_cpu() {
+        let scene = Scene::new(SdfOp::Sphere { radius: 0.5 }, Environment::default());
+        let options = RenderOptions::new(24, 16).with_backend(RenderBackend::Cpu);
+        let result = render_scene(&scene, &options).expect("CPU render should succeed");
+        assert_eq!(result.backend, RenderBackend::Cpu);
+        assert_eq!(result.image.dimensions(), (24, 16));
+
+        let empty = RenderOptions::new(0, 16).with_backend(RenderBackend::Cpu);
+        assert!(render_scene(&scene, &empty).is_err());
+    }
+}
@@ -3,11 +3,31 @@
 //! A Scene contains an SDF geometry and its associated environment settings
 //! (lighting, materials, background). It represents the complete renderable
 //! state produced by evaluating a Rhai script.
+//!
+//! Scenes can be saved as `.soyuz.json` files and loaded back without the
+//! script, for previewing and exporting on machines that only have the
+//! result. The format is described under "Saved Scenes" in the README.
 
+use serde::{Deserialize, Serialize};
+use soyuz_core::material::{Material, MaterialChannel};
+use soyuz_script::ScriptParam;
 use soyuz_sdf::{Environment, SdfOp};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+/// Value of the `format` field that identifies a saved scene
+pub const SCENE_FORMAT: &str = "soyuz-scene";
+
+/// Version of the saved scene format written by [`Scene::save`]
+///
+/// Bumped only when a change would make older readers misinterpret a file.
+/// Added shapes and fields don't need a bump: older versions reject shapes
+/// they don't know and fill in missing fields with defaults.
+pub const SCENE_FORMAT_VERSION: u32 = 1;
+
+/// File extension of saved scenes
+pub const SCENE_EXTENSION: &str = ".soyuz.json";
+
 /// Errors that can occur when working with scenes
 #[derive(Error, Debug)]
 pub enum SceneError {
@@ -22,6 +42,22 @@ pub enum SceneError {
     /// Failed to reload scene
     #[error("Failed to reload scene: {0}")]
     ReloadFailed(String),
+
+    /// Failed to read or write a scene file
+    #[error("Failed to access scene file: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Scene file is not valid JSON or doesn't match the format
+    #[error("Invalid scene file: {0}")]
+    InvalidFormat(#[from] serde_json::Error),
+
+    /// Scene file was written by a newer version of Soyuz
+    #[error("Scene file version {found} is newer than the supported version {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+
+    /// The material uses procedural textures, which can't be saved
+    #[error("Can't save material: the {0} channel uses a texture")]
+    TexturedMaterial(&'static str),
 }
 
 /// A complete scene with geometry and environment
@@ -30,6 +66,7 @@ pub enum SceneError {
 /// a procedural asset:
 /// - The SDF geometry tree
 /// - Environment settings (lighting, materials, background)
+/// - Optionally, a PBR material for textured export
 /// - Optionally, the source file path for reload/watch functionality
 #[derive(Debug, Clone)]
 pub struct Scene {
@@ -39,8 +76,22 @@ pub struct Scene {
     /// Environment configuration (lighting, materials, background)
     pub environment: Environment,
 
+    /// PBR material from `set_material()` (embedded in glTF/GLB exports)
+    pub material: Option<Material>,
+
     /// Source file path (if loaded from file)
     pub source_path: Option<PathBuf>,
+
+    /// Files the source script imported (not saved with the scene)
+    pub imports: Vec<PathBuf>,
+
+    /// Parameters the source script declared, with the values it used (not
+    /// saved with the scene)
+    pub params: Vec<ScriptParam>,
+
+    /// Whether the source script read `time` or `frame`, so each frame of
+    /// an animation must be evaluated again (not saved with the scene)
+    pub animated: bool,
 }
 
 impl Scene {
@@ -49,7 +100,11 @@ impl Scene {
         Self {
             sdf,
             environment,
+            material: None,
             source_path: None,
+            imports: Vec::new(),
+            params: Vec::new(),
+            animated: false,
         }
     }
 
@@ -58,7 +113,11 @@ impl Scene {
         Self {
             sdf,
             environment,
+            material: None,
             source_path: Some(path),
+            imports: Vec::new(),
+            params: Vec::new(),
+            animated: false,
         }
     }
 
@@ -67,7 +126,11 @@ impl Scene {
         Self {
             sdf: result.sdf,
             environment: result.environment,
+            material: result.material,
             source_path: None,
+            imports: result.imports,
+            params: result.params,
+            animated: result.animated,
         }
     }
 
@@ -83,6 +146,256 @@ impl Scene {
             .and_then(|p| p.file_name())
             .map(|n| n.to_string_lossy().to_string())
     }
+
+    /// Check whether a path names a saved scene (`.soyuz.json`)
+    pub fn is_scene_file(path: &Path) -> bool {
+        path.file_name()
+            .is_some_and(|n| n.to_string_lossy().ends_with(SCENE_EXTENSION))
+    }
+
+    /// Serialize the scene to pretty-printed JSON
+    ///
+    /// Fails if the material uses procedural textures, which have no saved
+    /// form; plain values and colors are kept.
+    pub fn to_json(&self) -> Result<String, SceneError> {
+        let file = SceneFile {
+            format: SCENE_FORMAT.to_string(),
+            version: SCENE_FORMAT_VERSION,
+            sdf: self.sdf.clone(),
+            environment: self.environment.clone(),
+            material: self
+                .material
+                .as_ref()
+                .map(MaterialData::try_from)
+                .transpose()?,
+        };
+        // Every node of the shape tree is two levels of JSON
+        let nesting = 2 * self.sdf.depth();
+        on_large_stack(nesting, || Ok(serde_json::to_string_pretty(&file)?))
+    }
+
+    /// Parse a scene from JSON written by [`to_json`](Self::to_json)
+    pub fn from_json(json: &str) -> Result<Self, SceneError> {
+        let depth = nesting_depth(json);
+        if depth > MAX_NESTING {
+            return Err(invalid_format(format!(
+                "nested {depth} levels deep, more than the limit of {MAX_NESTING}"
+            )));
+        }
+
+        on_large_stack(depth, || {
+            // Check the version before the contents, so a newer file reports
+            // that rather than whichever shape it doesn't recognise
+            let header: SceneHeader = parse_json(json)?;
+            if header.format != SCENE_FORMAT {
+                return Err(invalid_format(format!(
+                    "expected format \"{SCENE_FORMAT}\", found \"{}\"",
+                    header.format
+                )));
+            }
+            if header.version > SCENE_FORMAT_VERSION {
+                return Err(SceneError::UnsupportedVersion {
+                    found: header.version,
+                    supported: SCENE_FORMAT_VERSION,
+                });
+            }
+
+            let file: SceneFile = parse_json(json)?;
+            Ok(Self {
+                sdf: file.sdf,
+                environment: file.environment,
+                material: file.material.map(Material::from),
+                source_path: None,
+                imports: Vec::new(),
+                params: Vec::new(),
+                animated: false,
+            })
+        })
+    }
+
+    /// Save the scene to a `.soyuz.json` file
+    pub fn save(&self, path: &Path) -> Result<(), SceneError> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Load a scene saved with [`save`](Self::save)
+    ///
+    /// The file becomes the scene's source, so watching it reloads the
+    /// scene when it is saved again.
+    pub fn load(path: &Path) -> Result<Self, SceneError> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(Self {
+            source_path: Some(path.to_path_buf()),
+            ..Self::from_json(&json)?
+        })
+    }
+}
+
+/// Deepest JSON nesting a scene file may have, about a thousand nested shapes
+const MAX_NESTING: usize = 2048;
+
+/// Deepest JSON nesting saved and loaded on the caller's stack, about a
+/// hundred nested shapes
+///
+/// serde recurses a few times for every node of the shape tree, and
+/// unoptimized builds use kilobytes of stack on each call, so deeper scenes
+/// get a thread of their own with [`SERDE_STACK_SIZE`].
+const INLINE_NESTING: usize = 256;
+
+/// Stack size for saving and loading deeply nested scenes
+const SERDE_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+/// Run `f`, on a thread with a stack deep enough for the shape tree if its
+/// JSON `nesting` is more than the caller's stack can be trusted with
+fn on_large_stack<T: Send>(
+    nesting: usize,
+    f: impl FnOnce() -> Result<T, SceneError> + Send,
+) -> Result<T, SceneError> {
+    if nesting <= INLINE_NESTING {
+        return f();
+    }
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .name("soyuz-scene-io".to_string())
+            .stack_size(SERDE_STACK_SIZE)
+            .spawn_scoped(scope, f)?
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// Parse JSON without serde_json's nesting limit
+///
+/// Every node of the shape tree is two levels of JSON, so a union folded in
+/// a loop passes the default limit of 128 after a few dozen shapes. Callers
+/// check [`nesting_depth`] against [`MAX_NESTING`] first instead.
+fn parse_json<'de, T: Deserialize<'de>>(json: &'de str) -> Result<T, serde_json::Error> {
+    let mut de = serde_json::Deserializer::from_str(json);
+    de.disable_recursion_limit();
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Deepest nesting of objects and arrays in a JSON document
+fn nesting_depth(json: &str) -> usize {
+    let (mut depth, mut deepest) = (0usize, 0);
+    let (mut in_string, mut escaped) = (false, false);
+    for byte in json.bytes() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                deepest = deepest.max(depth);
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    deepest
+}
+
+fn invalid_format(message: String) -> SceneError {
+    SceneError::InvalidFormat(serde::de::Error::custom(message))
+}
+
+/// The fields read before the rest of a scene file
+#[derive(Deserialize)]
+struct SceneHeader {
+    format: String,
+    version: u32,
+}
+
+/// Layout of a `.soyuz.json` file
+#[derive(Serialize, Deserialize)]
+struct SceneFile {
+    format: String,
+    version: u32,
+    sdf: SdfOp,
+    #[serde(default)]
+    environment: Environment,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    material: Option<MaterialData>,
+}
+
+/// A [`Material`] without textures, in its saved form
+#[derive(Serialize, Deserialize)]
+struct MaterialData {
+    albedo: ChannelData,
+    roughness: ChannelData,
+    metallic: ChannelData,
+    ao: ChannelData,
+    emissive: ChannelData,
+    emissive_strength: f32,
+}
+
+/// A [`MaterialChannel`] that holds a plain value or color
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ChannelData {
+    Value(f32),
+    Color([f32; 4]),
+}
+
+impl ChannelData {
+    fn from_channel(channel: &MaterialChannel, name: &'static str) -> Result<Self, SceneError> {
+        match channel {
+            MaterialChannel::Value(v) => Ok(Self::Value(*v)),
+            MaterialChannel::Color(c) => Ok(Self::Color(*c)),
+            MaterialChannel::Texture(_) => Err(SceneError::TexturedMaterial(name)),
+        }
+    }
+}
+
+impl From<ChannelData> for MaterialChannel {
+    fn from(data: ChannelData) -> Self {
+        match data {
+            ChannelData::Value(v) => Self::Value(v),
+            ChannelData::Color(c) => Self::Color(c),
+        }
+    }
+}
+
+impl TryFrom<&Material> for MaterialData {
+    type Error = SceneError;
+
+    fn try_from(material: &Material) -> Result<Self, Self::Error> {
+        if material.normal.is_some() {
+            return Err(SceneError::TexturedMaterial("normal"));
+        }
+        Ok(Self {
+            albedo: ChannelData::from_channel(&material.albedo, "albedo")?,
+            roughness: ChannelData::from_channel(&material.roughness, "roughness")?,
+            metallic: ChannelData::from_channel(&material.metallic, "metallic")?,
+            ao: ChannelData::from_channel(&material.ao, "ao")?,
+            emissive: ChannelData::from_channel(&material.emissive, "emissive")?,
+            emissive_strength: material.emissive_strength,
+        })
+    }
+}
+
+impl From<MaterialData> for Material {
+    fn from(data: MaterialData) -> Self {
+        Self {
+            albedo: data.albedo.into(),
+            roughness: data.roughness.into(),
+            metallic: data.metallic.into(),
+            normal: None,
+            ao: data.ao.into(),
+            emissive: data.emissive.into(),
+            emissive_strength: data.emissive_strength,
+        }
+    }
 }
 
 impl Default for Scene {
@@ -90,14 +403,22 @@ impl Default for Scene {
         Self {
             sdf: SdfOp::Sphere { radius: 0.5 },
             environment: Environment::default(),
+            material: None,
             source_path: None,
+            imports: Vec::new(),
+            params: Vec::new(),
+            animated: false,
         }
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::expect_used)]
 mod tests {
     use super::*;
+    use soyuz_core::texture::pattern::Checker;
+    use soyuz_sdf::{Affine, SweepCurve, SweepPath};
+    use std::sync::Arc;
 
     #[test]
     fn test_scene_creation() {
@@ -124,4 +445,117 @@ mod tests {
         let scene = Scene::default();
         assert!(matches!(scene.sdf, SdfOp::Sphere { .. }));
     }
+
+    #[test]
+    fn test_json_round_trip() {
+        let path = SweepPath::new(
+            SweepCurve::CatmullRom,
+            vec![[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [2.0, 0.0, 0.0]],
+        );
+        let transform = Affine::new(
+            [[2.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            [0.0, 1.0, 0.0],
+        )
+        .expect("invertible");
+        let sdf = SdfOp::Union {
+            a: Arc::new(SdfOp::Tube {
+                path: path.clone(),
+                start_radius: 0.2,
+                end_radius: 0.1,
+            }),
+            b: Arc::new(SdfOp::Transform {
+                inner: Arc::new(SdfOp::Sphere { radius: 0.5 }),
+                transform,
+            }),
+        };
+        let mut scene = Scene::new(sdf, Environment::default());
+        scene.environment.fog_density = 0.25;
+        scene.material = Some(Material::pbr().albedo_color(0.9, 0.1, 0.1).metallic(1.0));
+
+        let json = scene.to_json().expect("scene saves");
+        let loaded = Scene::from_json(&json).expect("scene loads");
+
+        let SdfOp::Union { a, b } = &loaded.sdf else {
+            panic!("expected a union, got {:?}", loaded.sdf);
+        };
+        let SdfOp::Tube {
+            path: loaded_path, ..
+        } = a.as_ref()
+        else {
+            panic!("expected a tube, got {a:?}");
+        };
+        // Derived data is rebuilt from the saved inputs
+        assert_eq!(loaded_path.segments(), path.segments());
+        assert!(matches!(b.as_ref(), SdfOp::Transform { transform: t, .. } if *t == transform));
+        assert!((loaded.environment.fog_density - 0.25).abs() < f32::EPSILON);
+        let material = loaded.material.expect("material is kept");
+        assert!((material.metallic_factor() - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_deep_trees_load() {
+        let mut sdf = SdfOp::Sphere { radius: 0.1 };
+        for i in 0..500 {
+            sdf = SdfOp::Union {
+                a: Arc::new(sdf),
+                b: Arc::new(SdfOp::Translate {
+                    inner: Arc::new(SdfOp::Sphere { radius: 0.1 }),
+                    offset: [i as f32 * 0.1, 0.0, 0.0],
+                }),
+            };
+        }
+        // Deep enough to need the large stack
+        assert_eq!(sdf.depth(), 502);
+        assert!(2 * sdf.depth() > INLINE_NESTING);
+        let json = Scene::new(sdf, Environment::default())
+            .to_json()
+            .expect("scene saves");
+        assert!(Scene::from_json(&json).is_ok());
+    }
+
+    #[test]
+    fn test_textured_material_is_rejected() {
+        let scene = Scene {
+            material: Some(Material::pbr().roughness_texture(Checker::new(4.0))),
+            ..Scene::default()
+        };
+        assert!(matches!(
+            scene.to_json(),
+            Err(SceneError::TexturedMaterial("roughness"))
+        ));
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        let json = format!(
+            r#"{{"format": "{SCENE_FORMAT}", "version": {}, "sdf": {{"hyperbolic_sphere": {{}}}}}}"#,
+            SCENE_FORMAT_VERSION + 1
+        );
+        assert!(matches!(
+            Scene::from_json(&json),
+            Err(SceneError::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let json = format!(
+            r#"{{"format": "{SCENE_FORMAT}", "version": 1, "sdf": {{"sphere": {{"radius": 2.0}}}}}}"#
+        );
+        let scene = Scene::from_json(&json).expect("scene loads");
+        assert!(matches!(scene.sdf, SdfOp::Sphere { radius } if (radius - 2.0).abs() < 1e-6));
+        assert!(scene.material.is_none());
+
+        assert!(matches!(
+            Scene::from_json(r#"{"format": "other", "version": 1}"#),
+            Err(SceneError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_is_scene_file() {
+        assert!(Scene::is_scene_file(Path::new("out/crate.soyuz.json")));
+        assert!(!Scene::is_scene_file(Path::new("crate.json")));
+        assert!(!Scene::is_scene_file(Path::new("crate.rhai")));
+    }
 }
@@ -3,14 +3,14 @@
 //! Reads formula specifications from TOML files and generates:
 //! - Rust implementations
 //! - WGSL shader code
-//! - Test cases
+//! - Test cases, for both the Rust and the WGSL code
 //! - Documentation
 
 // Build scripts should panic on errors - the build must fail if something is wrong
 #![allow(clippy::unwrap_used)]
 #![allow(clippy::expect_used)]
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::env;
 use std::fs;
 use std::path::Path;
@@ -33,15 +33,18 @@ struct FormulaInfo {
     category: String,
     description: String,
     verified_date: String,
-    params: HashMap<String, ParamInfo>,
+    params: Vec<ParamInfo>,
     returns: ReturnInfo,
+    #[serde(default)]
     steps: Vec<Step>,
+    #[serde(default)]
     pitfalls: Vec<Pitfall>,
     tests: Vec<TestCase>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ParamInfo {
+    name: String,
     #[serde(rename = "type")]
     param_type: String,
     description: String,
@@ -72,23 +75,63 @@ struct Pitfall {
 #[derive(Debug, Deserialize)]
 struct TestCase {
     name: String,
-    input: TestInput,
-    expected: Vec<f64>,
+    /// Argument values keyed by parameter name
+    input: HashMap<String, TestValue>,
+    expected: TestValue,
     tolerance: f64,
     description: String,
 }
 
+/// A scalar (`f32`) or vector (`vec2`/`vec3`/`vec4`) test value
 #[derive(Debug, Deserialize)]
-struct TestInput {
-    p: Vec<f64>,
-    n: f64,
+#[serde(untagged)]
+enum TestValue {
+    Scalar(f64),
+    Vector(Vec<f64>),
+}
+
+impl TestValue {
+    /// Components separated by commas, e.g. `1.000000, 2.000000`
+    fn components(&self) -> String {
+        match self {
+            TestValue::Scalar(v) => format!("{v:.6}"),
+            TestValue::Vector(v) => v
+                .iter()
+                .map(|c| format!("{c:.6}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+
+    /// Rust literal for this value, e.g. `Vec3::new(1.000000, 2.000000, 3.000000)`
+    fn rust_literal(&self) -> String {
+        match self {
+            TestValue::Scalar(v) => format!("{v:.6}_f32"),
+            TestValue::Vector(v) => format!("Vec{}::new({})", v.len(), self.components()),
+        }
+    }
+
+    /// Compact form for the docs, e.g. `(1.00, 2.00)` or `8.00`
+    fn display(&self) -> String {
+        match self {
+            TestValue::Scalar(v) => format!("{v:.2}"),
+            TestValue::Vector(v) => format!(
+                "({})",
+                v.iter()
+                    .map(|c| format!("{c:.2}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct CodegenTemplates {
     rust: TemplateInfo,
     wgsl: TemplateInfo,
-    test: TemplateInfo,
+    /// Optional custom test template; a default assertion is generated otherwise
+    test: Option<TemplateInfo>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -120,53 +163,177 @@ fn generate_rust_code(spec: &FormulaSpec) -> String {
     let formula = &spec.formula;
     let template = &spec.codegen.rust.template;
 
-    template
+    let code = template
         .replace("{name}", &formula.name)
         .replace("{description}", &formula.description)
         .replace("{verified_date}", &formula.verified_date)
         .replace("{step_docs}", &generate_step_docs(&formula.steps))
-        .replace("{pitfall_docs}", &generate_pitfall_docs(&formula.pitfalls))
+        .replace("{pitfall_docs}", &generate_pitfall_docs(&formula.pitfalls));
+
+    unescape_braces(&code)
 }
 
 fn generate_wgsl_code(spec: &FormulaSpec) -> String {
     let formula = &spec.formula;
     let template = &spec.codegen.wgsl.template;
 
-    template
+    let code = template
         .replace("{name}", &formula.name)
         .replace("{description}", &formula.description)
-        .replace("{verified_date}", &formula.verified_date)
+        .replace("{verified_date}", &formula.verified_date);
+
+    unescape_braces(&code)
+}
+
+/// Turn the `{{` / `}}` escapes in templates into literal braces
+///
+/// Runs after placeholder substitution, so `{name}` and friends are already gone.
+fn unescape_braces(code: &str) -> String {
+    code.replace("{{", "{").replace("}}", "}")
 }
 
 fn generate_test_code(spec: &FormulaSpec) -> String {
     let formula = &spec.formula;
-    let template = &spec.codegen.test.template;
+
+    formula
+        .tests
+        .iter()
+        .map(|test| match &spec.codegen.test {
+            Some(template) => generate_custom_test(formula, test, &template.template),
+            None => generate_default_test(formula, test),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Fill a custom test template: `{input_<param>}` and `{expected}` expand to
+/// the value's components, so templates wrap them in their own constructors
+fn generate_custom_test(formula: &FormulaInfo, test: &TestCase, template: &str) -> String {
+    let mut code = template
+        .replace("{name}", &formula.name)
+        .replace("{test_name}", &test.name)
+        .replace("{expected}", &test.expected.components())
+        .replace("{tolerance}", &format!("{:.6}_f32", test.tolerance));
+
+    for param in &formula.params {
+        let value = test_input(formula, test, &param.name);
+        code = code.replace(&format!("{{input_{}}}", param.name), &value.components());
+    }
+
+    unescape_braces(&code)
+}
+
+/// Call the formula with the test inputs (in parameter order) and compare
+/// against the expected value, component-wise for vectors
+fn generate_default_test(formula: &FormulaInfo, test: &TestCase) -> String {
+    let args = formula
+        .params
+        .iter()
+        .map(|param| test_input(formula, test, &param.name).rust_literal())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let error = match test.expected {
+        TestValue::Scalar(_) => "(result - expected).abs()",
+        TestValue::Vector(_) => "(result - expected).abs().max_element()",
+    };
+
+    format!(
+        r#"#[test]
+fn test_{name}_{test_name}() {{
+    let result = {name}({args});
+    let expected = {expected};
+    assert!(
+        {error} < {tolerance:.6}_f32,
+        "{name}: got {{result:?}}, expected {{expected:?}}"
+    );
+}}"#,
+        name = formula.name,
+        test_name = test.name,
+        expected = test.expected.rust_literal(),
+        tolerance = test.tolerance,
+    )
+}
+
+/// Run the formula's WGSL on every test vector too, so its hand-written body
+/// can't drift from the Rust one
+fn generate_wgsl_test_code(spec: &FormulaSpec) -> String {
+    let formula = &spec.formula;
+    let wgsl = generate_wgsl_code(spec);
+    let function = wgsl_function_name(&wgsl)
+        .unwrap_or_else(|| panic!("{}: WGSL template defines no function", formula.name));
 
     formula
         .tests
         .iter()
         .map(|test| {
-            let input_p = format!(
-                "{:.6}, {:.6}, {:.6}",
-                test.input.p[0], test.input.p[1], test.input.p[2]
-            );
-            let expected = format!(
-                "{:.6}, {:.6}, {:.6}",
-                test.expected[0], test.expected[1], test.expected[2]
-            );
-
-            template
-                .replace("{name}", &formula.name)
-                .replace("{test_name}", &test.name)
-                .replace("{input_p}", &input_p)
-                .replace("{input_n}", &format!("{:.1}_f32", test.input.n))
-                .replace("{expected}", &expected)
-                .replace("{tolerance}", &format!("{:.6}_f32", test.tolerance))
+            let args = formula
+                .params
+                .iter()
+                .map(|param| {
+                    let value = test_input(formula, test, &param.name);
+                    format!("Value::from({})", value.rust_literal())
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                r#"#[test]
+fn test_wgsl_{name}_{test_name}() {{
+    check_wgsl(
+        "{function}",
+        &[{args}],
+        &Value::from({expected}),
+        {tolerance:.6}_f32,
+    );
+}}"#,
+                name = formula.name,
+                test_name = test.name,
+                expected = test.expected.rust_literal(),
+                tolerance = test.tolerance,
+            )
         })
         .collect::<Vec<_>>()
         .join("\n\n")
 }
 
+/// Name of the first function in generated WGSL
+fn wgsl_function_name(wgsl: &str) -> Option<&str> {
+    let start = wgsl.find("fn ")? + 3;
+    let rest = &wgsl[start..];
+    rest.find('(').map(|end| rest[..end].trim())
+}
+
+fn test_input<'a>(formula: &FormulaInfo, test: &'a TestCase, param: &str) -> &'a TestValue {
+    test.input.get(param).unwrap_or_else(|| {
+        panic!(
+            "{}: test '{}' has no input for parameter '{}'",
+            formula.name, test.name, param
+        )
+    })
+}
+
+/// Glam types referenced by a formula's signature or test values
+fn glam_types(spec: &FormulaSpec) -> Vec<&'static str> {
+    let formula = &spec.formula;
+    let mut types = Vec::new();
+
+    let signature = formula
+        .params
+        .iter()
+        .map(|p| p.param_type.as_str())
+        .chain(std::iter::once(formula.returns.return_type.as_str()));
+    for ty in signature {
+        match ty {
+            "vec2" => types.push("Vec2"),
+            "vec3" => types.push("Vec3"),
+            "vec4" => types.push("Vec4"),
+            _ => {}
+        }
+    }
+
+    types
+}
+
 fn generate_markdown_docs(spec: &FormulaSpec) -> String {
     let formula = &spec.formula;
 
@@ -178,10 +345,10 @@ fn generate_markdown_docs(spec: &FormulaSpec) -> String {
     doc.push_str(&format!("**Verified:** {}\n\n", formula.verified_date));
 
     doc.push_str("## Parameters\n\n");
-    for (name, info) in &formula.params {
+    for param in &formula.params {
         doc.push_str(&format!(
             "- `{}` ({}): {}\n",
-            name, info.param_type, info.description
+            param.name, param.param_type, param.description
         ));
     }
 
@@ -191,13 +358,17 @@ fn generate_markdown_docs(spec: &FormulaSpec) -> String {
         formula.returns.return_type, formula.returns.description
     ));
 
-    doc.push_str("## Formula Steps\n\n");
+    if !formula.steps.is_empty() {
+        doc.push_str("## Formula Steps\n\n");
+    }
     for step in &formula.steps {
         doc.push_str(&format!("1. **{}** = `{}`\n", step.name, step.expr));
         doc.push_str(&format!("   - {}\n\n", step.description));
     }
 
-    doc.push_str("## Pitfalls\n\n");
+    if !formula.pitfalls.is_empty() {
+        doc.push_str("## Pitfalls\n\n");
+    }
     for pitfall in &formula.pitfalls {
         doc.push_str(&format!("### {}\n\n", pitfall.name));
         doc.push_str(&format!("- **Wrong:** `{}`\n", pitfall.wrong));
@@ -206,19 +377,24 @@ fn generate_markdown_docs(spec: &FormulaSpec) -> String {
     }
 
     doc.push_str("## Test Vectors\n\n");
-    doc.push_str("| Test | Input P | N | Expected | Description |\n");
-    doc.push_str("|------|---------|---|----------|-------------|\n");
+    doc.push_str("| Test | Input | Expected | Description |\n");
+    doc.push_str("|------|-------|----------|-------------|\n");
     for test in &formula.tests {
+        let input = formula
+            .params
+            .iter()
+            .filter_map(|p| {
+                test.input
+                    .get(&p.name)
+                    .map(|v| format!("{} = {}", p.name, v.display()))
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
         doc.push_str(&format!(
-            "| {} | ({:.2}, {:.2}, {:.2}) | {} | ({:.2}, {:.2}, {:.2}) | {} |\n",
+            "| {} | {} | {} | {} |\n",
             test.name,
-            test.input.p[0],
-            test.input.p[1],
-            test.input.p[2],
-            test.input.n,
-            test.expected[0],
-            test.expected[1],
-            test.expected[2],
+            input,
+            test.expected.display(),
             test.description
         ));
     }
@@ -238,28 +414,18 @@ fn main() {
     let formulas_dir = Path::new(&manifest_dir).join("formulas");
 
     // Collect all generated code
-    let mut rust_code = String::new();
+    let mut rust_body = String::new();
     let mut wgsl_code = String::new();
-    let mut test_code = String::new();
+    let mut test_body = String::new();
     let mut docs = String::new();
 
-    // Header for Rust
-    rust_code.push_str("// AUTO-GENERATED by build.rs from formula specs\n");
-    rust_code.push_str("// DO NOT EDIT - modify the TOML files in formulas/ instead\n\n");
-    rust_code.push_str("use glam::Vec3;\n\n");
+    // Glam types used by any formula, imported once in the headers below
+    let mut types = BTreeSet::new();
 
     // Header for WGSL
     wgsl_code.push_str("// AUTO-GENERATED by build.rs from formula specs\n");
     wgsl_code.push_str("// DO NOT EDIT - modify the TOML files in formulas/ instead\n\n");
 
-    // Header for tests
-    // Note: tests.rs is included inside `mod tests { use super::*; ... }`
-    // so we only need Vec3, everything else comes from super::*
-    // Note: clippy allows are added in lib.rs since inner attributes don't work with include!()
-    test_code.push_str("// AUTO-GENERATED by build.rs from formula specs\n");
-    test_code.push_str("// DO NOT EDIT - modify the TOML files in formulas/ instead\n\n");
-    test_code.push_str("use glam::Vec3;\n\n");
-
     // Header for docs
     docs.push_str("# Soyuz Math Formula Reference\n\n");
     docs.push_str("*Auto-generated from formula specifications*\n\n");
@@ -284,20 +450,50 @@ fn main() {
                 panic!("Failed to parse {}: {}", path.display(), e);
             });
 
-            rust_code.push_str(&generate_rust_code(&spec));
-            rust_code.push_str("\n\n");
+            types.extend(glam_types(&spec));
+
+            rust_body.push_str(&generate_rust_code(&spec));
+            rust_body.push_str("\n\n");
 
             wgsl_code.push_str(&generate_wgsl_code(&spec));
             wgsl_code.push_str("\n\n");
 
-            test_code.push_str(&generate_test_code(&spec));
-            test_code.push_str("\n\n");
+            test_body.push_str(&generate_test_code(&spec));
+            test_body.push_str("\n\n");
+            test_body.push_str(&generate_wgsl_test_code(&spec));
+            test_body.push_str("\n\n");
 
             docs.push_str(&generate_markdown_docs(&spec));
             docs.push_str("\n---\n\n");
         }
     }
 
+    let imports = if types.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "use glam::{{{}}};\n\n",
+            types.into_iter().collect::<Vec<_>>().join(", ")
+        )
+    };
+
+    // Header for Rust
+    let mut rust_code = String::new();
+    rust_code.push_str("// AUTO-GENERATED by build.rs from formula specs\n");
+    rust_code.push_str("// DO NOT EDIT - modify the TOML files in formulas/ instead\n\n");
+    rust_code.push_str(&imports);
+    rust_code.push_str(&rust_body);
+
+    // Header for tests
+    // Note: tests.rs is included inside `mod tests { use super::*; ... }`
+    // so we only need the glam types, everything else comes from super::*
+    // Note: clippy allows are added in lib.rs since inner attributes don't work with include!()
+    let mut test_code = String::new();
+    test_code.push_str("// AUTO-GENERATED by build.rs from formula specs\n");
+    test_code.push_str("// DO NOT EDIT - modify the TOML files in formulas/ instead\n\n");
+    test_code.push_str(&imports);
+    test_code.push_str(&test_body);
+
     // Write output files
     fs::write(out_path.join("formulas.rs"), rust_code).unwrap();
     fs::write(out_path.join("formulas.wgsl"), wgsl_code).unwrap();
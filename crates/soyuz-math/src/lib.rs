@@ -20,6 +20,13 @@
 //! 3. Run `cargo build` to generate code
 //! 4. Use the generated functions in your code
 //!
+//! Every `[[formula.tests]]` entry becomes a unit test that calls the Rust
+//! function with its `input` values (matched to `[[formula.params]]` by name)
+//! and compares against `expected`. A `[codegen.test]` template is only needed
+//! for assertions the default can't express. Each entry also becomes a test
+//! that runs the WGSL function on the same values with [`wgsl_eval`], so the
+//! two hand-written bodies are held to the same results.
+//!
 //! # Example
 //!
 //! ```rust
@@ -33,12 +40,20 @@
 
 // Include the auto-generated Rust implementations
 // Allow doc_markdown because generated docs contain function names like cos(), sin(), etc.
+// Allow many_single_char_names because formulas keep the notation of their derivations
+// Allow unreadable_literal because constants are written the same way as in WGSL,
+// which has no digit separators
 #[allow(clippy::doc_markdown)]
+#[allow(clippy::many_single_char_names)]
+#[allow(clippy::unreadable_literal)]
 mod generated {
     include!(concat!(env!("OUT_DIR"), "/formulas.rs"));
 }
 pub use generated::*;
 
+#[cfg(any(test, feature = "wgsl-eval"))]
+pub mod wgsl_eval;
+
 /// Get the WGSL code for all formulas
 ///
 /// This returns the auto-generated WGSL code that should be injected
@@ -53,10 +68,43 @@ pub fn get_docs() -> &'static str {
     include_str!(concat!(env!("OUT_DIR"), "/FORMULAS.md"))
 }
 
+// Generated test vectors are printed with fixed precision, which may look
+// excessive or close to a named constant
 #[cfg(test)]
 #[allow(clippy::unreadable_literal)]
+#[allow(clippy::excessive_precision)]
+#[allow(clippy::approx_constant)]
+#[allow(clippy::expect_used)]
 mod tests {
     use super::*;
+    use crate::wgsl_eval::{Value, WgslModule};
+    use std::sync::OnceLock;
+
+    /// Run a formula's WGSL and compare the result with a test vector
+    fn check_wgsl(function: &str, arguments: &[Value], expected: &Value, tolerance: f32) {
+        static MODULE: OnceLock<WgslModule> = OnceLock::new();
+        let module = MODULE
+            .get_or_init(|| WgslModule::parse(get_wgsl_code()).expect("formulas should parse"));
+
+        let result = module
+            .call(function, arguments)
+            .unwrap_or_else(|e| panic!("{function}: {e}"));
+        let (result, expected) = (result.to_f32s(), expected.to_f32s());
+        let close = match (&result, &expected) {
+            (Some(result), Some(expected)) => {
+                result.len() == expected.len()
+                    && result
+                        .iter()
+                        .zip(expected)
+                        .all(|(r, e)| (r - e).abs() < tolerance)
+            }
+            _ => false,
+        };
+        assert!(
+            close,
+            "{function} (WGSL): got {result:?}, expected {expected:?}"
+        );
+    }
 
     // Include auto-generated tests
     include!(concat!(env!("OUT_DIR"), "/tests.rs"));
@@ -0,0 +1,1074 @@
+//! A small interpreter for the WGSL that formulas and scenes generate
+//!
+//! Runs WGSL functions on the CPU so tests can compare them with the Rust
+//! code: every formula test vector also checks the formula's WGSL body, and
+//! the SDF parity tests check the preview shader of whole scenes against the
+//! CPU evaluator. Source is parsed with naga and its IR is run directly.
+//!
+//! Covers what these shaders use: scalar, vector and matrix math, the math
+//! builtins, local variables, loops and function calls. Globals, textures,
+//! switches and other pipeline features are reported as unsupported.
+
+// Values are converted between WGSL's scalar types on purpose, with WGSL's
+// saturating and wrapping rules
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::cast_sign_loss)]
+#![allow(clippy::cast_precision_loss)]
+#![allow(clippy::float_cmp)]
+// Evaluation is one match over naga's expression kinds
+#![allow(clippy::too_many_lines)]
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use glam::{Vec2, Vec3, Vec4};
+use naga::{
+    Arena, BinaryOperator, Block, Expression, Function, Handle, Literal, LocalVariable,
+    MathFunction, Module, RelationalFunction, ScalarKind, Statement, Type, TypeInner,
+    UnaryOperator,
+};
+
+/// Most iterations a single loop may run before evaluation gives up
+const MAX_LOOP_ITERATIONS: usize = 1_000_000;
+
+/// Deepest chain of function calls; WGSL forbids recursion, so this only
+/// guards against malformed modules
+const MAX_CALL_DEPTH: usize = 64;
+
+/// Error from parsing or evaluating WGSL
+#[derive(Debug, Clone)]
+pub struct EvalError(String);
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+type Result<T> = std::result::Result<T, EvalError>;
+
+fn fail<T>(message: impl Into<String>) -> Result<T> {
+    Err(EvalError(message.into()))
+}
+
+/// A WGSL scalar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scalar {
+    F32(f32),
+    I32(i32),
+    U32(u32),
+    Bool(bool),
+}
+
+impl Scalar {
+    fn zero(kind: ScalarKind) -> Self {
+        match kind {
+            ScalarKind::Float | ScalarKind::AbstractFloat => Scalar::F32(0.0),
+            ScalarKind::Sint | ScalarKind::AbstractInt => Scalar::I32(0),
+            ScalarKind::Uint => Scalar::U32(0),
+            ScalarKind::Bool => Scalar::Bool(false),
+        }
+    }
+
+    fn float(self) -> Result<f32> {
+        match self {
+            Scalar::F32(v) => Ok(v),
+            other => fail(format!("expected a float, got {other:?}")),
+        }
+    }
+
+    fn bool(self) -> Result<bool> {
+        match self {
+            Scalar::Bool(v) => Ok(v),
+            other => fail(format!("expected a bool, got {other:?}")),
+        }
+    }
+
+    fn index(self) -> Result<usize> {
+        match self {
+            Scalar::I32(v) => usize::try_from(v).or_else(|_| fail(format!("negative index {v}"))),
+            Scalar::U32(v) => Ok(v as usize),
+            other => fail(format!("expected an index, got {other:?}")),
+        }
+    }
+}
+
+/// A WGSL value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Scalar(Scalar),
+    Vector(Vec<Scalar>),
+    /// Columns, each a vector of the rows
+    Matrix(Vec<Vec<Scalar>>),
+    /// Array elements or struct members
+    Composite(Vec<Value>),
+    /// A local variable, or a part of one
+    Pointer(Place),
+}
+
+/// Where a pointer leads: a local variable and the indices into it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Place {
+    variable: Handle<LocalVariable>,
+    path: Vec<usize>,
+}
+
+impl Value {
+    /// The components of a float scalar or vector, in order
+    pub fn to_f32s(&self) -> Option<Vec<f32>> {
+        match self {
+            Value::Scalar(Scalar::F32(v)) => Some(vec![*v]),
+            Value::Vector(components) => components.iter().map(|c| c.float().ok()).collect(),
+            _ => None,
+        }
+    }
+
+    fn scalar(&self) -> Result<Scalar> {
+        match self {
+            Value::Scalar(s) => Ok(*s),
+            other => fail(format!("expected a scalar, got {other:?}")),
+        }
+    }
+
+    fn vector(&self) -> Result<&[Scalar]> {
+        match self {
+            Value::Vector(components) => Ok(components),
+            other => fail(format!("expected a vector, got {other:?}")),
+        }
+    }
+
+    fn place(self) -> Result<Place> {
+        match self {
+            Value::Pointer(place) => Ok(place),
+            other => fail(format!("expected a pointer, got {other:?}")),
+        }
+    }
+
+    /// Element `index` of a vector, matrix, array or struct, or a pointer
+    /// to it
+    fn element(self, index: usize) -> Result<Value> {
+        let missing = || EvalError(format!("index {index} is out of range"));
+        match self {
+            Value::Vector(components) => components
+                .get(index)
+                .map(|c| Value::Scalar(*c))
+                .ok_or_else(missing),
+            Value::Matrix(mut columns) => (index < columns.len())
+                .then(|| Value::Vector(columns.swap_remove(index)))
+                .ok_or_else(missing),
+            Value::Composite(mut members) => (index < members.len())
+                .then(|| members.swap_remove(index))
+                .ok_or_else(missing),
+            Value::Pointer(mut place) => {
+                place.path.push(index);
+                Ok(Value::Pointer(place))
+            }
+            Value::Scalar(s) => fail(format!("can't index the scalar {s:?}")),
+        }
+    }
+
+    /// Replace the part of `self` at `path` with `value`
+    fn set(&mut self, path: &[usize], value: Value) -> Result<()> {
+        let Some((&first, rest)) = path.split_first() else {
+            *self = value;
+            return Ok(());
+        };
+        let missing = || EvalError(format!("index {first} is out of range"));
+        match self {
+            Value::Composite(members) => {
+                members.get_mut(first).ok_or_else(missing)?.set(rest, value)
+            }
+            Value::Matrix(columns) => {
+                let mut column = Value::Vector(columns.get(first).ok_or_else(missing)?.clone());
+                column.set(rest, value)?;
+                columns[first] = column.vector()?.to_vec();
+                Ok(())
+            }
+            Value::Vector(components) if rest.is_empty() => {
+                *components.get_mut(first).ok_or_else(missing)? = value.scalar()?;
+                Ok(())
+            }
+            other => fail(format!("can't store into part {path:?} of {other:?}")),
+        }
+    }
+}
+
+impl From<f32> for Value {
+    fn from(v: f32) -> Self {
+        Value::Scalar(Scalar::F32(v))
+    }
+}
+
+impl From<i32> for Value {
+    fn from(v: i32) -> Self {
+        Value::Scalar(Scalar::I32(v))
+    }
+}
+
+impl From<u32> for Value {
+    fn from(v: u32) -> Self {
+        Value::Scalar(Scalar::U32(v))
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::Scalar(Scalar::Bool(v))
+    }
+}
+
+impl From<Vec2> for Value {
+    fn from(v: Vec2) -> Self {
+        Value::Vector(v.to_array().map(Scalar::F32).to_vec())
+    }
+}
+
+impl From<Vec3> for Value {
+    fn from(v: Vec3) -> Self {
+        Value::Vector(v.to_array().map(Scalar::F32).to_vec())
+    }
+}
+
+impl From<Vec4> for Value {
+    fn from(v: Vec4) -> Self {
+        Value::Vector(v.to_array().map(Scalar::F32).to_vec())
+    }
+}
+
+/// WGSL source whose functions can be called
+pub struct WgslModule {
+    module: Module,
+}
+
+impl WgslModule {
+    /// Parse WGSL source
+    pub fn parse(source: &str) -> Result<Self> {
+        let module = naga::front::wgsl::parse_str(source)
+            .map_err(|e| EvalError(e.emit_to_string(source)))?;
+        Ok(Self { module })
+    }
+
+    /// Call the function `name` and return its result
+    pub fn call(&self, name: &str, arguments: &[Value]) -> Result<Value> {
+        let (function, _) = self
+            .module
+            .functions
+            .iter()
+            .find(|(_, f)| f.name.as_deref() == Some(name))
+            .ok_or_else(|| EvalError(format!("no function named `{name}`")))?;
+        call(&self.module, function, arguments.to_vec(), 0)?
+            .ok_or_else(|| EvalError(format!("`{name}` returns nothing")))
+    }
+}
+
+/// How a statement left its block
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return(Option<Value>),
+}
+
+/// Run a function with `arguments`, returning its result if it has one
+fn call(
+    module: &Module,
+    handle: Handle<Function>,
+    arguments: Vec<Value>,
+    depth: usize,
+) -> Result<Option<Value>> {
+    let function = &module.functions[handle];
+    let name = function.name.as_deref().unwrap_or("<unnamed>");
+    if depth > MAX_CALL_DEPTH {
+        return fail(format!(
+            "calls nested deeper than {MAX_CALL_DEPTH} at `{name}`"
+        ));
+    }
+    if arguments.len() != function.arguments.len() {
+        return fail(format!(
+            "`{name}` takes {} arguments, got {}",
+            function.arguments.len(),
+            arguments.len()
+        ));
+    }
+
+    let mut frame = Frame::new(module, &function.expressions, arguments, depth);
+    for (_, variable) in function.local_variables.iter() {
+        let value = match variable.init {
+            Some(init) => frame.eval(init)?,
+            None => zero_value(module, variable.ty)?,
+        };
+        frame.locals.push(value);
+    }
+
+    match frame.block(&function.body) {
+        Ok(Flow::Return(value)) => Ok(value),
+        Ok(_) => Ok(None),
+        Err(EvalError(message)) => fail(format!("in `{name}`: {message}")),
+    }
+}
+
+/// One function call's arguments, variables and evaluated expressions
+struct Frame<'a> {
+    module: &'a Module,
+    expressions: &'a Arena<Expression>,
+    arguments: Vec<Value>,
+    locals: Vec<Value>,
+    values: Vec<Option<Value>>,
+    depth: usize,
+}
+
+impl<'a> Frame<'a> {
+    fn new(
+        module: &'a Module,
+        expressions: &'a Arena<Expression>,
+        arguments: Vec<Value>,
+        depth: usize,
+    ) -> Self {
+        Self {
+            module,
+            expressions,
+            arguments,
+            locals: Vec::new(),
+            values: vec![None; expressions.len()],
+            depth,
+        }
+    }
+
+    fn block(&mut self, block: &Block) -> Result<Flow> {
+        for statement in block {
+            let flow = self.statement(statement)?;
+            if !matches!(flow, Flow::Next) {
+                return Ok(flow);
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<Flow> {
+        match statement {
+            // Expressions are evaluated where they're emitted, so loads see
+            // the variables' values at that point
+            Statement::Emit(range) => {
+                for handle in range.clone() {
+                    let value = self.compute(handle)?;
+                    self.values[handle.index()] = Some(value);
+                }
+            }
+            Statement::Block(block) => return self.block(block),
+            Statement::If {
+                condition,
+                accept,
+                reject,
+            } => {
+                let branch = if self.eval(*condition)?.scalar()?.bool()? {
+                    accept
+                } else {
+                    reject
+                };
+                return self.block(branch);
+            }
+            Statement::Loop {
+                body,
+                continuing,
+                break_if,
+            } => {
+                for _ in 0..MAX_LOOP_ITERATIONS {
+                    match self.block(body)? {
+                        Flow::Break => return Ok(Flow::Next),
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Next | Flow::Continue => {}
+                    }
+                    self.block(continuing)?;
+                    if let Some(condition) = break_if
+                        && self.eval(*condition)?.scalar()?.bool()?
+                    {
+                        return Ok(Flow::Next);
+                    }
+                }
+                return fail(format!("a loop ran over {MAX_LOOP_ITERATIONS} iterations"));
+            }
+            Statement::Break => return Ok(Flow::Break),
+            Statement::Continue => return Ok(Flow::Continue),
+            Statement::Return { value } => {
+                let value = (*value).map(|v| self.eval(v)).transpose()?;
+                return Ok(Flow::Return(value));
+            }
+            Statement::Store { pointer, value } => {
+                let place = self.eval(*pointer)?.place()?;
+                let value = self.eval(*value)?;
+                self.locals[place.variable.index()].set(&place.path, value)?;
+            }
+            Statement::Call {
+                function,
+                arguments,
+                result,
+            } => {
+                let arguments = arguments
+                    .iter()
+                    .map(|a| self.eval(*a))
+                    .collect::<Result<Vec<_>>>()?;
+                let value = call(self.module, *function, arguments, self.depth + 1)?;
+                if let Some(result) = result {
+                    self.values[result.index()] = value;
+                }
+            }
+            other => return fail(format!("unsupported statement {other:?}")),
+        }
+        Ok(Flow::Next)
+    }
+
+    /// The value of an expression, computing it if it hasn't been yet
+    fn eval(&mut self, handle: Handle<Expression>) -> Result<Value> {
+        if let Some(value) = &self.values[handle.index()] {
+            return Ok(value.clone());
+        }
+        let value = self.compute(handle)?;
+        self.values[handle.index()] = Some(value.clone());
+        Ok(value)
+    }
+
+    fn compute(&mut self, handle: Handle<Expression>) -> Result<Value> {
+        let (module, expressions) = (self.module, self.expressions);
+        Ok(match expressions[handle] {
+            Expression::Literal(literal) => Value::Scalar(literal_scalar(literal)?),
+            Expression::Constant(constant) => {
+                let init = module.constants[constant].init;
+                Frame::new(module, &module.global_expressions, Vec::new(), self.depth).eval(init)?
+            }
+            Expression::ZeroValue(ty) => zero_value(module, ty)?,
+            Expression::Compose { ty, ref components } => {
+                let components = components
+                    .iter()
+                    .map(|c| self.eval(*c))
+                    .collect::<Result<Vec<_>>>()?;
+                compose(module, ty, components)?
+            }
+            Expression::Access { base, index } => {
+                let index = self.eval(index)?.scalar()?.index()?;
+                self.eval(base)?.element(index)?
+            }
+            Expression::AccessIndex { base, index } => self.eval(base)?.element(index as usize)?,
+            Expression::Splat { size, value } => {
+                Value::Vector(vec![self.eval(value)?.scalar()?; size as usize])
+            }
+            Expression::Swizzle {
+                size,
+                vector,
+                pattern,
+            } => {
+                let vector = self.eval(vector)?;
+                let components = vector.vector()?;
+                let swizzled = pattern[..size as usize]
+                    .iter()
+                    .map(|c| {
+                        components
+                            .get(*c as usize)
+                            .copied()
+                            .ok_or_else(|| EvalError(format!("swizzle {c:?} is out of range")))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Value::Vector(swizzled)
+            }
+            Expression::FunctionArgument(index) => self
+                .arguments
+                .get(index as usize)
+                .cloned()
+                .ok_or_else(|| EvalError(format!("no argument {index}")))?,
+            Expression::LocalVariable(variable) => Value::Pointer(Place {
+                variable,
+                path: Vec::new(),
+            }),
+            Expression::Load { pointer } => {
+                let place = self.eval(pointer)?.place()?;
+                place
+                    .path
+                    .iter()
+                    .try_fold(self.locals[place.variable.index()].clone(), |value, i| {
+                        value.element(*i)
+                    })?
+            }
+            Expression::Unary { op, expr } => {
+                let value = self.eval(expr)?;
+                component_wise(&[value], |s| unary(op, s[0]))?
+            }
+            Expression::Binary { op, left, right } => {
+                let (left, right) = (self.eval(left)?, self.eval(right)?);
+                binary(op, left, right)?
+            }
+            Expression::Select {
+                condition,
+                accept,
+                reject,
+            } => {
+                let condition = self.eval(condition)?;
+                let (accept, reject) = (self.eval(accept)?, self.eval(reject)?);
+                match condition {
+                    Value::Scalar(c) => {
+                        if c.bool()? {
+                            accept
+                        } else {
+                            reject
+                        }
+                    }
+                    condition => component_wise(&[condition, accept, reject], |s| {
+                        Ok(if s[0].bool()? { s[1] } else { s[2] })
+                    })?,
+                }
+            }
+            Expression::Relational { fun, argument } => relational(fun, &self.eval(argument)?)?,
+            Expression::Math {
+                fun,
+                arg,
+                arg1,
+                arg2,
+                arg3,
+            } => {
+                let arguments = [Some(arg), arg1, arg2, arg3]
+                    .into_iter()
+                    .flatten()
+                    .map(|a| self.eval(a))
+                    .collect::<Result<Vec<_>>>()?;
+                math(fun, &arguments)?
+            }
+            Expression::As {
+                expr,
+                kind,
+                convert,
+            } => {
+                let value = self.eval(expr)?;
+                component_wise(&[value], |s| convert_scalar(s[0], kind, convert.is_some()))?
+            }
+            Expression::CallResult(_) => return fail("call result used before the call"),
+            ref other => return fail(format!("unsupported expression {other:?}")),
+        })
+    }
+}
+
+fn literal_scalar(literal: Literal) -> Result<Scalar> {
+    Ok(match literal {
+        Literal::F32(v) => Scalar::F32(v),
+        Literal::F64(v) | Literal::AbstractFloat(v) => Scalar::F32(v as f32),
+        Literal::F16(v) => Scalar::F32(v.to_f32()),
+        Literal::I32(v) => Scalar::I32(v),
+        Literal::U32(v) => Scalar::U32(v),
+        Literal::Bool(v) => Scalar::Bool(v),
+        Literal::AbstractInt(v) => Scalar::I32(
+            i32::try_from(v).or_else(|_| fail(format!("integer {v} doesn't fit an i32")))?,
+        ),
+        Literal::I64(_) | Literal::U64(_) => return fail("64-bit integers are unsupported"),
+    })
+}
+
+fn zero_value(module: &Module, ty: Handle<Type>) -> Result<Value> {
+    Ok(match module.types[ty].inner {
+        TypeInner::Scalar(scalar) => Value::Scalar(Scalar::zero(scalar.kind)),
+        TypeInner::Vector { size, scalar } => {
+            Value::Vector(vec![Scalar::zero(scalar.kind); size as usize])
+        }
+        TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } => Value::Matrix(vec![
+            vec![Scalar::zero(scalar.kind); rows as usize];
+            columns as usize
+        ]),
+        TypeInner::Array {
+            base,
+            size: naga::ArraySize::Constant(count),
+            ..
+        } => Value::Composite(vec![zero_value(module, base)?; count.get() as usize]),
+        TypeInner::Struct { ref members, .. } => Value::Composite(
+            members
+                .iter()
+                .map(|m| zero_value(module, m.ty))
+                .collect::<Result<_>>()?,
+        ),
+        ref other => return fail(format!("unsupported type {other:?}")),
+    })
+}
+
+/// Build a value of type `ty`; vectors and matrices may be built from
+/// smaller vectors, which are flattened
+fn compose(module: &Module, ty: Handle<Type>, components: Vec<Value>) -> Result<Value> {
+    let flatten = |components: Vec<Value>| -> Result<Vec<Scalar>> {
+        let mut scalars = Vec::new();
+        for component in components {
+            match component {
+                Value::Scalar(s) => scalars.push(s),
+                Value::Vector(v) => scalars.extend(v),
+                other => return fail(format!("can't build a vector from {other:?}")),
+            }
+        }
+        Ok(scalars)
+    };
+    Ok(match module.types[ty].inner {
+        TypeInner::Vector { .. } => Value::Vector(flatten(components)?),
+        TypeInner::Matrix { rows, .. } => Value::Matrix(
+            flatten(components)?
+                .chunks(rows as usize)
+                .map(<[Scalar]>::to_vec)
+                .collect(),
+        ),
+        TypeInner::Array { .. } | TypeInner::Struct { .. } => Value::Composite(components),
+        ref other => return fail(format!("can't compose {other:?}")),
+    })
+}
+
+/// Apply `f` to each component of the arguments, repeating scalar arguments
+/// across vector ones
+fn component_wise(arguments: &[Value], f: impl Fn(&[Scalar]) -> Result<Scalar>) -> Result<Value> {
+    let width = arguments.iter().find_map(|a| match a {
+        Value::Vector(v) => Some(v.len()),
+        _ => None,
+    });
+    let component = |i: Option<usize>| -> Result<Vec<Scalar>> {
+        arguments
+            .iter()
+            .map(|a| match (a, i) {
+                (Value::Scalar(s), _) => Ok(*s),
+                (Value::Vector(v), Some(i)) => v
+                    .get(i)
+                    .copied()
+                    .ok_or_else(|| EvalError("vectors differ in size".to_string())),
+                (other, _) => fail(format!("expected a scalar or vector, got {other:?}")),
+            })
+            .collect()
+    };
+    match width {
+        None => Ok(Value::Scalar(f(&component(None)?)?)),
+        Some(width) => (0..width)
+            .map(|i| f(&component(Some(i))?))
+            .collect::<Result<Vec<_>>>()
+            .map(Value::Vector),
+    }
+}
+
+fn unary(op: UnaryOperator, s: Scalar) -> Result<Scalar> {
+    Ok(match (op, s) {
+        (UnaryOperator::Negate, Scalar::F32(v)) => Scalar::F32(-v),
+        (UnaryOperator::Negate, Scalar::I32(v)) => Scalar::I32(v.wrapping_neg()),
+        (UnaryOperator::LogicalNot, Scalar::Bool(v)) => Scalar::Bool(!v),
+        (UnaryOperator::BitwiseNot, Scalar::I32(v)) => Scalar::I32(!v),
+        (UnaryOperator::BitwiseNot, Scalar::U32(v)) => Scalar::U32(!v),
+        (op, s) => return fail(format!("can't apply {op:?} to {s:?}")),
+    })
+}
+
+fn binary(op: BinaryOperator, left: Value, right: Value) -> Result<Value> {
+    let matrix_product = |m: &[Vec<Scalar>], v: &[Scalar]| -> Result<Vec<Scalar>> {
+        let rows = m.first().map_or(0, Vec::len);
+        (0..rows)
+            .map(|r| {
+                let column_terms = m.iter().map(|column| column[r]);
+                dot(&column_terms.collect::<Vec<_>>(), v)
+            })
+            .collect()
+    };
+    match (op, left, right) {
+        (BinaryOperator::Multiply, Value::Matrix(m), Value::Vector(v)) => {
+            Ok(Value::Vector(matrix_product(&m, &v)?))
+        }
+        (BinaryOperator::Multiply, Value::Vector(v), Value::Matrix(m)) => Ok(Value::Vector(
+            m.iter()
+                .map(|column| dot(&v, column))
+                .collect::<Result<_>>()?,
+        )),
+        (BinaryOperator::Multiply, Value::Matrix(a), Value::Matrix(b)) => Ok(Value::Matrix(
+            b.iter()
+                .map(|column| matrix_product(&a, column))
+                .collect::<Result<_>>()?,
+        )),
+        (op, Value::Matrix(a), Value::Matrix(b)) => Ok(Value::Matrix(
+            a.into_iter()
+                .zip(b)
+                .map(|(a, b)| {
+                    let column = component_wise(&[Value::Vector(a), Value::Vector(b)], |s| {
+                        binary_scalar(op, s[0], s[1])
+                    })?;
+                    Ok(column.vector()?.to_vec())
+                })
+                .collect::<Result<_>>()?,
+        )),
+        (BinaryOperator::Multiply, Value::Matrix(m), Value::Scalar(s))
+        | (BinaryOperator::Multiply, Value::Scalar(s), Value::Matrix(m)) => Ok(Value::Matrix(
+            m.into_iter()
+                .map(|column| {
+                    column
+                        .into_iter()
+                        .map(|c| binary_scalar(BinaryOperator::Multiply, c, s))
+                        .collect()
+                })
+                .collect::<Result<_>>()?,
+        )),
+        (op, left, right) => component_wise(&[left, right], |s| binary_scalar(op, s[0], s[1])),
+    }
+}
+
+fn binary_scalar(op: BinaryOperator, a: Scalar, b: Scalar) -> Result<Scalar> {
+    use BinaryOperator as B;
+    use Scalar as S;
+
+    let test: Option<fn(Option<Ordering>) -> bool> = match op {
+        B::Equal => Some(|o| o == Some(Ordering::Equal)),
+        B::NotEqual => Some(|o| o != Some(Ordering::Equal)),
+        B::Less => Some(|o| o == Some(Ordering::Less)),
+        B::LessEqual => Some(|o| matches!(o, Some(Ordering::Less | Ordering::Equal))),
+        B::Greater => Some(|o| o == Some(Ordering::Greater)),
+        B::GreaterEqual => Some(|o| matches!(o, Some(Ordering::Greater | Ordering::Equal))),
+        _ => None,
+    };
+    if let Some(test) = test {
+        let ordering = match (a, b) {
+            (S::F32(a), S::F32(b)) => a.partial_cmp(&b),
+            (S::I32(a), S::I32(b)) => Some(a.cmp(&b)),
+            (S::U32(a), S::U32(b)) => Some(a.cmp(&b)),
+            (S::Bool(a), S::Bool(b)) => Some(a.cmp(&b)),
+            (a, b) => return fail(format!("can't compare {a:?} and {b:?}")),
+        };
+        return Ok(S::Bool(test(ordering)));
+    }
+
+    Ok(match (a, b) {
+        (S::F32(a), S::F32(b)) => S::F32(match op {
+            B::Add => a + b,
+            B::Subtract => a - b,
+            B::Multiply => a * b,
+            B::Divide => a / b,
+            B::Modulo => a % b,
+            _ => return fail(format!("can't apply {op:?} to floats")),
+        }),
+        (S::I32(a), S::I32(b)) => S::I32(match op {
+            B::Add => a.wrapping_add(b),
+            B::Subtract => a.wrapping_sub(b),
+            B::Multiply => a.wrapping_mul(b),
+            B::Divide => a.checked_div(b).unwrap_or(a),
+            B::Modulo => a.checked_rem(b).unwrap_or(0),
+            B::And => a & b,
+            B::InclusiveOr => a | b,
+            B::ExclusiveOr => a ^ b,
+            _ => return fail(format!("can't apply {op:?} to i32s")),
+        }),
+        (S::U32(a), S::U32(b)) => S::U32(match op {
+            B::Add => a.wrapping_add(b),
+            B::Subtract => a.wrapping_sub(b),
+            B::Multiply => a.wrapping_mul(b),
+            B::Divide => a.checked_div(b).unwrap_or(a),
+            B::Modulo => a.checked_rem(b).unwrap_or(0),
+            B::And => a & b,
+            B::InclusiveOr => a | b,
+            B::ExclusiveOr => a ^ b,
+            B::ShiftLeft => a.wrapping_shl(b),
+            B::ShiftRight => a.wrapping_shr(b),
+            _ => return fail(format!("can't apply {op:?} to u32s")),
+        }),
+        (S::I32(a), S::U32(b)) => S::I32(match op {
+            B::ShiftLeft => a.wrapping_shl(b),
+            B::ShiftRight => a.wrapping_shr(b),
+            _ => return fail(format!("can't apply {op:?} to an i32 and a u32")),
+        }),
+        (S::Bool(a), S::Bool(b)) => S::Bool(match op {
+            B::And | B::LogicalAnd => a && b,
+            B::InclusiveOr | B::LogicalOr => a || b,
+            _ => return fail(format!("can't apply {op:?} to bools")),
+        }),
+        (a, b) => return fail(format!("can't apply {op:?} to {a:?} and {b:?}")),
+    })
+}
+
+fn relational(fun: RelationalFunction, argument: &Value) -> Result<Value> {
+    let bools = || -> Result<Vec<bool>> {
+        match argument {
+            Value::Scalar(s) => Ok(vec![s.bool()?]),
+            Value::Vector(v) => v.iter().map(|s| s.bool()).collect(),
+            other => fail(format!("expected bools, got {other:?}")),
+        }
+    };
+    Ok(match fun {
+        RelationalFunction::All => Value::from(bools()?.into_iter().all(|b| b)),
+        RelationalFunction::Any => Value::from(bools()?.into_iter().any(|b| b)),
+        RelationalFunction::IsNan => component_wise(std::slice::from_ref(argument), |s| {
+            Ok(Scalar::Bool(s[0].float()?.is_nan()))
+        })?,
+        RelationalFunction::IsInf => component_wise(std::slice::from_ref(argument), |s| {
+            Ok(Scalar::Bool(s[0].float()?.is_infinite()))
+        })?,
+    })
+}
+
+fn convert_scalar(s: Scalar, kind: ScalarKind, convert: bool) -> Result<Scalar> {
+    Ok(match (kind, s) {
+        (ScalarKind::Float, Scalar::F32(v)) => Scalar::F32(v),
+        (ScalarKind::Sint, Scalar::I32(v)) => Scalar::I32(v),
+        (ScalarKind::Uint, Scalar::U32(v)) => Scalar::U32(v),
+        (ScalarKind::Bool, Scalar::Bool(v)) => Scalar::Bool(v),
+
+        // Bitcasts
+        (ScalarKind::Float, Scalar::U32(v)) if !convert => Scalar::F32(f32::from_bits(v)),
+        (ScalarKind::Float, Scalar::I32(v)) if !convert => Scalar::F32(f32::from_bits(v as u32)),
+        (ScalarKind::Uint, Scalar::F32(v)) if !convert => Scalar::U32(v.to_bits()),
+        (ScalarKind::Sint, Scalar::F32(v)) if !convert => Scalar::I32(v.to_bits() as i32),
+        (ScalarKind::Uint, Scalar::I32(v)) => Scalar::U32(v as u32),
+        (ScalarKind::Sint, Scalar::U32(v)) => Scalar::I32(v as i32),
+
+        // Value conversions; float to integer saturates, like WGSL
+        (ScalarKind::Float, Scalar::I32(v)) => Scalar::F32(v as f32),
+        (ScalarKind::Float, Scalar::U32(v)) => Scalar::F32(v as f32),
+        (ScalarKind::Float, Scalar::Bool(v)) => Scalar::F32(f32::from(u8::from(v))),
+        (ScalarKind::Sint, Scalar::F32(v)) => Scalar::I32(v as i32),
+        (ScalarKind::Sint, Scalar::Bool(v)) => Scalar::I32(i32::from(v)),
+        (ScalarKind::Uint, Scalar::F32(v)) => Scalar::U32(v as u32),
+        (ScalarKind::Uint, Scalar::Bool(v)) => Scalar::U32(u32::from(v)),
+        (ScalarKind::Bool, Scalar::F32(v)) => Scalar::Bool(v != 0.0),
+        (ScalarKind::Bool, Scalar::I32(v)) => Scalar::Bool(v != 0),
+        (ScalarKind::Bool, Scalar::U32(v)) => Scalar::Bool(v != 0),
+        (kind, s) => return fail(format!("can't convert {s:?} to {kind:?}")),
+    })
+}
+
+fn dot(a: &[Scalar], b: &[Scalar]) -> Result<Scalar> {
+    if a.len() != b.len() {
+        return fail("vectors differ in size");
+    }
+    let mut terms = a
+        .iter()
+        .zip(b)
+        .map(|(x, y)| binary_scalar(BinaryOperator::Multiply, *x, *y));
+    let first = terms
+        .next()
+        .ok_or_else(|| EvalError("dot of empty vectors".to_string()))??;
+    terms.try_fold(first, |sum, term| {
+        binary_scalar(BinaryOperator::Add, sum, term?)
+    })
+}
+
+fn floats(value: &Value) -> Result<Vec<f32>> {
+    value
+        .to_f32s()
+        .ok_or_else(|| EvalError(format!("expected floats, got {value:?}")))
+}
+
+/// A float scalar or vector shaped like `like`
+fn shaped(like: &Value, components: Vec<f32>) -> Value {
+    match like {
+        Value::Vector(_) => Value::Vector(components.into_iter().map(Scalar::F32).collect()),
+        _ => Value::from(components[0]),
+    }
+}
+
+fn math(fun: MathFunction, arguments: &[Value]) -> Result<Value> {
+    use MathFunction as M;
+
+    let arity = match fun {
+        M::Min
+        | M::Max
+        | M::Atan2
+        | M::Pow
+        | M::Step
+        | M::Dot
+        | M::Cross
+        | M::Distance
+        | M::Reflect => 2,
+        M::Clamp | M::Mix | M::SmoothStep | M::Fma => 3,
+        _ => 1,
+    };
+    if arguments.len() != arity {
+        return fail(format!(
+            "{fun:?} takes {arity} arguments, got {}",
+            arguments.len()
+        ));
+    }
+
+    // Geometric functions work on whole vectors
+    match fun {
+        M::Dot => {
+            let (a, b) = (arguments[0].vector()?, arguments[1].vector()?);
+            return Ok(Value::Scalar(dot(a, b)?));
+        }
+        M::Length => {
+            let v = floats(&arguments[0])?;
+            return Ok(Value::from(v.iter().map(|c| c * c).sum::<f32>().sqrt()));
+        }
+        M::Distance => {
+            let (a, b) = (floats(&arguments[0])?, floats(&arguments[1])?);
+            let squared: f32 = a.iter().zip(&b).map(|(a, b)| (a - b) * (a - b)).sum();
+            return Ok(Value::from(squared.sqrt()));
+        }
+        M::Normalize => {
+            let v = floats(&arguments[0])?;
+            let length = v.iter().map(|c| c * c).sum::<f32>().sqrt();
+            return Ok(shaped(
+                &arguments[0],
+                v.iter().map(|c| c / length).collect(),
+            ));
+        }
+        M::Cross => {
+            let a = Vec3::from_slice(&floats(&arguments[0])?);
+            let b = Vec3::from_slice(&floats(&arguments[1])?);
+            return Ok(Value::from(a.cross(b)));
+        }
+        M::Reflect => {
+            let (e1, e2) = (floats(&arguments[0])?, floats(&arguments[1])?);
+            let d: f32 = e1.iter().zip(&e2).map(|(a, b)| a * b).sum();
+            let reflected = e1.iter().zip(&e2).map(|(a, b)| a - 2.0 * d * b).collect();
+            return Ok(shaped(&arguments[0], reflected));
+        }
+        _ => {}
+    }
+
+    component_wise(arguments, |s| match s {
+        [Scalar::I32(a), rest @ ..] => {
+            let b = |i: usize| match rest.get(i) {
+                Some(Scalar::I32(v)) => Ok(*v),
+                other => fail(format!("expected an i32, got {other:?}")),
+            };
+            Ok(Scalar::I32(match fun {
+                M::Abs => a.wrapping_abs(),
+                M::Sign => a.signum(),
+                M::Min => (*a).min(b(0)?),
+                M::Max => (*a).max(b(0)?),
+                M::Clamp => (*a).max(b(0)?).min(b(1)?),
+                _ => return fail(format!("{fun:?} is unsupported for i32")),
+            }))
+        }
+        [Scalar::U32(a), rest @ ..] => {
+            let b = |i: usize| match rest.get(i) {
+                Some(Scalar::U32(v)) => Ok(*v),
+                other => fail(format!("expected a u32, got {other:?}")),
+            };
+            Ok(Scalar::U32(match fun {
+                M::Abs => *a,
+                M::Min => (*a).min(b(0)?),
+                M::Max => (*a).max(b(0)?),
+                M::Clamp => (*a).max(b(0)?).min(b(1)?),
+                _ => return fail(format!("{fun:?} is unsupported for u32")),
+            }))
+        }
+        _ => {
+            let f = s.iter().map(|c| c.float()).collect::<Result<Vec<_>>>()?;
+            Ok(Scalar::F32(float_math(fun, &f)?))
+        }
+    })
+}
+
+fn float_math(fun: MathFunction, f: &[f32]) -> Result<f32> {
+    use MathFunction as M;
+
+    let x = f[0];
+    Ok(match fun {
+        M::Abs => x.abs(),
+        M::Min => x.min(f[1]),
+        M::Max => x.max(f[1]),
+        M::Clamp => x.max(f[1]).min(f[2]),
+        M::Saturate => x.clamp(0.0, 1.0),
+        M::Cos => x.cos(),
+        M::Cosh => x.cosh(),
+        M::Sin => x.sin(),
+        M::Sinh => x.sinh(),
+        M::Tan => x.tan(),
+        M::Tanh => x.tanh(),
+        M::Acos => x.acos(),
+        M::Asin => x.asin(),
+        M::Atan => x.atan(),
+        M::Atan2 => x.atan2(f[1]),
+        M::Asinh => x.asinh(),
+        M::Acosh => x.acosh(),
+        M::Atanh => x.atanh(),
+        M::Radians => x.to_radians(),
+        M::Degrees => x.to_degrees(),
+        M::Ceil => x.ceil(),
+        M::Floor => x.floor(),
+        M::Round => x.round_ties_even(),
+        M::Fract => x - x.floor(),
+        M::Trunc => x.trunc(),
+        M::Exp => x.exp(),
+        M::Exp2 => x.exp2(),
+        M::Log => x.ln(),
+        M::Log2 => x.log2(),
+        M::Pow => x.powf(f[1]),
+        M::Sqrt => x.sqrt(),
+        M::InverseSqrt => 1.0 / x.sqrt(),
+        M::Sign => {
+            if x > 0.0 {
+                1.0
+            } else if x < 0.0 {
+                -1.0
+            } else {
+                0.0
+            }
+        }
+        M::Step => {
+            if f[1] >= x {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        M::Mix => x + (f[1] - x) * f[2],
+        M::SmoothStep => {
+            let t = ((f[2] - x) / (f[1] - x)).clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        }
+        M::Fma => x.mul_add(f[1], f[2]),
+        _ => return fail(format!("{fun:?} is unsupported")),
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runs_loops_variables_and_calls() {
+        let module = WgslModule::parse(
+            "fn square(x: f32) -> f32 { return x * x; }
+             fn sum_squares(n: i32) -> vec2<f32> {
+                 var total = 0.0;
+                 for (var i = 1; i <= n; i = i + 1) {
+                     if i == 3 { continue; }
+                     total = total + square(f32(i));
+                 }
+                 var v = vec2<f32>(total, 0.0);
+                 v.y = select(1.0, 2.0, total > 10.0);
+                 return v;
+             }",
+        )
+        .expect("module should parse");
+
+        let result = module
+            .call("sum_squares", &[Value::from(4)])
+            .expect("call should succeed");
+        assert_eq!(result, Value::from(Vec2::new(21.0, 2.0)));
+    }
+
+    #[test]
+    fn test_matrix_math() {
+        let module = WgslModule::parse(
+            "fn turn(p: vec2<f32>) -> vec2<f32> {
+                 let m = mat2x2<f32>(0.0, 1.0, -1.0, 0.0);
+                 return m * p + p * m;
+             }",
+        )
+        .expect("module should parse");
+
+        let result = module
+            .call("turn", &[Value::from(Vec2::new(1.0, 0.0))])
+            .expect("call should succeed");
+        assert_eq!(result, Value::from(Vec2::new(0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_reports_unsupported_features() {
+        let module = WgslModule::parse(
+            "var<private> counter: f32;
+             fn bump() -> f32 { return counter; }",
+        )
+        .expect("module should parse");
+        assert!(module.call("bump", &[]).is_err());
+        assert!(module.call("missing", &[]).is_err());
+    }
+}
@@ -1,11 +1,19 @@
 //! Fixed camera angle presets for consistent, predictable renders
 //!
 //! Provides a set of standard viewing angles that MCP clients can use
-//! to get reproducible screenshots from any angle.
+//! to get reproducible screenshots from any angle. An [`OrbitView`]
+//! overrides parts of a preset (orbit angles, distance, target, field of
+//! view) to zoom in on details, and a [`RenderView`] adds the projection
+//! and diagnostic render mode.
 
 use glam::Vec3;
+use soyuz_engine::Axis;
 use soyuz_render::Camera;
 
+/// Pitch limit in degrees, short of straight up or down where the
+/// camera's up vector is undefined
+const MAX_PITCH: f32 = 89.0;
+
 /// Fixed camera viewing angles
 ///
 /// These presets provide predictable, reproducible camera positions
@@ -82,6 +90,150 @@ impl CameraAngle {
     pub fn all_names() -> &'static [&'static str] {
         &["front", "back", "left", "right", "top", "bottom", "isometric"]
     }
+
+    /// Orbit angles of the preset in degrees: yaw around the vertical axis
+    /// from the front, and pitch above the horizon
+    pub fn yaw_pitch(&self) -> (f32, f32) {
+        match self {
+            Self::Front => (0.0, 0.0),
+            Self::Back => (180.0, 0.0),
+            Self::Left => (-90.0, 0.0),
+            Self::Right => (90.0, 0.0),
+            Self::Top => (0.0, MAX_PITCH),
+            Self::Bottom => (0.0, -MAX_PITCH),
+            Self::Isometric => (45.0, 0.8_f32.atan2(std::f32::consts::SQRT_2).to_degrees()),
+        }
+    }
+}
+
+/// Explicit camera placement, overriding parts of a preset angle
+///
+/// Unset fields keep the preset's value: its orbit angles, a distance of
+/// 2.5 times the scene size, the scene center as target and a 45° field
+/// of view.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OrbitView {
+    /// Degrees around the vertical axis from the front (+Z); 90 looks from
+    /// the right (+X)
+    pub yaw: Option<f32>,
+    /// Degrees above the horizon, clamped to -89..89
+    pub pitch: Option<f32>,
+    /// Distance from the target
+    pub distance: Option<f32>,
+    /// Point to look at and orbit around
+    pub target: Option<Vec3>,
+    /// Vertical field of view in degrees
+    pub fov: Option<f32>,
+}
+
+impl OrbitView {
+    /// Camera for `angle` with these overrides, framing a scene with the
+    /// given center and size
+    pub fn to_camera(&self, angle: CameraAngle, center: Vec3, size: f32) -> Camera {
+        let placed = self.yaw.is_some()
+            || self.pitch.is_some()
+            || self.distance.is_some()
+            || self.target.is_some();
+
+        let mut camera = if placed {
+            let (preset_yaw, preset_pitch) = angle.yaw_pitch();
+            let yaw = self.yaw.unwrap_or(preset_yaw).to_radians();
+            let pitch = self
+                .pitch
+                .unwrap_or(preset_pitch)
+                .clamp(-MAX_PITCH, MAX_PITCH)
+                .to_radians();
+            let distance = self.distance.unwrap_or(size.max(1.0) * 2.5).max(0.01);
+            let target = self.target.unwrap_or(center);
+
+            let direction = Vec3::new(
+                pitch.cos() * yaw.sin(),
+                pitch.sin(),
+                pitch.cos() * yaw.cos(),
+            );
+            Camera::look_at(target + direction * distance, target)
+        } else {
+            angle.to_camera(center, size)
+        };
+
+        if let Some(fov) = self.fov {
+            camera.fov = fov.clamp(1.0, 170.0).to_radians();
+        }
+        camera
+    }
+}
+
+/// What a render draws
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ViewMode {
+    /// Lit surface, as in the preview
+    #[default]
+    Shaded,
+    /// Surface normals as colors
+    Normals,
+    /// Distance from the camera
+    Depth,
+    /// Raymarch step-count heatmap
+    Steps,
+    /// Distance-field cross-section on a plane perpendicular to `axis`, at
+    /// `offset` (the scene center if unset)
+    Slice { axis: Axis, offset: Option<f32> },
+}
+
+impl ViewMode {
+    /// Parse a mode name: "shaded", "normals", "depth", "steps" or "slice"
+    pub fn parse(name: &str, axis: Axis, offset: Option<f32>) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "shaded" => Some(Self::Shaded),
+            "normals" | "normal" => Some(Self::Normals),
+            "depth" => Some(Self::Depth),
+            "steps" | "heatmap" => Some(Self::Steps),
+            "slice" => Some(Self::Slice { axis, offset }),
+            _ => None,
+        }
+    }
+
+    /// Get all available mode names
+    pub fn all_names() -> &'static [&'static str] {
+        &["shaded", "normals", "depth", "steps", "slice"]
+    }
+}
+
+/// How to render: camera placement, projection and what to draw
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RenderView {
+    /// Overrides of the preset camera
+    pub orbit: OrbitView,
+    /// Parallel projection, as tall as the perspective view at the target
+    pub orthographic: bool,
+    /// What to draw
+    pub mode: ViewMode,
+}
+
+impl RenderView {
+    /// Whether this is a plain shaded render from the preset
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Describe where a camera is, in the terms [`OrbitView`] takes
+pub fn describe_camera(camera: &Camera) -> String {
+    let offset = camera.position - camera.target;
+    let distance = offset.length();
+    let yaw = offset.x.atan2(offset.z).to_degrees();
+    let pitch = (offset.y / distance.max(f32::EPSILON))
+        .clamp(-1.0, 1.0)
+        .asin()
+        .to_degrees();
+    let t = camera.target;
+    format!(
+        "yaw {yaw:.1}°, pitch {pitch:.1}°, distance {distance:.3}, target [{:.3}, {:.3}, {:.3}], fov {:.1}°",
+        t.x,
+        t.y,
+        t.z,
+        camera.fov.to_degrees()
+    )
 }
 
 impl std::str::FromStr for CameraAngle {
@@ -125,6 +277,54 @@ mod tests {
         assert_eq!(CameraAngle::parse("invalid"), None);
     }
 
+    #[test]
+    fn test_orbit_overrides() {
+        let center = Vec3::new(1.0, 0.0, 0.0);
+
+        // No overrides: exactly the preset
+        let preset = OrbitView::default().to_camera(CameraAngle::Front, center, 1.0);
+        assert_eq!(
+            preset.position,
+            CameraAngle::Front.to_camera(center, 1.0).position
+        );
+
+        // Yaw 90 looks from the right, at the requested distance
+        let orbit = OrbitView {
+            yaw: Some(90.0),
+            distance: Some(4.0),
+            fov: Some(20.0),
+            ..OrbitView::default()
+        };
+        let camera = orbit.to_camera(CameraAngle::Front, center, 1.0);
+        assert!((camera.position - Vec3::new(5.0, 0.0, 0.0)).length() < 1e-4);
+        assert!((camera.fov.to_degrees() - 20.0).abs() < 1e-4);
+        assert!(describe_camera(&camera).starts_with("yaw 90.0°, pitch 0.0°, distance 4.000"));
+
+        // Pitch is clamped short of the pole
+        let top = OrbitView {
+            pitch: Some(120.0),
+            ..OrbitView::default()
+        };
+        let camera = top.to_camera(CameraAngle::Front, Vec3::ZERO, 1.0);
+        assert!(camera.position.y < camera.distance());
+    }
+
+    #[test]
+    fn test_parse_modes() {
+        assert_eq!(
+            ViewMode::parse("Depth", Axis::Y, None),
+            Some(ViewMode::Depth)
+        );
+        assert_eq!(
+            ViewMode::parse("slice", Axis::X, Some(0.5)),
+            Some(ViewMode::Slice {
+                axis: Axis::X,
+                offset: Some(0.5)
+            })
+        );
+        assert_eq!(ViewMode::parse("wireframe", Axis::Y, None), None);
+    }
+
     #[test]
     fn test_to_camera() {
         let angle = CameraAngle::Isometric;
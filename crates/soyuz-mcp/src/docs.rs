@@ -0,0 +1,258 @@
+//! Bundled reference material served as MCP resources
+//!
+//! Agents write better scripts when they can start from working ones, so
+//! the server ships the example scripts, the cookbook (one resource per
+//! section) and the formula reference generated by `soyuz-math`:
+//!
+//! - `soyuz://examples/<name>` - example scripts, e.g. `soyuz://examples/barrel`
+//! - `soyuz://cookbook/<section>` - cookbook sections, e.g. `soyuz://cookbook/transforms`
+//! - `soyuz://docs/formulas` - math behind every primitive and operation
+
+/// URI prefix of example scripts
+pub const EXAMPLES_PREFIX: &str = "soyuz://examples/";
+
+/// URI prefix of cookbook sections
+pub const COOKBOOK_PREFIX: &str = "soyuz://cookbook/";
+
+/// URI of the formula reference
+pub const FORMULAS_URI: &str = "soyuz://docs/formulas";
+
+/// MIME type of example scripts
+pub const RHAI_MIME: &str = "text/x-rhai";
+
+/// MIME type of the cookbook and formula reference
+pub const MARKDOWN_MIME: &str = "text/markdown";
+
+/// The cookbook, as shipped at the repository root
+const COOKBOOK: &str = include_str!("../../../SOYUZ_COOKBOOK.md");
+
+/// Example scripts by name, one for every script in `examples/`
+const EXAMPLES: &[(&str, &str)] = &[
+    (
+        "alien_relay",
+        include_str!("../../../examples/alien_relay.rhai"),
+    ),
+    ("barrel", include_str!("../../../examples/barrel.rhai")),
+    ("donut", include_str!("../../../examples/donut.rhai")),
+    ("gear", include_str!("../../../examples/gear.rhai")),
+    ("lattice", include_str!("../../../examples/lattice.rhai")),
+    ("pistol", include_str!("../../../examples/pistol.rhai")),
+    (
+        "primitives",
+        include_str!("../../../examples/primitives.rhai"),
+    ),
+    (
+        "sci_fi_crate",
+        include_str!("../../../examples/sci_fi_crate.rhai"),
+    ),
+    (
+        "test_preview",
+        include_str!("../../../examples/test_preview.rhai"),
+    ),
+    (
+        "twisted_column",
+        include_str!("../../../examples/twisted_column.rhai"),
+    ),
+];
+
+/// A bundled document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Doc {
+    /// Resource URI
+    pub uri: String,
+    /// Short name, e.g. the example's file stem or the section title
+    pub name: String,
+    /// One-line summary
+    pub description: String,
+    /// MIME type
+    pub mime_type: &'static str,
+    /// Full text
+    pub text: &'static str,
+}
+
+/// Every bundled document: examples, then cookbook sections, then formulas
+pub fn all() -> Vec<Doc> {
+    let mut docs = examples();
+    docs.extend(cookbook_sections());
+    docs.push(formulas());
+    docs
+}
+
+/// Find a bundled document by URI
+pub fn find(uri: &str) -> Option<Doc> {
+    all().into_iter().find(|doc| doc.uri == uri)
+}
+
+/// The example scripts
+pub fn examples() -> Vec<Doc> {
+    EXAMPLES
+        .iter()
+        .map(|&(name, source)| Doc {
+            uri: format!("{EXAMPLES_PREFIX}{name}"),
+            name: format!("{name}.rhai"),
+            description: script_summary(source),
+            mime_type: RHAI_MIME,
+            text: source,
+        })
+        .collect()
+}
+
+/// Source of an example script by name
+pub fn example(name: &str) -> Option<&'static str> {
+    EXAMPLES
+        .iter()
+        .find(|&&(example, _)| example == name)
+        .map(|&(_, source)| source)
+}
+
+/// The cookbook split at its `##` headings, without the table of contents
+pub fn cookbook_sections() -> Vec<Doc> {
+    let mut sections = Vec::new();
+    let mut current: Option<(&str, usize)> = None;
+    let mut in_code = false;
+    let mut offset = 0;
+
+    for line in COOKBOOK.split_inclusive('\n') {
+        if line.starts_with("```") {
+            in_code = !in_code;
+        }
+        if !in_code && let Some(title) = line.strip_prefix("## ") {
+            if let Some((title, start)) = current {
+                sections.push((title, &COOKBOOK[start..offset]));
+            }
+            current = Some((title.trim(), offset));
+        }
+        offset += line.len();
+    }
+    if let Some((title, start)) = current {
+        sections.push((title, &COOKBOOK[start..]));
+    }
+
+    sections
+        .into_iter()
+        .filter(|(title, _)| *title != "Table of Contents")
+        .map(|(title, text)| {
+            // Sections end with a `---` rule before the next heading
+            let text = text.trim_end().trim_end_matches("---").trim_end();
+            Doc {
+                uri: format!("{COOKBOOK_PREFIX}{}", slug(title)),
+                name: title.to_string(),
+                description: format!("Cookbook: {title}"),
+                mime_type: MARKDOWN_MIME,
+                text,
+            }
+        })
+        .collect()
+}
+
+/// A cookbook section by slug, e.g. `"boolean-operations"`
+pub fn cookbook_section(slug: &str) -> Option<Doc> {
+    let uri = format!("{COOKBOOK_PREFIX}{slug}");
+    cookbook_sections().into_iter().find(|doc| doc.uri == uri)
+}
+
+/// The formula reference generated from `soyuz-math`
+pub fn formulas() -> Doc {
+    Doc {
+        uri: FORMULAS_URI.to_string(),
+        name: "formulas".to_string(),
+        description: "Distance functions and operations behind every primitive, with their math"
+            .to_string(),
+        mime_type: MARKDOWN_MIME,
+        text: soyuz_math::get_docs(),
+    }
+}
+
+/// First line of a script's header comment, without decoration
+fn script_summary(source: &str) -> String {
+    source
+        .lines()
+        .map_while(|line| line.trim().strip_prefix("//"))
+        .map(|line| line.trim_matches(|c: char| c == '=' || c.is_whitespace()))
+        .find(|line| !line.is_empty())
+        .unwrap_or("Example script")
+        .to_string()
+}
+
+/// URI-friendly section name: `"Materials & Colors"` becomes `"materials-colors"`
+fn slug(title: &str) -> String {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_examples_have_summaries() {
+        let examples = examples();
+        assert_eq!(examples.len(), EXAMPLES.len());
+        let barrel = examples
+            .iter()
+            .find(|doc| doc.uri == "soyuz://examples/barrel")
+            .expect("barrel should be bundled");
+        assert_eq!(
+            barrel.description,
+            "Barrel - A classic procedural asset example"
+        );
+        assert!(
+            examples
+                .iter()
+                .all(|doc| doc.description != "Example script")
+        );
+        assert_eq!(script_summary("// ====\n// PISTOL\n// ===="), "PISTOL");
+    }
+
+    #[test]
+    fn test_every_example_is_bundled() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+        let mut on_disk: Vec<String> = std::fs::read_dir(&dir)
+            .expect("examples directory should exist")
+            .map(|entry| entry.expect("examples entry").path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .map(|path| {
+                path.file_stem()
+                    .expect("script has a name")
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        on_disk.sort();
+
+        let bundled: Vec<&str> = EXAMPLES.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            bundled, on_disk,
+            "EXAMPLES should list every examples/*.rhai"
+        );
+    }
+
+    #[test]
+    fn test_cookbook_sections() {
+        let sections = cookbook_sections();
+        let titles: Vec<_> = sections.iter().map(|doc| doc.name.as_str()).collect();
+        assert_eq!(titles.first(), Some(&"Quick Start"));
+        assert!(titles.contains(&"Tips & Tricks"));
+        assert!(!titles.contains(&"Table of Contents"));
+
+        let materials = cookbook_section("materials-colors").expect("section should exist");
+        assert!(materials.text.starts_with("## Materials & Colors"));
+        assert!(!materials.text.ends_with("---"));
+
+        // Shell comments inside code blocks aren't headings
+        let animation = cookbook_section("animation").expect("section should exist");
+        assert!(animation.text.contains("turntable"));
+    }
+
+    #[test]
+    fn test_find() {
+        assert!(find(FORMULAS_URI).is_some_and(|doc| !doc.text.is_empty()));
+        assert!(find("soyuz://cookbook/transforms").is_some());
+        assert!(find("soyuz://examples/missing").is_none());
+    }
+}
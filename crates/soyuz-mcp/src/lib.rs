@@ -14,24 +14,54 @@
 //! 2. `render_preview` - See what the scene looks like
 //! 3. Iterate on the script based on visual feedback
 //! 4. `export_mesh` - Export the final result as a 3D file
+//!
+//! Scripts that declare parameters with `param()` can be re-run with
+//! different values through `run_script`'s `params` argument.
+//!
+//! When a preset angle isn't enough, the render tools take a custom orbit
+//! (yaw, pitch, distance, target, FOV) and an orthographic projection, and
+//! diagnostic modes show normals, depth, a raymarch step heatmap or a slice
+//! of the distance field.
+//!
+//! Large results don't have to travel inline: `export_mesh` and the render
+//! tools can save files under a sandboxed output directory (see [`output`])
+//! and return `soyuz://output/...` resource links, which clients read on
+//! demand through the resources API.
+//!
+//! The example scripts, the cookbook and the formula reference are resources
+//! too (see [`docs`]), and [`prompts`] has templates for common sessions
+//! that embed the API docs they need.
 
 pub mod camera;
+pub mod docs;
+pub mod output;
+pub mod prompts;
 pub mod state;
 pub mod tools;
 
+use std::path::Path;
+
 use base64::Engine as _;
 use rmcp::{
     ErrorData as McpError,
     ServerHandler,
     handler::server::tool::ToolRouter,
     handler::server::wrapper::Parameters,
-    model::{CallToolResult, Content, Implementation, ProtocolVersion, ServerCapabilities, ServerInfo},
+    model::{
+        AnnotateAble, CallToolResult, Content, GetPromptRequestParam, GetPromptResult,
+        Implementation, ListPromptsResult, ListResourcesResult, PaginatedRequestParam,
+        ProtocolVersion, RawResource, ReadResourceRequestParam, ReadResourceResult,
+        ResourceContents, ServerCapabilities, ServerInfo,
+    },
+    service::{RequestContext, RoleServer},
     tool, tool_handler, tool_router,
 };
 use serde_json::json;
 use soyuz_engine::ExportFormat;
 
 use crate::camera::CameraAngle;
+use crate::docs::Doc;
+use crate::output::{OutputError, OutputFile, URI_PREFIX};
 use crate::state::SoyuzState;
 use crate::tools::{
     discovery::{self, GetDocsRequest},
@@ -69,13 +99,22 @@ impl SoyuzMcpService {
     // Script Execution Tools
     // ========================================================================
 
-    #[tool(description = "Execute a Rhai script to create or update the current 3D scene. The script must return an SDF (Signed Distance Field) as its final expression (no trailing semicolon). Returns scene information on success or an error message.")]
+    #[tool(description = "Execute a Rhai script to create or update the current 3D scene. The script must return an SDF (Signed Distance Field) as its final expression (no trailing semicolon). Parameters the script declares with param() can be overridden with `params`. Returns scene information, including the declared parameters, on success or an error message.")]
     async fn run_script(
         &self,
         params: Parameters<RunScriptRequest>,
     ) -> Result<CallToolResult, McpError> {
         let request = params.0;
-        match self.state.run_script(&request.code).await {
+        let overrides = match request.param_overrides() {
+            Ok(overrides) => overrides,
+            Err(e) => {
+                return Ok(CallToolResult::success(vec![Content::text(format!(
+                    "Script error: {}",
+                    e
+                ))]));
+            }
+        };
+        match self.state.run_script(&request.code, overrides).await {
             Ok(info) => Ok(CallToolResult::success(vec![Content::text(info.to_string())])),
             Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
                 "Script error: {}",
@@ -105,21 +144,52 @@ impl SoyuzMcpService {
     // Rendering Tools
     // ========================================================================
 
-    #[tool(description = "Render the current scene as a PNG image. Returns a base64-encoded image that can be viewed to inspect the 3D model. Use different angles to see the model from various viewpoints.")]
+    #[tool(description = "Render the current scene as a PNG image. Returns a base64-encoded image that can be viewed to inspect the 3D model. Use different angles to see the model from various viewpoints. Optional yaw/pitch (degrees), distance, target and fov override the preset camera, and orthographic removes perspective. mode picks what is drawn: shaded (default), normals, depth, steps (raymarch step heatmap, to find loose distance bounds) or slice (signed distance on a plane through the scene, set with slice_axis and slice_offset).")]
     async fn render_preview(
         &self,
         params: Parameters<RenderPreviewRequest>,
     ) -> Result<CallToolResult, McpError> {
         let request = params.0;
         let angle = CameraAngle::parse(&request.angle).unwrap_or_default();
+        let view = match request.view.to_view() {
+            Ok(view) => view,
+            Err(e) => return Ok(CallToolResult::success(vec![Content::text(e)])),
+        };
+        let path = match request
+            .path
+            .as_deref()
+            .map(|path| output::with_extension(path, "png"))
+            .transpose()
+        {
+            Ok(path) => path,
+            Err(e) => {
+                return Ok(CallToolResult::success(vec![Content::text(format!(
+                    "Render error: {}",
+                    e
+                ))]));
+            }
+        };
 
-        match self.state.render(angle, request.width, request.height).await {
-            Ok(png_bytes) => {
-                let b64 = base64::engine::general_purpose::STANDARD.encode(&png_bytes);
-                Ok(CallToolResult::success(vec![Content::image(
-                    b64,
-                    "image/png",
-                )]))
+        match self
+            .state
+            .render(angle, view, request.width, request.height)
+            .await
+        {
+            Ok(rendered) => {
+                let mut contents = Vec::with_capacity(3);
+                if !view.is_default() {
+                    contents.push(Content::text(rendered.description));
+                }
+                if let Some(path) = path {
+                    match self.state.output().write(&path, &rendered.png) {
+                        Ok(file) => contents.extend(self.saved(&file)),
+                        Err(e) => contents.push(Content::text(format!("Render error: {}", e))),
+                    }
+                } else {
+                    let b64 = base64::engine::general_purpose::STANDARD.encode(&rendered.png);
+                    contents.push(Content::image(b64, "image/png"));
+                }
+                Ok(CallToolResult::success(contents))
             }
             Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
                 "Render error: {}",
@@ -128,13 +198,17 @@ impl SoyuzMcpService {
         }
     }
 
-    #[tool(description = "Render the current scene from multiple angles at once. Returns multiple PNG images. Use comma-separated angle names (e.g., \"front, right, isometric\") or \"all\" for all 7 standard angles.")]
+    #[tool(description = "Render the current scene from multiple angles at once. Returns multiple PNG images. Use comma-separated angle names (e.g., \"front, right, isometric\") or \"all\" for all 7 standard angles. Takes the same camera overrides and mode as render_preview; yaw/pitch replace each angle's direction. With directory, images are saved as <directory>/<angle>.png in the output directory and returned as resource links.")]
     async fn render_previews(
         &self,
         params: Parameters<RenderPreviewsRequest>,
     ) -> Result<CallToolResult, McpError> {
         let request = params.0;
         let angles = request.parse_angles();
+        let view = match request.view.to_view() {
+            Ok(view) => view,
+            Err(e) => return Ok(CallToolResult::success(vec![Content::text(e)])),
+        };
 
         if angles.is_empty() {
             return Ok(CallToolResult::success(vec![Content::text(
@@ -142,16 +216,44 @@ impl SoyuzMcpService {
             )]));
         }
 
-        let mut contents = Vec::with_capacity(angles.len() * 2);
+        let mut contents = Vec::with_capacity(angles.len() * 3);
+        let directory = request
+            .directory
+            .as_deref()
+            .map(|dir| dir.trim().trim_end_matches(['/', '\\']));
 
         for angle_name in angles {
             let angle = CameraAngle::parse(angle_name).unwrap_or_default();
 
-            match self.state.render(angle, request.width, request.height).await {
-                Ok(png_bytes) => {
-                    contents.push(Content::text(format!("[{}]", angle_name)));
-                    let b64 = base64::engine::general_purpose::STANDARD.encode(&png_bytes);
-                    contents.push(Content::image(b64, "image/png"));
+            match self
+                .state
+                .render(angle, view, request.width, request.height)
+                .await
+            {
+                Ok(rendered) => {
+                    if view.is_default() {
+                        contents.push(Content::text(format!("[{}]", angle_name)));
+                    } else {
+                        contents.push(Content::text(format!(
+                            "[{}] {}",
+                            angle_name, rendered.description
+                        )));
+                    }
+                    if let Some(dir) = directory {
+                        let path = if dir.is_empty() {
+                            format!("{}.png", angle_name)
+                        } else {
+                            format!("{}/{}.png", dir, angle_name)
+                        };
+                        match self.state.output().write(&path, &rendered.png) {
+                            Ok(file) => contents.extend(self.saved(&file)),
+                            Err(e) => contents
+                                .push(Content::text(format!("[{}] Error: {}", angle_name, e))),
+                        }
+                    } else {
+                        let b64 = base64::engine::general_purpose::STANDARD.encode(&rendered.png);
+                        contents.push(Content::image(b64, "image/png"));
+                    }
                 }
                 Err(e) => {
                     contents.push(Content::text(format!("[{}] Error: {}", angle_name, e)));
@@ -166,7 +268,7 @@ impl SoyuzMcpService {
     // Export Tools
     // ========================================================================
 
-    #[tool(description = "Export the current scene as a 3D mesh file. Returns base64-encoded file data. Supported formats: glb (binary glTF, recommended), gltf, obj, stl.")]
+    #[tool(description = "Export the current scene as a 3D mesh file. Returns base64-encoded file data, or with path, writes the file under the server's output directory and returns a soyuz://output/ resource link plus mesh stats (use this above resolution 64 to keep large meshes out of the conversation). Supported formats: glb (binary glTF, recommended), gltf, obj, stl.")]
     async fn export_mesh(
         &self,
         params: Parameters<ExportMeshRequest>,
@@ -185,8 +287,49 @@ impl SoyuzMcpService {
             }
         };
 
-        match self.state.export_mesh(format, request.resolution, request.optimize).await {
+        let destination = match request.path.as_deref().map(|path| {
+            output::with_extension(path, format.extension()).and_then(|path| {
+                let companions = format.companion_paths(Path::new(path.trim()));
+                self.state
+                    .output()
+                    .prepare_with_companions(&path, &companions)
+            })
+        }) {
+            Some(Ok(destination)) => Some(destination),
+            Some(Err(e)) => {
+                return Ok(CallToolResult::success(vec![Content::text(format!(
+                    "Export error: {}",
+                    e
+                ))]));
+            }
+            None => None,
+        };
+
+        match self
+            .state
+            .export_mesh(
+                format,
+                request.resolution,
+                request.cell_size,
+                request.optimize,
+                destination,
+            )
+            .await
+        {
             Ok(info) => {
+                if let Some(path) = &info.path {
+                    let mut contents = vec![Content::text(info.to_string())];
+                    for file in std::iter::once(path).chain(&info.companions) {
+                        match self.state.output().file(file) {
+                            Ok(file) => contents.extend(self.saved(&file)),
+                            Err(e) => {
+                                contents.push(Content::text(format!("Export error: {}", e)));
+                            }
+                        }
+                    }
+                    return Ok(CallToolResult::success(contents));
+                }
+
                 let b64 = base64::engine::general_purpose::STANDARD.encode(&info.bytes);
                 let summary = info.to_string();
 
@@ -257,14 +400,21 @@ impl SoyuzMcpService {
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "List all available math helper functions (deg, rad, PI, TAU) with their signatures and descriptions.")]
+    #[tool(description = "List procedural texture and PBR material functions (perlin, fbm, bricks, warp, pbr, set_material) with their signatures and descriptions.")]
+    async fn list_textures(&self) -> Result<CallToolResult, McpError> {
+        let textures = discovery::list_textures();
+        let json = serde_json::to_string_pretty(&textures).unwrap_or_default();
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "List all available math helper functions (deg, rad, PI, TAU) and param() for declaring script parameters, with their signatures and descriptions.")]
     async fn list_math(&self) -> Result<CallToolResult, McpError> {
         let math = discovery::list_math();
         let json = serde_json::to_string_pretty(&math).unwrap_or_default();
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "List ALL available Soyuz functions in one call. Returns complete documentation for primitives, operations, transforms, modifiers, environment, and math functions. Use this for comprehensive discovery.")]
+    #[tool(description = "List ALL available Soyuz functions in one call. Returns complete documentation for primitives, operations, transforms, modifiers, environment, textures, and math functions. Use this for comprehensive discovery.")]
     async fn list_all(&self) -> Result<CallToolResult, McpError> {
         let all_docs = json!({
             "primitives": discovery::list_primitives(),
@@ -272,6 +422,7 @@ impl SoyuzMcpService {
             "transforms": discovery::list_transforms(),
             "modifiers": discovery::list_modifiers(),
             "environment": discovery::list_environment(),
+            "textures": discovery::list_textures(),
             "math": discovery::list_math(),
         });
         let json = serde_json::to_string_pretty(&all_docs).unwrap_or_default();
@@ -331,12 +482,141 @@ impl SoyuzMcpService {
     }
 }
 
+impl SoyuzMcpService {
+    /// Where a saved file is, as text and as a resource link
+    fn saved(&self, file: &OutputFile) -> [Content; 2] {
+        let location = self.state.output().root().join(&file.path);
+        [
+            Content::text(format!(
+                "Saved {} ({} bytes) to {}",
+                file.uri(),
+                file.size,
+                location.display()
+            )),
+            Content::resource_link(resource(file)),
+        ]
+    }
+}
+
+/// Resource describing a file in the output directory
+fn resource(file: &OutputFile) -> RawResource {
+    RawResource {
+        mime_type: Some(file.mime_type().to_string()),
+        size: u32::try_from(file.size).ok(),
+        ..RawResource::new(file.uri(), file.path.clone())
+    }
+}
+
+/// Resource describing a bundled document
+fn doc_resource(doc: &Doc) -> RawResource {
+    RawResource {
+        description: Some(doc.description.clone()),
+        mime_type: Some(doc.mime_type.to_string()),
+        size: u32::try_from(doc.text.len()).ok(),
+        ..RawResource::new(doc.uri.clone(), doc.name.clone())
+    }
+}
+
 #[tool_handler]
 impl ServerHandler for SoyuzMcpService {
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let files = self
+            .state
+            .output()
+            .list()
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let docs = docs::all();
+        Ok(ListResourcesResult::with_all_items(
+            docs.iter()
+                .map(doc_resource)
+                .chain(files.iter().map(resource))
+                .map(AnnotateAble::no_annotation)
+                .collect(),
+        ))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        if !request.uri.starts_with(URI_PREFIX) {
+            let doc = docs::find(&request.uri).ok_or_else(|| {
+                McpError::resource_not_found(format!("No resource '{}'", request.uri), None)
+            })?;
+            return Ok(ReadResourceResult {
+                contents: vec![ResourceContents::TextResourceContents {
+                    uri: doc.uri,
+                    mime_type: Some(doc.mime_type.to_string()),
+                    text: doc.text.to_string(),
+                    meta: None,
+                }],
+            });
+        }
+
+        let (file, bytes) = self
+            .state
+            .output()
+            .read_uri(&request.uri)
+            .map_err(|e| match e {
+                OutputError::Io(_) | OutputError::NotOutputUri(_) => {
+                    McpError::resource_not_found(e.to_string(), None)
+                }
+                _ => McpError::invalid_params(e.to_string(), None),
+            })?;
+
+        let uri = file.uri();
+        let mime_type = Some(file.mime_type().to_string());
+        let contents = match String::from_utf8(bytes) {
+            Ok(text) if file.is_text() => ResourceContents::TextResourceContents {
+                uri,
+                mime_type,
+                text,
+                meta: None,
+            },
+            // Binary formats, and text files that aren't valid UTF-8
+            result => ResourceContents::BlobResourceContents {
+                uri,
+                mime_type,
+                blob: base64::engine::general_purpose::STANDARD
+                    .encode(result.map_or_else(|e| e.into_bytes(), String::into_bytes)),
+                meta: None,
+            },
+        };
+        Ok(ReadResourceResult {
+            contents: vec![contents],
+        })
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, McpError> {
+        Ok(ListPromptsResult::with_all_items(prompts::list()))
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        prompts::get(&request.name, request.arguments.as_ref())
+            .map_err(|e| McpError::invalid_params(e, None))
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_prompts()
+                .enable_resources()
+                .enable_tools()
+                .build(),
             server_info: Implementation {
                 name: "soyuz-mcp".to_string(),
                 title: Some("Soyuz 3D Asset Generator".to_string()),
@@ -351,7 +631,17 @@ impl ServerHandler for SoyuzMcpService {
                  2. render_preview() - See what the scene looks like\n\
                  3. Iterate on the script based on visual feedback\n\
                  4. export_mesh() - Export the final result as a 3D file\n\n\
+                 Scripts can declare parameters with param(); pass run_script(params) to make variants\n\
+                 without editing the script.\n\
+                 render_preview() also takes yaw/pitch/distance/target/fov and orthographic, and\n\
+                 mode = normals, depth, steps or slice to debug shapes that look wrong.\n\
+                 Pass path to export_mesh (or render_preview) to save the file under the output\n\
+                 directory and get a soyuz://output/ resource link instead of inline data.\n\
                  Use list_all() to discover all available functions in one call.\n\
+                 Working example scripts (soyuz://examples/...) and the cookbook\n\
+                 (soyuz://cookbook/...) are available as resources; start from them rather than\n\
+                 guessing syntax. The prompts model_hard_surface_prop and iterate_from_renders\n\
+                 set up common sessions.\n\
                  Use get_docs(function_name) for detailed documentation on any function."
                     .to_string(),
             ),
@@ -20,13 +20,20 @@
 //!   }
 //! }
 //! ```
+//!
+//! Exports and renders that tools save to disk go under `--output-dir DIR`,
+//! or `$SOYUZ_MCP_OUTPUT_DIR`, or `soyuz-mcp` in the user's cache directory.
+//! Tools can't write anywhere else.
+
+use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
 use rmcp::ServiceExt;
 use rmcp::transport::io::stdio;
 use tracing_subscriber::fmt;
 use tracing_subscriber::prelude::*;
 
+use soyuz_mcp::output::OutputDir;
 use soyuz_mcp::state::SoyuzState;
 use soyuz_mcp::SoyuzMcpService;
 
@@ -43,12 +50,25 @@ async fn main() -> Result<()> {
         .init();
 
     eprintln!("Soyuz MCP server v{}", env!("CARGO_PKG_VERSION"));
+
+    let output = match output_dir_arg()? {
+        Some(dir) => OutputDir::new(dir)?,
+        None => OutputDir::from_env()?,
+    };
+    eprintln!(
+        "Saving exports and renders under {}",
+        output.root().display()
+    );
     eprintln!("Initializing GPU...");
 
-    // Initialize headless GPU and state
-    let state = SoyuzState::new().await?;
+    // Initialize headless GPU (if there is one) and state
+    let state = SoyuzState::new(output).await?;
 
-    eprintln!("GPU initialized successfully.");
+    if state.has_gpu() {
+        eprintln!("GPU initialized successfully.");
+    } else {
+        eprintln!("No GPU available; rendering on the CPU.");
+    }
     eprintln!("Ready. Listening on stdio...");
 
     // Create service and serve on stdio transport
@@ -61,3 +81,23 @@ async fn main() -> Result<()> {
     eprintln!("Client disconnected. Shutting down.");
     Ok(())
 }
+
+/// Directory given with `--output-dir`, if any
+fn output_dir_arg() -> Result<Option<PathBuf>> {
+    let mut args = std::env::args_os().skip(1);
+    let mut dir = None;
+    while let Some(arg) = args.next() {
+        if arg == "--output-dir" {
+            let value = args
+                .next()
+                .ok_or_else(|| anyhow!("--output-dir needs a directory"))?;
+            dir = Some(PathBuf::from(value));
+        } else {
+            bail!(
+                "Unknown argument '{}'. Usage: soyuz-mcp [--output-dir DIR]",
+                arg.to_string_lossy()
+            );
+        }
+    }
+    Ok(dir)
+}
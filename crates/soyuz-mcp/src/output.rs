@@ -0,0 +1,413 @@
+//! Sandboxed output directory for exports and renders
+//!
+//! Meshes and images can be large, so instead of returning them inline the
+//! tools can write them under a single output root and hand back a
+//! `soyuz://output/<path>` resource that clients fetch when they need it.
+//!
+//! Paths from clients are always relative to the root. Absolute paths, `..`
+//! components and symlinks that lead outside the root are rejected.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+/// URI prefix of files in the output directory
+pub const URI_PREFIX: &str = "soyuz://output/";
+
+/// Environment variable that sets the output directory
+pub const OUTPUT_DIR_ENV: &str = "SOYUZ_MCP_OUTPUT_DIR";
+
+/// Errors from resolving, reading or writing output files
+#[derive(Error, Debug)]
+pub enum OutputError {
+    /// The path is empty, or names the output directory itself
+    #[error("Path is empty; give a file name such as \"model.glb\"")]
+    Empty,
+
+    /// The path is absolute instead of relative to the output directory
+    #[error("Path '{0}' must be relative to the output directory")]
+    Absolute(String),
+
+    /// The path leaves the output directory
+    #[error("Path '{0}' is outside the output directory")]
+    Traversal(String),
+
+    /// The path's extension doesn't match the kind of file written
+    #[error("Path '{path}' should end in .{expected}")]
+    WrongExtension {
+        path: String,
+        expected: &'static str,
+    },
+
+    /// The URI doesn't name a file in the output directory
+    #[error("'{0}' is not a {URI_PREFIX} resource")]
+    NotOutputUri(String),
+
+    /// No output directory was configured and the user has no cache
+    /// directory to default to
+    #[error("No user cache directory to save files in; set {OUTPUT_DIR_ENV} or pass --output-dir")]
+    NoDefaultDir,
+
+    /// Failed to read or write a file
+    #[error("Failed to access output file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A file in the output directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputFile {
+    /// Path relative to the output directory, with `/` separators
+    pub path: String,
+    /// Size in bytes
+    pub size: u64,
+}
+
+impl OutputFile {
+    /// Resource URI of the file
+    pub fn uri(&self) -> String {
+        format!("{URI_PREFIX}{}", self.path)
+    }
+
+    /// MIME type, from the file extension
+    pub fn mime_type(&self) -> &'static str {
+        let extension = Path::new(&self.path)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("glb") => "model/gltf-binary",
+            Some("gltf") => "model/gltf+json",
+            Some("obj") => "model/obj",
+            Some("mtl") => "model/mtl",
+            Some("stl") => "model/stl",
+            Some("png") => "image/png",
+            Some("gif") => "image/gif",
+            Some("json") => "application/json",
+            Some("rhai" | "txt") => "text/plain",
+            _ => "application/octet-stream",
+        }
+    }
+
+    /// Whether the file is text, rather than binary data
+    pub fn is_text(&self) -> bool {
+        matches!(
+            self.mime_type(),
+            "model/gltf+json" | "model/obj" | "model/mtl" | "application/json" | "text/plain"
+        )
+    }
+}
+
+/// Directory that tools write files into, and that resources are read from
+#[derive(Debug, Clone)]
+pub struct OutputDir {
+    root: PathBuf,
+}
+
+impl OutputDir {
+    /// Use `root` as the output directory, creating it if needed
+    pub fn new(root: impl AsRef<Path>) -> Result<Self, OutputError> {
+        let root = root.as_ref();
+        fs::create_dir_all(root)?;
+        Ok(Self {
+            root: root.canonicalize()?,
+        })
+    }
+
+    /// The directory from `SOYUZ_MCP_OUTPUT_DIR`, or `soyuz-mcp` in the
+    /// user's cache directory
+    ///
+    /// The default is per user: in a shared location such as the system temp
+    /// directory, another user could create the directory first and swap its
+    /// contents under the server.
+    pub fn from_env() -> Result<Self, OutputError> {
+        match std::env::var_os(OUTPUT_DIR_ENV) {
+            Some(dir) if !dir.is_empty() => Self::new(dir),
+            _ => {
+                let cache = dirs::cache_dir().ok_or(OutputError::NoDefaultDir)?;
+                Self::new(cache.join("soyuz-mcp"))
+            }
+        }
+    }
+
+    /// Absolute path of the output directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolve a client path to a location inside the output directory
+    ///
+    /// The file doesn't have to exist yet, but any part of the path that
+    /// does exist must not lead outside the root through a symlink.
+    pub fn resolve(&self, path: &str) -> Result<PathBuf, OutputError> {
+        let relative = normalize(path)?;
+        let resolved = self.root.join(&relative);
+
+        // The deepest part of the path that exists decides where it leads.
+        // Symlinks count even when broken, so writing can't create their target
+        let existing = resolved
+            .ancestors()
+            .find(|ancestor| fs::symlink_metadata(ancestor).is_ok())
+            .unwrap_or(&self.root);
+        if !existing.canonicalize()?.starts_with(&self.root) {
+            return Err(OutputError::Traversal(path.to_string()));
+        }
+        Ok(resolved)
+    }
+
+    /// Resolve a path and create the directories it's in
+    pub fn prepare(&self, path: &str) -> Result<PathBuf, OutputError> {
+        let resolved = self.resolve(path)?;
+        if let Some(parent) = resolved.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(resolved)
+    }
+
+    /// Resolve a path like [`prepare`](Self::prepare), first checking that
+    /// the `companions` written next to it (paths relative to the root, like
+    /// `path`) stay inside the root too
+    pub fn prepare_with_companions(
+        &self,
+        path: &str,
+        companions: &[PathBuf],
+    ) -> Result<PathBuf, OutputError> {
+        for companion in companions {
+            self.resolve(&companion.to_string_lossy())?;
+        }
+        self.prepare(path)
+    }
+
+    /// Write `bytes` to `path`, replacing any file already there
+    pub fn write(&self, path: &str, bytes: &[u8]) -> Result<OutputFile, OutputError> {
+        let resolved = self.prepare(path)?;
+        fs::write(&resolved, bytes)?;
+        self.file(&resolved)
+    }
+
+    /// Describe a file inside the output directory, e.g. one written with a
+    /// path from [`prepare`](Self::prepare)
+    pub fn file(&self, resolved: &Path) -> Result<OutputFile, OutputError> {
+        let relative = resolved
+            .strip_prefix(&self.root)
+            .map_err(|_| OutputError::Traversal(resolved.display().to_string()))?;
+        Ok(OutputFile {
+            path: to_slash(relative),
+            size: fs::metadata(resolved)?.len(),
+        })
+    }
+
+    /// Read a file from a `soyuz://output/` URI
+    pub fn read_uri(&self, uri: &str) -> Result<(OutputFile, Vec<u8>), OutputError> {
+        let path = uri
+            .strip_prefix(URI_PREFIX)
+            .ok_or_else(|| OutputError::NotOutputUri(uri.to_string()))?;
+        let resolved = self.resolve(path)?;
+        let bytes = fs::read(&resolved)?;
+        Ok((self.file(&resolved)?, bytes))
+    }
+
+    /// All files in the output directory, sorted by path
+    ///
+    /// Symlinks are skipped, so listing never leaves the root.
+    pub fn list(&self) -> Result<Vec<OutputFile>, OutputError> {
+        let mut files = Vec::new();
+        let mut dirs = vec![self.root.clone()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let entry = entry?;
+                let file_type = entry.file_type()?;
+                if file_type.is_dir() {
+                    dirs.push(entry.path());
+                } else if file_type.is_file() {
+                    files.push(self.file(&entry.path())?);
+                }
+            }
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+}
+
+/// Add `extension` to a client path that has none
+///
+/// A path with a different extension is rejected rather than producing,
+/// say, a GLB file named `model.stl`.
+pub fn with_extension(path: &str, extension: &'static str) -> Result<String, OutputError> {
+    let path = path.trim();
+    match Path::new(path).extension() {
+        None => Ok(format!("{path}.{extension}")),
+        Some(ext) if ext.eq_ignore_ascii_case(extension) => Ok(path.to_string()),
+        Some(_) => Err(OutputError::WrongExtension {
+            path: path.to_string(),
+            expected: extension,
+        }),
+    }
+}
+
+/// Check a client path and drop `.` components
+fn normalize(path: &str) -> Result<PathBuf, OutputError> {
+    // Accept either separator, whatever the host platform
+    let path = path.trim();
+    let unified = path.replace('\\', "/");
+
+    let mut relative = PathBuf::new();
+    for component in Path::new(&unified).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => return Err(OutputError::Traversal(path.to_string())),
+            Component::RootDir | Component::Prefix(_) => {
+                return Err(OutputError::Absolute(path.to_string()));
+            }
+        }
+    }
+
+    // Drive letters are only a prefix on Windows
+    if unified.as_bytes().get(1) == Some(&b':') {
+        return Err(OutputError::Absolute(path.to_string()));
+    }
+    if relative.as_os_str().is_empty() {
+        return Err(OutputError::Empty);
+    }
+    Ok(relative)
+}
+
+/// A relative path with `/` separators, for URIs
+fn to_slash(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn output_dir(name: &str) -> OutputDir {
+        let root =
+            std::env::temp_dir().join(format!("soyuz_mcp_test_{}_{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        OutputDir::new(&root).expect("output dir should be created")
+    }
+
+    #[test]
+    fn test_rejects_paths_outside_root() {
+        let output = output_dir("traversal");
+        for path in [
+            "../escape.glb",
+            "models/../../escape.glb",
+            "a/..\\..\\b.png",
+        ] {
+            assert!(
+                matches!(output.resolve(path), Err(OutputError::Traversal(_))),
+                "{path} should be rejected"
+            );
+        }
+        for path in ["/etc/passwd", "\\temp\\x.png", "C:/x.glb"] {
+            assert!(
+                matches!(output.resolve(path), Err(OutputError::Absolute(_))),
+                "{path} should be rejected"
+            );
+        }
+        assert!(matches!(output.resolve("./"), Err(OutputError::Empty)));
+        assert!(matches!(
+            output.read_uri("file:///etc/passwd"),
+            Err(OutputError::NotOutputUri(_))
+        ));
+        let _ = fs::remove_dir_all(output.root());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_symlinks_outside_root() {
+        let output = output_dir("symlink");
+        let outside = output_dir("symlink_target");
+        std::os::unix::fs::symlink(outside.root(), output.root().join("link"))
+            .expect("symlink should be created");
+
+        assert!(matches!(
+            output.write("link/escape.glb", b"data"),
+            Err(OutputError::Traversal(_))
+        ));
+        assert!(!outside.root().join("escape.glb").exists());
+        assert!(output.list().expect("listing should succeed").is_empty());
+
+        // A dangling link would otherwise create its target on write
+        std::os::unix::fs::symlink(
+            outside.root().join("created.glb"),
+            output.root().join("dangling.glb"),
+        )
+        .expect("symlink should be created");
+        assert!(output.write("dangling.glb", b"data").is_err());
+        assert!(!outside.root().join("created.glb").exists());
+
+        let _ = fs::remove_dir_all(output.root());
+        let _ = fs::remove_dir_all(outside.root());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_companions_outside_root() {
+        let output = output_dir("companion");
+        let outside = output_dir("companion_target");
+        std::os::unix::fs::symlink(
+            outside.root().join("model.mtl"),
+            output.root().join("model.mtl"),
+        )
+        .expect("symlink should be created");
+
+        assert!(
+            output
+                .prepare_with_companions("model.obj", &[PathBuf::from("model.mtl")])
+                .is_err()
+        );
+        assert!(!outside.root().join("model.mtl").exists());
+        assert!(output.prepare_with_companions("model.obj", &[]).is_ok());
+
+        let _ = fs::remove_dir_all(output.root());
+        let _ = fs::remove_dir_all(outside.root());
+    }
+
+    #[test]
+    fn test_with_extension() {
+        assert_eq!(
+            with_extension("ship", "glb").ok().as_deref(),
+            Some("ship.glb")
+        );
+        assert_eq!(
+            with_extension("out/ship.GLB", "glb").ok().as_deref(),
+            Some("out/ship.GLB")
+        );
+        assert!(matches!(
+            with_extension("ship.stl", "glb"),
+            Err(OutputError::WrongExtension { .. })
+        ));
+    }
+
+    #[test]
+    fn test_write_list_and_read() {
+        let output = output_dir("files");
+        let file = output
+            .write("./renders\\front.png", b"png")
+            .expect("write should succeed");
+        assert_eq!(file.path, "renders/front.png");
+        assert_eq!(file.uri(), "soyuz://output/renders/front.png");
+        assert_eq!(file.mime_type(), "image/png");
+        assert!(!file.is_text());
+
+        output
+            .write("model.obj", b"v 0 0 0")
+            .expect("write should succeed");
+        let files = output.list().expect("listing should succeed");
+        let paths: Vec<_> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["model.obj", "renders/front.png"]);
+
+        let (read, bytes) = output
+            .read_uri("soyuz://output/model.obj")
+            .expect("read should succeed");
+        assert!(read.is_text());
+        assert_eq!(bytes, b"v 0 0 0");
+        let _ = fs::remove_dir_all(output.root());
+    }
+}
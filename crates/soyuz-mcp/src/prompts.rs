@@ -0,0 +1,256 @@
+//! Prompt templates for common modeling sessions
+//!
+//! Each prompt spells out a workflow with the server's tools and embeds the
+//! cookbook sections (and, where it helps, a working example script) that
+//! the task needs, so the agent doesn't have to guess at the syntax.
+
+use rmcp::model::{
+    GetPromptResult, JsonObject, Prompt, PromptArgument, PromptMessage, PromptMessageRole,
+};
+
+use crate::docs;
+
+/// A prompt template
+struct Template {
+    name: &'static str,
+    description: &'static str,
+    /// Name, description and whether the argument is required
+    arguments: &'static [(&'static str, &'static str, bool)],
+    /// Task and workflow, from the arguments
+    instructions: fn(&Arguments) -> String,
+    /// Cookbook sections embedded after the instructions, by slug
+    sections: &'static [&'static str],
+    /// Example script embedded before the sections
+    example: Option<&'static str>,
+}
+
+const TEMPLATES: &[Template] = &[
+    Template {
+        name: "model_hard_surface_prop",
+        description: "Model a hard-surface prop (crate, weapon, machine part) from blockout to \
+                      export, with the primitive, boolean, transform and repetition docs embedded",
+        arguments: &[
+            ("prop", "What to model, e.g. \"sci-fi ammo crate\"", true),
+            ("details", "Size, style or features it must have", false),
+        ],
+        instructions: hard_surface_prop,
+        sections: &[
+            "quick-start",
+            "primitives",
+            "boolean-operations",
+            "transforms",
+            "modifiers",
+            "repetition",
+            "parameters",
+            "materials-colors",
+        ],
+        example: Some("sci_fi_crate"),
+    },
+    Template {
+        name: "iterate_from_renders",
+        description: "Refine a script in small steps, comparing renders from several angles \
+                      against a goal and using diagnostic render modes when shapes look wrong",
+        arguments: &[
+            ("goal", "What the finished model should look like", true),
+            (
+                "script",
+                "Script to start from; omit to continue from the loaded scene",
+                false,
+            ),
+        ],
+        instructions: iterate_from_renders,
+        sections: &[
+            "quick-start",
+            "transforms",
+            "boolean-operations",
+            "tips-tricks",
+        ],
+        example: None,
+    },
+];
+
+/// Every prompt template, for `prompts/list`
+pub fn list() -> Vec<Prompt> {
+    TEMPLATES
+        .iter()
+        .map(|template| {
+            let arguments = template
+                .arguments
+                .iter()
+                .map(|&(name, description, required)| PromptArgument {
+                    name: name.to_string(),
+                    title: None,
+                    description: Some(description.to_string()),
+                    required: Some(required),
+                })
+                .collect();
+            Prompt::new(template.name, Some(template.description), Some(arguments))
+        })
+        .collect()
+}
+
+/// Fill in a prompt template
+///
+/// Fails if the prompt doesn't exist or a required argument is missing.
+pub fn get(name: &str, arguments: Option<&JsonObject>) -> Result<GetPromptResult, String> {
+    let template = TEMPLATES
+        .iter()
+        .find(|template| template.name == name)
+        .ok_or_else(|| {
+            let names: Vec<_> = TEMPLATES.iter().map(|template| template.name).collect();
+            format!("Unknown prompt '{name}'. Available: {}", names.join(", "))
+        })?;
+
+    let arguments = Arguments(arguments);
+    for &(key, _, required) in template.arguments {
+        if required && arguments.get(key).is_none() {
+            return Err(format!("Prompt '{name}' needs the '{key}' argument"));
+        }
+    }
+
+    let mut text = (template.instructions)(&arguments);
+
+    text.push_str("\n\n# Reference\n\nFollow this syntax exactly; it's from working scripts.");
+    if let Some(example) = template.example
+        && let Some(source) = docs::example(example)
+    {
+        text.push_str(&format!(
+            "\n\n## Example: {example}.rhai\n\n```rhai\n{source}```"
+        ));
+    }
+    for slug in template.sections {
+        if let Some(section) = docs::cookbook_section(slug) {
+            text.push_str("\n\n");
+            text.push_str(section.text);
+        }
+    }
+
+    Ok(GetPromptResult {
+        description: Some(template.description.to_string()),
+        messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+    })
+}
+
+/// Arguments of a `prompts/get` request
+struct Arguments<'a>(Option<&'a JsonObject>);
+
+impl Arguments<'_> {
+    /// A non-empty string argument
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .and_then(|arguments| arguments.get(key))
+            .and_then(|value| value.as_str())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// Instructions for `model_hard_surface_prop`
+fn hard_surface_prop(arguments: &Arguments) -> String {
+    let prop = arguments.get("prop").unwrap_or_default();
+    let details = arguments
+        .get("details")
+        .map(|details| format!("\n\nRequirements: {details}"))
+        .unwrap_or_default();
+    format!(
+        "Model this hard-surface prop with Soyuz: {prop}.{details}
+
+Work in passes, calling run_script after each one:
+1. Block out the main volumes with box3, rounded_box and cylinder. Get the proportions \
+right before adding detail.
+2. Check the silhouette with render_previews (angles \"all\").
+3. Add secondary detail: panel lines and recesses with subtract, bolts and vents with \
+repetition, small bevels with rounded shapes or smooth_union with k around 0.01-0.03. \
+Hard-surface models want crisp edges, so keep k small.
+4. Declare the main dimensions with param() so variants don't need edits.
+5. Inspect details with render_preview: distance and target to zoom in, mode \"normals\" to \
+spot seams and unwanted blends.
+6. Export with export_mesh and a path, e.g. \"props/{{name}}.glb\".
+
+Make cutters slightly larger than the part they cut, so no thin skins are left behind. \
+More working scripts are available as soyuz://examples/ resources."
+    )
+}
+
+/// Instructions for `iterate_from_renders`
+fn iterate_from_renders(arguments: &Arguments) -> String {
+    let goal = arguments.get("goal").unwrap_or_default();
+    let start = match arguments.get("script") {
+        Some(script) => {
+            format!(
+                "Start from this script; run it with run_script first:\n\n```rhai\n{script}\n```"
+            )
+        }
+        None => "Start from the scene that's already loaded (get_scene_info describes it). \
+                 If nothing is loaded, write a first version."
+            .to_string(),
+    };
+    format!(
+        "Refine a Soyuz script until its renders match this goal: {goal}
+
+{start}
+
+Each iteration:
+1. Call render_previews with angles \"front, right, top, isometric\" and compare every view \
+with the goal. Name concrete differences: proportions, missing parts, misplaced parts.
+2. Change one or two things, then run_script and render again. Small steps show which edit \
+caused what.
+3. When a shape looks wrong rather than misplaced, use render_preview modes: \"normals\" for \
+seams and bad blends, \"slice\" to see inside the shape, \"steps\" for slow or broken distance \
+fields. Zoom in with distance and target.
+4. Stop when every view matches the goal, then summarize the changes and show the final script."
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use rmcp::model::PromptMessageContent;
+
+    fn message_text(result: &GetPromptResult) -> &str {
+        match &result.messages[0].content {
+            PromptMessageContent::Text { text } => text,
+            _ => "",
+        }
+    }
+
+    #[test]
+    fn test_embedded_sections_exist() {
+        for template in TEMPLATES {
+            for slug in template.sections {
+                assert!(
+                    docs::cookbook_section(slug).is_some(),
+                    "{} embeds missing section {slug}",
+                    template.name
+                );
+            }
+            if let Some(example) = template.example {
+                assert!(docs::example(example).is_some());
+            }
+        }
+        assert_eq!(list().len(), TEMPLATES.len());
+    }
+
+    #[test]
+    fn test_get_prompt() {
+        let mut arguments = JsonObject::new();
+        arguments.insert("prop".into(), "ammo crate".into());
+        let result =
+            get("model_hard_surface_prop", Some(&arguments)).expect("prompt should be filled");
+        let text = message_text(&result);
+        assert!(text.contains("prop with Soyuz: ammo crate."));
+        assert!(text.contains("## Example: sci_fi_crate.rhai"));
+        assert!(text.contains("## Boolean Operations"));
+
+        assert!(get("model_hard_surface_prop", None).is_err());
+        assert!(get("missing", None).is_err());
+
+        let mut arguments = JsonObject::new();
+        arguments.insert("goal".into(), "a taller barrel".into());
+        arguments.insert("script".into(), "cylinder(0.5, 1.0)".into());
+        let result =
+            get("iterate_from_renders", Some(&arguments)).expect("prompt should be filled");
+        assert!(message_text(&result).contains("```rhai\ncylinder(0.5, 1.0)\n```"));
+    }
+}
@@ -4,25 +4,30 @@
 //! a channel-based architecture where the engine runs in a dedicated thread
 //! and tool calls communicate via message passing.
 
+use std::path::PathBuf;
 use std::thread;
 
 use anyhow::{anyhow, Result};
 use image::ImageEncoder;
-use soyuz_core::export::MeshExport;
-use soyuz_core::mesh::{MeshConfig, OptimizeConfig, SdfToMesh};
 use soyuz_core::sdf::Sdf;
-use soyuz_engine::{Engine, ExportFormat};
+use soyuz_engine::cpu_raymarcher::MAX_STEPS;
+use soyuz_engine::{
+    CpuRaymarcher, Engine, ExportFormat, ExportOptions, ExportResult, Projection, RenderMode,
+    SlicePlane,
+};
 use soyuz_render::{Raymarcher, init_headless};
-use soyuz_script::CpuSdf;
+use soyuz_script::{CpuSdf, ParamValue, ScriptParam};
 use soyuz_sdf::{Environment, build_shader};
 use tokio::sync::{mpsc, oneshot};
 
-use crate::camera::CameraAngle;
+use crate::camera::{CameraAngle, RenderView, ViewMode, describe_camera};
+use crate::output::OutputDir;
 
 /// Commands sent to the engine thread
 enum Command {
     RunScript {
         code: String,
+        params: Vec<(String, ParamValue)>,
         respond: oneshot::Sender<Result<SceneInfo>>,
     },
     CompileScript {
@@ -31,14 +36,17 @@ enum Command {
     },
     Render {
         angle: CameraAngle,
+        view: RenderView,
         width: u32,
         height: u32,
-        respond: oneshot::Sender<Result<Vec<u8>>>,
+        respond: oneshot::Sender<Result<RenderedImage>>,
     },
     ExportMesh {
         format: ExportFormat,
         resolution: u32,
+        cell_size: Option<f32>,
         optimize: bool,
+        destination: Option<PathBuf>,
         respond: oneshot::Sender<Result<ExportInfo>>,
     },
     GetWgsl {
@@ -58,19 +66,27 @@ enum Command {
 #[derive(Clone)]
 pub struct SoyuzState {
     sender: mpsc::UnboundedSender<Command>,
+    has_gpu: bool,
+    output: OutputDir,
 }
 
 impl SoyuzState {
-    /// Create a new state instance with initialized GPU
+    /// Create a new state instance, initializing the GPU if there is one
     ///
     /// This spawns a dedicated thread for the Rhai engine and GPU operations.
-    ///
-    /// # Errors
-    /// Returns an error if GPU initialization fails.
+    /// Without a GPU adapter, renders fall back to the CPU raymarcher.
+    /// Exports and renders saved to disk go under `output`.
     #[allow(clippy::too_many_lines)]
-    pub async fn new() -> Result<Self> {
+    pub async fn new(output: OutputDir) -> Result<Self> {
         // Initialize GPU first (this is async)
-        let (device, queue) = init_headless().await?;
+        let gpu = match init_headless().await {
+            Ok(gpu) => Some(gpu),
+            Err(e) => {
+                tracing::warn!("GPU unavailable ({e}), rendering on the CPU");
+                None
+            }
+        };
+        let has_gpu = gpu.is_some();
 
         // Create channel for commands
         let (tx, mut rx) = mpsc::unbounded_channel::<Command>();
@@ -83,17 +99,28 @@ impl SoyuzState {
             // Process commands
             while let Some(cmd) = rx.blocking_recv() {
                 match cmd {
-                    Command::RunScript { code, respond } => {
+                    Command::RunScript {
+                        code,
+                        params,
+                        respond,
+                    } => {
+                        // Overrides apply to this run only
+                        engine.clear_params();
+                        for (name, value) in params {
+                            engine.set_param(name, value);
+                        }
+
                         let result = engine.run_script(&code).map(|scene| {
                             // Create raymarcher for the new scene
-                            let rm = Raymarcher::with_sdf_and_env(
-                                device.clone(),
-                                queue.clone(),
-                                wgpu::TextureFormat::Rgba8UnormSrgb,
-                                &scene.sdf,
-                                scene.environment.clone(),
-                            );
-                            raymarcher = Some(rm);
+                            raymarcher = gpu.as_ref().map(|(device, queue)| {
+                                Raymarcher::with_sdf_and_env(
+                                    device.clone(),
+                                    queue.clone(),
+                                    wgpu::TextureFormat::Rgba8UnormSrgb,
+                                    &scene.sdf,
+                                    scene.environment.clone(),
+                                )
+                            });
 
                             // Get scene info
                             let cpu_sdf = CpuSdf::new(scene.sdf.clone());
@@ -105,6 +132,7 @@ impl SoyuzState {
                                 bounds_max: bounds.max.to_array(),
                                 bounds_size: bounds.size().to_array(),
                                 environment: Some(EnvironmentInfo::from(&scene.environment)),
+                                params: scene.params.clone(),
                             }
                         });
                         let _ = respond.send(result.map_err(|e| anyhow!(e.to_string())));
@@ -117,15 +145,12 @@ impl SoyuzState {
 
                     Command::Render {
                         angle,
+                        view,
                         width,
                         height,
                         respond,
                     } => {
-                        let result = (|| -> Result<Vec<u8>> {
-                            let rm = raymarcher
-                                .as_ref()
-                                .ok_or_else(|| anyhow!("No scene loaded"))?;
-
+                        let result = (|| -> Result<RenderedImage> {
                             let scene = engine.scene().ok_or_else(|| anyhow!("No scene loaded"))?;
 
                             // Get bounds for camera positioning
@@ -135,11 +160,89 @@ impl SoyuzState {
                             let size = bounds.size().max_element();
 
                             // Create camera
-                            let mut camera = angle.to_camera(center, size);
+                            let mut camera = view.orbit.to_camera(angle, center, size);
                             camera.aspect = width as f32 / height as f32;
 
-                            // Render
-                            let image = rm.render_to_image(width, height, &camera, 0.0)?;
+                            let projection = if view.orthographic {
+                                // As tall as the perspective view at the target
+                                let height = 2.0 * camera.distance() * (camera.fov * 0.5).tan();
+                                Projection::Orthographic { height }
+                            } else {
+                                Projection::Perspective
+                            };
+                            let mut description = describe_camera(&camera);
+                            if view.orthographic {
+                                description.push_str(", orthographic");
+                            }
+
+                            let mode = match view.mode {
+                                ViewMode::Shaded => RenderMode::Shaded,
+                                ViewMode::Normals => {
+                                    description.push_str(
+                                        "\nNormals: x, y, z as red, green, blue (mid grey is 0); \
+                                         black is empty space",
+                                    );
+                                    RenderMode::Normals
+                                }
+                                ViewMode::Depth => {
+                                    description.push_str(
+                                        "\nDepth: nearest surface white, farthest dark grey; \
+                                         black is empty space",
+                                    );
+                                    RenderMode::Depth
+                                }
+                                ViewMode::Steps => {
+                                    description.push_str(&format!(
+                                        "\nRaymarch steps: dark blue (few) to red ({MAX_STEPS}, the limit). \
+                                         Red on a surface means its distance bound is too loose"
+                                    ));
+                                    RenderMode::Steps
+                                }
+                                ViewMode::Slice { axis, offset } => {
+                                    let plane = SlicePlane {
+                                        axis,
+                                        offset: offset.unwrap_or_else(|| axis.component(center)),
+                                        center,
+                                        size: size.max(0.1) * 1.25,
+                                    };
+                                    description = format!(
+                                        "Slice {axis} = {:.3}, {:.3} units wide, centered on [{:.3}, {:.3}, {:.3}]\n\
+                                         Blue inside, orange outside, white on the surface, \
+                                         contour bands every {:.4} units",
+                                        plane.offset,
+                                        plane.size,
+                                        center.x,
+                                        center.y,
+                                        center.z,
+                                        plane.contour_spacing()
+                                    );
+                                    RenderMode::Slice(plane)
+                                }
+                            };
+
+                            // The GPU only draws shaded perspective images
+                            let gpu = if mode == RenderMode::Shaded && !view.orthographic {
+                                raymarcher.as_ref()
+                            } else {
+                                None
+                            };
+
+                            // Render on the GPU, or on the CPU without one
+                            let gpu_image = gpu.and_then(|rm| {
+                                rm.render_to_image(width, height, &camera, 0.0)
+                                    .map_err(|e| {
+                                        tracing::warn!(
+                                            "GPU render failed ({e}), rendering on the CPU"
+                                        );
+                                    })
+                                    .ok()
+                            });
+                            let image = gpu_image.unwrap_or_else(|| {
+                                CpuRaymarcher::new(&scene.sdf, scene.environment.clone())
+                                    .with_mode(mode)
+                                    .with_projection(projection)
+                                    .render_to_image(width, height, &camera)
+                            });
 
                             // Encode as PNG
                             let mut png_bytes = Vec::new();
@@ -151,7 +254,10 @@ impl SoyuzState {
                                 image::ExtendedColorType::Rgba8,
                             )?;
 
-                            Ok(png_bytes)
+                            Ok(RenderedImage {
+                                png: png_bytes,
+                                description,
+                            })
                         })();
                         let _ = respond.send(result);
                     }
@@ -159,48 +265,56 @@ impl SoyuzState {
                     Command::ExportMesh {
                         format,
                         resolution,
+                        cell_size,
                         optimize,
+                        destination,
                         respond,
                     } => {
                         let result = (|| -> Result<ExportInfo> {
-                            let scene = engine.scene().ok_or_else(|| anyhow!("No scene loaded"))?;
-
-                            // Create CPU SDF
-                            let cpu_sdf = CpuSdf::new(scene.sdf.clone());
-                            let bounds = cpu_sdf.bounds();
-
-                            // Generate mesh
-                            let config = MeshConfig::default()
-                                .with_resolution(resolution)
-                                .with_bounds(bounds);
-
-                            let mut mesh = cpu_sdf.to_mesh(config)?;
-
-                            if optimize {
-                                mesh.optimize(&OptimizeConfig::default());
+                            let options = |path: PathBuf| {
+                                let options = ExportOptions::new(path)
+                                    .with_format(format)
+                                    .with_resolution(resolution)
+                                    .with_optimize(optimize);
+                                match cell_size {
+                                    Some(cell_size) => options.with_cell_size(cell_size),
+                                    None => options,
+                                }
+                            };
+
+                            // Write straight to the destination, so files the
+                            // format writes next to it (glTF buffers, OBJ
+                            // materials) end up there too
+                            if let Some(path) = destination {
+                                let exported = engine.export(&options(path))?;
+                                return Ok(ExportInfo {
+                                    format,
+                                    size: std::fs::metadata(&exported.path)?.len(),
+                                    bytes: Vec::new(),
+                                    path: Some(exported.path),
+                                    companions: exported.companions,
+                                    vertex_count: exported.vertex_count,
+                                    triangle_count: exported.triangle_count,
+                                });
                             }
 
-                            let vertex_count = mesh.vertex_count();
-                            let triangle_count = mesh.triangle_count();
-
                             // Export to temp file
-                            let temp_dir = std::env::temp_dir();
-                            let temp_path = temp_dir.join(format!(
+                            let temp_path = std::env::temp_dir().join(format!(
                                 "soyuz_export_{}.{}",
                                 std::process::id(),
                                 format.extension()
                             ));
-
-                            mesh.export(&temp_path)?;
-
-                            let bytes = std::fs::read(&temp_path)?;
-                            let _ = std::fs::remove_file(&temp_path);
+                            let (exported, bytes) =
+                                export_inline(&engine, &options(temp_path), format)?;
 
                             Ok(ExportInfo {
                                 format,
+                                size: bytes.len() as u64,
                                 bytes,
-                                vertex_count,
-                                triangle_count,
+                                path: None,
+                                companions: Vec::new(),
+                                vertex_count: exported.vertex_count,
+                                triangle_count: exported.triangle_count,
                             })
                         })();
                         let _ = respond.send(result);
@@ -225,6 +339,7 @@ impl SoyuzState {
                                 bounds_max: bounds.max.to_array(),
                                 bounds_size: bounds.size().to_array(),
                                 environment: Some(EnvironmentInfo::from(&scene.environment)),
+                                params: scene.params.clone(),
                             }
                         } else {
                             SceneInfo {
@@ -233,6 +348,7 @@ impl SoyuzState {
                                 bounds_max: [0.0; 3],
                                 bounds_size: [0.0; 3],
                                 environment: None,
+                                params: Vec::new(),
                             }
                         };
                         let _ = respond.send(info);
@@ -247,14 +363,35 @@ impl SoyuzState {
             }
         });
 
-        Ok(Self { sender: tx })
+        Ok(Self {
+            sender: tx,
+            has_gpu,
+            output,
+        })
+    }
+
+    /// Whether renders run on the GPU (otherwise they run on the CPU)
+    pub fn has_gpu(&self) -> bool {
+        self.has_gpu
+    }
+
+    /// Directory that exports and renders are saved to
+    pub fn output(&self) -> &OutputDir {
+        &self.output
     }
 
     /// Execute a Rhai script and update the current scene
-    pub async fn run_script(&self, code: &str) -> Result<SceneInfo> {
+    ///
+    /// `params` overrides parameters the script declares with `param()`.
+    pub async fn run_script(
+        &self,
+        code: &str,
+        params: Vec<(String, ParamValue)>,
+    ) -> Result<SceneInfo> {
         let (tx, rx) = oneshot::channel();
         self.sender.send(Command::RunScript {
             code: code.to_string(),
+            params,
             respond: tx,
         })?;
         rx.await?
@@ -271,10 +408,20 @@ impl SoyuzState {
     }
 
     /// Render the current scene to a PNG image
-    pub async fn render(&self, angle: CameraAngle, width: u32, height: u32) -> Result<Vec<u8>> {
+    ///
+    /// `view` overrides the preset camera and picks a diagnostic mode;
+    /// anything but a shaded perspective image is drawn on the CPU.
+    pub async fn render(
+        &self,
+        angle: CameraAngle,
+        view: RenderView,
+        width: u32,
+        height: u32,
+    ) -> Result<RenderedImage> {
         let (tx, rx) = oneshot::channel();
         self.sender.send(Command::Render {
             angle,
+            view,
             width,
             height,
             respond: tx,
@@ -283,17 +430,24 @@ impl SoyuzState {
     }
 
     /// Export the current scene to a mesh file format
+    ///
+    /// With a `destination`, the mesh is written there instead of being
+    /// returned in [`ExportInfo::bytes`].
     pub async fn export_mesh(
         &self,
         format: ExportFormat,
         resolution: u32,
+        cell_size: Option<f32>,
         optimize: bool,
+        destination: Option<PathBuf>,
     ) -> Result<ExportInfo> {
         let (tx, rx) = oneshot::channel();
         self.sender.send(Command::ExportMesh {
             format,
             resolution,
+            cell_size,
             optimize,
+            destination,
             respond: tx,
         })?;
         rx.await?
@@ -316,6 +470,7 @@ impl SoyuzState {
             bounds_max: [0.0; 3],
             bounds_size: [0.0; 3],
             environment: None,
+            params: Vec::new(),
         })
     }
 
@@ -340,6 +495,8 @@ pub struct SceneInfo {
     pub bounds_size: [f32; 3],
     /// Environment settings
     pub environment: Option<EnvironmentInfo>,
+    /// Parameters the script declared, with the values it used
+    pub params: Vec<ScriptParam>,
 }
 
 impl std::fmt::Display for SceneInfo {
@@ -350,13 +507,38 @@ impl std::fmt::Display for SceneInfo {
                 "Scene loaded. Bounds: [{:.2}, {:.2}, {:.2}] to [{:.2}, {:.2}, {:.2}]",
                 self.bounds_min[0], self.bounds_min[1], self.bounds_min[2],
                 self.bounds_max[0], self.bounds_max[1], self.bounds_max[2]
-            )
+            )?;
+            if !self.params.is_empty() {
+                write!(f, "\nParameters:")?;
+                for param in &self.params {
+                    write!(
+                        f,
+                        "\n  {} = {} ({}, {}..={}, default {})",
+                        param.name,
+                        param.value,
+                        param.default.type_name(),
+                        param.min,
+                        param.max,
+                        param.default
+                    )?;
+                }
+            }
+            Ok(())
         } else {
             write!(f, "No scene loaded")
         }
     }
 }
 
+/// A rendered PNG and a description of how it was drawn
+#[derive(Debug)]
+pub struct RenderedImage {
+    /// PNG-encoded image
+    pub png: Vec<u8>,
+    /// Camera placement, and the color legend of diagnostic modes
+    pub description: String,
+}
+
 /// Information about environment settings
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct EnvironmentInfo {
@@ -382,8 +564,14 @@ impl From<&Environment> for EnvironmentInfo {
 pub struct ExportInfo {
     /// Export format used
     pub format: ExportFormat,
-    /// Raw bytes of the exported file
+    /// Raw bytes of the exported file; empty when it was written to disk
     pub bytes: Vec<u8>,
+    /// Size of the exported file in bytes
+    pub size: u64,
+    /// Where the file was written, if it was
+    pub path: Option<PathBuf>,
+    /// Files written next to it (glTF buffers and textures, OBJ materials)
+    pub companions: Vec<PathBuf>,
     /// Number of vertices in the mesh
     pub vertex_count: usize,
     /// Number of triangles in the mesh
@@ -398,7 +586,64 @@ impl std::fmt::Display for ExportInfo {
             self.format.extension(),
             self.vertex_count,
             self.triangle_count,
-            self.bytes.len()
+            self.size
         )
     }
 }
+
+/// Export to the temporary file `options.path` and read it back, removing
+/// it and every file the export wrote next to it
+fn export_inline(
+    engine: &Engine,
+    options: &ExportOptions,
+    format: ExportFormat,
+) -> Result<(ExportResult, Vec<u8>)> {
+    let result = engine
+        .export(options)
+        .and_then(|exported| Ok((std::fs::read(&exported.path)?, exported)));
+
+    // A failed export may have written some companions before it stopped
+    let written = match &result {
+        Ok((_, exported)) => exported.companions.clone(),
+        Err(_) => format.companion_paths(&options.path),
+    };
+    let _ = std::fs::remove_file(&options.path);
+    for file in written {
+        let _ = std::fs::remove_file(file);
+    }
+
+    result.map(|(bytes, exported)| (exported, bytes))
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_textured_gltf_leaves_no_files() {
+        let dir = std::env::temp_dir().join(format!("soyuz_mcp_inline_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+
+        let mut engine = Engine::new();
+        engine
+            .run_script("set_material(pbr().albedo(checker(4.0)).roughness(0.3)); sphere(0.5)")
+            .expect("script should run");
+        let options = ExportOptions::new(dir.join("inline.gltf"))
+            .with_format(ExportFormat::Gltf)
+            .with_resolution(8);
+
+        let (exported, bytes) =
+            export_inline(&engine, &options, ExportFormat::Gltf).expect("export should succeed");
+        assert!(String::from_utf8_lossy(&bytes).contains("inline_texture_0.png"));
+        assert_eq!(exported.companions.len(), 3);
+
+        let left: Vec<_> = std::fs::read_dir(&dir)
+            .expect("temp dir")
+            .map(|entry| entry.expect("entry").path())
+            .collect();
+        assert!(left.is_empty(), "{left:?}");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
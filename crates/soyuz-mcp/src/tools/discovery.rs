@@ -126,6 +126,96 @@ pub fn list_primitives() -> Vec<FunctionInfo> {
             description: "Creates a chain link shape",
             example: "link(0.3, 0.2, 0.05)",
         },
+        FunctionInfo {
+            name: "capped_cone",
+            signature: "capped_cone(bottom_radius: f64, top_radius: f64, height: f64) -> Sdf",
+            description: "Creates a truncated cone along the Y axis with a different radius at each end",
+            example: "capped_cone(0.5, 0.2, 1.0)",
+        },
+        FunctionInfo {
+            name: "round_cone",
+            signature: "round_cone(bottom_radius: f64, top_radius: f64, height: f64) -> Sdf",
+            description: "Creates two spheres joined by a tapered cone; height is the distance between their centers",
+            example: "round_cone(0.4, 0.2, 0.8)",
+        },
+        FunctionInfo {
+            name: "box_frame",
+            signature: "box_frame(width: f64, height: f64, depth: f64, thickness: f64) -> Sdf",
+            description: "Creates the twelve edges of a box as square bars of the given thickness",
+            example: "box_frame(1.0, 0.8, 1.0, 0.05)",
+        },
+        FunctionInfo {
+            name: "capped_torus",
+            signature: "capped_torus(major_radius: f64, minor_radius: f64, angle: f64) -> Sdf",
+            description: "Creates an arc of a torus in the XZ plane, spanning angle radians to each side of +Z",
+            example: "capped_torus(0.5, 0.08, deg(120.0))",
+        },
+        FunctionInfo {
+            name: "cut_sphere",
+            signature: "cut_sphere(radius: f64, cut_height: f64) -> Sdf",
+            description: "Creates the part of a sphere above y = cut_height, with a flat face",
+            example: "cut_sphere(0.5, 0.2)",
+        },
+        FunctionInfo {
+            name: "cut_hollow_sphere",
+            signature: "cut_hollow_sphere(radius: f64, cut_height: f64, thickness: f64) -> Sdf",
+            description: "Creates a bowl: a spherical shell open above y = cut_height, with the wall extending thickness to each side",
+            example: "cut_hollow_sphere(0.5, 0.2, 0.03)",
+        },
+        FunctionInfo {
+            name: "rhombus",
+            signature: "rhombus(width: f64, depth: f64, height: f64, radius: f64) -> Sdf",
+            description: "Creates a diamond-shaped slab in the XZ plane with rounded corners",
+            example: "rhombus(1.0, 0.6, 0.1, 0.02)",
+        },
+        FunctionInfo {
+            name: "solid_angle",
+            signature: "solid_angle(angle: f64, radius: f64) -> Sdf",
+            description: "Creates a cone-shaped wedge of a sphere, apex at the origin, opening angle radians around +Y",
+            example: "solid_angle(deg(30.0), 1.0)",
+        },
+        FunctionInfo {
+            name: "capsule_between",
+            signature: "capsule_between(ax: f64, ay: f64, az: f64, bx: f64, by: f64, bz: f64, radius: f64) -> Sdf",
+            description: "Creates a capsule around the segment from point a to point b",
+            example: "capsule_between(0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.1)",
+        },
+        FunctionInfo {
+            name: "cylinder_between",
+            signature: "cylinder_between(ax: f64, ay: f64, az: f64, bx: f64, by: f64, bz: f64, radius: f64) -> Sdf",
+            description: "Creates a flat-capped cylinder whose axis runs from point a to point b",
+            example: "cylinder_between(0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.1)",
+        },
+        FunctionInfo {
+            name: "infinite_cylinder",
+            signature: "infinite_cylinder(dx: f64, dy: f64, dz: f64, radius: f64) -> Sdf",
+            description: "Creates an endless cylinder through the origin along direction d; intersect or subtract to use it",
+            example: "cube(1.0).subtract(infinite_cylinder(1.0, 0.0, 0.0, 0.2))",
+        },
+        FunctionInfo {
+            name: "mandelbulb",
+            signature: "mandelbulb(power: f64, iterations: i64) -> Sdf",
+            description: "Creates a Mandelbulb fractal about 2.4 units across (power 8 is the classic bulb)",
+            example: "mandelbulb(8.0, 8)",
+        },
+        FunctionInfo {
+            name: "menger_sponge",
+            signature: "menger_sponge(size: f64, iterations: i64) -> Sdf",
+            description: "Creates a Menger sponge: a cube with square holes at every level",
+            example: "menger_sponge(1.0, 3)",
+        },
+        FunctionInfo {
+            name: "sierpinski",
+            signature: "sierpinski(size: f64, iterations: i64) -> Sdf",
+            description: "Creates a Sierpinski tetrahedron fitting a cube of the given size",
+            example: "sierpinski(1.0, 5)",
+        },
+        FunctionInfo {
+            name: "julia",
+            signature: "julia(cx: f64, cy: f64, cz: f64, cw: f64, iterations: i64) -> Sdf",
+            description: "Creates a 3D slice of a quaternion Julia set with constant c (keep |c| below 1)",
+            example: "julia(-0.2, 0.6, 0.2, 0.2, 10)",
+        },
         FunctionInfo {
             name: "extrude_circle",
             signature: "extrude_circle(radius: f64, depth: f64) -> Sdf",
@@ -150,6 +240,102 @@ pub fn list_primitives() -> Vec<FunctionInfo> {
             description: "Revolves a 2D rectangle around the Y axis",
             example: "revolve_rect(0.2, 0.1, 0.4)",
         },
+        FunctionInfo {
+            name: "circle",
+            signature: "circle(radius: f64) -> Sdf2d",
+            description: "Creates a 2D circle profile for extrude, revolve and sweep",
+            example: "circle(0.3).extrude(0.2)",
+        },
+        FunctionInfo {
+            name: "rect",
+            signature: "rect(width: f64, height: f64) -> Sdf2d",
+            description: "Creates a 2D rectangle profile centered on the origin",
+            example: "rect(0.5, 0.3).extrude(0.2)",
+        },
+        FunctionInfo {
+            name: "rounded_rect",
+            signature: "rounded_rect(width: f64, height: f64, radius: f64) -> Sdf2d",
+            description: "Creates a 2D rectangle profile with rounded corners",
+            example: "rounded_rect(0.5, 0.3, 0.05).extrude(0.2)",
+        },
+        FunctionInfo {
+            name: "polygon",
+            signature: "polygon(points: [[x, y], ...]) -> Sdf2d",
+            description: "Creates a 2D polygon profile from at least 3 points; self-intersecting outlines use the even-odd rule",
+            example: "polygon([[-0.5, -0.3], [0.5, -0.3], [0.0, 0.5]]).extrude(0.1)",
+        },
+        FunctionInfo {
+            name: "arc",
+            signature: "arc(radius: f64, angle: f64, thickness: f64) -> Sdf2d",
+            description: "Creates a 2D ring segment centered on +Y, spanning angle radians on each side, with rounded ends",
+            example: "arc(0.5, deg(60.0), 0.1).extrude(0.1)",
+        },
+        FunctionInfo {
+            name: "offset",
+            signature: "shape2d.offset(distance: f64) -> Sdf2d",
+            description: "Grows a 2D profile outward (negative shrinks it), rounding convex corners; 2D profiles also take union, subtract, intersect, smooth_*, shell, translate(x, y), rotate(angle), scale, repeat(sx, sy), repeat_limited(sx, sy, cx, cy) and repeat_polar(n)",
+            example: "rect(0.4, 0.2).offset(0.05).extrude(0.1)",
+        },
+        FunctionInfo {
+            name: "extrude",
+            signature: "shape2d.extrude(depth: f64) -> Sdf",
+            description: "Extrudes a 2D profile along Z, reaching depth on each side of the XY plane",
+            example: "circle(0.4).subtract(circle(0.2)).extrude(0.1)",
+        },
+        FunctionInfo {
+            name: "revolve",
+            signature: "shape2d.revolve(offset: f64) -> Sdf",
+            description: "Revolves a 2D profile around the Y axis; the profile's x axis points away from the axis, starting offset from it",
+            example: "rect(0.1, 0.6).union(circle(0.15).translate(0.0, 0.3)).revolve(0.3)",
+        },
+        FunctionInfo {
+            name: "polyline",
+            signature: "polyline(points: [[x, y, z], ...]) -> Path",
+            description: "Creates a path of straight lines through the points, for tube and sweep_*",
+            example: "polyline([[0, 0, 0], [1, 0, 0], [1, 1, 0]]).tube(0.1)",
+        },
+        FunctionInfo {
+            name: "bezier",
+            signature: "bezier(points: [[x, y, z], ...]) -> Path",
+            description: "Creates a path of quadratic Bezier spans; points alternate between on-curve and control points (odd count)",
+            example: "bezier([[-1, 0, 0], [0, 1, 0], [1, 0, 0]]).tube(0.1)",
+        },
+        FunctionInfo {
+            name: "catmull_rom",
+            signature: "catmull_rom(points: [[x, y, z], ...]) -> Path",
+            description: "Creates a smooth Catmull-Rom spline path through every point",
+            example: "catmull_rom([[0, 0, 0], [1, 0.5, 0], [2, 0, 1]]).tube(0.1)",
+        },
+        FunctionInfo {
+            name: "tube",
+            signature: "path.tube(radius: f64) -> Sdf | path.tube(start_radius: f64, end_radius: f64) -> Sdf",
+            description: "Creates a round tube along a path, optionally tapering from start to end, with rounded ends",
+            example: "catmull_rom([[0, 0, 0], [1, 0.5, 0], [2, 0, 1]]).tube(0.2, 0.05)",
+        },
+        FunctionInfo {
+            name: "sweep_rect",
+            signature: "path.sweep_rect(width: f64, height: f64) -> Sdf",
+            description: "Sweeps a rectangle along a path with flat ends; the profile stays level on horizontal paths",
+            example: "polyline([[0, 0, 0], [1, 0, 0], [1, 0, 1]]).sweep_rect(0.2, 0.1)",
+        },
+        FunctionInfo {
+            name: "sweep_rounded_rect",
+            signature: "path.sweep_rounded_rect(width: f64, height: f64, radius: f64) -> Sdf",
+            description: "Sweeps a rounded rectangle along a path with flat ends",
+            example: "bezier([[-1, 0, 0], [0, 0, 1], [1, 0, 0]]).sweep_rounded_rect(0.3, 0.1, 0.03)",
+        },
+        FunctionInfo {
+            name: "sweep_circle",
+            signature: "path.sweep_circle(radius: f64) -> Sdf",
+            description: "Sweeps a circle along a path with flat ends (tube has rounded ends)",
+            example: "polyline([[0, 0, 0], [0, 1, 0]]).sweep_circle(0.1)",
+        },
+        FunctionInfo {
+            name: "sweep",
+            signature: "path.sweep(profile: Sdf2d) -> Sdf",
+            description: "Sweeps any 2D profile along a path with flat ends",
+            example: "polyline([[0, 0, 0], [1, 0, 0]]).sweep(polygon([[-0.1, 0], [0.1, 0], [0, 0.15]]))",
+        },
     ]
 }
 
@@ -202,6 +388,7 @@ pub fn list_operations() -> Vec<FunctionInfo> {
 }
 
 /// Get all available transforms
+#[allow(clippy::too_many_lines)]
 pub fn list_transforms() -> Vec<FunctionInfo> {
     vec![
         FunctionInfo {
@@ -248,9 +435,39 @@ pub fn list_transforms() -> Vec<FunctionInfo> {
         },
         FunctionInfo {
             name: "scale",
-            signature: "sdf.scale(factor: f64) -> Sdf",
-            description: "Uniformly scales the shape",
-            example: "sphere(0.5).scale(2.0)",
+            signature: "sdf.scale(factor: f64) -> Sdf | sdf.scale(x: f64, y: f64, z: f64) -> Sdf",
+            description: "Scales the shape uniformly, or by a separate factor per axis (distances stay conservative)",
+            example: "sphere(0.5).scale(2.0, 1.0, 0.5)",
+        },
+        FunctionInfo {
+            name: "rotate",
+            signature: "sdf.rotate(ax: f64, ay: f64, az: f64, angle: f64) -> Sdf",
+            description: "Rotates the shape around any axis (angle in radians, right-hand rule)",
+            example: "box3(1.0, 0.2, 0.2).rotate(1.0, 1.0, 0.0, deg(45.0))",
+        },
+        FunctionInfo {
+            name: "rotate_quat",
+            signature: "sdf.rotate_quat(x: f64, y: f64, z: f64, w: f64) -> Sdf",
+            description: "Rotates the shape by a quaternion (normalized automatically)",
+            example: "box3(1.0, 0.2, 0.2).rotate_quat(0.0, 0.0, 0.383, 0.924)",
+        },
+        FunctionInfo {
+            name: "align_to",
+            signature: "sdf.align_to(dx: f64, dy: f64, dz: f64) -> Sdf",
+            description: "Turns the shape so its +Y axis points along the direction, e.g. a cylinder between two points",
+            example: "cylinder(0.05, 1.732).align_to(1.0, 1.0, 1.0).translate(0.5, 0.5, 0.5)",
+        },
+        FunctionInfo {
+            name: "look_at",
+            signature: "sdf.look_at(ex: f64, ey: f64, ez: f64, tx: f64, ty: f64, tz: f64) -> Sdf",
+            description: "Moves the shape's origin to eye and turns its +Z axis toward target, keeping +Y up",
+            example: "cone(0.2, 0.5).rotate_x(deg(90.0)).look_at(1.0, 1.0, 0.0, 0.0, 0.0, 0.0)",
+        },
+        FunctionInfo {
+            name: "transform",
+            signature: "sdf.transform(rows: [[a, b, c, tx], [d, e, f, ty], [g, h, i, tz]]) -> Sdf",
+            description: "Maps the shape by any invertible 3x3 matrix given as rows, with an optional translation column (shear, stretch, mirror)",
+            example: "cube(0.5).transform([[1, 0.5, 0, 0], [0, 1, 0, 0], [0, 0, 1, 0]])",
         },
         FunctionInfo {
             name: "mirror_x",
@@ -429,6 +646,78 @@ pub fn list_environment() -> Vec<FunctionInfo> {
     ]
 }
 
+/// Get all available procedural texture and PBR material functions
+pub fn list_textures() -> Vec<FunctionInfo> {
+    vec![
+        FunctionInfo {
+            name: "perlin",
+            signature: "perlin(scale: f64, [seed: i64]) -> Texture",
+            description: "Perlin noise texture in UV space (0..1)",
+            example: "perlin(8.0)",
+        },
+        FunctionInfo {
+            name: "fbm",
+            signature: "fbm(octaves: i64, scale: f64, [seed: i64]) -> Texture",
+            description: "Layered fractal noise, good for organic surfaces",
+            example: "fbm(5, 6.0)",
+        },
+        FunctionInfo {
+            name: "worley",
+            signature: "worley(scale: f64, [seed: i64]) -> Texture",
+            description: "Cellular noise (distance to nearest feature point)",
+            example: "worley(10.0)",
+        },
+        FunctionInfo {
+            name: "voronoi",
+            signature: "voronoi(scale: f64, [seed: i64]) -> Texture",
+            description: "Voronoi cells with a random value per cell",
+            example: "voronoi(8.0)",
+        },
+        FunctionInfo {
+            name: "bricks",
+            signature: "bricks(width: f64, height: f64, mortar: f64) -> Texture",
+            description: "Brick pattern (1 = brick, 0 = mortar)",
+            example: "bricks(0.25, 0.1, 0.02)",
+        },
+        FunctionInfo {
+            name: "checker",
+            signature: "checker(scale: f64) -> Texture",
+            description: "Checkerboard pattern",
+            example: "checker(8.0)",
+        },
+        FunctionInfo {
+            name: "warp",
+            signature: "texture.warp(warper: Texture, amount: f64) -> Texture",
+            description: "Distorts a texture by another texture",
+            example: "fbm(4, 4.0).warp(perlin(2.0), 0.3)",
+        },
+        FunctionInfo {
+            name: "colorize",
+            signature: "texture.colorize(r0, g0, b0, r1, g1, b1) -> Texture",
+            description: "Maps values 0..1 onto a color ramp (also colorize_hex(low, high))",
+            example: "worley(6.0).colorize(0.3, 0.1, 0.05, 0.7, 0.4, 0.2)",
+        },
+        FunctionInfo {
+            name: "to_normal",
+            signature: "texture.to_normal(strength: f64) -> Texture",
+            description: "Converts a height texture into a normal map",
+            example: "bricks(0.25, 0.1, 0.02).to_normal(2.0)",
+        },
+        FunctionInfo {
+            name: "pbr",
+            signature: "pbr() -> Material",
+            description: "Creates a PBR material; chain albedo, roughness, metallic, normal, ao, emissive (values or textures)",
+            example: "pbr().albedo(fbm(4, 6.0)).roughness(0.6).metallic(0.0)",
+        },
+        FunctionInfo {
+            name: "set_material",
+            signature: "set_material(material: Material)",
+            description: "Uses a PBR material for the exported mesh (embedded as textures in glTF/GLB)",
+            example: "set_material(pbr().albedo(0.8, 0.2, 0.1))",
+        },
+    ]
+}
+
 /// Get all available math helpers
 pub fn list_math() -> Vec<FunctionInfo> {
     vec![
@@ -456,6 +745,42 @@ pub fn list_math() -> Vec<FunctionInfo> {
             description: "Converts radians to degrees",
             example: "let angle_deg = rad(PI() / 4.0)",
         },
+        FunctionInfo {
+            name: "param",
+            signature: "param(name: &str, default, min, max) -> f64 | i64, param(name: &str, default: bool) -> bool",
+            description: "Declares a named parameter and returns its value; integer arguments make an integer parameter. Override it with run_script's params",
+            example: "let radius = param(\"radius\", 0.5, 0.1, 1.0)",
+        },
+        FunctionInfo {
+            name: "rand",
+            signature: "rand() -> f64, rand(name: &str) -> f64",
+            description: "Deterministic random float in 0.0..1.0, so the same seed always gives the same shape. Unnamed calls are numbered within their function; a named call draws from the stream with that name and keeps its values whatever else changes",
+            example: "let wobble = rand() * 0.1",
+        },
+        FunctionInfo {
+            name: "rand_range",
+            signature: "rand_range(min: f64, max: f64) -> f64, rand_range(min: i64, max: i64) -> i64, rand_range(name: &str, min, max)",
+            description: "Deterministic random float in min..max, or integer in min..=max when both bounds are integers. A leading name picks the stream, as with rand",
+            example: "let bolts = rand_range(3, 6)",
+        },
+        FunctionInfo {
+            name: "seed",
+            signature: "seed(n: i64)",
+            description: "Restarts every random stream from seed n, ignoring the seed the script was run with",
+            example: "seed(42)",
+        },
+        FunctionInfo {
+            name: "time",
+            signature: "time: f64 (variable)",
+            description: "Animation time in seconds, 0.0 unless the script is rendered as an animation",
+            example: "box3(0.5, 0.1, 0.5).twist(time * 2.0)",
+        },
+        FunctionInfo {
+            name: "frame",
+            signature: "frame: i64 (variable)",
+            description: "Animation frame number, 0 unless the script is rendered as an animation",
+            example: "let lit = frame % 2 == 0",
+        },
     ]
 }
 
@@ -468,6 +793,7 @@ pub fn get_docs(function_name: &str) -> Option<FunctionInfo> {
         .chain(list_transforms())
         .chain(list_modifiers())
         .chain(list_environment())
+        .chain(list_textures())
         .chain(list_math())
         .collect();
 
@@ -25,11 +25,25 @@ pub struct ExportMeshRequest {
     pub format: String,
 
     /// Mesh resolution - higher values produce more detailed meshes but take longer.
-    /// Typical values: 32 (fast/low), 64 (default), 128 (high), 256 (very high)
+    /// Counts cells along the longest axis of the scene; shorter axes get fewer so
+    /// cells stay cubic. Typical values: 32 (fast/low), 64 (default), 128 (high),
+    /// 256 (very high)
     #[serde(default = "default_resolution")]
     pub resolution: u32,
 
+    /// Target cell size in world units; overrides resolution when set.
+    /// Grids over 2^28 cells (about 645 per side of a cube) are rejected
+    #[serde(default)]
+    pub cell_size: Option<f32>,
+
     /// Whether to optimize the mesh by removing duplicate vertices (default: true)
     #[serde(default = "default_optimize")]
     pub optimize: bool,
+
+    /// Write the file to this path under the server's output directory and
+    /// return a resource link instead of inline base64, e.g. "ships/hull.glb".
+    /// The format's extension is added if missing. Recommended above
+    /// resolution 64, where inline data gets very large.
+    #[serde(default)]
+    pub path: Option<String>,
 }
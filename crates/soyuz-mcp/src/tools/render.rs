@@ -1,9 +1,14 @@
 //! Rendering tools for the MCP server
 //!
-//! Provides tools for rendering the current scene to images.
+//! Provides tools for rendering the current scene to images, from preset
+//! angles or an explicit camera, shaded or as diagnostic views.
 
+use glam::Vec3;
 use schemars::JsonSchema;
 use serde::Deserialize;
+use soyuz_engine::Axis;
+
+use crate::camera::{OrbitView, RenderView, ViewMode};
 
 fn default_angle() -> String {
     "isometric".to_string()
@@ -17,6 +22,90 @@ fn default_size() -> u32 {
     512
 }
 
+fn default_mode() -> String {
+    "shaded".to_string()
+}
+
+fn default_slice_axis() -> String {
+    "y".to_string()
+}
+
+/// Camera overrides and render mode, shared by the render tools
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ViewRequest {
+    /// Orbit angle in degrees around the vertical axis, overriding the
+    /// preset angle's: 0 looks from the front (+Z), 90 from the right (+X)
+    pub yaw: Option<f32>,
+
+    /// Orbit angle in degrees above the horizon (-89 to 89), overriding
+    /// the preset angle's
+    pub pitch: Option<f32>,
+
+    /// Camera distance from the target, overriding the preset's 2.5 times
+    /// the scene size. Smaller values zoom in
+    pub distance: Option<f32>,
+
+    /// Point to look at and orbit around, as [x, y, z] (default: the
+    /// scene's center)
+    pub target: Option<[f32; 3]>,
+
+    /// Vertical field of view in degrees (default: 45). Narrow values
+    /// zoom in with less perspective
+    pub fov: Option<f32>,
+
+    /// Orthographic projection, as tall as the perspective view at the
+    /// target (default: false)
+    #[serde(default)]
+    pub orthographic: bool,
+
+    /// What to draw: "shaded" (default), "normals" (surface normals as
+    /// colors), "depth" (near white, far dark), "steps" (raymarch
+    /// step-count heatmap, red where rays give up) or "slice" (signed
+    /// distance on a plane, ignoring the camera)
+    #[serde(default = "default_mode")]
+    pub mode: String,
+
+    /// Axis the slice plane is perpendicular to, for mode "slice":
+    /// "x", "y" (default) or "z"
+    #[serde(default = "default_slice_axis")]
+    pub slice_axis: String,
+
+    /// Position of the slice plane along slice_axis (default: the scene's
+    /// center)
+    pub slice_offset: Option<f32>,
+}
+
+impl ViewRequest {
+    /// Parse into a render view, or explain what's wrong
+    pub fn to_view(&self) -> Result<RenderView, String> {
+        let axis = Axis::from_name(&self.slice_axis).ok_or_else(|| {
+            format!(
+                "Unknown slice axis '{}'. Valid options: x, y, z",
+                self.slice_axis
+            )
+        })?;
+        let mode = ViewMode::parse(&self.mode, axis, self.slice_offset).ok_or_else(|| {
+            format!(
+                "Unknown render mode '{}'. Valid options: {}",
+                self.mode,
+                ViewMode::all_names().join(", ")
+            )
+        })?;
+
+        Ok(RenderView {
+            orbit: OrbitView {
+                yaw: self.yaw,
+                pitch: self.pitch,
+                distance: self.distance,
+                target: self.target.map(Vec3::from_array),
+                fov: self.fov,
+            },
+            orthographic: self.orthographic,
+            mode,
+        })
+    }
+}
+
 /// Request for rendering a preview image
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct RenderPreviewRequest {
@@ -32,6 +121,15 @@ pub struct RenderPreviewRequest {
     /// Image height in pixels (default: 512)
     #[serde(default = "default_size")]
     pub height: u32,
+
+    /// Save the PNG to this path under the server's output directory and
+    /// return a resource link instead of the inline image, e.g. "renders/front.png"
+    #[serde(default)]
+    pub path: Option<String>,
+
+    /// Camera overrides and render mode
+    #[serde(flatten)]
+    pub view: ViewRequest,
 }
 
 /// Request for rendering multiple preview images at different angles
@@ -51,6 +149,15 @@ pub struct RenderPreviewsRequest {
     /// Image height in pixels for each render (default: 512)
     #[serde(default = "default_size")]
     pub height: u32,
+
+    /// Save each PNG as <directory>/<angle>.png under the server's output
+    /// directory and return resource links instead of inline images
+    #[serde(default)]
+    pub directory: Option<String>,
+
+    /// Camera overrides and render mode, applied to every angle
+    #[serde(flatten)]
+    pub view: ViewRequest,
 }
 
 impl RenderPreviewsRequest {
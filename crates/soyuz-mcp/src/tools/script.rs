@@ -4,6 +4,8 @@
 
 use schemars::JsonSchema;
 use serde::Deserialize;
+use soyuz_script::ParamValue;
+use std::collections::BTreeMap;
 
 /// Request for running a script
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -19,6 +21,39 @@ pub struct RunScriptRequest {
     /// body.subtract(hole)
     /// ```
     pub code: String,
+
+    /// Overrides for parameters the script declares with
+    /// `param(name, default, min, max)`, by name. Values are numbers or
+    /// booleans and must lie in the declared range, e.g.
+    /// `{"radius": 0.7, "staves": 16}`. Parameters left out use their
+    /// defaults.
+    #[serde(default)]
+    pub params: BTreeMap<String, serde_json::Value>,
+}
+
+impl RunScriptRequest {
+    /// The parameter overrides as script values
+    ///
+    /// # Errors
+    /// Returns an error naming the first override that isn't a number or
+    /// boolean.
+    pub fn param_overrides(&self) -> Result<Vec<(String, ParamValue)>, String> {
+        self.params
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    serde_json::Value::Bool(b) => ParamValue::Bool(*b),
+                    serde_json::Value::Number(n) => n
+                        .as_i64()
+                        .map(ParamValue::Int)
+                        .or_else(|| n.as_f64().map(ParamValue::Float))
+                        .ok_or_else(|| format!("parameter '{name}' is out of range"))?,
+                    _ => return Err(format!("parameter '{name}' must be a number or boolean")),
+                };
+                Ok((name.clone(), value))
+            })
+            .collect()
+    }
 }
 
 /// Request for compiling (validating) a script
@@ -48,6 +48,9 @@ pub struct WatchWindowConfig {
     pub width: u32,
     pub height: u32,
     pub script_path: Option<PathBuf>,
+    /// Other files whose changes also reload the script, such as parameter
+    /// overrides
+    pub watch_paths: Vec<PathBuf>,
 }
 
 impl Default for WatchWindowConfig {
@@ -57,6 +60,7 @@ impl Default for WatchWindowConfig {
             width: 1280,
             height: 720,
             script_path: None,
+            watch_paths: Vec::new(),
         }
     }
 }
@@ -94,7 +98,8 @@ struct WatchApp<'a> {
     eval_callback: Option<EvalCallback>,
     last_check_time: Instant,
     check_interval: Duration,
-    last_modified: Option<std::time::SystemTime>,
+    /// Modification times of the script and `watch_paths`, in that order
+    last_modified: Vec<Option<std::time::SystemTime>>,
     error_flash_time: Option<Instant>,
 }
 
@@ -124,7 +129,7 @@ impl<'a> WatchApp<'a> {
             eval_callback,
             last_check_time: now,
             check_interval: Duration::from_millis(100),
-            last_modified: None,
+            last_modified: Vec::new(),
             error_flash_time: None,
         }
     }
@@ -153,8 +158,11 @@ impl<'a> WatchApp<'a> {
         }
         self.last_check_time = Instant::now();
 
-        // Check file modification time
-        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        // Check file modification times
+        let modified: Vec<_> = std::iter::once(path)
+            .chain(&self.config.watch_paths)
+            .map(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+            .collect();
 
         if modified != self.last_modified {
             self.last_modified = modified;
@@ -0,0 +1,851 @@
+//! Bounds analysis for SDF trees
+//!
+//! Computes a box that contains the solid of an [`SdfOp`] tree, working up
+//! from the primitives. Every rule is conservative: rotations transform the
+//! child's corners, twists and polar repeats sweep it around their axis, and
+//! displacement grows it by the largest offset the noise can produce.
+//! Unbounded shapes (planes, infinite repetition) give infinite extents, which
+//! intersections can cut back down.
+//!
+//! The result is the tightest box these rules can prove, but not the
+//! tightest box around the surface: use [`CpuSdf::tight_bounds`] for that.
+//!
+//! [`CpuSdf::tight_bounds`]: crate::CpuSdf::tight_bounds
+
+// One arm per SdfOp variant keeps the analysis in one place
+#![allow(clippy::match_same_arms)]
+#![allow(clippy::too_many_lines)]
+
+use soyuz_core::prelude::{Quat, Vec2, Vec3, Vec4};
+use soyuz_core::sdf::Aabb;
+use soyuz_sdf::{Sdf2dOp, SdfOp, SweepPath};
+
+/// Half size of the box that unbounded shapes are clipped to for meshing
+pub const UNBOUNDED_EXTENT: f32 = 10.0;
+
+/// Bounds of the solid of `op`, possibly infinite along some axes
+pub fn sdf_bounds(op: &SdfOp) -> Aabb {
+    match op {
+        SdfOp::Sphere { radius } => Aabb::cube(*radius),
+
+        SdfOp::Box { half_extents } | SdfOp::RoundedBox { half_extents, .. } => {
+            let h = Vec3::from_array(*half_extents);
+            Aabb::new(-h, h)
+        }
+
+        SdfOp::Cylinder {
+            radius,
+            half_height,
+        } => Aabb::new(
+            Vec3::new(-*radius, -*half_height, -*radius),
+            Vec3::new(*radius, *half_height, *radius),
+        ),
+
+        SdfOp::Capsule {
+            radius,
+            half_height,
+        } => {
+            let h = *half_height + *radius;
+            Aabb::new(
+                Vec3::new(-*radius, -h, -*radius),
+                Vec3::new(*radius, h, *radius),
+            )
+        }
+
+        SdfOp::Torus {
+            major_radius,
+            minor_radius,
+        } => {
+            let r = *major_radius + *minor_radius;
+            Aabb::new(
+                Vec3::new(-r, -*minor_radius, -r),
+                Vec3::new(r, *minor_radius, r),
+            )
+        }
+
+        SdfOp::Cone { radius, height } => Aabb::new(
+            Vec3::new(-*radius, 0.0, -*radius),
+            Vec3::new(*radius, *height, *radius),
+        ),
+
+        SdfOp::Plane { normal, offset } => plane_bounds(Vec3::from_array(*normal), *offset),
+
+        SdfOp::Ellipsoid { radii } => {
+            let r = Vec3::from_array(*radii);
+            Aabb::new(-r, r)
+        }
+
+        SdfOp::Octahedron { size } => Aabb::cube(*size),
+
+        SdfOp::HexPrism {
+            half_height,
+            radius,
+        } => {
+            // `radius` is the apothem; the corners reach 2/sqrt(3) times further
+            let r = *radius * 1.154_700_5;
+            Aabb::new(
+                Vec3::new(-r, -*half_height, -r),
+                Vec3::new(r, *half_height, r),
+            )
+        }
+
+        SdfOp::TriPrism { size } => Aabb::cube(size[0].max(size[1])),
+
+        SdfOp::Pyramid { height } => Aabb::new(
+            Vec3::new(-0.5, height.min(0.0), -0.5),
+            Vec3::new(0.5, height.max(0.0), 0.5),
+        ),
+
+        SdfOp::Link {
+            length,
+            major_radius,
+            minor_radius,
+        } => {
+            let r = *major_radius + *minor_radius;
+            let h = *length + r;
+            Aabb::new(
+                Vec3::new(-r, -h, -*minor_radius),
+                Vec3::new(r, h, *minor_radius),
+            )
+        }
+
+        SdfOp::CappedCone {
+            half_height,
+            bottom_radius,
+            top_radius,
+        } => {
+            let r = bottom_radius.max(*top_radius);
+            Aabb::new(
+                Vec3::new(-r, -*half_height, -r),
+                Vec3::new(r, *half_height, r),
+            )
+        }
+
+        SdfOp::RoundCone {
+            bottom_radius,
+            top_radius,
+            height,
+        } => {
+            let r = bottom_radius.max(*top_radius);
+            Aabb::new(
+                Vec3::new(-r, -0.5 * *height - *bottom_radius, -r),
+                Vec3::new(r, 0.5 * *height + *top_radius, r),
+            )
+        }
+
+        SdfOp::BoxFrame { half_extents, .. } => {
+            let h = Vec3::from_array(*half_extents);
+            Aabb::new(-h, h)
+        }
+
+        // The arc is part of the full torus; its span isn't worth tracking
+        SdfOp::CappedTorus {
+            major_radius,
+            minor_radius,
+            ..
+        } => {
+            let r = *major_radius + *minor_radius;
+            Aabb::new(
+                Vec3::new(-r, -*minor_radius, -r),
+                Vec3::new(r, *minor_radius, r),
+            )
+        }
+
+        SdfOp::CutSphere { radius, cut_height } => Aabb::new(
+            Vec3::new(-*radius, cut_height.max(-*radius), -*radius),
+            Vec3::splat(*radius),
+        ),
+
+        SdfOp::CutHollowSphere {
+            radius,
+            cut_height,
+            thickness,
+        } => {
+            let r = *radius + *thickness;
+            Aabb::new(
+                Vec3::splat(-r),
+                Vec3::new(r, cut_height.min(*radius) + *thickness, r),
+            )
+        }
+
+        SdfOp::Rhombus {
+            half_diagonals,
+            half_height,
+            radius,
+        } => {
+            let h = Vec3::new(half_diagonals[0], 0.0, half_diagonals[1]) + Vec3::splat(*radius);
+            Aabb::new(
+                Vec3::new(-h.x, -*half_height, -h.z),
+                Vec3::new(h.x, *half_height, h.z),
+            )
+        }
+
+        SdfOp::SolidAngle { radius, .. } => Aabb::cube(*radius),
+
+        SdfOp::CapsuleBetween { a, b, radius } | SdfOp::CylinderBetween { a, b, radius } => {
+            Aabb::from_points([Vec3::from_array(*a), Vec3::from_array(*b)]).expand(*radius)
+        }
+
+        SdfOp::InfiniteCylinder { axis, radius } => {
+            infinite_cylinder_bounds(Vec3::from_array(*axis), *radius)
+        }
+
+        SdfOp::Mandelbulb { power, iterations } => {
+            Aabb::cube(mandelbulb_radius(*power, *iterations))
+        }
+
+        SdfOp::Julia { c, iterations } => {
+            Aabb::cube(julia_radius(Vec4::from_array(*c).length(), *iterations))
+        }
+
+        SdfOp::MengerSponge { size, .. } | SdfOp::Sierpinski { size, .. } => Aabb::cube(*size),
+
+        // Booleans: a subtraction or intersection never grows past its
+        // operands, and smooth blending only shrinks those further
+        SdfOp::Union { a, b } | SdfOp::Xor { a, b } => sdf_bounds(a).union(&sdf_bounds(b)),
+
+        SdfOp::Subtract { a, .. } | SdfOp::SmoothSubtract { a, .. } => sdf_bounds(a),
+
+        SdfOp::Intersect { a, b } | SdfOp::SmoothIntersect { a, b, .. } => {
+            sdf_bounds(a).intersection(&sdf_bounds(b))
+        }
+
+        SdfOp::SmoothUnion { a, b, k } => sdf_bounds(a).union(&sdf_bounds(b)).expand(k.abs()),
+
+        // Modifiers
+        SdfOp::Shell { inner, thickness } | SdfOp::Onion { inner, thickness } => {
+            sdf_bounds(inner).expand(thickness.abs())
+        }
+
+        SdfOp::Round { inner, radius } => sdf_bounds(inner).expand(radius.abs()),
+
+        SdfOp::Elongate { inner, h } => {
+            let bounds = sdf_bounds(inner);
+            let h = Vec3::from_array(*h).abs();
+            Aabb::new(bounds.min - h, bounds.max + h)
+        }
+
+        // Transforms move the shape by the inverse of what they do to the point
+        SdfOp::Translate { inner, offset } => {
+            let bounds = sdf_bounds(inner);
+            let o = Vec3::from_array(*offset);
+            Aabb::new(bounds.min + o, bounds.max + o)
+        }
+
+        SdfOp::RotateX { inner, angle } => transform_bounds(&sdf_bounds(inner), |p| {
+            soyuz_math::op_rotate_x(p, angle.cos(), -angle.sin())
+        }),
+
+        SdfOp::RotateY { inner, angle } => transform_bounds(&sdf_bounds(inner), |p| {
+            soyuz_math::op_rotate_y(p, angle.cos(), -angle.sin())
+        }),
+
+        SdfOp::RotateZ { inner, angle } => transform_bounds(&sdf_bounds(inner), |p| {
+            soyuz_math::op_rotate_z(p, angle.cos(), -angle.sin())
+        }),
+
+        SdfOp::Scale { inner, factor } => {
+            let bounds = sdf_bounds(inner);
+            Aabb::from_points([bounds.min * *factor, bounds.max * *factor])
+        }
+
+        SdfOp::Rotate { inner, rotation } => {
+            let q = Vec4::from_array(*rotation)
+                .try_normalize()
+                .unwrap_or(Vec4::W);
+            let q = Quat::from_vec4(q);
+            transform_bounds(&sdf_bounds(inner), |p| q * p)
+        }
+
+        SdfOp::NonUniformScale { inner, factors } => {
+            let bounds = sdf_bounds(inner);
+            let s = Vec3::from_array(*factors);
+            Aabb::from_points([bounds.min * s, bounds.max * s])
+        }
+
+        SdfOp::Transform { inner, transform } => transform_bounds(&sdf_bounds(inner), |p| {
+            Vec3::from_array(transform.apply(p.to_array()))
+        }),
+
+        // The shape is the child's half on the positive side plus its mirror
+        // image, so the union of the child and its reflection covers it
+        SdfOp::Mirror { inner, axis } => {
+            let bounds = sdf_bounds(inner);
+            let n = Vec3::from_array(*axis).normalize_or_zero();
+            bounds.union(&transform_bounds(&bounds, |p| p - 2.0 * p.dot(n) * n))
+        }
+
+        SdfOp::SymmetryX { inner } => symmetric_bounds(&sdf_bounds(inner), 0),
+
+        SdfOp::SymmetryY { inner } => symmetric_bounds(&sdf_bounds(inner), 1),
+
+        SdfOp::SymmetryZ { inner } => symmetric_bounds(&sdf_bounds(inner), 2),
+
+        // Twisting turns each slice around Y, so it stays within the radius
+        // of the child's furthest XZ corner
+        SdfOp::Twist { inner, .. } => {
+            let bounds = sdf_bounds(inner);
+            let r = max_radius(&bounds, 0, 2);
+            Aabb::new(
+                Vec3::new(-r, bounds.min.y, -r),
+                Vec3::new(r, bounds.max.y, r),
+            )
+        }
+
+        // Bending rotates each point within the XY plane, so the same
+        // argument as for twist applies around Z
+        SdfOp::Bend { inner, .. } => {
+            let bounds = sdf_bounds(inner);
+            let r = max_radius(&bounds, 0, 1);
+            Aabb::new(
+                Vec3::new(-r, -r, bounds.min.z),
+                Vec3::new(r, r, bounds.max.z),
+            )
+        }
+
+        // noise3d stays within [-1.5, 1.5]
+        SdfOp::Displacement { inner, amount, .. } => sdf_bounds(inner).expand(amount.abs() * 1.5),
+
+        // 2D-to-3D operations
+        SdfOp::Extrude { profile, depth } => {
+            let bounds = profile_bounds(profile);
+            Aabb::new(
+                Vec3::new(bounds.min.x, bounds.min.y, -*depth),
+                Vec3::new(bounds.max.x, bounds.max.y, *depth),
+            )
+        }
+
+        // The profile's X is the distance from the axis minus the offset
+        SdfOp::Revolve { profile, offset } => {
+            let bounds = profile_bounds(profile);
+            let r = (*offset + bounds.max.x).max(0.0);
+            Aabb::new(
+                Vec3::new(-r, bounds.min.y, -r),
+                Vec3::new(r, bounds.max.y, r),
+            )
+        }
+
+        // Sweeps
+        SdfOp::Tube {
+            path,
+            start_radius,
+            end_radius,
+        } => path_bounds(path, start_radius.abs().max(end_radius.abs())),
+
+        SdfOp::Sweep { path, profile } => {
+            let reach = max_radius(&profile_bounds(profile), 0, 1);
+            // Miters stick out past the path at sharp bends
+            path_bounds(path, reach / path.min_miter_cos().max(1e-3))
+        }
+
+        // Repetition: only axes with a positive spacing repeat
+        SdfOp::RepeatInfinite { inner, spacing } => {
+            let bounds = sdf_bounds(inner);
+            let repeated = Vec3::from_array(*spacing).cmpgt(Vec3::ZERO);
+            Aabb::new(
+                Vec3::select(repeated, Vec3::NEG_INFINITY, bounds.min),
+                Vec3::select(repeated, Vec3::INFINITY, bounds.max),
+            )
+        }
+
+        SdfOp::RepeatLimited {
+            inner,
+            spacing,
+            count,
+        } => {
+            // Copies sit at `spacing * i` for `i` in `-count..=count`
+            let bounds = sdf_bounds(inner);
+            let spacing = Vec3::from_array(*spacing);
+            let reach = Vec3::select(
+                spacing.cmpgt(Vec3::ZERO),
+                spacing * Vec3::from_array(*count).abs(),
+                Vec3::ZERO,
+            );
+            Aabb::new(bounds.min - reach, bounds.max + reach)
+        }
+
+        SdfOp::RepeatPolar { inner, .. } => {
+            let bounds = sdf_bounds(inner);
+            let r = max_radius(&bounds, 0, 2);
+            Aabb::new(
+                Vec3::new(-r, bounds.min.y, -r),
+                Vec3::new(r, bounds.max.y, r),
+            )
+        }
+
+        // Materials
+        SdfOp::Material { inner, .. } => sdf_bounds(inner),
+
+        // Handle non-exhaustive enum
+        _ => Aabb::cube(UNBOUNDED_EXTENT),
+    }
+}
+
+/// Bounds of a 2D profile in the XY plane
+///
+/// Only the X and Y extents are meaningful; the same conservative rules as
+/// for 3D trees apply.
+fn profile_bounds(op: &Sdf2dOp) -> Aabb {
+    let flat = |min: Vec2, max: Vec2| Aabb::new(min.extend(0.0), max.extend(0.0));
+    match op {
+        Sdf2dOp::Circle { radius } => flat(Vec2::splat(-*radius), Vec2::splat(*radius)),
+
+        Sdf2dOp::Rectangle { half_extents } | Sdf2dOp::RoundedRectangle { half_extents, .. } => {
+            let h = Vec2::from_array(*half_extents);
+            flat(-h, h)
+        }
+
+        Sdf2dOp::Polygon { points } => {
+            Aabb::from_points(points.iter().map(|p| Vec2::from_array(*p).extend(0.0)))
+        }
+
+        Sdf2dOp::Arc {
+            radius,
+            half_thickness,
+            ..
+        } => {
+            let r = radius.abs() + half_thickness.abs();
+            flat(Vec2::splat(-r), Vec2::splat(r))
+        }
+
+        Sdf2dOp::Union { a, b } => profile_bounds(a).union(&profile_bounds(b)),
+
+        Sdf2dOp::Subtract { a, .. } | Sdf2dOp::SmoothSubtract { a, .. } => profile_bounds(a),
+
+        Sdf2dOp::Intersect { a, b } | Sdf2dOp::SmoothIntersect { a, b, .. } => {
+            profile_bounds(a).intersection(&profile_bounds(b))
+        }
+
+        Sdf2dOp::SmoothUnion { a, b, k } => {
+            profile_bounds(a).union(&profile_bounds(b)).expand(k.abs())
+        }
+
+        Sdf2dOp::Offset { inner, distance } => profile_bounds(inner).expand(distance.max(0.0)),
+
+        Sdf2dOp::Shell { inner, thickness } => profile_bounds(inner).expand(thickness.abs()),
+
+        Sdf2dOp::Translate { inner, offset } => {
+            let bounds = profile_bounds(inner);
+            let o = Vec2::from_array(*offset).extend(0.0);
+            Aabb::new(bounds.min + o, bounds.max + o)
+        }
+
+        Sdf2dOp::Rotate { inner, angle } => transform_bounds(&profile_bounds(inner), |p| {
+            soyuz_math::op_rotate_z(p, angle.cos(), -angle.sin())
+        }),
+
+        Sdf2dOp::Scale { inner, factor } => {
+            let bounds = profile_bounds(inner);
+            Aabb::from_points([bounds.min * *factor, bounds.max * *factor])
+        }
+
+        Sdf2dOp::RepeatInfinite { inner, spacing } => {
+            let bounds = profile_bounds(inner);
+            let repeated = Vec2::from_array(*spacing).extend(0.0).cmpgt(Vec3::ZERO);
+            Aabb::new(
+                Vec3::select(repeated, Vec3::NEG_INFINITY, bounds.min),
+                Vec3::select(repeated, Vec3::INFINITY, bounds.max),
+            )
+        }
+
+        Sdf2dOp::RepeatLimited {
+            inner,
+            spacing,
+            count,
+        } => {
+            let bounds = profile_bounds(inner);
+            let spacing = Vec2::from_array(*spacing).extend(0.0);
+            let reach = Vec3::select(
+                spacing.cmpgt(Vec3::ZERO),
+                spacing * Vec2::from_array(*count).extend(0.0).abs(),
+                Vec3::ZERO,
+            );
+            Aabb::new(bounds.min - reach, bounds.max + reach)
+        }
+
+        Sdf2dOp::RepeatPolar { inner, .. } => {
+            let r = max_radius(&profile_bounds(inner), 0, 1);
+            flat(Vec2::splat(-r), Vec2::splat(r))
+        }
+
+        // Handle non-exhaustive enum
+        _ => Aabb::cube(UNBOUNDED_EXTENT),
+    }
+}
+
+/// Box around a sweep path's segment ends, grown by `reach`
+fn path_bounds(path: &SweepPath, reach: f32) -> Aabb {
+    Aabb::from_points(
+        path.segments()
+            .iter()
+            .flat_map(|s| [Vec3::from(s.start), Vec3::from(s.end)]),
+    )
+    .expand(reach)
+}
+
+/// Bounds of `op`, with unbounded axes clipped to [`UNBOUNDED_EXTENT`]
+pub fn finite_bounds(op: &SdfOp) -> Aabb {
+    let bounds = sdf_bounds(op).intersection(&Aabb::cube(UNBOUNDED_EXTENT));
+
+    // Disjoint intersections leave nothing to mesh; keep a valid box
+    if bounds.size().min_element() < 0.0 {
+        return Aabb::cube(0.0);
+    }
+    bounds
+}
+
+/// The half-space `dot(p, n) + offset < 0`
+///
+/// Only axis-aligned planes bound anything, and then only along their axis.
+fn plane_bounds(normal: Vec3, offset: f32) -> Aabb {
+    let n = normal.normalize_or_zero();
+    let mut bounds = Aabb::infinite();
+    let Some(axis) = aligned_axis(n) else {
+        return bounds;
+    };
+    if n[axis] > 0.0 {
+        bounds.max[axis] = -offset;
+    } else {
+        bounds.min[axis] = offset;
+    }
+    bounds
+}
+
+/// Bounds of an infinite cylinder: only an axis-aligned one is limited, to
+/// its radius across the axis
+fn infinite_cylinder_bounds(axis: Vec3, radius: f32) -> Aabb {
+    let mut bounds = Aabb::infinite();
+    if let Some(k) = aligned_axis(axis) {
+        for across in [(k + 1) % 3, (k + 2) % 3] {
+            bounds.min[across] = -radius;
+            bounds.max[across] = radius;
+        }
+    }
+    bounds
+}
+
+/// Radius of the ball holding a Mandelbulb
+///
+/// Orbits are cut off once they leave radius 2, and the first step from a
+/// point at radius `r` lands at least `r^power - r` from the origin. Above a
+/// power of 2, that passes 2 before `r` does.
+fn mandelbulb_radius(power: f32, iterations: u32) -> f32 {
+    if iterations == 0 || power <= 2.0 {
+        return 2.0;
+    }
+    // r^power - r is 0 at r = 1 and above 2 at r = 2
+    let (mut low, mut high) = (1.0_f32, 2.0_f32);
+    for _ in 0..24 {
+        let mid = 0.5 * (low + high);
+        if mid.powf(power) - mid > 2.0 {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    high
+}
+
+/// Radius of the ball holding a quaternion Julia set whose constant has
+/// length `c`
+///
+/// Orbits are cut off once they leave radius 2, and a point whose next step
+/// stays within radius `r` lies within `sqrt(r + c)`, so each iteration
+/// tightens the radius towards the set's escape radius.
+fn julia_radius(c: f32, iterations: u32) -> f32 {
+    let mut radius = 2.0_f32;
+    for _ in 0..iterations {
+        let next = (radius + c).sqrt();
+        if next.is_nan() || next >= radius {
+            break;
+        }
+        radius = next;
+    }
+    radius
+}
+
+/// The coordinate axis `v` points along, if it has a single nonzero component
+fn aligned_axis(v: Vec3) -> Option<usize> {
+    (0..3).find(|&k| v[k] != 0.0 && v[(k + 1) % 3] == 0.0 && v[(k + 2) % 3] == 0.0)
+}
+
+/// Box around the transformed corners of `bounds`
+///
+/// Only valid for linear and affine maps. Infinite boxes stay infinite, since
+/// their corners can't be transformed.
+fn transform_bounds(bounds: &Aabb, map: impl Fn(Vec3) -> Vec3) -> Aabb {
+    if !bounds.is_finite() {
+        return Aabb::infinite();
+    }
+    Aabb::from_points(bounds.corners().map(map))
+}
+
+/// Bounds after folding space with `abs()` on one axis
+///
+/// Only the child's positive half along the axis is kept, then mirrored.
+fn symmetric_bounds(bounds: &Aabb, axis: usize) -> Aabb {
+    let reach = bounds.max[axis].max(0.0);
+    let mut out = *bounds;
+    out.min[axis] = -reach;
+    out.max[axis] = reach;
+    out
+}
+
+/// Largest distance from the axis through the origin perpendicular to
+/// the `a` and `b` axes, over the whole box
+fn max_radius(bounds: &Aabb, a: usize, b: usize) -> f32 {
+    let far = bounds.min.abs().max(bounds.max.abs());
+    Vec2::new(far[a], far[b]).length()
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use soyuz_sdf::SweepCurve;
+    use std::f32::consts::FRAC_PI_4;
+    use std::sync::Arc;
+
+    fn unit_box() -> Arc<SdfOp> {
+        Arc::new(SdfOp::Box {
+            half_extents: [1.0, 0.5, 0.25],
+        })
+    }
+
+    fn assert_bounds(bounds: Aabb, min: [f32; 3], max: [f32; 3]) {
+        let (min, max) = (Vec3::from_array(min), Vec3::from_array(max));
+        assert!(
+            bounds.min.abs_diff_eq(min, 1e-4) && bounds.max.abs_diff_eq(max, 1e-4),
+            "expected {min}..{max}, got {}..{}",
+            bounds.min,
+            bounds.max
+        );
+    }
+
+    #[test]
+    fn test_rotation_turns_corners() {
+        // A quarter turn around Z swaps the X and Y extents exactly
+        let op = SdfOp::RotateZ {
+            inner: unit_box(),
+            angle: std::f32::consts::FRAC_PI_2,
+        };
+        assert_bounds(sdf_bounds(&op), [-0.5, -1.0, -0.25], [0.5, 1.0, 0.25]);
+
+        // An eighth turn around Y stays tighter than the enclosing sphere
+        let op = SdfOp::RotateY {
+            inner: unit_box(),
+            angle: FRAC_PI_4,
+        };
+        let r = 1.25 * FRAC_PI_4.cos();
+        assert_bounds(sdf_bounds(&op), [-r, -0.5, -r], [r, 0.5, r]);
+    }
+
+    #[test]
+    fn test_plane_cut_bounds() {
+        let ground = SdfOp::Plane {
+            normal: [0.0, 1.0, 0.0],
+            offset: 0.0,
+        };
+        assert!(!sdf_bounds(&ground).is_finite());
+
+        // Intersecting a sphere with the half-space below y = 0
+        let op = SdfOp::Intersect {
+            a: Arc::new(SdfOp::Sphere { radius: 1.0 }),
+            b: Arc::new(ground),
+        };
+        assert_bounds(sdf_bounds(&op), [-1.0, -1.0, -1.0], [1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_infinite_cylinder_bounds() {
+        let op = SdfOp::InfiniteCylinder {
+            axis: [0.0, 0.0, 2.0],
+            radius: 0.5,
+        };
+        let bounds = sdf_bounds(&op);
+        assert!(bounds.min.z.is_infinite() && bounds.max.z.is_infinite());
+        assert_bounds(
+            bounds.intersection(&Aabb::cube(1.0)),
+            [-0.5, -0.5, -1.0],
+            [0.5, 0.5, 1.0],
+        );
+
+        // Tilted axes cross every slab
+        let op = SdfOp::InfiniteCylinder {
+            axis: [1.0, 1.0, 0.0],
+            radius: 0.5,
+        };
+        assert!(!sdf_bounds(&op).min.is_finite());
+    }
+
+    #[test]
+    fn test_sweep_bounds_cover_miters() {
+        let path = SweepPath::new(
+            SweepCurve::Polyline,
+            vec![[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [2.0, 0.0, 2.0]],
+        );
+        let tube = SdfOp::Tube {
+            path: path.clone(),
+            start_radius: 0.5,
+            end_radius: 0.25,
+        };
+        assert_bounds(sdf_bounds(&tube), [-0.5, -0.5, -0.5], [2.5, 0.5, 2.5]);
+
+        // The outer corner of a square profile's miter reaches (2.5, _, -0.5)
+        let sweep = SdfOp::Sweep {
+            path,
+            profile: Arc::new(Sdf2dOp::Rectangle {
+                half_extents: [0.5, 0.5],
+            }),
+        };
+        let bounds = sdf_bounds(&sweep);
+        let corner = Aabb::new(Vec3::new(2.5, -0.5, -0.5), Vec3::new(2.5, 0.5, -0.5));
+        assert!(bounds.contains(&corner), "{bounds:?}");
+    }
+
+    #[test]
+    fn test_unbounded_shapes_are_clipped() {
+        let op = SdfOp::RepeatInfinite {
+            inner: unit_box(),
+            spacing: [3.0, 0.0, 0.0],
+        };
+        assert_bounds(
+            sdf_bounds(&op).intersection(&Aabb::cube(5.0)),
+            [-5.0, -0.5, -0.25],
+            [5.0, 0.5, 0.25],
+        );
+        assert_bounds(
+            finite_bounds(&op),
+            [-UNBOUNDED_EXTENT, -0.5, -0.25],
+            [UNBOUNDED_EXTENT, 0.5, 0.25],
+        );
+    }
+
+    #[test]
+    fn test_symmetry_keeps_other_axes() {
+        let op = SdfOp::SymmetryX {
+            inner: Arc::new(SdfOp::Translate {
+                inner: unit_box(),
+                offset: [2.0, 1.0, 0.0],
+            }),
+        };
+        assert_bounds(sdf_bounds(&op), [-3.0, 0.5, -0.25], [3.0, 1.5, 0.25]);
+    }
+
+    #[test]
+    fn test_polar_repeat_covers_corners() {
+        let op = SdfOp::RepeatPolar {
+            inner: Arc::new(SdfOp::Translate {
+                inner: unit_box(),
+                offset: [2.0, 0.0, 0.0],
+            }),
+            count: 6,
+        };
+        let r = Vec2::new(3.0, 0.25).length();
+        assert_bounds(sdf_bounds(&op), [-r, -0.5, -r], [r, 0.5, r]);
+    }
+
+    #[test]
+    fn test_profile_bounds_follow_the_2d_tree() {
+        let polygon = Arc::new(Sdf2dOp::Polygon {
+            points: vec![[-0.5, -0.2], [1.0, -0.2], [0.0, 0.8]],
+        });
+        let op = SdfOp::Extrude {
+            profile: Arc::clone(&polygon),
+            depth: 0.3,
+        };
+        assert_bounds(sdf_bounds(&op), [-0.5, -0.2, -0.3], [1.0, 0.8, 0.3]);
+
+        // Revolving reaches as far from the axis as the profile's right edge
+        let op = SdfOp::Revolve {
+            profile: Arc::new(Sdf2dOp::Translate {
+                inner: Arc::new(Sdf2dOp::Rectangle {
+                    half_extents: [0.1, 0.2],
+                }),
+                offset: [0.1, 0.3],
+            }),
+            offset: 0.5,
+        };
+        assert_bounds(sdf_bounds(&op), [-0.7, 0.1, -0.7], [0.7, 0.5, 0.7]);
+
+        // Subtracting never grows the profile
+        let op = SdfOp::Extrude {
+            profile: Arc::new(Sdf2dOp::Subtract {
+                a: polygon,
+                b: Arc::new(Sdf2dOp::Circle { radius: 5.0 }),
+            }),
+            depth: 0.3,
+        };
+        assert_bounds(sdf_bounds(&op), [-0.5, -0.2, -0.3], [1.0, 0.8, 0.3]);
+    }
+
+    #[test]
+    fn test_general_transforms_move_the_corners() {
+        // Half turn around Z
+        let op = SdfOp::Rotate {
+            inner: unit_box(),
+            rotation: [0.0, 0.0, 1.0, 0.0],
+        };
+        assert_bounds(sdf_bounds(&op), [-1.0, -0.5, -0.25], [1.0, 0.5, 0.25]);
+
+        let op = SdfOp::NonUniformScale {
+            inner: unit_box(),
+            factors: [2.0, -1.0, 4.0],
+        };
+        assert_bounds(sdf_bounds(&op), [-2.0, -0.5, -1.0], [2.0, 0.5, 1.0]);
+
+        // Shearing X by Y, then moving up
+        let op = SdfOp::Transform {
+            inner: unit_box(),
+            transform: soyuz_sdf::Affine::new(
+                [[1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+                [0.0, 1.0, 0.0],
+            )
+            .expect("invertible"),
+        };
+        assert_bounds(sdf_bounds(&op), [-1.5, 0.5, -0.25], [1.5, 1.5, 0.25]);
+    }
+
+    #[test]
+    fn test_fractal_bounds_follow_parameters() {
+        // With c = 0 each step squares the radius, so only points within
+        // 2^(1/2^n) stay inside radius 2 for n steps
+        let julia = |c| SdfOp::Julia { c, iterations: 3 };
+        let r = 2.0_f32.powf(0.125);
+        assert_bounds(sdf_bounds(&julia([0.0; 4])), [-r; 3], [r; 3]);
+        // A large constant can pull any point back inside the bailout radius
+        assert_bounds(
+            sdf_bounds(&julia([3.0, 0.0, 0.0, 0.0])),
+            [-2.0; 3],
+            [2.0; 3],
+        );
+
+        // Just outside the box, the surface is never reached
+        let c = Vec4::new(-0.2, 0.6, 0.2, 0.2);
+        let r = sdf_bounds(&SdfOp::Julia {
+            c: c.to_array(),
+            iterations: 10,
+        })
+        .max
+        .x;
+        assert!(r < 2.0);
+        let bulb = sdf_bounds(&SdfOp::Mandelbulb {
+            power: 8.0,
+            iterations: 8,
+        })
+        .max
+        .x;
+        assert!(bulb < 1.2);
+        for dir in [Vec3::X, -Vec3::Y, Vec3::Z, Vec3::ONE.normalize()] {
+            assert!(soyuz_math::sd_julia(dir * r * 1.001, c, 10.0) > 0.0);
+            assert!(soyuz_math::sd_mandelbulb(dir * bulb * 1.001, 8.0, 8.0) > 0.0);
+        }
+
+        // No steps leave the whole bailout ball
+        let op = SdfOp::Mandelbulb {
+            power: 8.0,
+            iterations: 0,
+        };
+        assert_bounds(sdf_bounds(&op), [-2.0; 3], [2.0; 3]);
+    }
+}
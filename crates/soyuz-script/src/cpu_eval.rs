@@ -14,11 +14,11 @@
 #![allow(clippy::match_same_arms)]
 
 use soyuz_core::sdf::{Aabb, Sdf};
-use soyuz_sdf::SdfOp;
+use soyuz_sdf::{Affine, MaterialTag, Sdf2dOp, SdfOp, TaggedNodes};
 use std::sync::Arc;
 
 // Re-export from soyuz-core prelude
-use soyuz_core::prelude::{Vec2, Vec3};
+use soyuz_core::prelude::{Vec2, Vec3, Vec4};
 
 /// Wrapper around [`SdfOp`] that implements the [`Sdf`] trait.
 ///
@@ -28,28 +28,100 @@ use soyuz_core::prelude::{Vec2, Vec3};
 #[derive(Debug, Clone)]
 pub struct CpuSdf {
     /// The underlying SDF operation tree
+    ///
+    /// Replacing it leaves the material lookups built for the old tree in
+    /// place; build a new [`CpuSdf`] instead.
+    #[deprecated(note = "read the tree with `CpuSdf::op` and build a new `CpuSdf` to change it")]
     pub op: Arc<SdfOp>,
+    /// Which nodes have a material tag somewhere below them, found once so
+    /// material lookups skip untagged branches in O(1)
+    tagged: Arc<TaggedNodes>,
 }
 
+// Deprecated for callers outside this module only
+#[allow(deprecated)]
 impl CpuSdf {
     /// Create a new [`CpuSdf`] from an [`SdfOp`]
     pub fn new(op: SdfOp) -> Self {
-        Self { op: Arc::new(op) }
+        Self::from_arc(Arc::new(op))
     }
 
     /// Create a new [`CpuSdf`] from an `Arc<SdfOp>`
     pub fn from_arc(op: Arc<SdfOp>) -> Self {
-        Self { op }
+        let tagged = Arc::new(op.tagged_nodes());
+        Self { op, tagged }
+    }
+
+    /// The SDF operation tree being evaluated
+    pub fn op(&self) -> &Arc<SdfOp> {
+        &self.op
+    }
+
+    /// Check whether the tree assigns any materials
+    pub fn has_materials(&self) -> bool {
+        self.tagged.is_tagged()
+    }
+
+    /// Bounds shrunk to the region the surface actually passes through
+    ///
+    /// Starts from the analytic [`Sdf::bounds`] and refines them by sampling,
+    /// which removes the slack that rotations, twists and blends leave.
+    pub fn tight_bounds(&self) -> Aabb {
+        soyuz_core::sdf::bounds::refine_bounds(self, self.bounds())
+    }
+
+    /// Evaluate the surface material at point p
+    ///
+    /// Follows [`SdfOp::Material`] tags through the tree the same way the
+    /// preview shader does. Untagged geometry gets material 0 with
+    /// `default_color` (usually the environment's material color).
+    pub fn material_at(&self, p: Vec3, default_color: [f32; 3]) -> SurfaceMaterial {
+        let default = SurfaceMaterial {
+            id: 0,
+            color: default_color,
+        };
+        eval_material(&self.op, &self.tagged, p, default, default_color).1
+    }
+}
+
+/// Material ID and resolved color at a surface point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurfaceMaterial {
+    /// Material ID (0 = default material)
+    pub id: u32,
+    /// Linear RGB color (0-1)
+    pub color: [f32; 3],
+}
+
+impl SurfaceMaterial {
+    fn from_tag(tag: MaterialTag, default_color: [f32; 3]) -> Self {
+        Self {
+            id: tag.id,
+            color: tag.resolve_color(default_color),
+        }
+    }
+
+    /// Blend towards `other` by `t`, taking the ID of whichever side dominates
+    fn mix(self, other: Self, t: f32) -> Self {
+        Self {
+            id: if t < 0.5 { self.id } else { other.id },
+            color: [
+                lerp(self.color[0], other.color[0], t),
+                lerp(self.color[1], other.color[1], t),
+                lerp(self.color[2], other.color[2], t),
+            ],
+        }
     }
 }
 
+#[allow(deprecated)]
 impl Sdf for CpuSdf {
     fn distance(&self, p: Vec3) -> f32 {
         eval_distance(&self.op, p)
     }
 
     fn bounds(&self) -> Aabb {
-        eval_bounds(&self.op)
+        crate::bounds::finite_bounds(&self.op)
     }
 }
 
@@ -57,446 +129,578 @@ impl Sdf for CpuSdf {
 fn eval_distance(op: &SdfOp, p: Vec3) -> f32 {
     match op {
         // === Primitives ===
-        SdfOp::Sphere { radius } => p.length() - *radius,
+        SdfOp::Sphere { radius } => soyuz_math::sd_sphere(p, *radius),
 
-        SdfOp::Box { half_extents } => {
-            let h = Vec3::new(half_extents[0], half_extents[1], half_extents[2]);
-            let q = p.abs() - h;
-            q.max(Vec3::ZERO).length() + q.x.max(q.y.max(q.z)).min(0.0)
-        }
+        SdfOp::Box { half_extents } => soyuz_math::sd_box(p, Vec3::from_array(*half_extents)),
 
         SdfOp::RoundedBox {
             half_extents,
             radius,
-        } => {
-            let h = Vec3::new(half_extents[0], half_extents[1], half_extents[2]);
-            let q = p.abs() - h + Vec3::splat(*radius);
-            q.max(Vec3::ZERO).length() + q.x.max(q.y.max(q.z)).min(0.0) - *radius
-        }
+        } => soyuz_math::sd_rounded_box(p, Vec3::from_array(*half_extents), *radius),
 
         SdfOp::Cylinder {
             radius,
             half_height,
-        } => {
-            let d = Vec2::new(Vec2::new(p.x, p.z).length(), p.y).abs()
-                - Vec2::new(*radius, *half_height);
-            d.x.max(d.y).min(0.0) + d.max(Vec2::ZERO).length()
-        }
+        } => soyuz_math::sd_cylinder(p, *radius, *half_height),
 
         SdfOp::Capsule {
             radius,
             half_height,
-        } => {
-            let p_clamped = Vec3::new(p.x, p.y.clamp(-*half_height, *half_height), p.z);
-            (p - p_clamped).length() - *radius
-        }
+        } => soyuz_math::sd_capsule(p, *radius, *half_height),
 
         SdfOp::Torus {
             major_radius,
             minor_radius,
-        } => {
-            let q = Vec2::new(Vec2::new(p.x, p.z).length() - *major_radius, p.y);
-            q.length() - *minor_radius
-        }
+        } => soyuz_math::sd_torus(p, Vec2::new(*major_radius, *minor_radius)),
 
-        SdfOp::Cone { radius, height } => {
-            let q = Vec2::new(*height, -*radius).normalize();
-            let w = Vec2::new(Vec2::new(p.x, p.z).length(), p.y);
-            let a = w - q * w.dot(q).clamp(0.0, *height / q.x);
-            let b = w - q * Vec2::new(*height / q.x, 0.0).min(w);
-            let k = q.y.signum();
-            let d = a.length_squared().min(b.length_squared());
-            let s = (k * (w.x * q.y - w.y * q.x)).max(k * (w.y - *height));
-            d.sqrt() * s.signum()
-        }
+        SdfOp::Cone { radius, height } => soyuz_math::sd_cone(p, *radius, *height),
 
         SdfOp::Plane { normal, offset } => {
-            let n = Vec3::new(normal[0], normal[1], normal[2]).normalize();
-            p.dot(n) + *offset
+            let n = Vec3::from_array(*normal).normalize_or_zero();
+            soyuz_math::sd_plane(p, n, *offset)
         }
 
-        SdfOp::Ellipsoid { radii } => {
-            let r = Vec3::new(radii[0], radii[1], radii[2]);
-            let k0 = (p / r).length();
-            let k1 = (p / (r * r)).length();
-            k0 * (k0 - 1.0) / k1
-        }
+        SdfOp::Ellipsoid { radii } => soyuz_math::sd_ellipsoid(p, Vec3::from_array(*radii)),
+
+        SdfOp::Octahedron { size } => soyuz_math::sd_octahedron(p, *size),
+
+        SdfOp::HexPrism {
+            half_height,
+            radius,
+        } => soyuz_math::sd_hex_prism(p, Vec2::new(*radius, *half_height)),
+
+        SdfOp::TriPrism { size } => soyuz_math::sd_tri_prism(p, Vec2::new(size[0], size[1])),
+
+        SdfOp::Pyramid { height } => soyuz_math::sd_pyramid(p, *height),
+
+        SdfOp::Link {
+            length,
+            major_radius,
+            minor_radius,
+        } => soyuz_math::sd_link(p, *length, *major_radius, *minor_radius),
+
+        SdfOp::CappedCone {
+            half_height,
+            bottom_radius,
+            top_radius,
+        } => soyuz_math::sd_capped_cone(p, *half_height, *bottom_radius, *top_radius),
+
+        SdfOp::RoundCone {
+            bottom_radius,
+            top_radius,
+            height,
+        } => soyuz_math::sd_round_cone(p, *bottom_radius, *top_radius, *height),
 
-        SdfOp::Octahedron { size } => {
-            let s = *size;
-            let p = p.abs();
-            let m = p.x + p.y + p.z - s;
+        SdfOp::BoxFrame {
+            half_extents,
+            half_thickness,
+        } => soyuz_math::sd_box_frame(p, Vec3::from_array(*half_extents), *half_thickness),
 
-            let q = if 3.0 * p.x < m {
-                p
-            } else if 3.0 * p.y < m {
-                Vec3::new(p.y, p.z, p.x)
-            } else if 3.0 * p.z < m {
-                Vec3::new(p.z, p.x, p.y)
-            } else {
-                return m * 0.57735027; // 1/sqrt(3)
-            };
+        SdfOp::CappedTorus {
+            half_angle,
+            major_radius,
+            minor_radius,
+        } => soyuz_math::sd_capped_torus(
+            p,
+            Vec2::new(half_angle.sin(), half_angle.cos()),
+            *major_radius,
+            *minor_radius,
+        ),
 
-            let k = (0.5_f32 * (q.z - q.y + s)).clamp(0.0, s);
-            Vec3::new(q.x, q.y - s + k, q.z - k).length()
+        SdfOp::CutSphere { radius, cut_height } => {
+            soyuz_math::sd_cut_sphere(p, *radius, *cut_height)
         }
 
-        SdfOp::HexPrism {
+        SdfOp::CutHollowSphere {
+            radius,
+            cut_height,
+            thickness,
+        } => soyuz_math::sd_cut_hollow_sphere(p, *radius, *cut_height, *thickness),
+
+        SdfOp::Rhombus {
+            half_diagonals,
             half_height,
             radius,
-        } => {
-            const K: Vec3 = Vec3::new(-0.866025404, 0.5, 0.577350269);
-            let p_abs = p.abs();
-            let xy = Vec2::new(p_abs.x, p_abs.z);
-            let xy = xy - 2.0 * K.x.min(xy.dot(Vec2::new(K.x, K.y))) * Vec2::new(K.x, K.y);
-            let d = Vec2::new(
-                (xy - Vec2::new(xy.x.clamp(-K.z * *radius, K.z * *radius), *radius)).length()
-                    * (xy.y - *radius).signum(),
-                p_abs.y - *half_height,
-            );
-            d.x.max(d.y).min(0.0) + d.max(Vec2::ZERO).length()
+        } => soyuz_math::sd_rhombus(
+            p,
+            half_diagonals[0],
+            half_diagonals[1],
+            *half_height,
+            *radius,
+        ),
+
+        SdfOp::SolidAngle { half_angle, radius } => {
+            soyuz_math::sd_solid_angle(p, Vec2::new(half_angle.sin(), half_angle.cos()), *radius)
+        }
+
+        SdfOp::CapsuleBetween { a, b, radius } => {
+            soyuz_math::sd_capsule_between(p, Vec3::from_array(*a), Vec3::from_array(*b), *radius)
+        }
+
+        SdfOp::CylinderBetween { a, b, radius } => {
+            soyuz_math::sd_cylinder_between(p, Vec3::from_array(*a), Vec3::from_array(*b), *radius)
+        }
+
+        SdfOp::InfiniteCylinder { axis, radius } => {
+            let d = Vec3::from_array(*axis).normalize_or_zero();
+            soyuz_math::sd_infinite_cylinder(p, d, *radius)
+        }
+
+        // === Fractals ===
+        SdfOp::Mandelbulb { power, iterations } => {
+            soyuz_math::sd_mandelbulb(p, *power, *iterations as f32)
         }
 
-        SdfOp::TriPrism { size } => {
-            let s = Vec2::new(size[0], size[1]);
-            let q = p.abs();
-            (q.z - s.y).max((q.x * 0.866025 + p.y * 0.5).max(-p.y) - s.x * 0.5)
+        SdfOp::MengerSponge { size, iterations } => {
+            soyuz_math::sd_menger_sponge(p, *size, *iterations as f32)
+        }
+
+        SdfOp::Sierpinski { size, iterations } => {
+            soyuz_math::sd_sierpinski(p, *size, *iterations as f32)
+        }
+
+        SdfOp::Julia { c, iterations } => {
+            soyuz_math::sd_julia(p, Vec4::from_array(*c), *iterations as f32)
         }
 
         // === Boolean Operations ===
-        SdfOp::Union { a, b } => eval_distance(a, p).min(eval_distance(b, p)),
+        SdfOp::Union { a, b } => soyuz_math::op_union(eval_distance(a, p), eval_distance(b, p)),
 
-        SdfOp::Subtract { a, b } => eval_distance(a, p).max(-eval_distance(b, p)),
+        SdfOp::Subtract { a, b } => {
+            soyuz_math::op_subtract(eval_distance(a, p), eval_distance(b, p))
+        }
 
-        SdfOp::Intersect { a, b } => eval_distance(a, p).max(eval_distance(b, p)),
+        SdfOp::Intersect { a, b } => {
+            soyuz_math::op_intersect(eval_distance(a, p), eval_distance(b, p))
+        }
 
         SdfOp::SmoothUnion { a, b, k } => {
-            let d1 = eval_distance(a, p);
-            let d2 = eval_distance(b, p);
-            let h = (0.5 + 0.5 * (d2 - d1) / *k).clamp(0.0, 1.0);
-            lerp(d2, d1, h) - *k * h * (1.0 - h)
+            soyuz_math::op_smooth_union(eval_distance(a, p), eval_distance(b, p), *k)
         }
 
         SdfOp::SmoothSubtract { a, b, k } => {
-            let d1 = eval_distance(a, p);
-            let d2 = eval_distance(b, p);
-            let h = (0.5 - 0.5 * (d2 + d1) / *k).clamp(0.0, 1.0);
-            lerp(d1, -d2, h) + *k * h * (1.0 - h)
+            soyuz_math::op_smooth_subtract(eval_distance(a, p), eval_distance(b, p), *k)
         }
 
         SdfOp::SmoothIntersect { a, b, k } => {
-            let d1 = eval_distance(a, p);
-            let d2 = eval_distance(b, p);
-            let h = (0.5 - 0.5 * (d2 - d1) / *k).clamp(0.0, 1.0);
-            lerp(d2, d1, h) + *k * h * (1.0 - h)
+            soyuz_math::op_smooth_intersect(eval_distance(a, p), eval_distance(b, p), *k)
         }
 
+        SdfOp::Xor { a, b } => soyuz_math::op_xor(eval_distance(a, p), eval_distance(b, p)),
+
         // === Modifiers ===
-        SdfOp::Shell { inner, thickness } => eval_distance(inner, p).abs() - *thickness,
+        SdfOp::Shell { inner, thickness } => {
+            soyuz_math::op_shell(eval_distance(inner, p), *thickness)
+        }
 
-        SdfOp::Round { inner, radius } => eval_distance(inner, p) - *radius,
+        SdfOp::Round { inner, radius } => soyuz_math::op_round(eval_distance(inner, p), *radius),
 
         SdfOp::Onion { inner, thickness } => {
-            (eval_distance(inner, p).abs() % (*thickness * 2.0)) - *thickness
+            soyuz_math::op_onion(eval_distance(inner, p), *thickness)
         }
 
         SdfOp::Elongate { inner, h } => {
-            let h = Vec3::new(h[0], h[1], h[2]);
-            let q = p.abs() - h;
-            eval_distance(inner, q.max(Vec3::ZERO)) + q.x.max(q.y.max(q.z)).min(0.0)
+            let q = soyuz_math::op_elongate(p, Vec3::from_array(*h));
+            eval_distance(inner, q.truncate()) + q.w
         }
 
         // === Transforms ===
-        SdfOp::Translate { inner, offset } => {
-            let o = Vec3::new(offset[0], offset[1], offset[2]);
-            eval_distance(inner, p - o)
+        SdfOp::Scale { inner, factor } => {
+            eval_distance(inner, soyuz_math::op_scale(p, *factor)) * *factor
         }
 
-        SdfOp::RotateX { inner, angle } => {
-            let c = angle.cos();
-            let s = angle.sin();
-            let q = Vec3::new(p.x, c * p.y + s * p.z, -s * p.y + c * p.z);
-            eval_distance(inner, q)
+        SdfOp::NonUniformScale { inner, factors } => {
+            let s = Vec3::from_array(*factors);
+            eval_distance(inner, soyuz_math::op_scale_xyz(p, s)) * s.abs().min_element()
         }
 
-        SdfOp::RotateY { inner, angle } => {
-            let c = angle.cos();
-            let s = angle.sin();
-            let q = Vec3::new(c * p.x - s * p.z, p.y, s * p.x + c * p.z);
-            eval_distance(inner, q)
+        SdfOp::Transform { inner, transform } => {
+            eval_distance(inner, affine_point(transform, p)) * transform.distance_scale()
         }
 
-        SdfOp::RotateZ { inner, angle } => {
-            let c = angle.cos();
-            let s = angle.sin();
-            let q = Vec3::new(c * p.x + s * p.y, -s * p.x + c * p.y, p.z);
-            eval_distance(inner, q)
+        // === Deformations ===
+        SdfOp::Displacement {
+            inner,
+            amount,
+            frequency,
+        } => soyuz_math::op_displacement(eval_distance(inner, p), p, *amount, *frequency),
+
+        // === 2D-to-3D Operations ===
+        SdfOp::Extrude { profile, depth } => {
+            soyuz_math::op_extrude(eval_distance_2d(profile, Vec2::new(p.x, p.y)), p.z, *depth)
         }
 
-        SdfOp::Scale { inner, factor } => eval_distance(inner, p / *factor) * *factor,
+        SdfOp::Revolve { profile, offset } => {
+            eval_distance_2d(profile, soyuz_math::op_revolve(p, *offset))
+        }
 
-        SdfOp::Mirror { inner, axis } => {
-            // Mirror along arbitrary axis by reflecting the point
-            let axis = Vec3::new(axis[0], axis[1], axis[2]).normalize();
-            let d = p.dot(axis);
-            let q = if d < 0.0 { p - 2.0 * d * axis } else { p };
-            eval_distance(inner, q)
+        // === Sweeps ===
+        SdfOp::Tube {
+            path,
+            start_radius,
+            end_radius,
+        } => {
+            let radius = |t: f32| start_radius + (end_radius - start_radius) * t;
+            path.segments()
+                .iter()
+                .map(|s| {
+                    soyuz_math::sd_round_cone_between(
+                        p,
+                        Vec3::from(s.start),
+                        Vec3::from(s.end),
+                        radius(s.start_t),
+                        radius(s.end_t),
+                    )
+                })
+                .fold(f32::MAX, f32::min)
+        }
+
+        SdfOp::Sweep { path, profile } => path
+            .segments()
+            .iter()
+            .map(|s| {
+                let f = soyuz_math::op_sweep_frame(
+                    p,
+                    Vec3::from(s.start),
+                    Vec3::from(s.end),
+                    Vec3::from(s.side),
+                    Vec3::from(s.up),
+                    Vec3::from(s.start_miter),
+                    Vec3::from(s.end_miter),
+                );
+                soyuz_math::op_sweep_cap(eval_distance_2d(profile, f.truncate()), f.z)
+            })
+            .fold(f32::MAX, f32::min),
+
+        // === Translations, rotations, symmetry, deformations, repetition ===
+        _ => match warp_point(op, p) {
+            Some((inner, q)) => eval_distance(inner, q),
+            // Variants added to the non-exhaustive SdfOp after this evaluator
+            None => f32::MAX,
+        },
+    }
+}
+
+/// Evaluate the signed distance of a 2D profile at a point in its plane
+///
+/// Transforms and repetition run through the 3D formulas in the XY plane,
+/// exactly as the generated shader does.
+fn eval_distance_2d(op: &Sdf2dOp, p: Vec2) -> f32 {
+    match op {
+        // === Primitives ===
+        Sdf2dOp::Circle { radius } => soyuz_math::sd_circle_2d(p, *radius),
+
+        Sdf2dOp::Rectangle { half_extents } => {
+            soyuz_math::sd_box_2d(p, Vec2::from_array(*half_extents))
         }
 
-        SdfOp::SymmetryX { inner } => {
-            let q = Vec3::new(p.x.abs(), p.y, p.z);
-            eval_distance(inner, q)
+        Sdf2dOp::RoundedRectangle {
+            half_extents,
+            radius,
+        } => soyuz_math::sd_rounded_box_2d(p, Vec2::from_array(*half_extents), *radius),
+
+        Sdf2dOp::Polygon { points } => {
+            let mut acc = Vec2::new(1e20, 1.0);
+            for (i, a) in points.iter().enumerate() {
+                let b = points[(i + points.len() - 1) % points.len()];
+                acc =
+                    soyuz_math::sd_polygon_edge(p, Vec2::from_array(*a), Vec2::from_array(b), acc);
+            }
+            acc.y * acc.x.sqrt()
         }
 
-        SdfOp::SymmetryY { inner } => {
-            let q = Vec3::new(p.x, p.y.abs(), p.z);
-            eval_distance(inner, q)
+        Sdf2dOp::Arc {
+            half_angle,
+            radius,
+            half_thickness,
+        } => soyuz_math::sd_arc_2d(
+            p,
+            Vec2::new(half_angle.sin(), half_angle.cos()),
+            *radius,
+            *half_thickness,
+        ),
+
+        // === Boolean Operations ===
+        Sdf2dOp::Union { a, b } => {
+            soyuz_math::op_union(eval_distance_2d(a, p), eval_distance_2d(b, p))
         }
 
-        SdfOp::SymmetryZ { inner } => {
-            let q = Vec3::new(p.x, p.y, p.z.abs());
-            eval_distance(inner, q)
+        Sdf2dOp::Subtract { a, b } => {
+            soyuz_math::op_subtract(eval_distance_2d(a, p), eval_distance_2d(b, p))
         }
 
-        // === Deformations ===
-        SdfOp::Twist { inner, amount } => {
-            let c = (*amount * p.y).cos();
-            let s = (*amount * p.y).sin();
-            let q = Vec3::new(c * p.x - s * p.z, p.y, s * p.x + c * p.z);
-            eval_distance(inner, q)
+        Sdf2dOp::Intersect { a, b } => {
+            soyuz_math::op_intersect(eval_distance_2d(a, p), eval_distance_2d(b, p))
+        }
+
+        Sdf2dOp::SmoothUnion { a, b, k } => {
+            soyuz_math::op_smooth_union(eval_distance_2d(a, p), eval_distance_2d(b, p), *k)
+        }
+
+        Sdf2dOp::SmoothSubtract { a, b, k } => {
+            soyuz_math::op_smooth_subtract(eval_distance_2d(a, p), eval_distance_2d(b, p), *k)
+        }
+
+        Sdf2dOp::SmoothIntersect { a, b, k } => {
+            soyuz_math::op_smooth_intersect(eval_distance_2d(a, p), eval_distance_2d(b, p), *k)
+        }
+
+        // === Modifiers ===
+        Sdf2dOp::Offset { inner, distance } => {
+            soyuz_math::op_round(eval_distance_2d(inner, p), *distance)
+        }
+
+        Sdf2dOp::Shell { inner, thickness } => {
+            soyuz_math::op_shell(eval_distance_2d(inner, p), *thickness)
         }
 
-        SdfOp::Bend { inner, amount } => {
-            let c = (*amount * p.x).cos();
-            let s = (*amount * p.x).sin();
-            let q = Vec3::new(c * p.x - s * p.y, s * p.x + c * p.y, p.z);
-            eval_distance(inner, q)
+        // === Transforms ===
+        Sdf2dOp::Translate { inner, offset } => {
+            let o = Vec2::from_array(*offset).extend(0.0);
+            eval_distance_2d(inner, soyuz_math::op_translate(p.extend(0.0), o).truncate())
+        }
+
+        Sdf2dOp::Rotate { inner, angle } => {
+            let q = soyuz_math::op_rotate_z(p.extend(0.0), angle.cos(), angle.sin());
+            eval_distance_2d(inner, q.truncate())
+        }
+
+        Sdf2dOp::Scale { inner, factor } => {
+            let q = soyuz_math::op_scale(p.extend(0.0), *factor);
+            eval_distance_2d(inner, q.truncate()) * *factor
         }
 
         // === Repetition ===
-        SdfOp::RepeatInfinite { inner, spacing } => {
-            let s = Vec3::new(spacing[0], spacing[1], spacing[2]);
-            // Only repeat along axes with non-zero spacing
-            let q = Vec3::new(
-                if s.x > 0.0 {
-                    (p.x + s.x * 0.5).rem_euclid(s.x) - s.x * 0.5
-                } else {
-                    p.x
-                },
-                if s.y > 0.0 {
-                    (p.y + s.y * 0.5).rem_euclid(s.y) - s.y * 0.5
-                } else {
-                    p.y
-                },
-                if s.z > 0.0 {
-                    (p.z + s.z * 0.5).rem_euclid(s.z) - s.z * 0.5
-                } else {
-                    p.z
-                },
-            );
-            eval_distance(inner, q)
+        Sdf2dOp::RepeatInfinite { inner, spacing } => {
+            let c = Vec2::from_array(*spacing).extend(0.0);
+            eval_distance_2d(inner, soyuz_math::op_repeat(p.extend(0.0), c).truncate())
         }
 
-        SdfOp::RepeatLimited {
+        Sdf2dOp::RepeatLimited {
             inner,
             spacing,
             count,
         } => {
-            let s = Vec3::new(spacing[0], spacing[1], spacing[2]);
-            let c = Vec3::new(count[0], count[1], count[2]);
-            let q = p - s * (p / s).round().clamp(-c, c);
-            eval_distance(inner, q)
+            let q = soyuz_math::op_repeat_limited(
+                p.extend(0.0),
+                Vec2::from_array(*spacing).extend(0.0),
+                Vec2::from_array(*count).extend(0.0),
+            );
+            eval_distance_2d(inner, q.truncate())
         }
 
-        SdfOp::RepeatPolar { inner, count } => {
-            // Use SSOT formula from soyuz-math (generated from formulas/repeat_polar.toml)
-            let q = soyuz_math::repeat_polar(p, *count as f32);
-            eval_distance(inner, q)
+        // The 3D polar repeat works in XZ, so carry Y through Z
+        Sdf2dOp::RepeatPolar { inner, count } => {
+            let q = soyuz_math::repeat_polar(Vec3::new(p.x, 0.0, p.y), *count as f32);
+            eval_distance_2d(inner, Vec2::new(q.x, q.z))
         }
 
-        // Handle non-exhaustive enum
-        _ => {
-            // Unknown variant - return a large distance
-            f32::MAX
-        }
+        // Variants added to the non-exhaustive Sdf2dOp after this evaluator
+        _ => f32::MAX,
     }
 }
 
-/// Evaluate bounding box for an SDF operation
-fn eval_bounds(op: &SdfOp) -> Aabb {
+/// Map a point into the child's space for domain-warping operations
+///
+/// Covers operations whose distance is just the child's distance at a
+/// transformed point. Returns `None` for every other operation.
+fn warp_point(op: &SdfOp, p: Vec3) -> Option<(&Arc<SdfOp>, Vec3)> {
     match op {
-        SdfOp::Sphere { radius } => Aabb::cube(*radius),
+        // === Transforms ===
+        SdfOp::Translate { inner, offset } => {
+            let q = soyuz_math::op_translate(p, Vec3::from_array(*offset));
+            Some((inner, q))
+        }
 
-        SdfOp::Box { half_extents } => {
-            let h = Vec3::new(half_extents[0], half_extents[1], half_extents[2]);
-            Aabb::new(-h, h)
+        SdfOp::RotateX { inner, angle } => {
+            Some((inner, soyuz_math::op_rotate_x(p, angle.cos(), angle.sin())))
         }
 
-        SdfOp::RoundedBox { half_extents, .. } => {
-            let h = Vec3::new(half_extents[0], half_extents[1], half_extents[2]);
-            Aabb::new(-h, h)
+        SdfOp::RotateY { inner, angle } => {
+            Some((inner, soyuz_math::op_rotate_y(p, angle.cos(), angle.sin())))
         }
 
-        SdfOp::Cylinder {
-            radius,
-            half_height,
-        } => Aabb::new(
-            Vec3::new(-*radius, -*half_height, -*radius),
-            Vec3::new(*radius, *half_height, *radius),
-        ),
+        SdfOp::RotateZ { inner, angle } => {
+            Some((inner, soyuz_math::op_rotate_z(p, angle.cos(), angle.sin())))
+        }
 
-        SdfOp::Capsule {
-            radius,
-            half_height,
-        } => {
-            let h = *half_height + *radius;
-            Aabb::new(
-                Vec3::new(-*radius, -h, -*radius),
-                Vec3::new(*radius, h, *radius),
-            )
+        SdfOp::Rotate { inner, rotation } => {
+            let q = Vec4::from_array(*rotation)
+                .try_normalize()
+                .unwrap_or(Vec4::W);
+            Some((inner, soyuz_math::op_rotate_quat(p, q)))
         }
 
-        SdfOp::Torus {
-            major_radius,
-            minor_radius,
-        } => {
-            let r = *major_radius + *minor_radius;
-            Aabb::new(
-                Vec3::new(-r, -*minor_radius, -r),
-                Vec3::new(r, *minor_radius, r),
-            )
+        SdfOp::Mirror { inner, axis } => {
+            let n = Vec3::from_array(*axis).normalize_or_zero();
+            Some((inner, soyuz_math::op_mirror(p, n)))
         }
 
-        SdfOp::Cone { radius, height } => Aabb::new(
-            Vec3::new(-*radius, 0.0, -*radius),
-            Vec3::new(*radius, *height, *radius),
-        ),
+        SdfOp::SymmetryX { inner } => Some((inner, soyuz_math::op_symmetry_x(p))),
 
-        SdfOp::Plane { .. } => Aabb::cube(100.0),
+        SdfOp::SymmetryY { inner } => Some((inner, soyuz_math::op_symmetry_y(p))),
 
-        SdfOp::Ellipsoid { radii } => {
-            let r = Vec3::new(radii[0], radii[1], radii[2]);
-            Aabb::new(-r, r)
-        }
+        SdfOp::SymmetryZ { inner } => Some((inner, soyuz_math::op_symmetry_z(p))),
 
-        SdfOp::Octahedron { size } => Aabb::cube(*size),
+        // === Deformations ===
+        SdfOp::Twist { inner, amount } => Some((inner, soyuz_math::op_twist(p, *amount))),
 
-        SdfOp::HexPrism {
-            half_height,
-            radius,
-        } => Aabb::new(
-            Vec3::new(-*radius, -*half_height, -*radius),
-            Vec3::new(*radius, *half_height, *radius),
-        ),
+        SdfOp::Bend { inner, amount } => Some((inner, soyuz_math::op_bend(p, *amount))),
 
-        SdfOp::TriPrism { size } => {
-            let s = size[0].max(size[1]);
-            Aabb::cube(s)
+        // === Repetition ===
+        SdfOp::RepeatInfinite { inner, spacing } => {
+            Some((inner, soyuz_math::op_repeat(p, Vec3::from_array(*spacing))))
         }
 
-        // Boolean operations
-        SdfOp::Union { a, b } => eval_bounds(a).union(&eval_bounds(b)),
+        SdfOp::RepeatLimited {
+            inner,
+            spacing,
+            count,
+        } => {
+            let q = soyuz_math::op_repeat_limited(
+                p,
+                Vec3::from_array(*spacing),
+                Vec3::from_array(*count),
+            );
+            Some((inner, q))
+        }
 
-        SdfOp::Subtract { a, .. } => eval_bounds(a),
+        SdfOp::RepeatPolar { inner, count } => {
+            Some((inner, soyuz_math::repeat_polar(p, *count as f32)))
+        }
 
-        SdfOp::Intersect { a, .. } => eval_bounds(a),
+        // === Materials ===
+        // Material tags don't change the shape
+        SdfOp::Material { inner, .. } => Some((inner, p)),
 
-        SdfOp::SmoothUnion { a, b, k } => eval_bounds(a).union(&eval_bounds(b)).expand(*k),
+        _ => None,
+    }
+}
 
-        SdfOp::SmoothSubtract { a, .. } => eval_bounds(a),
+/// Evaluate distance and material at point p
+///
+/// `current` is the material inherited from enclosing tags, and `tags` the
+/// entry for `op` in the tree's [`SdfOp::tagged_nodes`]. Booleans pick
+/// (or blend, for smooth variants) the material of the surface that wins.
+fn eval_material(
+    op: &SdfOp,
+    tags: &TaggedNodes,
+    p: Vec3,
+    current: SurfaceMaterial,
+    default_color: [f32; 3],
+) -> (f32, SurfaceMaterial) {
+    if !tags.is_tagged() {
+        return (eval_distance(op, p), current);
+    }
 
-        SdfOp::SmoothIntersect { a, .. } => eval_bounds(a),
+    match op {
+        SdfOp::Material { inner, material } => eval_material(
+            inner,
+            tags.child(0),
+            p,
+            SurfaceMaterial::from_tag(*material, default_color),
+            default_color,
+        ),
 
-        // Modifiers
-        SdfOp::Shell { inner, thickness } => eval_bounds(inner).expand(*thickness),
+        // === Boolean Operations ===
+        SdfOp::Union { a, b } => {
+            let (d1, m1) = eval_material(a, tags.child(0), p, current, default_color);
+            let (d2, m2) = eval_material(b, tags.child(1), p, current, default_color);
+            if d1 < d2 { (d1, m1) } else { (d2, m2) }
+        }
 
-        SdfOp::Round { inner, radius } => eval_bounds(inner).expand(*radius),
+        SdfOp::Subtract { a, b } => {
+            // Cut surfaces keep the material of the shape being carved
+            let (d1, m1) = eval_material(a, tags.child(0), p, current, default_color);
+            let d2 = eval_distance(b, p);
+            (soyuz_math::op_subtract(d1, d2), m1)
+        }
 
-        SdfOp::Onion { inner, thickness } => eval_bounds(inner).expand(*thickness),
+        SdfOp::Intersect { a, b } => {
+            let (d1, m1) = eval_material(a, tags.child(0), p, current, default_color);
+            let (d2, m2) = eval_material(b, tags.child(1), p, current, default_color);
+            if d1 > d2 { (d1, m1) } else { (d2, m2) }
+        }
 
-        SdfOp::Elongate { inner, h } => {
-            let bounds = eval_bounds(inner);
-            let h = Vec3::new(h[0], h[1], h[2]);
-            Aabb::new(bounds.min - h, bounds.max + h)
+        SdfOp::SmoothUnion { a, b, k } => {
+            let (d1, m1) = eval_material(a, tags.child(0), p, current, default_color);
+            let (d2, m2) = eval_material(b, tags.child(1), p, current, default_color);
+            let h = soyuz_math::smooth_union_blend(d1, d2, *k);
+            (soyuz_math::op_smooth_union(d1, d2, *k), m2.mix(m1, h))
         }
 
-        // Transforms
-        SdfOp::Translate { inner, offset } => {
-            let bounds = eval_bounds(inner);
-            let o = Vec3::new(offset[0], offset[1], offset[2]);
-            Aabb::new(bounds.min + o, bounds.max + o)
+        SdfOp::SmoothSubtract { a, b, k } => {
+            let (d1, m1) = eval_material(a, tags.child(0), p, current, default_color);
+            let d2 = eval_distance(b, p);
+            (soyuz_math::op_smooth_subtract(d1, d2, *k), m1)
         }
 
-        SdfOp::RotateX { inner, .. }
-        | SdfOp::RotateY { inner, .. }
-        | SdfOp::RotateZ { inner, .. } => {
-            // Conservative: expand to enclosing sphere
-            let bounds = eval_bounds(inner);
-            let r = bounds.size().length() * 0.5;
-            let center = bounds.center();
-            Aabb::from_center(center, Vec3::splat(r))
+        SdfOp::SmoothIntersect { a, b, k } => {
+            let (d1, m1) = eval_material(a, tags.child(0), p, current, default_color);
+            let (d2, m2) = eval_material(b, tags.child(1), p, current, default_color);
+            let h = soyuz_math::smooth_intersect_blend(d1, d2, *k);
+            (soyuz_math::op_smooth_intersect(d1, d2, *k), m2.mix(m1, h))
         }
 
-        SdfOp::Scale { inner, factor } => {
-            let bounds = eval_bounds(inner);
-            Aabb::new(bounds.min * *factor, bounds.max * *factor)
+        SdfOp::Xor { a, b } => {
+            let (d1, m1) = eval_material(a, tags.child(0), p, current, default_color);
+            let (d2, m2) = eval_material(b, tags.child(1), p, current, default_color);
+            let m = if d1.abs() < d2.abs() { m1 } else { m2 };
+            (soyuz_math::op_xor(d1, d2), m)
         }
 
-        SdfOp::Mirror { inner, .. }
-        | SdfOp::SymmetryX { inner }
-        | SdfOp::SymmetryY { inner }
-        | SdfOp::SymmetryZ { inner } => {
-            let bounds = eval_bounds(inner);
-            // Mirror expands to cover both sides
-            let max_extent = bounds.min.abs().max(bounds.max.abs());
-            Aabb::new(-max_extent, max_extent)
+        // === Modifiers: same point, distance adjusted by the full op ===
+        SdfOp::Shell { inner, .. }
+        | SdfOp::Round { inner, .. }
+        | SdfOp::Onion { inner, .. }
+        | SdfOp::Displacement { inner, .. } => {
+            let (_, m) = eval_material(inner, tags.child(0), p, current, default_color);
+            (eval_distance(op, p), m)
         }
 
-        // Deformations
-        SdfOp::Twist { inner, .. } | SdfOp::Bend { inner, .. } => {
-            // Conservative bounds for deformations
-            eval_bounds(inner).expand(0.5)
+        SdfOp::Elongate { inner, h } => {
+            let q = soyuz_math::op_elongate(p, Vec3::from_array(*h));
+            let (_, m) = eval_material(inner, tags.child(0), q.truncate(), current, default_color);
+            (eval_distance(op, p), m)
         }
 
-        // Repetition
-        SdfOp::RepeatInfinite { .. } => {
-            // Can't have finite bounds for infinite repetition
-            // Return large bounds, mesh generation will need to limit sampling area
-            Aabb::cube(10.0)
+        SdfOp::Scale { inner, factor } => {
+            let (d, m) = eval_material(
+                inner,
+                tags.child(0),
+                soyuz_math::op_scale(p, *factor),
+                current,
+                default_color,
+            );
+            (d * *factor, m)
         }
 
-        SdfOp::RepeatLimited {
-            inner,
-            spacing,
-            count,
-        } => {
-            let bounds = eval_bounds(inner);
-            let s = Vec3::new(spacing[0], spacing[1], spacing[2]);
-            let c = Vec3::new(count[0], count[1], count[2]);
-            let total = s * c * 2.0;
-            Aabb::new(bounds.min - total * 0.5, bounds.max + total * 0.5)
-        }
-
-        SdfOp::RepeatPolar { inner, .. } => {
-            let bounds = eval_bounds(inner);
-            let r = bounds
-                .max
-                .x
-                .abs()
-                .max(bounds.max.z.abs())
-                .max(bounds.min.x.abs())
-                .max(bounds.min.z.abs());
-            Aabb::new(
-                Vec3::new(-r, bounds.min.y, -r),
-                Vec3::new(r, bounds.max.y, r),
-            )
-        }
-
-        // Handle non-exhaustive enum
-        _ => Aabb::cube(10.0),
+        SdfOp::NonUniformScale { inner, factors } => {
+            let s = Vec3::from_array(*factors);
+            let q = soyuz_math::op_scale_xyz(p, s);
+            let (d, m) = eval_material(inner, tags.child(0), q, current, default_color);
+            (d * s.abs().min_element(), m)
+        }
+
+        SdfOp::Transform { inner, transform } => {
+            let q = affine_point(transform, p);
+            let (d, m) = eval_material(inner, tags.child(0), q, current, default_color);
+            (d * transform.distance_scale(), m)
+        }
+
+        _ => match warp_point(op, p) {
+            Some((inner, q)) => eval_material(inner, tags.child(0), q, current, default_color),
+            None => (eval_distance(op, p), current),
+        },
     }
 }
 
+/// Map a point into the local space of an [`Affine`] transform
+fn affine_point(transform: &Affine, p: Vec3) -> Vec3 {
+    let [c0, c1, c2] = transform.inverse().map(Vec3::from_array);
+    soyuz_math::op_affine(p, c0, c1, c2, Vec3::from_array(transform.offset()))
+}
+
 #[inline]
 fn lerp(a: f32, b: f32, t: f32) -> f32 {
     a + (b - a) * t
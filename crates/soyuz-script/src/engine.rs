@@ -6,11 +6,20 @@
 #![allow(clippy::uninlined_format_args)]
 
 use crate::env_api::{get_current_environment, register_env_api, reset_environment};
+use crate::imports::{ImportResolver, ImportState, canonical};
+use crate::param_api::{ParamState, ParamValue, ScriptParam, register_param_api};
+use crate::rand_api::{RandState, register_rand_api};
 use crate::sdf_api::{RhaiSdf, register_sdf_api};
+use crate::texture_api::{get_current_material, register_texture_api, reset_material};
+use crate::time_api::{TimeState, register_time_api};
 use anyhow::{Result, anyhow};
+use parking_lot::Mutex;
 use rhai::{Dynamic, Engine, Scope};
+use soyuz_core::material::Material;
 use soyuz_sdf::{Environment, SdfOp};
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Result of evaluating a script - contains both the SDF and environment settings
 #[derive(Debug, Clone)]
@@ -19,11 +28,31 @@ pub struct SceneResult {
     pub sdf: SdfOp,
     /// Environment settings (lighting, material, background)
     pub environment: Environment,
+    /// PBR material set with `set_material()`, used by glTF export
+    pub material: Option<Material>,
+    /// Files the script imported, directly or through other imports
+    pub imports: Vec<PathBuf>,
+    /// Parameters the script declared with `param()`, in declaration order
+    pub params: Vec<ScriptParam>,
+    /// Whether the script read `time` or `frame`, so other frames of an
+    /// animation may build a different shape
+    pub animated: bool,
 }
 
 /// Soyuz script engine for evaluating SDF scripts
+///
+/// Scripts can `import` other scripts; see [`crate::imports`] for how paths
+/// are resolved. Parameters declared with `param()` take the overrides set
+/// with [`set_param`](Self::set_param); see [`crate::param_api`]. Random
+/// numbers come from the seed set with [`set_seed`](Self::set_seed); see
+/// [`crate::rand_api`]. The `time` and `frame` variables come from
+/// [`set_time`](Self::set_time); see [`crate::time_api`].
 pub struct ScriptEngine {
     engine: Engine,
+    imports: Arc<Mutex<ImportState>>,
+    params: Arc<Mutex<ParamState>>,
+    rand: Arc<Mutex<RandState>>,
+    time: Arc<Mutex<TimeState>>,
 }
 
 impl ScriptEngine {
@@ -37,10 +66,147 @@ impl ScriptEngine {
         // Register environment configuration API
         register_env_api(&mut engine);
 
+        // Register procedural texture and PBR material API
+        register_texture_api(&mut engine);
+
+        // Register `param()`, which reads overrides set on this engine
+        let params = Arc::new(Mutex::new(ParamState::default()));
+        register_param_api(&mut engine, &params);
+
+        // Register `rand()` and friends, seeded from this engine
+        let rand = Arc::new(Mutex::new(RandState::default()));
+        register_rand_api(&mut engine, &rand);
+
+        // Resolve the `time` and `frame` variables set on this engine
+        let time = Arc::new(Mutex::new(TimeState::default()));
+        register_time_api(&mut engine, &time);
+
+        // Resolve `import` statements against the script and library paths
+        let imports = Arc::new(Mutex::new(ImportState::default()));
+        engine.set_module_resolver(ImportResolver::new(imports.clone(), rand.clone()));
+
         // Configure engine for better errors
         engine.set_max_expr_depths(64, 64);
 
-        Self { engine }
+        Self {
+            engine,
+            imports,
+            params,
+            rand,
+            time,
+        }
+    }
+
+    /// Add a directory to search for imported scripts
+    ///
+    /// Library directories are searched in the order they were added, after
+    /// the importing script's own directory.
+    pub fn add_library_path(&mut self, path: impl Into<PathBuf>) {
+        self.imports.lock().library_paths.push(path.into());
+    }
+
+    /// Directories searched for imported scripts
+    pub fn library_paths(&self) -> Vec<PathBuf> {
+        self.imports.lock().library_paths.clone()
+    }
+
+    /// Override a parameter for later evaluations
+    ///
+    /// Evaluation fails if the script doesn't declare `name`, or if the
+    /// value doesn't fit the declared type and range.
+    pub fn set_param(&mut self, name: impl Into<String>, value: impl Into<ParamValue>) {
+        self.params
+            .lock()
+            .overrides
+            .insert(name.into(), value.into());
+    }
+
+    /// Remove a parameter override, so the script's default is used again
+    pub fn remove_param(&mut self, name: &str) {
+        self.params.lock().overrides.remove(name);
+    }
+
+    /// Remove all parameter overrides
+    pub fn clear_params(&mut self) {
+        self.params.lock().overrides.clear();
+    }
+
+    /// Parameter overrides set with [`set_param`](Self::set_param)
+    pub fn param_overrides(&self) -> BTreeMap<String, ParamValue> {
+        self.params.lock().overrides.clone()
+    }
+
+    /// Seed the script's random numbers for later evaluations
+    ///
+    /// The same seed always gives the same numbers, unless the script picks
+    /// its own with `seed()`.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rand.lock().seed = seed;
+    }
+
+    /// Seed set with [`set_seed`](Self::set_seed) (0 by default)
+    pub fn seed(&self) -> u64 {
+        self.rand.lock().seed
+    }
+
+    /// Set the `time` (in seconds) and `frame` that later evaluations see
+    pub fn set_time(&mut self, time: f64, frame: i64) {
+        let mut state = self.time.lock();
+        state.time = time;
+        state.frame = frame;
+    }
+
+    /// Time set with [`set_time`](Self::set_time) (0.0 by default)
+    pub fn time(&self) -> f64 {
+        self.time.lock().time
+    }
+
+    /// Frame set with [`set_time`](Self::set_time) (0 by default)
+    pub fn frame(&self) -> i64 {
+        self.time.lock().frame
+    }
+
+    /// Evaluate a script and list the parameters it declares
+    ///
+    /// Each parameter's `value` reflects the current overrides.
+    pub fn params(&self, script: &str) -> Result<Vec<ScriptParam>> {
+        let _ = self.eval_fresh(script, None)?;
+        Ok(self.params.lock().declared.clone())
+    }
+
+    /// Run a script from scratch: fresh environment, material, imports,
+    /// parameters, random streams and time reads
+    ///
+    /// `source` is the file the script came from, which relative imports
+    /// are resolved against.
+    fn eval_fresh(&self, script: &str, source: Option<&Path>) -> Result<Dynamic> {
+        reset_environment();
+        reset_material();
+        self.imports.lock().reset();
+        self.params.lock().reset();
+        self.time.lock().read = false;
+
+        let mut ast = self
+            .engine
+            .compile(script)
+            .map_err(|e| anyhow!("Failed to evaluate script: {}", e))?;
+        let source = source.map(|path| canonical(path).to_string_lossy().into_owned());
+        if let Some(source) = &source {
+            ast.set_source(source.as_str());
+        }
+        self.rand.lock().reset(source, script);
+
+        let result = self
+            .engine
+            .eval_ast(&ast)
+            .map_err(|e| anyhow!("Failed to evaluate script: {}", e))?;
+
+        // An override the script never asked for is most likely a typo
+        self.params
+            .lock()
+            .check_overrides()
+            .map_err(|e| anyhow!("Failed to evaluate script: {}", e))?;
+        Ok(result)
     }
 
     /// Evaluate a script and return the resulting SDF
@@ -54,29 +220,8 @@ impl ScriptEngine {
     /// let sdf = engine.eval_sdf("sphere(1.0)")?;
     /// ```
     pub fn eval_sdf(&self, script: &str) -> Result<RhaiSdf> {
-        // Reset environment before evaluation
-        reset_environment();
-
-        let result: Dynamic = self
-            .engine
-            .eval(script)
-            .map_err(|e| anyhow!("Failed to evaluate script: {}", e))?;
-
-        // Try to extract RhaiSdf from the result
-        result.try_cast::<RhaiSdf>().ok_or_else(|| {
-            let trimmed = script.trim();
-            if trimmed.ends_with(';') {
-                anyhow!(
-                    "Script did not return an SDF.\n\n\
-                    HINT: Your script ends with ';' which returns nothing.\n\
-                    Add the variable name at the end:\n\n\
-                      let shape = sphere(0.5);\n\
-                      shape  // <- return it!"
-                )
-            } else {
-                anyhow!("Script did not return an SDF. The last expression must be a shape.")
-            }
-        })
+        let result = self.eval_fresh(script, None)?;
+        expect_sdf(result, script)
     }
 
     /// Evaluate a script file and return the resulting SDF
@@ -84,7 +229,8 @@ impl ScriptEngine {
         let script = std::fs::read_to_string(path)
             .map_err(|e| anyhow!("Failed to read script file {}: {}", path.display(), e))?;
 
-        self.eval_sdf(&script)
+        self.eval_fresh(&script, Some(path))
+            .and_then(|result| expect_sdf(result, &script))
             .map_err(|e| anyhow!("Error in script {}: {}", path.display(), e))
     }
 
@@ -105,29 +251,29 @@ impl ScriptEngine {
     /// This is the recommended method for preview rendering as it captures
     /// any environment configuration done in the script.
     pub fn eval_scene(&self, script: &str) -> Result<SceneResult> {
-        // Reset environment before evaluation
-        reset_environment();
+        self.eval_scene_from(script, None)
+    }
 
-        let result: Dynamic = self
-            .engine
-            .eval(script)
-            .map_err(|e| anyhow!("Failed to evaluate script: {}", e))?;
+    /// Evaluate a script as if it were stored at `path`
+    ///
+    /// Relative imports are resolved against `path`'s directory. Useful for
+    /// editor buffers that haven't been saved yet.
+    pub fn eval_scene_with_path(&self, script: &str, path: &Path) -> Result<SceneResult> {
+        self.eval_scene_from(script, Some(path))
+    }
 
-        // Extract SDF
-        let rhai_sdf = result.try_cast::<RhaiSdf>().ok_or_else(|| {
-            let trimmed = script.trim();
-            if trimmed.ends_with(';') {
-                anyhow!(
-                    "Script did not return an SDF.\n\n\
-                    HINT: Your script ends with ';' which returns nothing.\n\
-                    Add the variable name at the end:\n\n\
-                      let shape = sphere(0.5);\n\
-                      shape  // <- return it!"
-                )
-            } else {
-                anyhow!("Script did not return an SDF. The last expression must be a shape.")
-            }
-        })?;
+    /// Evaluate a script file and return both SDF and environment settings
+    pub fn eval_scene_file(&self, path: &Path) -> Result<SceneResult> {
+        let script = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read script file {}: {}", path.display(), e))?;
+
+        self.eval_scene_with_path(&script, path)
+            .map_err(|e| anyhow!("Error in script {}: {}", path.display(), e))
+    }
+
+    fn eval_scene_from(&self, script: &str, source: Option<&Path>) -> Result<SceneResult> {
+        let result = self.eval_fresh(script, source)?;
+        let rhai_sdf = expect_sdf(result, script)?;
 
         // Get the environment that was configured during script execution
         let environment = get_current_environment();
@@ -135,18 +281,13 @@ impl ScriptEngine {
         Ok(SceneResult {
             sdf: rhai_sdf.to_sdf_op(),
             environment,
+            material: get_current_material(),
+            imports: self.imports.lock().imported.clone(),
+            params: self.params.lock().declared.clone(),
+            animated: self.time.lock().read,
         })
     }
 
-    /// Evaluate a script file and return both SDF and environment settings
-    pub fn eval_scene_file(&self, path: &Path) -> Result<SceneResult> {
-        let script = std::fs::read_to_string(path)
-            .map_err(|e| anyhow!("Failed to read script file {}: {}", path.display(), e))?;
-
-        self.eval_scene(&script)
-            .map_err(|e| anyhow!("Error in script {}: {}", path.display(), e))
-    }
-
     /// Evaluate a script without expecting a return value
     pub fn run(&self, script: &str) -> Result<()> {
         self.engine
@@ -197,6 +338,25 @@ impl Default for ScriptEngine {
     }
 }
 
+/// Extract the shape a script returned, explaining the usual mistake if
+/// it returned something else
+fn expect_sdf(result: Dynamic, script: &str) -> Result<RhaiSdf> {
+    result.try_cast::<RhaiSdf>().ok_or_else(|| {
+        let trimmed = script.trim();
+        if trimmed.ends_with(';') {
+            anyhow!(
+                "Script did not return an SDF.\n\n\
+                HINT: Your script ends with ';' which returns nothing.\n\
+                Add the variable name at the end:\n\n\
+                  let shape = sphere(0.5);\n\
+                  shape  // <- return it!"
+            )
+        } else {
+            anyhow!("Script did not return an SDF. The last expression must be a shape.")
+        }
+    })
+}
+
 /// Result of evaluating a script - either an SDF or an error
 #[derive(Debug)]
 pub enum ScriptResult {
@@ -256,6 +416,7 @@ pub fn try_eval_script(engine: &ScriptEngine, script: &str) -> ScriptResult {
 }
 
 #[cfg(test)]
+#[allow(clippy::expect_used)]
 mod tests {
     use super::*;
 
@@ -278,10 +439,384 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_eval_scene_with_material() {
+        let engine = ScriptEngine::new();
+        let script = r##"
+            let wood = fbm(4, 6.0).warp(perlin(3.0), 0.2).colorize_hex("#5a3a1e", "#a0703c");
+            set_material(pbr().albedo(wood).roughness(0.7).normal(bricks(0.25, 0.1, 0.02).to_normal(2.0)));
+            cylinder(0.5, 1.2)
+        "##;
+        let result = engine.eval_scene(script).expect("script should evaluate");
+        let material = result.material.expect("script should set a material");
+        assert!(material.has_textures());
+        assert!(material.normal.is_some());
+
+        // The material does not leak into the next evaluation
+        let result = engine
+            .eval_scene("sphere(0.5)")
+            .expect("script should evaluate");
+        assert!(result.material.is_none());
+    }
+
+    /// A fresh directory of scripts for import tests
+    fn script_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("soyuz_import_test_{}_{name}", std::process::id()));
+        for (file, code) in files {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().expect("file has a parent")).ok();
+            std::fs::write(path, code).expect("script should be written");
+        }
+        dir
+    }
+
+    #[test]
+    fn test_import_relative_to_script() {
+        let dir = script_dir(
+            "relative",
+            &[
+                (
+                    "model.rhai",
+                    r#"import "parts/bolt" as bolt; bolt::bolt(0.1).translate_y(bolt::HEIGHT)"#,
+                ),
+                (
+                    "parts/bolt.rhai",
+                    r#"import "head" as head; export const HEIGHT = 0.5; fn bolt(r) { cylinder(r, 0.4).union(head::head(r)) }"#,
+                ),
+                ("parts/head.rhai", "fn head(r) { hex_prism(r * 2.0, 0.1) }"),
+            ],
+        );
+
+        let engine = ScriptEngine::new();
+        let result = engine
+            .eval_scene_file(&dir.join("model.rhai"))
+            .expect("imports should resolve");
+        assert!(matches!(result.sdf, SdfOp::Translate { .. }));
+        assert_eq!(
+            result.imports,
+            vec![
+                canonical(&dir.join("parts/bolt.rhai")),
+                canonical(&dir.join("parts/head.rhai"))
+            ]
+        );
+
+        // Plain evaluation forgets the previous script's imports
+        let result = engine
+            .eval_scene("sphere(0.5)")
+            .expect("script should evaluate");
+        assert!(result.imports.is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_import_dotted_module_name() {
+        let dir = script_dir(
+            "dotted",
+            &[
+                (
+                    "model.rhai",
+                    r#"import "parts/bolt.v2" as a; import "parts/v1.2.rhai" as b; a::bolt().union(b::nut())"#,
+                ),
+                ("parts/bolt.v2.rhai", "fn bolt() { cylinder(0.1, 0.4) }"),
+                ("parts/v1.2.rhai", "fn nut() { hex_prism(0.2, 0.1) }"),
+            ],
+        );
+
+        let engine = ScriptEngine::new();
+        let result = engine
+            .eval_scene_file(&dir.join("model.rhai"))
+            .expect("dotted imports should resolve");
+        assert_eq!(
+            result.imports,
+            vec![
+                canonical(&dir.join("parts/bolt.v2.rhai")),
+                canonical(&dir.join("parts/v1.2.rhai"))
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_import_from_library_path() {
+        let lib = script_dir("library", &[("rivet.rhai", "fn rivet() { sphere(0.05) }")]);
+        let mut engine = ScriptEngine::new();
+        let script = r#"import "rivet" as r; r::rivet()"#;
+        assert!(engine.eval_scene(script).is_err());
+
+        engine.add_library_path(&lib);
+        assert!(engine.eval_scene(script).is_ok());
+
+        std::fs::remove_dir_all(&lib).ok();
+    }
+
+    #[test]
+    fn test_import_errors_name_the_file() {
+        let dir = script_dir(
+            "errors",
+            &[
+                ("missing.rhai", r#"import "nope" as n; sphere(0.5)"#),
+                ("broken.rhai", r#"import "part" as p; p::shape()"#),
+                (
+                    "part.rhai",
+                    "fn shape() {
+    sphere(radius)
+}",
+                ),
+                ("a.rhai", r#"import "b" as b; sphere(0.5)"#),
+                ("b.rhai", r#"import "a" as a; sphere(0.5)"#),
+            ],
+        );
+        let engine = ScriptEngine::new();
+        let error = |file: &str| {
+            engine
+                .eval_scene_file(&dir.join(file))
+                .expect_err("script should fail")
+                .to_string()
+        };
+
+        assert!(error("missing.rhai").contains("Module not found: nope"));
+
+        let message = error("broken.rhai");
+        assert!(message.contains("part.rhai"), "{message}");
+        assert!(message.contains("line 2"), "{message}");
+
+        assert!(error("a.rhai").contains("Circular import"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
     #[test]
     fn test_syntax_error() {
         let engine = ScriptEngine::new();
         let result = engine.eval_sdf("sphere(");
         assert!(result.is_err());
     }
+
+    const BARREL: &str = r#"
+let radius = param("radius", 0.5, 0.1, 1.0);
+let staves = param("staves", 12, 6, 24);
+let banded = param("banded", true);
+
+let barrel = cylinder(radius, 1.2)
+    .subtract(box3(0.01, 1.3, 0.05).translate_x(radius).repeat_polar(staves));
+if banded { barrel.union(torus(radius, 0.03)) } else { barrel }
+"#;
+
+    #[test]
+    fn test_params_listed_with_defaults() {
+        let engine = ScriptEngine::new();
+        let params = engine.params(BARREL).expect("script should evaluate");
+
+        let names: Vec<&str> = params.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["radius", "staves", "banded"]);
+        assert_eq!(params[0].value, ParamValue::Float(0.5));
+        assert_eq!(params[1].min, ParamValue::Int(6));
+        assert_eq!(params[2].value, ParamValue::Bool(true));
+    }
+
+    #[test]
+    fn test_param_overrides_change_the_shape() {
+        let mut engine = ScriptEngine::new();
+        engine.set_param("radius", 0.8);
+        engine.set_param("staves", 20.0);
+        engine.set_param("banded", false);
+
+        let result = engine.eval_scene(BARREL).expect("script should evaluate");
+        assert_eq!(result.params[0].value, ParamValue::Float(0.8));
+        assert_eq!(result.params[1].value, ParamValue::Int(20));
+        assert!(matches!(result.sdf, SdfOp::Subtract { .. }));
+
+        engine.clear_params();
+        let result = engine.eval_scene(BARREL).expect("script should evaluate");
+        assert_eq!(result.params[0].value, ParamValue::Float(0.5));
+        assert!(matches!(result.sdf, SdfOp::Union { .. }));
+    }
+
+    #[test]
+    fn test_param_errors() {
+        let mut engine = ScriptEngine::new();
+
+        let err = engine
+            .eval_scene(r#"sphere(param("r", 2.0, 0.1, 1.0))"#)
+            .expect_err("default out of range");
+        assert!(err.to_string().contains("default 2 is outside"), "{err}");
+
+        let err = engine
+            .eval_scene(
+                r#"sphere(param("r", 0.5, 0.1, 1.0)).union(cube(param("r", 0.2, 0.1, 1.0)))"#,
+            )
+            .expect_err("conflicting declarations");
+        assert!(err.to_string().contains("declared twice"), "{err}");
+
+        engine.set_param("radius", 1.5);
+        let err = engine
+            .eval_scene(BARREL)
+            .expect_err("override out of range");
+        assert!(err.to_string().contains("must be in 0.1..=1"), "{err}");
+
+        engine.clear_params();
+        engine.set_param("radus", 0.5);
+        let err = engine.eval_scene(BARREL).expect_err("unknown override");
+        assert!(
+            err.to_string()
+                .contains("unknown parameter 'radus' (the script declares radius, staves, banded)"),
+            "{err}"
+        );
+    }
+
+    const ROCK: &str = r#"
+let rock = sphere(1.0);
+for i in 0..6 {
+    let dent = sphere(rand_range(0.1, 0.3)).translate(rand(), rand(), rand());
+    rock = rock.smooth_subtract(dent, 0.05);
+}
+rock.scale(rand_range(1, 3).to_float())
+"#;
+
+    fn shape(engine: &ScriptEngine, script: &str) -> String {
+        let result = engine.eval_scene(script).expect("script should evaluate");
+        format!("{:?}", result.sdf)
+    }
+
+    #[test]
+    fn test_rand_is_deterministic_per_seed() {
+        let mut engine = ScriptEngine::new();
+        let first = shape(&engine, ROCK);
+        assert_eq!(shape(&engine, ROCK), first);
+        assert_eq!(shape(&ScriptEngine::new(), ROCK), first);
+
+        engine.set_seed(7);
+        assert_eq!(engine.seed(), 7);
+        let seven = shape(&engine, ROCK);
+        assert_ne!(seven, first);
+        assert_eq!(shape(&engine, ROCK), seven);
+    }
+
+    #[test]
+    fn test_rand_streams_are_stable_under_edits() {
+        let engine = ScriptEngine::new();
+
+        // Lines inserted above a call, or code around it, keep its values
+        let script = "let a = rand();\nsphere(a + rand())";
+        assert_eq!(
+            shape(
+                &engine,
+                "let a = rand();\nlet b = 1.0;\n\nsphere(a  +  rand())"
+            ),
+            shape(&engine, script)
+        );
+
+        // Windows line endings and calls in string interpolation are
+        // numbered too, rather than keyed by where they sit
+        assert_eq!(
+            shape(&engine, "let a = rand();\r\n\r\nsphere(a + rand())"),
+            shape(&engine, script)
+        );
+        let interpolated = "let r = `${rand()}`;\nsphere(r.parse_float())";
+        assert_eq!(
+            shape(&engine, &format!("let b = 1.0;\n{interpolated}")),
+            shape(&engine, interpolated)
+        );
+
+        // Unnamed calls are numbered per function, so a call added to
+        // another function doesn't shift them
+        let body = "fn size() { rand() }\nsphere(size())";
+        assert_eq!(
+            shape(&engine, &format!("fn other() {{ rand() }}\n{body}")),
+            shape(&engine, body)
+        );
+
+        // A call added earlier in the same function renumbers the ones after
+        // it, unless they're named
+        assert_ne!(
+            shape(&engine, "let unused = rand();\nsphere(rand())"),
+            shape(&engine, "sphere(rand())")
+        );
+        assert_eq!(
+            shape(&engine, "let unused = rand();\nsphere(rand(\"size\"))"),
+            shape(&engine, "sphere(rand(\"size\"))")
+        );
+        assert_eq!(
+            shape(
+                &engine,
+                "let n = rand_range(1, 9);\nsphere(rand_range(\"r\", 0.5, 1.0))"
+            ),
+            shape(&engine, "sphere(rand_range(\"r\", 0.5, 1.0))")
+        );
+        assert_eq!(
+            shape(
+                &engine,
+                "let name = \"dent\";\nlet d = rand(name) + rand_range(name + \"x\", 0, 1);\nsphere(rand())"
+            ),
+            shape(&engine, "sphere(rand())")
+        );
+
+        // Repeated calls draw different values
+        engine
+            .eval_scene(
+                r#"let r = []; for i in 0..2 { r.push(rand()); r.push(rand("n")); }
+                if r[0] == r[2] || r[1] == r[3] { throw "same value twice"; }
+                sphere(r[0])"#,
+            )
+            .expect("a loop should draw fresh values");
+    }
+
+    #[test]
+    fn test_rand_streams_in_imports_are_keyed_by_path() {
+        let part = "fn size() { rand() }";
+        let dir = script_dir("rand", &[("a/part.rhai", part), ("b/part.rhai", part)]);
+        let size = |path: &str| {
+            shape(
+                &ScriptEngine::new(),
+                &format!(
+                    r#"import "{}" as p; sphere(p::size())"#,
+                    dir.join(path).display()
+                ),
+            )
+        };
+
+        // The same code in another file draws other values
+        assert_ne!(size("a/part"), size("b/part"));
+        // Reached by another path, the same file draws the same values
+        assert_eq!(size("a/part"), size("a/../a/part"));
+    }
+
+    #[test]
+    fn test_script_seed_overrides_host_seed() {
+        let mut engine = ScriptEngine::new();
+        let script = "seed(42); sphere(rand_range(0.5, 1.0))";
+        let fixed = shape(&engine, script);
+        engine.set_seed(3);
+        assert_eq!(shape(&engine, script), fixed);
+
+        let err = engine
+            .eval_scene("sphere(rand_range(5, 1).to_float())")
+            .expect_err("empty range");
+        assert!(
+            err.to_string().contains("min 5 is greater than max 1"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_time_and_frame_variables() {
+        let mut engine = ScriptEngine::new();
+        let script = "fn size() { 0.5 + time } sphere(size()).translate_x(frame.to_float())";
+
+        let still = engine.eval_scene(script).expect("time defaults to 0");
+        assert!(still.animated);
+        engine.set_time(0.25, 6);
+        assert_eq!((engine.time(), engine.frame()), (0.25, 6));
+        assert_ne!(shape(&engine, script), format!("{:?}", still.sdf));
+
+        // Scripts that never read the time aren't animated, and locals win
+        let local = engine
+            .eval_scene("let time = 1.0; sphere(time)")
+            .expect("a local time should evaluate");
+        assert!(!local.animated);
+        assert!(!engine.eval_scene("sphere(0.5)").expect("plain").animated);
+    }
 }
@@ -254,7 +254,7 @@ fn env_clay() {
 // ============================================================================
 
 /// Parse a hex color string like "#ff5500" or "ff5500"
-fn parse_hex_color(hex: &str) -> Option<(f32, f32, f32)> {
+pub(crate) fn parse_hex_color(hex: &str) -> Option<(f32, f32, f32)> {
     let hex = hex.trim_start_matches('#');
     if hex.len() != 6 {
         return None;
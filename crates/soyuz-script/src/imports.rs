@@ -0,0 +1,160 @@
+//! Module resolution for `import` statements
+//!
+//! Scripts share parts by importing other scripts:
+//!
+//! ```rhai
+//! import "parts/bolt" as bolt;
+//!
+//! bolt::hex_bolt(0.1).translate_y(0.5)
+//! ```
+//!
+//! A relative path is looked up next to the importing script first (the
+//! current directory for scripts that aren't files), then in each library
+//! directory in order. The `.rhai` extension is optional. Modules are loaded
+//! fresh on every evaluation, so edits to imported files are picked up, and
+//! every file loaded is recorded so watchers know which files a script
+//! depends on.
+
+use crate::rand_api::RandState;
+use parking_lot::Mutex;
+use rhai::module_resolvers::ModuleResolver;
+use rhai::{Engine, EvalAltResult, Module, Position, Scope, Shared};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Library directories and the files imported by the current evaluation
+#[derive(Debug, Default)]
+pub(crate) struct ImportState {
+    /// Directories searched after the importing script's own
+    pub library_paths: Vec<PathBuf>,
+    /// Every file imported since the last [`reset`](Self::reset), in order
+    pub imported: Vec<PathBuf>,
+    /// Files whose top-level code is running, to catch circular imports
+    loading: Vec<PathBuf>,
+}
+
+impl ImportState {
+    /// Forget the files imported by the previous evaluation
+    pub fn reset(&mut self) {
+        self.imported.clear();
+        self.loading.clear();
+    }
+}
+
+/// Resolves `import` paths against the importing script and library paths
+pub(crate) struct ImportResolver {
+    state: Arc<Mutex<ImportState>>,
+    /// Random streams, which number the calls in each imported file
+    rand: Arc<Mutex<RandState>>,
+}
+
+impl ImportResolver {
+    pub fn new(state: Arc<Mutex<ImportState>>, rand: Arc<Mutex<RandState>>) -> Self {
+        Self { state, rand }
+    }
+
+    /// Directories to look in, in order
+    fn search_dirs(&self, source: Option<&str>) -> Vec<PathBuf> {
+        let script_dir = source
+            .and_then(|s| Path::new(s).parent())
+            .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+
+        let mut dirs = vec![script_dir];
+        dirs.extend(self.state.lock().library_paths.iter().cloned());
+        dirs
+    }
+}
+
+impl ModuleResolver for ImportResolver {
+    fn resolve(
+        &self,
+        engine: &Engine,
+        source: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        let dirs = self.search_dirs(source);
+        let Some(file) = find_module(path, &dirs) else {
+            let searched: Vec<String> = dirs.iter().map(|d| d.display().to_string()).collect();
+            return Err(EvalAltResult::ErrorModuleNotFound(
+                format!("{path} (searched {})", searched.join(", ")),
+                pos,
+            )
+            .into());
+        };
+        let name = file.display().to_string();
+
+        {
+            let mut state = self.state.lock();
+            if state.loading.contains(&file) {
+                return Err(
+                    EvalAltResult::ErrorInModule(name, "Circular import".into(), pos).into(),
+                );
+            }
+            state.loading.push(file.clone());
+            if !state.imported.contains(&file) {
+                state.imported.push(file.clone());
+            }
+        }
+
+        let result = load_module(engine, &file, &self.rand);
+        self.state.lock().loading.retain(|f| f != &file);
+
+        result.map_err(|err| EvalAltResult::ErrorInModule(name, err, pos).into())
+    }
+}
+
+/// Compile and run a script file as a module
+fn load_module(
+    engine: &Engine,
+    file: &Path,
+    rand: &Mutex<RandState>,
+) -> Result<Shared<Module>, Box<EvalAltResult>> {
+    let mut script = std::fs::read_to_string(file).map_err(|err| {
+        EvalAltResult::ErrorSystem(
+            format!("Cannot read script file '{}'", file.display()),
+            err.into(),
+        )
+    })?;
+    // Blank out a shebang line as `compile_file` does, keeping line numbers
+    if script.starts_with("#!") {
+        script.drain(..script.find('\n').unwrap_or(script.len()));
+    }
+    let mut ast = engine.compile(&script)?;
+    // Nested imports resolve against this file, errors name it, and its
+    // random streams are keyed by it
+    let source = file.to_string_lossy();
+    ast.set_source(source.as_ref());
+    rand.lock().add_import(&source, &script);
+    let module = Module::eval_ast_as_new(Scope::new(), &ast, engine)?;
+    Ok(module.into())
+}
+
+/// Find the file an import path refers to
+///
+/// Absolute paths are used as they are; relative ones are tried in each
+/// directory in turn. `.rhai` is appended unless the path already ends with
+/// it, so dotted names like `parts/bolt.v2` still find `bolt.v2.rhai`.
+fn find_module(path: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    let mut relative = PathBuf::from(path);
+    if relative.extension().is_none_or(|ext| ext != "rhai") {
+        let mut file = relative.into_os_string();
+        file.push(".rhai");
+        relative = PathBuf::from(file);
+    }
+
+    if relative.is_absolute() {
+        return relative.is_file().then(|| canonical(&relative));
+    }
+
+    dirs.iter()
+        .map(|dir| dir.join(&relative))
+        .find(|candidate| candidate.is_file())
+        .map(|file| canonical(&file))
+}
+
+/// Absolute form of a path, so the same file is recorded once however it
+/// was reached
+pub(crate) fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
@@ -37,6 +37,65 @@
 //! sphere(0.5)
 //! ```
 //!
+//! ## Textures and PBR Materials
+//!
+//! Procedural textures can drive a PBR material that is embedded in glTF/GLB
+//! exports:
+//!
+//! ```rhai
+//! let rust = worley(6.0).colorize(0.35, 0.15, 0.05, 0.6, 0.3, 0.1);
+//! set_material(pbr().albedo(rust).roughness(0.8).metallic(0.3));
+//!
+//! box3(0.5, 0.5, 0.5)
+//! ```
+//!
+//! ## Imports
+//!
+//! Scripts can share parts by importing other scripts. Paths are resolved
+//! next to the importing script, then in each directory added with
+//! [`ScriptEngine::add_library_path`]; the `.rhai` extension is optional:
+//!
+//! ```rhai
+//! import "parts/bolt" as bolt;
+//!
+//! bolt::hex_bolt(0.1).translate_y(0.5)
+//! ```
+//!
+//! ## Parameters
+//!
+//! `param()` declares a named, ranged value, so one script can produce many
+//! variants. Hosts list the declared parameters and override them with
+//! [`ScriptEngine::set_param`]:
+//!
+//! ```rhai
+//! let radius = param("radius", 0.5, 0.1, 1.0);
+//! let staves = param("staves", 12, 6, 24);
+//!
+//! cylinder(radius, 1.2).subtract(box3(0.01, 1.3, 0.05).translate_x(radius).repeat_polar(staves))
+//! ```
+//!
+//! ## Random Numbers
+//!
+//! `rand()`, `rand_range(a, b)` and `seed(n)` give deterministic random
+//! numbers for procedural variation. Each call site has its own stream, and
+//! the host picks the seed with [`ScriptEngine::set_seed`], so the same seed
+//! always builds the same shape:
+//!
+//! ```rhai
+//! let bolts = rand_range(3, 6);
+//! cylinder(0.5, 0.2).subtract(cylinder(0.05, 0.3).translate_x(0.35).repeat_polar(bolts))
+//! ```
+//!
+//! ## Animation
+//!
+//! `time` (seconds) and `frame` are variables set by the host with
+//! [`ScriptEngine::set_time`], so one script can build every frame of a
+//! turntable or animation:
+//!
+//! ```rhai
+//! box3(0.5, 0.1, 0.5).twist(time * 2.0)
+//! ```
+//!
 //! ## Precision Notes
 //!
 //! Rhai scripts use `f64` for numeric literals, but all values are
@@ -44,24 +103,32 @@
 //! required for GPU shader compatibility. For most use cases,
 //! the precision loss is negligible.
 
+pub mod bounds;
 pub mod cpu_eval;
 pub mod engine;
 pub mod env_api;
+pub mod imports;
+pub mod param_api;
+pub mod rand_api;
 pub mod sdf_api;
+pub mod texture_api;
+pub mod time_api;
 
 #[cfg(feature = "file-watcher")]
 pub mod watcher;
 
-pub use cpu_eval::CpuSdf;
+pub use cpu_eval::{CpuSdf, SurfaceMaterial};
 pub use engine::{SceneResult, ScriptEngine};
 pub use env_api::{get_current_environment, register_env_api, reset_environment};
-pub use sdf_api::{RhaiSdf, register_sdf_api};
+pub use param_api::{ParamValue, ScriptParam, parse_param_override};
+pub use sdf_api::{RhaiPath, RhaiSdf, RhaiSdf2d, register_sdf_api};
+pub use texture_api::{RhaiMaterial, RhaiTexture, register_texture_api};
 
 #[cfg(feature = "file-watcher")]
 pub use watcher::{ScriptWatcher, WatchEvent};
 
 // Re-export for convenience
-pub use soyuz_sdf::{Environment, SdfOp};
+pub use soyuz_sdf::{Environment, MaterialTag, SdfOp};
 
 // Re-export soyuz_core Sdf trait for users who need CPU evaluation
 pub use soyuz_core::sdf::Sdf;
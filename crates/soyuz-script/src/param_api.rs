@@ -0,0 +1,386 @@
+//! Rhai API for declared script parameters
+//!
+//! `param()` declares a named, ranged value the script is built from, so one
+//! script can produce many variants:
+//!
+//! ```rhai
+//! let radius = param("radius", 0.5, 0.1, 1.0);   // float in 0.1..=1.0
+//! let staves = param("staves", 12, 6, 24);       // integer in 6..=24
+//! let banded = param("banded", true);            // on/off
+//!
+//! let barrel = cylinder(radius, 1.2)
+//!     .subtract(box3(0.01, 1.3, 0.05).translate_x(radius).repeat_polar(staves));
+//! if banded { barrel.union(torus(radius, 0.03)) } else { barrel }
+//! ```
+//!
+//! Each call returns the parameter's default, unless the host set an
+//! override with [`ScriptEngine::set_param`](crate::ScriptEngine::set_param).
+//! The type comes from the arguments: all integers make an integer
+//! parameter, any float makes a float one.
+
+use parking_lot::Mutex;
+use rhai::{Dynamic, Engine, EvalAltResult};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Value of a script parameter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamValue {
+    Float(f64),
+    Int(i64),
+    Bool(bool),
+}
+
+impl ParamValue {
+    /// Parse a value as typed on a command line: `true`/`false`, an
+    /// integer, or a float
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        match text {
+            "true" => Some(Self::Bool(true)),
+            "false" => Some(Self::Bool(false)),
+            _ => text.parse::<i64>().map(Self::Int).ok().or_else(|| {
+                text.parse::<f64>()
+                    .ok()
+                    .filter(|v| v.is_finite())
+                    .map(Self::Float)
+            }),
+        }
+    }
+
+    /// Name of the value's type, as shown to users
+    pub fn type_name(self) -> &'static str {
+        match self {
+            Self::Float(_) => "float",
+            Self::Int(_) => "int",
+            Self::Bool(_) => "bool",
+        }
+    }
+
+    /// The value as a number (`bool`s are 0 or 1)
+    #[allow(clippy::cast_precision_loss)]
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Self::Float(v) => v,
+            Self::Int(v) => v as f64,
+            Self::Bool(v) => f64::from(u8::from(v)),
+        }
+    }
+
+    fn to_dynamic(self) -> Dynamic {
+        match self {
+            Self::Float(v) => Dynamic::from_float(v),
+            Self::Int(v) => Dynamic::from_int(v),
+            Self::Bool(v) => Dynamic::from_bool(v),
+        }
+    }
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Float(v) => write!(f, "{v}"),
+            Self::Int(v) => write!(f, "{v}"),
+            Self::Bool(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+impl From<f64> for ParamValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<i64> for ParamValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<bool> for ParamValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+/// Parse a `name=value` override, as passed to `--param`
+pub fn parse_param_override(text: &str) -> Result<(String, ParamValue), String> {
+    let (name, value) = text
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=VALUE, got '{text}'"))?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(format!("missing parameter name in '{text}'"));
+    }
+    let value = ParamValue::parse(value)
+        .ok_or_else(|| format!("'{}' is not a number or true/false", value.trim()))?;
+    Ok((name.to_string(), value))
+}
+
+/// A parameter declared by a script with `param()`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptParam {
+    /// Name passed to `param()`
+    pub name: String,
+    /// Value used when there is no override; its type is the parameter's
+    pub default: ParamValue,
+    /// Smallest allowed value (`false` for bools)
+    pub min: ParamValue,
+    /// Largest allowed value (`true` for bools)
+    pub max: ParamValue,
+    /// Value the script received: the override if one was set, otherwise
+    /// the default
+    pub value: ParamValue,
+}
+
+impl ScriptParam {
+    /// Convert an override to this parameter's type, checking its range
+    ///
+    /// Integers are accepted for float parameters, and whole floats for
+    /// integer ones.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn accept(&self, value: ParamValue) -> Result<ParamValue, String> {
+        let converted = match (self.default, value) {
+            (ParamValue::Float(_), ParamValue::Float(_) | ParamValue::Int(_)) => {
+                ParamValue::Float(value.as_f64())
+            }
+            (ParamValue::Int(_), ParamValue::Int(_))
+            | (ParamValue::Bool(_), ParamValue::Bool(_)) => value,
+            (ParamValue::Int(_), ParamValue::Float(v)) if v.fract() == 0.0 => {
+                ParamValue::Int(v as i64)
+            }
+            _ => {
+                return Err(format!(
+                    "parameter '{}' is a {}, got {value}",
+                    self.name,
+                    self.default.type_name()
+                ));
+            }
+        };
+
+        if !self.contains(converted) {
+            return Err(format!(
+                "parameter '{}' must be in {}..={}, got {value}",
+                self.name, self.min, self.max
+            ));
+        }
+        Ok(converted)
+    }
+
+    fn contains(&self, value: ParamValue) -> bool {
+        (self.min.as_f64()..=self.max.as_f64()).contains(&value.as_f64())
+    }
+
+    fn same_declaration(&self, other: &Self) -> bool {
+        self.default == other.default && self.min == other.min && self.max == other.max
+    }
+}
+
+/// Overrides set by the host and the parameters the current evaluation
+/// declared
+#[derive(Debug, Default)]
+pub(crate) struct ParamState {
+    pub overrides: BTreeMap<String, ParamValue>,
+    pub declared: Vec<ScriptParam>,
+}
+
+impl ParamState {
+    /// Forget the parameters declared by the previous evaluation
+    pub fn reset(&mut self) {
+        self.declared.clear();
+    }
+
+    /// Check that every override named a parameter the script declared
+    pub fn check_overrides(&self) -> Result<(), String> {
+        let unknown: Vec<&str> = self
+            .overrides
+            .keys()
+            .filter(|name| !self.declared.iter().any(|p| &p.name == *name))
+            .map(String::as_str)
+            .collect();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+
+        let declared: Vec<&str> = self.declared.iter().map(|p| p.name.as_str()).collect();
+        let declared = if declared.is_empty() {
+            "the script declares none".to_string()
+        } else {
+            format!("the script declares {}", declared.join(", "))
+        };
+        Err(format!(
+            "unknown parameter {} ({declared})",
+            unknown
+                .iter()
+                .map(|name| format!("'{name}'"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    }
+
+    /// Record a declaration and return the value the script should use
+    fn declare(&mut self, mut param: ScriptParam) -> Result<ParamValue, String> {
+        if param.min.as_f64() > param.max.as_f64() {
+            return Err(format!(
+                "param '{}': min {} is greater than max {}",
+                param.name, param.min, param.max
+            ));
+        }
+        if !param.contains(param.default) {
+            return Err(format!(
+                "param '{}': default {} is outside {}..={}",
+                param.name, param.default, param.min, param.max
+            ));
+        }
+
+        // Declaring the same parameter again (say, from a loop) is fine as
+        // long as it means the same thing
+        if let Some(existing) = self.declared.iter().find(|p| p.name == param.name) {
+            if existing.same_declaration(&param) {
+                return Ok(existing.value);
+            }
+            return Err(format!(
+                "param '{}' is declared twice with different settings",
+                param.name
+            ));
+        }
+
+        if let Some(&value) = self.overrides.get(&param.name) {
+            param.value = param.accept(value)?;
+        }
+        let value = param.value;
+        self.declared.push(param);
+        Ok(value)
+    }
+}
+
+/// Read an integer or float argument
+fn number(value: &Dynamic) -> Option<ParamValue> {
+    if let Ok(v) = value.as_int() {
+        Some(ParamValue::Int(v))
+    } else {
+        value.as_float().ok().map(ParamValue::Float)
+    }
+}
+
+/// `param(name, default, min, max)` for numbers
+fn declare_number(
+    state: &Mutex<ParamState>,
+    name: &str,
+    default: &Dynamic,
+    min: &Dynamic,
+    max: &Dynamic,
+) -> Result<ParamValue, String> {
+    let (Some(default), Some(min), Some(max)) = (number(default), number(min), number(max)) else {
+        return Err(format!(
+            "param '{name}': default, min and max must be numbers"
+        ));
+    };
+
+    // All integers make an integer parameter; anything else is a float one
+    let (default, min, max) = match (default, min, max) {
+        (ParamValue::Int(_), ParamValue::Int(_), ParamValue::Int(_)) => (default, min, max),
+        _ => (
+            ParamValue::Float(default.as_f64()),
+            ParamValue::Float(min.as_f64()),
+            ParamValue::Float(max.as_f64()),
+        ),
+    };
+
+    state.lock().declare(ScriptParam {
+        name: name.to_string(),
+        default,
+        min,
+        max,
+        value: default,
+    })
+}
+
+/// Register `param()` with the Rhai engine
+///
+/// Declarations and overrides live in `state`, which the owning
+/// [`ScriptEngine`](crate::ScriptEngine) resets before each evaluation.
+pub(crate) fn register_param_api(engine: &mut Engine, state: &Arc<Mutex<ParamState>>) {
+    let numbers = state.clone();
+    engine.register_fn(
+        "param",
+        move |name: &str,
+              default: Dynamic,
+              min: Dynamic,
+              max: Dynamic|
+              -> Result<Dynamic, Box<EvalAltResult>> {
+            declare_number(&numbers, name, &default, &min, &max)
+                .map(ParamValue::to_dynamic)
+                .map_err(Into::into)
+        },
+    );
+
+    let bools = state.clone();
+    engine.register_fn(
+        "param",
+        move |name: &str, default: bool| -> Result<bool, Box<EvalAltResult>> {
+            let value = bools.lock().declare(ScriptParam {
+                name: name.to_string(),
+                default: ParamValue::Bool(default),
+                min: ParamValue::Bool(false),
+                max: ParamValue::Bool(true),
+                value: ParamValue::Bool(default),
+            })?;
+            Ok(matches!(value, ParamValue::Bool(true)))
+        },
+    );
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(ParamValue::parse("true"), Some(ParamValue::Bool(true)));
+        assert_eq!(ParamValue::parse(" 12 "), Some(ParamValue::Int(12)));
+        assert_eq!(ParamValue::parse("0.25"), Some(ParamValue::Float(0.25)));
+        assert_eq!(ParamValue::parse("-1e-2"), Some(ParamValue::Float(-0.01)));
+        assert_eq!(ParamValue::parse("inf"), None);
+        assert_eq!(ParamValue::parse("big"), None);
+    }
+
+    #[test]
+    fn test_parse_override() {
+        let (name, value) = parse_param_override("radius=0.75").expect("valid override");
+        assert_eq!(name, "radius");
+        assert_eq!(value, ParamValue::Float(0.75));
+
+        assert!(parse_param_override("radius").is_err());
+        assert!(parse_param_override("=1").is_err());
+        assert!(parse_param_override("radius=wide").is_err());
+    }
+
+    #[test]
+    fn test_accept_converts_and_checks_range() {
+        let float = ScriptParam {
+            name: "radius".to_string(),
+            default: ParamValue::Float(0.5),
+            min: ParamValue::Float(0.0),
+            max: ParamValue::Float(1.0),
+            value: ParamValue::Float(0.5),
+        };
+        assert_eq!(float.accept(ParamValue::Int(1)), Ok(ParamValue::Float(1.0)));
+        assert!(float.accept(ParamValue::Float(1.5)).is_err());
+        assert!(float.accept(ParamValue::Bool(true)).is_err());
+
+        let int = ScriptParam {
+            name: "count".to_string(),
+            default: ParamValue::Int(4),
+            min: ParamValue::Int(1),
+            max: ParamValue::Int(8),
+            value: ParamValue::Int(4),
+        };
+        assert_eq!(int.accept(ParamValue::Float(6.0)), Ok(ParamValue::Int(6)));
+        assert!(int.accept(ParamValue::Float(6.5)).is_err());
+        assert!(int.accept(ParamValue::Int(0)).is_err());
+    }
+}
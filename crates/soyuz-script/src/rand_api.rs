@@ -0,0 +1,558 @@
+//! Rhai API for deterministic random numbers
+//!
+//! Scripts draw random values for procedural variation: dents, bolt
+//! placement, rock shapes.
+//!
+//! ```rhai
+//! let rock = sphere(1.0);
+//! for i in 0..12 {
+//!     let dent = sphere(rand_range("dent size", 0.1, 0.3))
+//!         .translate(rand_range(-1.0, 1.0), rand_range(-1.0, 1.0), rand_range(-1.0, 1.0));
+//!     rock = rock.smooth_subtract(dent, 0.05);
+//! }
+//! rock
+//! ```
+//!
+//! - `rand()` - float in `0.0..1.0`
+//! - `rand_range(a, b)` - float in `a..b`, or integer in `a..=b` when both
+//!   bounds are integers
+//! - `rand(name)`, `rand_range(name, a, b)` - the same, drawn from the
+//!   stream called `name`
+//! - `seed(n)` - restart every stream from seed `n`, ignoring the host's seed
+//!
+//! Numbers are a pure function of the seed and the stream, and each stream
+//! advances once per call. The same seed always gives the same numbers.
+//! Named calls draw from the stream with their name, shared by every call
+//! with that name in the same file. Unnamed calls get a stream each, keyed
+//! by the function they're in and their place among that function's unnamed
+//! calls (top-level code counts as one function). Editing code around a call
+//! keeps its values; adding or removing an unnamed call earlier in the same
+//! function renumbers the calls after it, which names avoid. Imported files
+//! are keyed by their canonical path. The host picks the seed with
+//! [`ScriptEngine::set_seed`](crate::ScriptEngine::set_seed); it defaults to
+//! 0.
+
+use parking_lot::Mutex;
+use rhai::{EvalAltResult, NativeCallContext, Position};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Functions whose unnamed calls are numbered, with the number of arguments
+/// an unnamed call takes (a named one takes the name first)
+const RAND_FUNCTIONS: [(&str, usize); 2] = [("rand", 0), ("rand_range", 2)];
+
+/// Where an unnamed call sits: its enclosing function (empty for top-level
+/// code) and its index among that function's unnamed calls
+type CallSlot = (String, usize);
+
+/// Seed and per-stream positions for one evaluation
+#[derive(Debug, Default)]
+pub(crate) struct RandState {
+    /// Seed set by the host, used at the start of every evaluation
+    pub seed: u64,
+    /// Seed the current evaluation draws from (changed by `seed()`)
+    current: u64,
+    /// Source of the main script, whose streams aren't tagged with a file
+    main_source: Option<String>,
+    /// Unnamed calls by line and column, for each file (empty for the main
+    /// script)
+    slots: HashMap<String, HashMap<(usize, usize), CallSlot>>,
+    /// Values drawn so far from each stream
+    streams: HashMap<String, u64>,
+}
+
+/// Which stream a call draws from
+enum Stream<'a> {
+    /// `rand(name)` and friends
+    Named(&'a str),
+    /// An unnamed call at this position
+    Call(Position),
+}
+
+impl RandState {
+    /// Start a new evaluation of `script`, which came from `main_source`
+    pub fn reset(&mut self, main_source: Option<String>, script: &str) {
+        self.current = self.seed;
+        self.main_source = main_source;
+        self.slots.clear();
+        self.slots.insert(String::new(), call_slots(script));
+        self.streams.clear();
+    }
+
+    /// Number the unnamed calls of an imported file at `source`
+    pub fn add_import(&mut self, source: &str, script: &str) {
+        self.slots.insert(source.to_string(), call_slots(script));
+    }
+
+    /// Restart every stream from `seed`
+    fn reseed(&mut self, seed: u64) {
+        self.current = seed;
+        self.streams.clear();
+    }
+
+    /// Next 64 random bits from `stream` of the file at `source`
+    fn next(&mut self, source: Option<&str>, stream: &Stream) -> u64 {
+        let file = match source {
+            Some(source) if Some(source) != self.main_source.as_deref() => source,
+            _ => "",
+        };
+        let key = match *stream {
+            Stream::Named(name) => format!("{file}\0name\0{name}"),
+            Stream::Call(position) => {
+                let line = position.line().unwrap_or(0);
+                let column = position.position().unwrap_or(0);
+                // Calls the scan missed fall back to their position
+                let slot = self
+                    .slots
+                    .get(file)
+                    .and_then(|slots| slots.get(&(line, column)));
+                match slot {
+                    Some((function, index)) => format!("{file}\0call\0{function}\0{index}"),
+                    None => format!("{file}\0at\0{line}:{column}"),
+                }
+            }
+        };
+
+        let stream_hash = fnv1a(key.as_bytes());
+        let counter = self.streams.entry(key).or_insert(0);
+        let index = *counter;
+        *counter += 1;
+
+        mix(mix(mix(self.current) ^ stream_hash) ^ index)
+    }
+}
+
+/// Find the unnamed `rand()` and `rand_range()` calls in `script`, keyed by
+/// the line and column Rhai reports for them
+///
+/// This is a token scan rather than a parse: it skips comments and string
+/// literals, scans the code in `${...}` interpolations, tracks which `fn`
+/// body each brace belongs to, and tells unnamed calls from named ones by
+/// their number of arguments, whatever the name expression. Positions
+/// match Rhai's with `\n` and `\r\n` line endings alike. The scan doesn't
+/// resolve names, so a script function called `rand` has its calls numbered
+/// too; that only shifts the numbers of the calls after it, the same way on
+/// every run. A call the scan misses falls back to a stream keyed by its
+/// line and column.
+fn call_slots(script: &str) -> HashMap<(usize, usize), CallSlot> {
+    let chars: Vec<char> = script.chars().collect();
+    let mut slots = HashMap::new();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    // Functions whose bodies are open, with the brace depth outside them
+    let mut functions: Vec<(String, usize)> = Vec::new();
+    let mut pending_fn: Option<String> = None;
+    let mut after_fn = false;
+    let mut depth = 0;
+    // Inside the text of a backtick string, and the brace depths at which
+    // each open `${` interpolation returns to its string
+    let mut in_template = false;
+    let mut interpolations: Vec<usize> = Vec::new();
+    let (mut line, mut column) = (1, 1);
+    let mut i = 0;
+
+    // Move past `count` characters, keeping track of the position
+    let advance = |i: &mut usize, line: &mut usize, column: &mut usize, count: usize| {
+        for _ in 0..count {
+            if chars.get(*i) == Some(&'\n') {
+                *line += 1;
+                *column = 1;
+            } else {
+                *column += 1;
+            }
+            *i += 1;
+        }
+    };
+
+    while let Some(&c) = chars.get(i) {
+        let next = chars.get(i + 1).copied();
+        if in_template {
+            let (len, interpolates) = template_text_len(&chars[i..]);
+            in_template = false;
+            if interpolates {
+                interpolations.push(depth);
+                depth += 1;
+            }
+            advance(&mut i, &mut line, &mut column, len);
+            continue;
+        }
+        match c {
+            '/' if next == Some('/') => {
+                let len = chars[i..].iter().take_while(|&&c| c != '\n').count();
+                advance(&mut i, &mut line, &mut column, len);
+            }
+            '/' if next == Some('*') => {
+                let len = block_comment_len(&chars[i..]);
+                advance(&mut i, &mut line, &mut column, len);
+            }
+            '`' => {
+                in_template = true;
+                advance(&mut i, &mut line, &mut column, 1);
+            }
+            '"' | '\'' => {
+                let len = quoted_len(&chars[i..]);
+                advance(&mut i, &mut line, &mut column, len);
+            }
+            '{' => {
+                if let Some(name) = pending_fn.take() {
+                    functions.push((name, depth));
+                }
+                depth += 1;
+                advance(&mut i, &mut line, &mut column, 1);
+            }
+            '}' => {
+                depth = depth.saturating_sub(1);
+                if interpolations.last() == Some(&depth) {
+                    interpolations.pop();
+                    in_template = true;
+                } else if functions.last().is_some_and(|(_, outer)| *outer == depth) {
+                    functions.pop();
+                }
+                advance(&mut i, &mut line, &mut column, 1);
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let word: String = chars[i..]
+                    .iter()
+                    .take_while(|c| c.is_alphanumeric() || **c == '_')
+                    .collect();
+                let len = word.chars().count();
+                if after_fn {
+                    pending_fn = Some(word.clone());
+                    after_fn = false;
+                } else if word == "fn" {
+                    after_fn = true;
+                } else if RAND_FUNCTIONS.iter().any(|&(name, unnamed)| {
+                    name == word && argument_count(&chars[i + len..]) == Some(unnamed)
+                }) {
+                    let function = functions.last().map_or("", |(name, _)| name);
+                    let count = counts.entry(function.to_string()).or_insert(0);
+                    slots.insert((line, column), (function.to_string(), *count));
+                    *count += 1;
+                }
+                advance(&mut i, &mut line, &mut column, len);
+            }
+            _ => advance(&mut i, &mut line, &mut column, 1),
+        }
+    }
+    slots
+}
+
+/// Number of arguments of the call whose argument list `chars` starts with,
+/// after any whitespace, or `None` if there's no argument list
+fn argument_count(chars: &[char]) -> Option<usize> {
+    let start = chars.iter().position(|c| !c.is_whitespace())?;
+    if chars[start] != '(' {
+        return None;
+    }
+    let mut count = 0;
+    // Whether the argument being scanned has any code yet, so a trailing
+    // comma doesn't count as another argument
+    let mut in_argument = false;
+    let mut depth = 0;
+    let mut len = start + 1;
+    while let Some(&c) = chars.get(len) {
+        let next = chars.get(len + 1).copied();
+        match c {
+            '/' if next == Some('/') => {
+                len += chars[len..].iter().take_while(|&&c| c != '\n').count();
+                continue;
+            }
+            '/' if next == Some('*') => {
+                len += block_comment_len(&chars[len..]);
+                continue;
+            }
+            '"' | '\'' | '`' => {
+                in_argument = true;
+                len += quoted_len(&chars[len..]);
+                continue;
+            }
+            ')' | ']' | '}' if depth == 0 => return Some(count + usize::from(in_argument)),
+            ')' | ']' | '}' => depth -= 1,
+            '(' | '[' | '{' => depth += 1,
+            ',' if depth == 0 => {
+                count += 1;
+                in_argument = false;
+                len += 1;
+                continue;
+            }
+            _ => {}
+        }
+        in_argument |= !c.is_whitespace();
+        len += 1;
+    }
+    None
+}
+
+/// Length of the block comment `chars` starts with; block comments nest
+fn block_comment_len(chars: &[char]) -> usize {
+    let mut nesting = 0;
+    let mut len = 0;
+    while let Some(&c) = chars.get(len) {
+        let next = chars.get(len + 1).copied();
+        if c == '/' && next == Some('*') {
+            nesting += 1;
+            len += 2;
+        } else if c == '*' && next == Some('/') {
+            nesting -= 1;
+            len += 2;
+            if nesting == 0 {
+                break;
+            }
+        } else {
+            len += 1;
+        }
+    }
+    len
+}
+
+/// Length of the string or character literal `chars` starts with, including
+/// its quotes
+fn quoted_len(chars: &[char]) -> usize {
+    let quote = chars.first().copied();
+    let mut len = 1;
+    while let Some(&inner) = chars.get(len) {
+        len += if inner == '\\' { 2 } else { 1 };
+        if Some(inner) == quote {
+            break;
+        }
+    }
+    len
+}
+
+/// Length of the backtick string text `chars` starts with, through its
+/// closing backtick or the `${` of an interpolation, and whether it ended at
+/// an interpolation
+fn template_text_len(chars: &[char]) -> (usize, bool) {
+    let mut len = 0;
+    while let Some(&c) = chars.get(len) {
+        match c {
+            '\\' => len += 2,
+            '`' => return (len + 1, false),
+            '$' if chars.get(len + 1) == Some(&'{') => return (len + 2, true),
+            _ => len += 1,
+        }
+    }
+    (chars.len(), false)
+}
+
+/// FNV-1a hash; unlike std's hasher it's stable across Rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// `SplitMix64` output function, which scrambles nearby inputs into
+/// unrelated outputs
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Float in `0.0..1.0` from the top 53 bits
+#[allow(clippy::cast_precision_loss)]
+fn unit_float(bits: u64) -> f64 {
+    (bits >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+/// Float in `min..max`
+fn float_in(bits: u64, min: f64, max: f64) -> f64 {
+    min + (max - min) * unit_float(bits)
+}
+
+/// Reject an integer range with no values in it
+fn check_int_range(min: i64, max: i64) -> Result<(), Box<EvalAltResult>> {
+    if min > max {
+        return Err(format!("rand_range: min {min} is greater than max {max}").into());
+    }
+    Ok(())
+}
+
+/// Integer in `min..=max`
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn int_in(bits: u64, min: i64, max: i64) -> i64 {
+    // Width of the range minus one; the full i64 range wraps to u64::MAX
+    let span = max.wrapping_sub(min) as u64;
+    let Some(count) = span.checked_add(1) else {
+        return bits.cast_signed();
+    };
+    let offset = ((u128::from(bits) * u128::from(count)) >> 64) as u64;
+    min.wrapping_add(offset.cast_signed())
+}
+
+/// Register `rand()`, `rand_range()` and `seed()` with the Rhai engine
+///
+/// Stream positions live in `state`, which the owning
+/// [`ScriptEngine`](crate::ScriptEngine) resets before each evaluation.
+pub(crate) fn register_rand_api(engine: &mut rhai::Engine, state: &Arc<Mutex<RandState>>) {
+    let unit = state.clone();
+    engine.register_fn("rand", move |ctx: NativeCallContext| -> f64 {
+        let stream = Stream::Call(ctx.call_position());
+        unit_float(unit.lock().next(ctx.call_source(), &stream))
+    });
+
+    let named_unit = state.clone();
+    engine.register_fn("rand", move |ctx: NativeCallContext, name: &str| -> f64 {
+        let bits = named_unit
+            .lock()
+            .next(ctx.call_source(), &Stream::Named(name));
+        unit_float(bits)
+    });
+
+    let floats = state.clone();
+    engine.register_fn(
+        "rand_range",
+        move |ctx: NativeCallContext, min: f64, max: f64| -> f64 {
+            let stream = Stream::Call(ctx.call_position());
+            let bits = floats.lock().next(ctx.call_source(), &stream);
+            float_in(bits, min, max)
+        },
+    );
+
+    let named_floats = state.clone();
+    engine.register_fn(
+        "rand_range",
+        move |ctx: NativeCallContext, name: &str, min: f64, max: f64| -> f64 {
+            let bits = named_floats
+                .lock()
+                .next(ctx.call_source(), &Stream::Named(name));
+            float_in(bits, min, max)
+        },
+    );
+
+    let ints = state.clone();
+    engine.register_fn(
+        "rand_range",
+        move |ctx: NativeCallContext, min: i64, max: i64| -> Result<i64, Box<EvalAltResult>> {
+            check_int_range(min, max)?;
+            let stream = Stream::Call(ctx.call_position());
+            let bits = ints.lock().next(ctx.call_source(), &stream);
+            Ok(int_in(bits, min, max))
+        },
+    );
+
+    let named_ints = state.clone();
+    engine.register_fn(
+        "rand_range",
+        move |ctx: NativeCallContext,
+              name: &str,
+              min: i64,
+              max: i64|
+              -> Result<i64, Box<EvalAltResult>> {
+            check_int_range(min, max)?;
+            let bits = named_ints
+                .lock()
+                .next(ctx.call_source(), &Stream::Named(name));
+            Ok(int_in(bits, min, max))
+        },
+    );
+
+    let seeds = state.clone();
+    engine.register_fn("seed", move |seed: i64| {
+        seeds.lock().reseed(seed.cast_unsigned());
+    });
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streams_are_per_call() {
+        let mut state = RandState::default();
+        state.reset(None, "let a = rand();\nlet b = rand();");
+        let a = Stream::Call(Position::new(1, 9));
+        let b = Stream::Call(Position::new(2, 9));
+
+        let first = state.next(None, &a);
+        let second = state.next(None, &a);
+        let other = state.next(None, &b);
+        assert_ne!(first, second);
+        assert_ne!(first, other);
+
+        // Same seed, same numbers, whatever order the streams are drawn in
+        state.reset(None, "let a = rand();\nlet b = rand();");
+        assert_eq!(state.next(None, &b), other);
+        assert_eq!(state.next(None, &a), first);
+        assert_eq!(state.next(None, &a), second);
+
+        // Named streams are shared by name, and separate per file
+        let named = state.next(None, &Stream::Named("dent"));
+        assert_ne!(state.next(None, &Stream::Named("dent")), named);
+        state.reset(None, "");
+        assert_eq!(state.next(None, &Stream::Named("dent")), named);
+        assert_ne!(
+            state.next(Some("/lib/a.rhai"), &Stream::Named("dent")),
+            named
+        );
+
+        state.seed = 1;
+        state.reset(None, "let a = rand();\nlet b = rand();");
+        assert_ne!(state.next(None, &a), first);
+    }
+
+    #[test]
+    fn test_call_slots() {
+        let script = r#"// rand() in a comment
+let a = rand_range(1, 2); /* rand() */ let s = "rand()";
+fn dent(r) {
+    if r > 0 { sphere(rand()) } else { box3(rand ( ), 1, 1) }
+}
+let b = rand("named") + rand();
+let c = rand(name) + rand_range(name + "x", 1, 2) + rand_range(lo, hi,) + rand_range(f(1, 2), /* , */ 0, 1);
+"#;
+        let slots = call_slots(script);
+        let slot = |line, column| slots.get(&(line, column)).cloned();
+        assert_eq!(slots.len(), 5);
+        assert_eq!(slot(2, 9), Some((String::new(), 0)));
+        assert_eq!(slot(4, 23), Some(("dent".to_string(), 0)));
+        assert_eq!(slot(4, 45), Some(("dent".to_string(), 1)));
+        assert_eq!(slot(6, 25), Some((String::new(), 1)));
+
+        // Names can be any expression; only the argument count tells
+        assert_eq!(slot(7, 53), Some((String::new(), 2)));
+    }
+
+    #[test]
+    fn test_call_slots_match_rhai_positions() {
+        let script = "let a = rand();\r\nlet s = `x ${rand()} y ${ `z${ if true { rand() } else { 0.0 } }` }`;\r\n\
+                      fn rand_pair() { [rand(), rand()] }\r\n/* \r\n */ let b = rand_pair();";
+        let positions = Arc::new(Mutex::new(Vec::new()));
+        let mut engine = rhai::Engine::new();
+        let recorded = positions.clone();
+        engine.register_fn("rand", move |ctx: NativeCallContext| -> f64 {
+            recorded.lock().push(ctx.call_position());
+            0.5
+        });
+        engine.run(script).expect("script should run");
+
+        let slots = call_slots(script);
+        let positions = positions.lock();
+        assert_eq!(positions.len(), 5);
+        assert_eq!(slots.len(), 5);
+        for position in positions.iter() {
+            let key = (
+                position.line().unwrap_or(0),
+                position.position().unwrap_or(0),
+            );
+            assert!(slots.contains_key(&key), "{position:?} not in {slots:?}");
+        }
+        assert_eq!(slots.get(&(2, 42)), Some(&(String::new(), 2)));
+        assert_eq!(slots.get(&(3, 19)), Some(&("rand_pair".to_string(), 0)));
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut state = RandState::default();
+        state.reset(None, "");
+        for column in 1..200 {
+            let bits = state.next(None, &Stream::Call(Position::new(1, column)));
+            assert!((0.0..1.0).contains(&unit_float(bits)));
+            assert!((-3..=3).contains(&int_in(bits, -3, 3)));
+            assert_eq!(int_in(bits, 7, 7), 7);
+        }
+        assert_eq!(int_in(u64::MAX, i64::MIN, i64::MAX), -1);
+    }
+}
@@ -9,10 +9,13 @@
 //! required for GPU shader compatibility. For most use cases,
 //! the precision loss is negligible.
 
-use rhai::{Engine, Module};
-use soyuz_sdf::{ExtrudeProfile, RevolveProfile, SdfOp};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Module};
+use soyuz_core::prelude::{Mat3, Quat, Vec3};
+use soyuz_sdf::{Affine, MaterialTag, Sdf2dOp, SdfOp, SweepCurve, SweepPath};
 use std::sync::Arc;
 
+use crate::env_api::parse_hex_color;
+
 /// SDF node representation for Rhai
 ///
 /// This wrapper holds an `Arc<SdfOp>` for efficient cloning (O(1) reference
@@ -181,6 +184,85 @@ impl RhaiSdf {
         })
     }
 
+    pub fn scale_xyz(&mut self, x: f64, y: f64, z: f64) -> RhaiSdf {
+        RhaiSdf::new(SdfOp::NonUniformScale {
+            inner: Arc::clone(&self.op),
+            factors: [x as f32, y as f32, z as f32],
+        })
+    }
+
+    pub fn rotate(&mut self, ax: f64, ay: f64, az: f64, angle: f64) -> RhaiSdf {
+        let axis = Vec3::new(ax as f32, ay as f32, az as f32).normalize_or_zero();
+        self.rotated(Quat::from_axis_angle(axis, angle as f32))
+    }
+
+    pub fn rotate_quat(&mut self, x: f64, y: f64, z: f64, w: f64) -> RhaiSdf {
+        RhaiSdf::new(SdfOp::Rotate {
+            inner: Arc::clone(&self.op),
+            rotation: [x as f32, y as f32, z as f32, w as f32],
+        })
+    }
+
+    /// Turn the shape so its +Y axis points along a direction
+    pub fn align_to(&mut self, dx: f64, dy: f64, dz: f64) -> RhaiSdf {
+        match Vec3::new(dx as f32, dy as f32, dz as f32).try_normalize() {
+            Some(dir) => self.rotated(Quat::from_rotation_arc(Vec3::Y, dir)),
+            None => self.clone(),
+        }
+    }
+
+    /// Move the shape's origin to `eye` and turn its +Z axis toward
+    /// `target`, keeping its +Y axis as close to straight up as possible
+    pub fn look_at(&mut self, ex: f64, ey: f64, ez: f64, tx: f64, ty: f64, tz: f64) -> RhaiSdf {
+        let eye = Vec3::new(ex as f32, ey as f32, ez as f32);
+        let target = Vec3::new(tx as f32, ty as f32, tz as f32);
+        let mut turned = match (target - eye).try_normalize() {
+            Some(forward) => {
+                // Looking straight up or down, keep +Y toward -Z instead
+                let up = if forward.y.abs() > 0.999 {
+                    -Vec3::Z
+                } else {
+                    Vec3::Y
+                };
+                let side = up.cross(forward).normalize();
+                let rotation = Mat3::from_cols(side, forward.cross(side), forward);
+                self.rotated(Quat::from_mat3(&rotation))
+            }
+            None => self.clone(),
+        };
+        turned.translate(f64::from(eye.x), f64::from(eye.y), f64::from(eye.z))
+    }
+
+    /// Map the shape by a 3x3 matrix given as rows, each optionally ending
+    /// in a translation: `[[a, b, c, tx], [d, e, f, ty], [g, h, i, tz]]`
+    pub fn transform(&mut self, rows: Array) -> Result<RhaiSdf, Box<EvalAltResult>> {
+        let count = rows.len();
+        let rows: [Dynamic; 3] = rows
+            .try_into()
+            .map_err(|_| format!("transform: expected 3 rows, got {count}"))?;
+        let mut parsed = [[0.0; 4]; 3];
+        for (i, (row, out)) in rows.iter().zip(&mut parsed).enumerate() {
+            *out = point_from_dynamic::<4>(row)
+                .or_else(|| point_from_dynamic::<3>(row).map(|[a, b, c]| [a, b, c, 0.0]))
+                .ok_or_else(|| format!("transform: row {i} is not an array of 3 or 4 numbers"))?;
+        }
+        let columns = [0, 1, 2].map(|j| parsed.map(|row| row[j]));
+        let offset = parsed.map(|row| row[3]);
+        let transform = Affine::new(columns, offset)
+            .ok_or("transform: the matrix flattens space and can't be inverted")?;
+        Ok(RhaiSdf::new(SdfOp::Transform {
+            inner: Arc::clone(&self.op),
+            transform,
+        }))
+    }
+
+    fn rotated(&self, rotation: Quat) -> RhaiSdf {
+        RhaiSdf::new(SdfOp::Rotate {
+            inner: Arc::clone(&self.op),
+            rotation: rotation.to_array(),
+        })
+    }
+
     pub fn mirror_x(&mut self) -> RhaiSdf {
         RhaiSdf::new(SdfOp::Mirror {
             inner: Arc::clone(&self.op),
@@ -269,11 +351,55 @@ impl RhaiSdf {
         })
     }
 
-    pub fn repeat_polar(&mut self, count: i64) -> RhaiSdf {
-        RhaiSdf::new(SdfOp::RepeatPolar {
+    pub fn repeat_polar(&mut self, count: i64) -> Result<RhaiSdf, Box<EvalAltResult>> {
+        Ok(RhaiSdf::new(SdfOp::RepeatPolar {
             inner: Arc::clone(&self.op),
-            count: count as u32,
-        })
+            count: polar_count(count)?,
+        }))
+    }
+
+    // === Materials ===
+
+    pub fn material(&mut self, id: i64) -> RhaiSdf {
+        let (inner, tag) = self.split_material();
+        RhaiSdf::tagged(
+            inner,
+            MaterialTag {
+                id: id.max(0) as u32,
+                color: tag.color,
+            },
+        )
+    }
+
+    pub fn color(&mut self, r: f64, g: f64, b: f64) -> RhaiSdf {
+        let (inner, tag) = self.split_material();
+        RhaiSdf::tagged(
+            inner,
+            MaterialTag {
+                id: tag.id,
+                color: Some([r as f32, g as f32, b as f32]),
+            },
+        )
+    }
+
+    pub fn color_hex(&mut self, hex: &str) -> RhaiSdf {
+        match parse_hex_color(hex) {
+            Some((r, g, b)) => self.color(f64::from(r), f64::from(g), f64::from(b)),
+            None => self.clone(),
+        }
+    }
+
+    /// Split off an outer material tag so chained `.material(id).color(r, g, b)`
+    /// calls produce a single node instead of nesting
+    fn split_material(&self) -> (Arc<SdfOp>, MaterialTag) {
+        match self.op.as_ref() {
+            SdfOp::Material { inner, material } => (Arc::clone(inner), *material),
+            _ => (Arc::clone(&self.op), MaterialTag::DEFAULT),
+        }
+    }
+
+    fn tagged(inner: Arc<SdfOp>, material: MaterialTag) -> RhaiSdf {
+        RhaiSdf::new(SdfOp::Material { inner, material })
     }
 }
 
@@ -381,57 +507,499 @@ pub fn link(length: f64, major_radius: f64, minor_radius: f64) -> RhaiSdf {
     })
 }
 
+pub fn capped_cone(bottom_radius: f64, top_radius: f64, height: f64) -> RhaiSdf {
+    RhaiSdf::new(SdfOp::CappedCone {
+        half_height: (height / 2.0) as f32,
+        bottom_radius: bottom_radius as f32,
+        top_radius: top_radius as f32,
+    })
+}
+
+pub fn round_cone(bottom_radius: f64, top_radius: f64, height: f64) -> RhaiSdf {
+    RhaiSdf::new(SdfOp::RoundCone {
+        bottom_radius: bottom_radius as f32,
+        top_radius: top_radius as f32,
+        height: height as f32,
+    })
+}
+
+pub fn box_frame(width: f64, height: f64, depth: f64, thickness: f64) -> RhaiSdf {
+    RhaiSdf::new(SdfOp::BoxFrame {
+        half_extents: [
+            (width / 2.0) as f32,
+            (height / 2.0) as f32,
+            (depth / 2.0) as f32,
+        ],
+        half_thickness: (thickness / 2.0) as f32,
+    })
+}
+
+pub fn capped_torus(major_radius: f64, minor_radius: f64, angle: f64) -> RhaiSdf {
+    RhaiSdf::new(SdfOp::CappedTorus {
+        half_angle: angle as f32,
+        major_radius: major_radius as f32,
+        minor_radius: minor_radius as f32,
+    })
+}
+
+pub fn cut_sphere(radius: f64, cut_height: f64) -> RhaiSdf {
+    RhaiSdf::new(SdfOp::CutSphere {
+        radius: radius as f32,
+        cut_height: cut_height as f32,
+    })
+}
+
+pub fn cut_hollow_sphere(radius: f64, cut_height: f64, thickness: f64) -> RhaiSdf {
+    RhaiSdf::new(SdfOp::CutHollowSphere {
+        radius: radius as f32,
+        cut_height: cut_height as f32,
+        thickness: thickness as f32,
+    })
+}
+
+pub fn rhombus(width: f64, depth: f64, height: f64, radius: f64) -> RhaiSdf {
+    RhaiSdf::new(SdfOp::Rhombus {
+        half_diagonals: [(width / 2.0) as f32, (depth / 2.0) as f32],
+        half_height: (height / 2.0) as f32,
+        radius: radius as f32,
+    })
+}
+
+pub fn solid_angle(angle: f64, radius: f64) -> RhaiSdf {
+    RhaiSdf::new(SdfOp::SolidAngle {
+        half_angle: angle as f32,
+        radius: radius as f32,
+    })
+}
+
+pub fn capsule_between(
+    ax: f64,
+    ay: f64,
+    az: f64,
+    bx: f64,
+    by: f64,
+    bz: f64,
+    radius: f64,
+) -> RhaiSdf {
+    RhaiSdf::new(SdfOp::CapsuleBetween {
+        a: [ax as f32, ay as f32, az as f32],
+        b: [bx as f32, by as f32, bz as f32],
+        radius: radius as f32,
+    })
+}
+
+pub fn cylinder_between(
+    ax: f64,
+    ay: f64,
+    az: f64,
+    bx: f64,
+    by: f64,
+    bz: f64,
+    radius: f64,
+) -> RhaiSdf {
+    RhaiSdf::new(SdfOp::CylinderBetween {
+        a: [ax as f32, ay as f32, az as f32],
+        b: [bx as f32, by as f32, bz as f32],
+        radius: radius as f32,
+    })
+}
+
+pub fn infinite_cylinder(dx: f64, dy: f64, dz: f64, radius: f64) -> RhaiSdf {
+    RhaiSdf::new(SdfOp::InfiniteCylinder {
+        axis: [dx as f32, dy as f32, dz as f32],
+        radius: radius as f32,
+    })
+}
+
+// === Repetition ===
+
+/// Most copies `repeat_polar` accepts, well under a degree apart
+pub const MAX_POLAR_COPIES: i64 = 1024;
+
+fn polar_count(count: i64) -> Result<u32, Box<EvalAltResult>> {
+    if !(1..=MAX_POLAR_COPIES).contains(&count) {
+        return Err(format!(
+            "repeat_polar: count must be between 1 and {MAX_POLAR_COPIES}, got {count}"
+        )
+        .into());
+    }
+    Ok(count as u32)
+}
+
+// === Fractals ===
+
+/// Most iterations a fractal accepts
+///
+/// Every iteration runs again at each of the millions of samples a preview or
+/// mesh takes, and past this the added detail is far finer than any mesh.
+pub const MAX_FRACTAL_ITERATIONS: i64 = 32;
+
+fn fractal_iterations(name: &str, iterations: i64) -> Result<u32, Box<EvalAltResult>> {
+    if iterations > MAX_FRACTAL_ITERATIONS {
+        return Err(format!(
+            "{name}: iterations must be at most {MAX_FRACTAL_ITERATIONS}, got {iterations}"
+        )
+        .into());
+    }
+    Ok(iterations.max(0) as u32)
+}
+
+pub fn mandelbulb(power: f64, iterations: i64) -> Result<RhaiSdf, Box<EvalAltResult>> {
+    Ok(RhaiSdf::new(SdfOp::Mandelbulb {
+        power: power as f32,
+        iterations: fractal_iterations("mandelbulb", iterations)?,
+    }))
+}
+
+pub fn menger_sponge(size: f64, iterations: i64) -> Result<RhaiSdf, Box<EvalAltResult>> {
+    Ok(RhaiSdf::new(SdfOp::MengerSponge {
+        size: (size / 2.0) as f32,
+        iterations: fractal_iterations("menger_sponge", iterations)?,
+    }))
+}
+
+pub fn sierpinski(size: f64, iterations: i64) -> Result<RhaiSdf, Box<EvalAltResult>> {
+    Ok(RhaiSdf::new(SdfOp::Sierpinski {
+        size: (size / 2.0) as f32,
+        iterations: fractal_iterations("sierpinski", iterations)?,
+    }))
+}
+
+pub fn julia(
+    cx: f64,
+    cy: f64,
+    cz: f64,
+    cw: f64,
+    iterations: i64,
+) -> Result<RhaiSdf, Box<EvalAltResult>> {
+    Ok(RhaiSdf::new(SdfOp::Julia {
+        c: [cx as f32, cy as f32, cz as f32, cw as f32],
+        iterations: fractal_iterations("julia", iterations)?,
+    }))
+}
+
+// === 2D Profiles ===
+
+/// 2D profile shape for Rhai, turned into a solid by extrusion, revolution
+/// or sweeping
+#[derive(Debug, Clone)]
+pub struct RhaiSdf2d {
+    /// The underlying 2D profile tree
+    pub op: Arc<Sdf2dOp>,
+}
+
+impl RhaiSdf2d {
+    /// Create a new RhaiSdf2d from an Sdf2dOp
+    pub fn new(op: Sdf2dOp) -> Self {
+        Self { op: Arc::new(op) }
+    }
+
+    // === Boolean Operations ===
+
+    pub fn union(&mut self, other: RhaiSdf2d) -> RhaiSdf2d {
+        RhaiSdf2d::new(Sdf2dOp::Union {
+            a: Arc::clone(&self.op),
+            b: other.op,
+        })
+    }
+
+    pub fn subtract(&mut self, other: RhaiSdf2d) -> RhaiSdf2d {
+        RhaiSdf2d::new(Sdf2dOp::Subtract {
+            a: Arc::clone(&self.op),
+            b: other.op,
+        })
+    }
+
+    pub fn intersect(&mut self, other: RhaiSdf2d) -> RhaiSdf2d {
+        RhaiSdf2d::new(Sdf2dOp::Intersect {
+            a: Arc::clone(&self.op),
+            b: other.op,
+        })
+    }
+
+    pub fn smooth_union(&mut self, other: RhaiSdf2d, k: f64) -> RhaiSdf2d {
+        RhaiSdf2d::new(Sdf2dOp::SmoothUnion {
+            a: Arc::clone(&self.op),
+            b: other.op,
+            k: k as f32,
+        })
+    }
+
+    pub fn smooth_subtract(&mut self, other: RhaiSdf2d, k: f64) -> RhaiSdf2d {
+        RhaiSdf2d::new(Sdf2dOp::SmoothSubtract {
+            a: Arc::clone(&self.op),
+            b: other.op,
+            k: k as f32,
+        })
+    }
+
+    pub fn smooth_intersect(&mut self, other: RhaiSdf2d, k: f64) -> RhaiSdf2d {
+        RhaiSdf2d::new(Sdf2dOp::SmoothIntersect {
+            a: Arc::clone(&self.op),
+            b: other.op,
+            k: k as f32,
+        })
+    }
+
+    // === Modifiers ===
+
+    pub fn offset(&mut self, distance: f64) -> RhaiSdf2d {
+        RhaiSdf2d::new(Sdf2dOp::Offset {
+            inner: Arc::clone(&self.op),
+            distance: distance as f32,
+        })
+    }
+
+    pub fn round(&mut self, radius: f64) -> RhaiSdf2d {
+        self.offset(radius)
+    }
+
+    pub fn shell(&mut self, thickness: f64) -> RhaiSdf2d {
+        RhaiSdf2d::new(Sdf2dOp::Shell {
+            inner: Arc::clone(&self.op),
+            thickness: thickness as f32,
+        })
+    }
+
+    // === Transforms ===
+
+    pub fn translate(&mut self, x: f64, y: f64) -> RhaiSdf2d {
+        RhaiSdf2d::new(Sdf2dOp::Translate {
+            inner: Arc::clone(&self.op),
+            offset: [x as f32, y as f32],
+        })
+    }
+
+    pub fn rotate(&mut self, angle: f64) -> RhaiSdf2d {
+        RhaiSdf2d::new(Sdf2dOp::Rotate {
+            inner: Arc::clone(&self.op),
+            angle: angle as f32,
+        })
+    }
+
+    pub fn scale(&mut self, factor: f64) -> RhaiSdf2d {
+        RhaiSdf2d::new(Sdf2dOp::Scale {
+            inner: Arc::clone(&self.op),
+            factor: factor as f32,
+        })
+    }
+
+    // === Repetition ===
+
+    pub fn repeat(&mut self, sx: f64, sy: f64) -> RhaiSdf2d {
+        RhaiSdf2d::new(Sdf2dOp::RepeatInfinite {
+            inner: Arc::clone(&self.op),
+            spacing: [sx as f32, sy as f32],
+        })
+    }
+
+    pub fn repeat_limited(&mut self, sx: f64, sy: f64, cx: f64, cy: f64) -> RhaiSdf2d {
+        RhaiSdf2d::new(Sdf2dOp::RepeatLimited {
+            inner: Arc::clone(&self.op),
+            spacing: [sx as f32, sy as f32],
+            count: [cx as f32, cy as f32],
+        })
+    }
+
+    pub fn repeat_polar(&mut self, count: i64) -> Result<RhaiSdf2d, Box<EvalAltResult>> {
+        Ok(RhaiSdf2d::new(Sdf2dOp::RepeatPolar {
+            inner: Arc::clone(&self.op),
+            count: polar_count(count)?,
+        }))
+    }
+
+    // === 2D-to-3D Operations ===
+
+    /// Extrude along Z; `depth` is the distance from the profile plane to
+    /// each cap, as in `extrude_rect`
+    pub fn extrude(&mut self, depth: f64) -> RhaiSdf {
+        RhaiSdf::new(SdfOp::Extrude {
+            profile: Arc::clone(&self.op),
+            depth: depth as f32,
+        })
+    }
+
+    /// Revolve around the Y axis, with the profile's X measured from
+    /// `offset` away from the axis
+    pub fn revolve(&mut self, offset: f64) -> RhaiSdf {
+        RhaiSdf::new(SdfOp::Revolve {
+            profile: Arc::clone(&self.op),
+            offset: offset as f32,
+        })
+    }
+}
+
+pub fn circle(radius: f64) -> RhaiSdf2d {
+    RhaiSdf2d::new(Sdf2dOp::Circle {
+        radius: radius as f32,
+    })
+}
+
+pub fn rect(width: f64, height: f64) -> RhaiSdf2d {
+    RhaiSdf2d::new(Sdf2dOp::Rectangle {
+        half_extents: [(width / 2.0) as f32, (height / 2.0) as f32],
+    })
+}
+
+pub fn rounded_rect(width: f64, height: f64, radius: f64) -> RhaiSdf2d {
+    RhaiSdf2d::new(Sdf2dOp::RoundedRectangle {
+        half_extents: [(width / 2.0) as f32, (height / 2.0) as f32],
+        radius: radius as f32,
+    })
+}
+
+pub fn polygon(points: Array) -> Result<RhaiSdf2d, Box<EvalAltResult>> {
+    let points = points_from_array("polygon", points, 3)?;
+    Ok(RhaiSdf2d::new(Sdf2dOp::Polygon { points }))
+}
+
+/// Ring segment of `radius`, symmetric about +Y and spanning `angle`
+/// radians on each side of it
+pub fn arc(radius: f64, angle: f64, thickness: f64) -> RhaiSdf2d {
+    RhaiSdf2d::new(Sdf2dOp::Arc {
+        half_angle: angle as f32,
+        radius: radius as f32,
+        half_thickness: (thickness / 2.0) as f32,
+    })
+}
+
 // === 2D-to-3D Operations ===
 
 pub fn extrude_circle(radius: f64, depth: f64) -> RhaiSdf {
-    RhaiSdf::new(SdfOp::Extrude {
-        profile: ExtrudeProfile::Circle {
-            radius: radius as f32,
-        },
-        depth: depth as f32,
-    })
+    circle(radius).extrude(depth)
 }
 
 pub fn extrude_rect(width: f64, height: f64, depth: f64) -> RhaiSdf {
-    RhaiSdf::new(SdfOp::Extrude {
-        profile: ExtrudeProfile::Rectangle {
-            width: width as f32,
-            height: height as f32,
-        },
-        depth: depth as f32,
-    })
+    rect(width, height).extrude(depth)
 }
 
 pub fn extrude_rounded_rect(width: f64, height: f64, radius: f64, depth: f64) -> RhaiSdf {
-    RhaiSdf::new(SdfOp::Extrude {
-        profile: ExtrudeProfile::RoundedRectangle {
-            width: width as f32,
-            height: height as f32,
-            radius: radius as f32,
-        },
-        depth: depth as f32,
-    })
+    rounded_rect(width, height, radius).extrude(depth)
 }
 
 pub fn revolve_circle(radius: f64, offset: f64) -> RhaiSdf {
-    RhaiSdf::new(SdfOp::Revolve {
-        profile: RevolveProfile::Circle {
-            radius: radius as f32,
-        },
-        offset: offset as f32,
-    })
+    circle(radius).revolve(offset)
 }
 
 pub fn revolve_rect(width: f64, height: f64, offset: f64) -> RhaiSdf {
-    RhaiSdf::new(SdfOp::Revolve {
-        profile: RevolveProfile::Rectangle {
-            width: width as f32,
-            height: height as f32,
-        },
-        offset: offset as f32,
+    rect(width, height).revolve(offset)
+}
+
+// === Sweeps ===
+
+/// Path for sweeping tubes and profiles, built from an array of `[x, y, z]` points
+#[derive(Debug, Clone)]
+pub struct RhaiPath {
+    pub path: SweepPath,
+}
+
+impl RhaiPath {
+    pub fn tube(&mut self, radius: f64) -> RhaiSdf {
+        self.tapered_tube(radius, radius)
+    }
+
+    pub fn tapered_tube(&mut self, start_radius: f64, end_radius: f64) -> RhaiSdf {
+        RhaiSdf::new(SdfOp::Tube {
+            path: self.path.clone(),
+            start_radius: start_radius as f32,
+            end_radius: end_radius as f32,
+        })
+    }
+
+    pub fn sweep(&mut self, profile: RhaiSdf2d) -> RhaiSdf {
+        RhaiSdf::new(SdfOp::Sweep {
+            path: self.path.clone(),
+            profile: profile.op,
+        })
+    }
+
+    pub fn sweep_circle(&mut self, radius: f64) -> RhaiSdf {
+        self.sweep(circle(radius))
+    }
+
+    pub fn sweep_rect(&mut self, width: f64, height: f64) -> RhaiSdf {
+        self.sweep(rect(width, height))
+    }
+
+    pub fn sweep_rounded_rect(&mut self, width: f64, height: f64, radius: f64) -> RhaiSdf {
+        self.sweep(rounded_rect(width, height, radius))
+    }
+}
+
+pub fn polyline(points: Array) -> Result<RhaiPath, Box<EvalAltResult>> {
+    path_from_points("polyline", SweepCurve::Polyline, points, 2)
+}
+
+pub fn bezier(points: Array) -> Result<RhaiPath, Box<EvalAltResult>> {
+    if points.len().is_multiple_of(2) {
+        return Err(format!(
+            "bezier: expected an odd number of points (start, control, end, control, end, ...), got {}",
+            points.len()
+        )
+        .into());
+    }
+    path_from_points("bezier", SweepCurve::QuadraticBezier, points, 3)
+}
+
+pub fn catmull_rom(points: Array) -> Result<RhaiPath, Box<EvalAltResult>> {
+    path_from_points("catmull_rom", SweepCurve::CatmullRom, points, 2)
+}
+
+fn path_from_points(
+    name: &str,
+    curve: SweepCurve,
+    points: Array,
+    min_points: usize,
+) -> Result<RhaiPath, Box<EvalAltResult>> {
+    Ok(RhaiPath {
+        path: SweepPath::new(curve, points_from_array(name, points, min_points)?),
     })
 }
 
+/// Convert a Rhai array of `[x, y]` or `[x, y, z]` arrays into points
+fn points_from_array<const N: usize>(
+    name: &str,
+    points: Array,
+    min_points: usize,
+) -> Result<Vec<[f32; N]>, Box<EvalAltResult>> {
+    if points.len() < min_points {
+        return Err(format!(
+            "{name}: expected at least {min_points} points, got {}",
+            points.len()
+        )
+        .into());
+    }
+    let points = points
+        .into_iter()
+        .enumerate()
+        .map(|(i, point)| {
+            point_from_dynamic(&point).ok_or_else(|| {
+                let axes = ["x", "y", "z"][..N].join(", ");
+                format!("{name}: point {i} is not an [{axes}] array of numbers")
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(points)
+}
+
+fn point_from_dynamic<const N: usize>(point: &Dynamic) -> Option<[f32; N]> {
+    let coords = point.read_lock::<Array>()?;
+    if coords.len() != N {
+        return None;
+    }
+    let number = |v: &Dynamic| {
+        v.as_float()
+            .ok()
+            .or_else(|| v.as_int().ok().map(|i| i as f64))
+            .map(|v| v as f32)
+    };
+    let mut out = [0.0; N];
+    for (o, v) in out.iter_mut().zip(coords.iter()) {
+        *o = number(v)?;
+    }
+    Some(out)
+}
+
 // === Math constants ===
 
 pub fn pi() -> f64 {
@@ -451,6 +1019,7 @@ pub fn rad_to_deg(rad: f64) -> f64 {
 }
 
 /// Register all SDF functions with a Rhai engine
+#[allow(clippy::too_many_lines)]
 pub fn register_sdf_api(engine: &mut Engine) {
     // Register the RhaiSdf type
     engine
@@ -474,14 +1043,70 @@ pub fn register_sdf_api(engine: &mut Engine) {
     engine.register_fn("tri_prism", tri_prism);
     engine.register_fn("pyramid", pyramid);
     engine.register_fn("link", link);
+    engine.register_fn("capped_cone", capped_cone);
+    engine.register_fn("round_cone", round_cone);
+    engine.register_fn("box_frame", box_frame);
+    engine.register_fn("capped_torus", capped_torus);
+    engine.register_fn("cut_sphere", cut_sphere);
+    engine.register_fn("cut_hollow_sphere", cut_hollow_sphere);
+    engine.register_fn("rhombus", rhombus);
+    engine.register_fn("solid_angle", solid_angle);
+    engine.register_fn("capsule_between", capsule_between);
+    engine.register_fn("cylinder_between", cylinder_between);
+    engine.register_fn("infinite_cylinder", infinite_cylinder);
+
+    // === Fractals ===
+    engine.register_fn("mandelbulb", mandelbulb);
+    engine.register_fn("menger_sponge", menger_sponge);
+    engine.register_fn("sierpinski", sierpinski);
+    engine.register_fn("julia", julia);
+
+    // === 2D profiles ===
+    engine
+        .register_type_with_name::<RhaiSdf2d>("Sdf2d")
+        .register_fn("to_string", |sdf: &mut RhaiSdf2d| format!("{:?}", sdf.op));
+    engine.register_fn("circle", circle);
+    engine.register_fn("rect", rect);
+    engine.register_fn("rounded_rect", rounded_rect);
+    engine.register_fn("polygon", polygon);
+    engine.register_fn("arc", arc);
+    engine.register_fn("union", RhaiSdf2d::union);
+    engine.register_fn("subtract", RhaiSdf2d::subtract);
+    engine.register_fn("intersect", RhaiSdf2d::intersect);
+    engine.register_fn("smooth_union", RhaiSdf2d::smooth_union);
+    engine.register_fn("smooth_subtract", RhaiSdf2d::smooth_subtract);
+    engine.register_fn("smooth_intersect", RhaiSdf2d::smooth_intersect);
+    engine.register_fn("offset", RhaiSdf2d::offset);
+    engine.register_fn("round", RhaiSdf2d::round);
+    engine.register_fn("shell", RhaiSdf2d::shell);
+    engine.register_fn("translate", RhaiSdf2d::translate);
+    engine.register_fn("rotate", RhaiSdf2d::rotate);
+    engine.register_fn("scale", RhaiSdf2d::scale);
+    engine.register_fn("repeat", RhaiSdf2d::repeat);
+    engine.register_fn("repeat_limited", RhaiSdf2d::repeat_limited);
+    engine.register_fn("repeat_polar", RhaiSdf2d::repeat_polar);
 
     // === 2D-to-3D operations ===
+    engine.register_fn("extrude", RhaiSdf2d::extrude);
+    engine.register_fn("revolve", RhaiSdf2d::revolve);
     engine.register_fn("extrude_circle", extrude_circle);
     engine.register_fn("extrude_rect", extrude_rect);
     engine.register_fn("extrude_rounded_rect", extrude_rounded_rect);
     engine.register_fn("revolve_circle", revolve_circle);
     engine.register_fn("revolve_rect", revolve_rect);
 
+    // === Sweeps ===
+    engine.register_type_with_name::<RhaiPath>("Path");
+    engine.register_fn("polyline", polyline);
+    engine.register_fn("bezier", bezier);
+    engine.register_fn("catmull_rom", catmull_rom);
+    engine.register_fn("tube", RhaiPath::tube);
+    engine.register_fn("tube", RhaiPath::tapered_tube);
+    engine.register_fn("sweep", RhaiPath::sweep);
+    engine.register_fn("sweep_circle", RhaiPath::sweep_circle);
+    engine.register_fn("sweep_rect", RhaiPath::sweep_rect);
+    engine.register_fn("sweep_rounded_rect", RhaiPath::sweep_rounded_rect);
+
     // === Boolean operations ===
     engine.register_fn("union", RhaiSdf::union);
     engine.register_fn("subtract", RhaiSdf::subtract);
@@ -507,6 +1132,12 @@ pub fn register_sdf_api(engine: &mut Engine) {
     engine.register_fn("rotate_y", RhaiSdf::rotate_y);
     engine.register_fn("rotate_z", RhaiSdf::rotate_z);
     engine.register_fn("scale", RhaiSdf::scale);
+    engine.register_fn("scale", RhaiSdf::scale_xyz);
+    engine.register_fn("rotate", RhaiSdf::rotate);
+    engine.register_fn("rotate_quat", RhaiSdf::rotate_quat);
+    engine.register_fn("align_to", RhaiSdf::align_to);
+    engine.register_fn("look_at", RhaiSdf::look_at);
+    engine.register_fn("transform", RhaiSdf::transform);
     engine.register_fn("mirror_x", RhaiSdf::mirror_x);
     engine.register_fn("mirror_y", RhaiSdf::mirror_y);
     engine.register_fn("mirror_z", RhaiSdf::mirror_z);
@@ -524,6 +1155,11 @@ pub fn register_sdf_api(engine: &mut Engine) {
     engine.register_fn("repeat_limited", RhaiSdf::repeat_limited);
     engine.register_fn("repeat_polar", RhaiSdf::repeat_polar);
 
+    // === Materials ===
+    engine.register_fn("material", RhaiSdf::material);
+    engine.register_fn("color", RhaiSdf::color);
+    engine.register_fn("color_hex", RhaiSdf::color_hex);
+
     // === Math helpers ===
     engine.register_fn("PI", pi);
     engine.register_fn("TAU", tau);
@@ -0,0 +1,444 @@
+//! Rhai API for procedural textures and PBR materials
+//!
+//! This module exposes `soyuz_core::texture` (noise, patterns, combinators)
+//! and `soyuz_core::material` to scripts. Textures are sampled in UV space
+//! (0..1) and only take effect when exported to formats with material
+//! support (glTF/GLB).
+//!
+//! ```rhai
+//! let wood = fbm(5, 8.0).warp(perlin(3.0), 0.2).colorize_hex("#5a3a1e", "#a0703c");
+//!
+//! set_material(pbr().albedo(wood).roughness(0.7));
+//!
+//! cylinder(0.5, 1.2)
+//! ```
+
+use rhai::{Engine, EvalAltResult};
+use soyuz_core::material::Material;
+use soyuz_core::prelude::Vec2;
+use soyuz_core::texture::{Texture, TextureExt, noise, ops, pattern};
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use crate::env_api::parse_hex_color;
+
+// Thread-local material set by the script with `set_material()`
+thread_local! {
+    static CURRENT_MATERIAL: RefCell<Option<Material>> = const { RefCell::new(None) };
+}
+
+/// Clear the script material (called before each script evaluation)
+pub fn reset_material() {
+    CURRENT_MATERIAL.with(|mat| {
+        *mat.borrow_mut() = None;
+    });
+}
+
+/// Get the material set by the script, if any (called after script evaluation)
+pub fn get_current_material() -> Option<Material> {
+    CURRENT_MATERIAL.with(|mat| mat.borrow().clone())
+}
+
+/// Texture representation for Rhai
+///
+/// Wraps the generic texture combinators of `soyuz_core` behind an
+/// `Arc<dyn Texture>` so they can be chained freely from scripts.
+#[derive(Clone)]
+pub struct RhaiTexture {
+    /// The underlying texture generator
+    pub texture: Arc<dyn Texture>,
+}
+
+impl RhaiTexture {
+    /// Create a new RhaiTexture from any texture
+    pub fn new<T: Texture + 'static>(texture: T) -> Self {
+        Self {
+            texture: Arc::new(texture),
+        }
+    }
+
+    // === Value Operations ===
+
+    pub fn scale(&mut self, factor: f64) -> RhaiTexture {
+        RhaiTexture::new(TextureExt::scale(self.clone(), factor as f32))
+    }
+
+    pub fn invert(&mut self) -> RhaiTexture {
+        RhaiTexture::new(ops::Invert::new(self.clone()))
+    }
+
+    pub fn remap(&mut self, in_min: f64, in_max: f64, out_min: f64, out_max: f64) -> RhaiTexture {
+        RhaiTexture::new(ops::Remap::new(
+            self.clone(),
+            in_min as f32,
+            in_max as f32,
+            out_min as f32,
+            out_max as f32,
+        ))
+    }
+
+    pub fn clamp(&mut self, min: f64, max: f64) -> RhaiTexture {
+        RhaiTexture::new(ops::Clamp::new(self.clone(), min as f32, max as f32))
+    }
+
+    pub fn pow(&mut self, exp: f64) -> RhaiTexture {
+        RhaiTexture::new(ops::Pow::new(self.clone(), exp as f32))
+    }
+
+    pub fn abs(&mut self) -> RhaiTexture {
+        RhaiTexture::new(ops::Abs::new(self.clone()))
+    }
+
+    pub fn threshold(&mut self, value: f64) -> RhaiTexture {
+        RhaiTexture::new(ops::Threshold::new(self.clone(), value as f32))
+    }
+
+    pub fn smoothstep(&mut self, edge0: f64, edge1: f64) -> RhaiTexture {
+        RhaiTexture::new(ops::Smoothstep::new(
+            self.clone(),
+            edge0 as f32,
+            edge1 as f32,
+        ))
+    }
+
+    // === Combinators ===
+
+    pub fn add(&mut self, other: RhaiTexture) -> RhaiTexture {
+        RhaiTexture::new(ops::Add::new(self.clone(), other))
+    }
+
+    pub fn multiply(&mut self, other: RhaiTexture) -> RhaiTexture {
+        RhaiTexture::new(ops::Multiply::new(self.clone(), other))
+    }
+
+    pub fn mix(&mut self, other: RhaiTexture, factor: f64) -> RhaiTexture {
+        RhaiTexture::new(ops::Mix::new(self.clone(), other, factor as f32))
+    }
+
+    pub fn warp(&mut self, warper: RhaiTexture, amount: f64) -> RhaiTexture {
+        RhaiTexture::new(ops::Warp::new(self.clone(), warper, amount as f32))
+    }
+
+    // === UV Transforms ===
+
+    pub fn rotate(&mut self, angle: f64) -> RhaiTexture {
+        RhaiTexture::new(ops::Rotate::new(self.clone(), angle as f32))
+    }
+
+    pub fn translate(&mut self, x: f64, y: f64) -> RhaiTexture {
+        RhaiTexture::new(ops::Translate::new(
+            self.clone(),
+            Vec2::new(x as f32, y as f32),
+        ))
+    }
+
+    pub fn tile(&mut self, x: f64, y: f64) -> RhaiTexture {
+        RhaiTexture::new(ops::Tile::new(self.clone(), Vec2::new(x as f32, y as f32)))
+    }
+
+    // === Color Output ===
+
+    pub fn colorize(
+        &mut self,
+        r0: f64,
+        g0: f64,
+        b0: f64,
+        r1: f64,
+        g1: f64,
+        b1: f64,
+    ) -> RhaiTexture {
+        RhaiTexture::new(TextureExt::colorize(
+            self.clone(),
+            [r0 as f32, g0 as f32, b0 as f32],
+            [r1 as f32, g1 as f32, b1 as f32],
+        ))
+    }
+
+    pub fn colorize_hex(&mut self, low: &str, high: &str) -> RhaiTexture {
+        let parse = |hex| parse_hex_color(hex).map_or([0.0; 3], |(r, g, b)| [r, g, b]);
+        RhaiTexture::new(TextureExt::colorize(self.clone(), parse(low), parse(high)))
+    }
+
+    pub fn to_normal(&mut self, strength: f64) -> RhaiTexture {
+        RhaiTexture::new(ops::ToNormal::new(self.clone(), strength as f32))
+    }
+}
+
+impl Texture for RhaiTexture {
+    fn sample(&self, uv: Vec2) -> f32 {
+        self.texture.sample(uv)
+    }
+
+    fn sample_color(&self, uv: Vec2) -> [f32; 4] {
+        self.texture.sample_color(uv)
+    }
+}
+
+/// PBR material representation for Rhai
+#[derive(Clone)]
+pub struct RhaiMaterial {
+    /// The underlying material
+    pub material: Material,
+}
+
+impl RhaiMaterial {
+    /// Apply a builder step to a copy of the material
+    fn with(&self, f: impl FnOnce(Material) -> Material) -> RhaiMaterial {
+        RhaiMaterial {
+            material: f(self.material.clone()),
+        }
+    }
+
+    pub fn albedo(&mut self, r: f64, g: f64, b: f64) -> RhaiMaterial {
+        self.with(|m| m.albedo_color(r as f32, g as f32, b as f32))
+    }
+
+    pub fn albedo_hex(&mut self, hex: &str) -> RhaiMaterial {
+        match parse_hex_color(hex) {
+            Some((r, g, b)) => self.with(|m| m.albedo_color(r, g, b)),
+            None => self.clone(),
+        }
+    }
+
+    pub fn albedo_texture(&mut self, texture: RhaiTexture) -> RhaiMaterial {
+        self.with(|m| m.albedo_texture(texture))
+    }
+
+    pub fn roughness(&mut self, value: f64) -> RhaiMaterial {
+        self.with(|m| m.roughness(value as f32))
+    }
+
+    pub fn roughness_texture(&mut self, texture: RhaiTexture) -> RhaiMaterial {
+        self.with(|m| m.roughness_texture(texture))
+    }
+
+    pub fn metallic(&mut self, value: f64) -> RhaiMaterial {
+        self.with(|m| m.metallic(value as f32))
+    }
+
+    pub fn metallic_texture(&mut self, texture: RhaiTexture) -> RhaiMaterial {
+        self.with(|m| m.metallic_texture(texture))
+    }
+
+    pub fn normal(&mut self, texture: RhaiTexture) -> RhaiMaterial {
+        self.with(|m| m.normal(texture))
+    }
+
+    pub fn ao(&mut self, value: f64) -> RhaiMaterial {
+        self.with(|m| m.ao(value as f32))
+    }
+
+    pub fn ao_texture(&mut self, texture: RhaiTexture) -> RhaiMaterial {
+        self.with(|m| m.ao_texture(texture))
+    }
+
+    pub fn emissive(&mut self, r: f64, g: f64, b: f64, strength: f64) -> RhaiMaterial {
+        self.with(|m| m.emissive(r as f32, g as f32, b as f32, strength as f32))
+    }
+}
+
+// === Noise ===
+
+pub fn perlin(scale: f64) -> RhaiTexture {
+    RhaiTexture::new(noise::perlin(scale as f32))
+}
+
+pub fn perlin_seeded(scale: f64, seed: i64) -> RhaiTexture {
+    RhaiTexture::new(noise::PerlinNoise::with_seed(seed as u32, scale as f32))
+}
+
+pub fn simplex(scale: f64) -> RhaiTexture {
+    RhaiTexture::new(noise::simplex(scale as f32))
+}
+
+pub fn simplex_seeded(scale: f64, seed: i64) -> RhaiTexture {
+    RhaiTexture::new(noise::SimplexNoise::with_seed(seed as u32, scale as f32))
+}
+
+pub fn worley(scale: f64) -> RhaiTexture {
+    RhaiTexture::new(noise::worley(scale as f32))
+}
+
+pub fn worley_seeded(scale: f64, seed: i64) -> RhaiTexture {
+    RhaiTexture::new(noise::WorleyNoise::with_seed(seed as u32, scale as f32))
+}
+
+/// Most octaves a fractal noise accepts
+///
+/// Every octave is another noise lookup at each texel, and past this the
+/// added detail is finer than float precision.
+pub const MAX_NOISE_OCTAVES: i64 = 16;
+
+fn noise_octaves(name: &str, octaves: i64) -> Result<u32, Box<EvalAltResult>> {
+    if octaves > MAX_NOISE_OCTAVES {
+        return Err(
+            format!("{name}: octaves must be at most {MAX_NOISE_OCTAVES}, got {octaves}").into(),
+        );
+    }
+    Ok(octaves.max(1) as u32)
+}
+
+pub fn fbm(octaves: i64, scale: f64) -> Result<RhaiTexture, Box<EvalAltResult>> {
+    Ok(RhaiTexture::new(
+        noise::fbm(noise_octaves("fbm", octaves)?).scale(scale as f32),
+    ))
+}
+
+pub fn fbm_seeded(octaves: i64, scale: f64, seed: i64) -> Result<RhaiTexture, Box<EvalAltResult>> {
+    Ok(RhaiTexture::new(
+        noise::Fbm::with_seed(seed as u32, noise_octaves("fbm", octaves)?).scale(scale as f32),
+    ))
+}
+
+pub fn ridged(octaves: i64, scale: f64) -> Result<RhaiTexture, Box<EvalAltResult>> {
+    Ok(RhaiTexture::new(TextureExt::scale(
+        noise::ridged(noise_octaves("ridged", octaves)?),
+        scale as f32,
+    )))
+}
+
+pub fn turbulence(octaves: i64, scale: f64) -> Result<RhaiTexture, Box<EvalAltResult>> {
+    Ok(RhaiTexture::new(TextureExt::scale(
+        noise::turbulence(noise_octaves("turbulence", octaves)?),
+        scale as f32,
+    )))
+}
+
+// === Patterns ===
+
+pub fn constant(value: f64) -> RhaiTexture {
+    RhaiTexture::new(soyuz_core::texture::constant(value as f32))
+}
+
+pub fn gradient(dx: f64, dy: f64) -> RhaiTexture {
+    RhaiTexture::new(pattern::gradient(Vec2::new(dx as f32, dy as f32)))
+}
+
+pub fn radial(cx: f64, cy: f64, radius: f64) -> RhaiTexture {
+    RhaiTexture::new(pattern::radial(
+        Vec2::new(cx as f32, cy as f32),
+        radius as f32,
+    ))
+}
+
+pub fn checker(scale: f64) -> RhaiTexture {
+    RhaiTexture::new(pattern::checker(scale as f32))
+}
+
+pub fn bricks(width: f64, height: f64, mortar: f64) -> RhaiTexture {
+    RhaiTexture::new(pattern::bricks(
+        Vec2::new(width as f32, height as f32),
+        mortar as f32,
+    ))
+}
+
+pub fn hexagons(scale: f64) -> RhaiTexture {
+    RhaiTexture::new(pattern::hexagons(scale as f32))
+}
+
+pub fn voronoi(scale: f64) -> RhaiTexture {
+    RhaiTexture::new(pattern::voronoi(scale as f32))
+}
+
+pub fn voronoi_seeded(scale: f64, seed: i64) -> RhaiTexture {
+    RhaiTexture::new(pattern::Voronoi::with_seed(scale as f32, seed as u32))
+}
+
+pub fn dots(scale: f64, size: f64) -> RhaiTexture {
+    RhaiTexture::new(pattern::dots(scale as f32, size as f32))
+}
+
+pub fn stripes(scale: f64, angle: f64) -> RhaiTexture {
+    RhaiTexture::new(pattern::stripes(scale as f32, angle as f32))
+}
+
+pub fn waves(scale: f64, amplitude: f64) -> RhaiTexture {
+    RhaiTexture::new(pattern::waves(scale as f32, amplitude as f32))
+}
+
+// === Materials ===
+
+pub fn pbr() -> RhaiMaterial {
+    RhaiMaterial {
+        material: Material::pbr(),
+    }
+}
+
+/// Use a PBR material for the exported mesh
+fn set_material(material: RhaiMaterial) {
+    CURRENT_MATERIAL.with(|mat| {
+        *mat.borrow_mut() = Some(material.material);
+    });
+}
+
+/// Register all texture and material functions with a Rhai engine
+pub fn register_texture_api(engine: &mut Engine) {
+    engine.register_type_with_name::<RhaiTexture>("Texture");
+    engine.register_type_with_name::<RhaiMaterial>("Material");
+
+    // === Noise ===
+    engine.register_fn("perlin", perlin);
+    engine.register_fn("perlin", perlin_seeded);
+    engine.register_fn("simplex", simplex);
+    engine.register_fn("simplex", simplex_seeded);
+    engine.register_fn("worley", worley);
+    engine.register_fn("worley", worley_seeded);
+    engine.register_fn("fbm", fbm);
+    engine.register_fn("fbm", fbm_seeded);
+    engine.register_fn("ridged", ridged);
+    engine.register_fn("turbulence", turbulence);
+
+    // === Patterns ===
+    engine.register_fn("constant", constant);
+    engine.register_fn("gradient", gradient);
+    engine.register_fn("radial", radial);
+    engine.register_fn("checker", checker);
+    engine.register_fn("bricks", bricks);
+    engine.register_fn("hexagons", hexagons);
+    engine.register_fn("voronoi", voronoi);
+    engine.register_fn("voronoi", voronoi_seeded);
+    engine.register_fn("dots", dots);
+    engine.register_fn("stripes", stripes);
+    engine.register_fn("waves", waves);
+
+    // === Value operations ===
+    engine.register_fn("scale", RhaiTexture::scale);
+    engine.register_fn("invert", RhaiTexture::invert);
+    engine.register_fn("remap", RhaiTexture::remap);
+    engine.register_fn("clamp", RhaiTexture::clamp);
+    engine.register_fn("pow", RhaiTexture::pow);
+    engine.register_fn("abs", RhaiTexture::abs);
+    engine.register_fn("threshold", RhaiTexture::threshold);
+    engine.register_fn("smoothstep", RhaiTexture::smoothstep);
+
+    // === Combinators ===
+    engine.register_fn("add", RhaiTexture::add);
+    engine.register_fn("multiply", RhaiTexture::multiply);
+    engine.register_fn("mix", RhaiTexture::mix);
+    engine.register_fn("warp", RhaiTexture::warp);
+
+    // === UV transforms ===
+    engine.register_fn("rotate", RhaiTexture::rotate);
+    engine.register_fn("translate", RhaiTexture::translate);
+    engine.register_fn("tile", RhaiTexture::tile);
+
+    // === Color output ===
+    engine.register_fn("colorize", RhaiTexture::colorize);
+    engine.register_fn("colorize_hex", RhaiTexture::colorize_hex);
+    engine.register_fn("to_normal", RhaiTexture::to_normal);
+
+    // === PBR materials ===
+    engine.register_fn("pbr", pbr);
+    engine.register_fn("albedo", RhaiMaterial::albedo);
+    engine.register_fn("albedo", RhaiMaterial::albedo_texture);
+    engine.register_fn("albedo_hex", RhaiMaterial::albedo_hex);
+    engine.register_fn("roughness", RhaiMaterial::roughness);
+    engine.register_fn("roughness", RhaiMaterial::roughness_texture);
+    engine.register_fn("metallic", RhaiMaterial::metallic);
+    engine.register_fn("metallic", RhaiMaterial::metallic_texture);
+    engine.register_fn("normal", RhaiMaterial::normal);
+    engine.register_fn("ao", RhaiMaterial::ao);
+    engine.register_fn("ao", RhaiMaterial::ao_texture);
+    engine.register_fn("emissive", RhaiMaterial::emissive);
+    engine.register_fn("set_material", set_material);
+}
@@ -0,0 +1,55 @@
+//! Rhai variables for animated scripts
+//!
+//! Scripts read `time` (seconds, a float) and `frame` (an integer) to build
+//! a different shape for each frame of an animation:
+//!
+//! ```rhai
+//! let bob = 0.2 * sin(time * 2.0 * PI());
+//! sphere(0.5).translate(0.0, bob, 0.0)
+//! ```
+//!
+//! Both are 0 unless the host sets them with
+//! [`ScriptEngine::set_time`](crate::ScriptEngine::set_time). They can be
+//! read anywhere, including inside functions and imported scripts, and a
+//! local variable with the same name hides them.
+
+use parking_lot::Mutex;
+use rhai::Dynamic;
+use std::sync::Arc;
+
+/// Current animation time, and whether the evaluation has read it
+#[derive(Debug, Default)]
+pub(crate) struct TimeState {
+    /// Seconds since the start of the animation
+    pub time: f64,
+    /// Frame number
+    pub frame: i64,
+    /// Whether the current evaluation read `time` or `frame`
+    pub read: bool,
+}
+
+/// Resolve the `time` and `frame` variables from `state`
+///
+/// Reads are recorded, so the host can tell a script that never looks at
+/// the time apart from an animated one and evaluate it only once.
+pub(crate) fn register_time_api(engine: &mut rhai::Engine, state: &Arc<Mutex<TimeState>>) {
+    let state = state.clone();
+
+    // `on_var` is marked volatile, not deprecated
+    #[allow(deprecated)]
+    engine.on_var(move |name, index, context| {
+        // Locals were already found at compile time, or are in the scope
+        if index > 0 || context.scope().contains(name) {
+            return Ok(None);
+        }
+
+        let mut state = state.lock();
+        let value = match name {
+            "time" => Dynamic::from_float(state.time),
+            "frame" => Dynamic::from_int(state.frame),
+            _ => return Ok(None),
+        };
+        state.read = true;
+        Ok(Some(value))
+    });
+}
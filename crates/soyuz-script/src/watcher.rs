@@ -1,11 +1,15 @@
 //! File watcher for hot reloading Rhai scripts
 //!
 //! Watches script files for changes and notifies when they should be re-evaluated.
+//! A script's imports can be watched on its behalf with
+//! [`ScriptWatcher::watch_imports`]; a change to one of them is reported as a
+//! change to the script.
 
 use anyhow::{Result, anyhow};
 use notify::RecursiveMode;
 use notify_debouncer_mini::{DebouncedEvent, new_debouncer};
 use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::mpsc::{Receiver, channel};
@@ -32,6 +36,8 @@ pub struct ScriptWatcher {
     receiver: Receiver<WatchEvent>,
     /// Paths being watched
     watched_paths: Arc<Mutex<Vec<PathBuf>>>,
+    /// Imported file -> scripts that import it
+    importers: Arc<Mutex<HashMap<PathBuf, Vec<PathBuf>>>>,
 }
 
 impl ScriptWatcher {
@@ -44,6 +50,8 @@ impl ScriptWatcher {
         let (tx, rx) = channel();
         let watched_paths = Arc::new(Mutex::new(Vec::new()));
         let watched_paths_clone = watched_paths.clone();
+        let importers: Arc<Mutex<HashMap<PathBuf, Vec<PathBuf>>>> = Arc::default();
+        let importers_clone = importers.clone();
 
         let debounce_duration = Duration::from_millis(debounce_ms.unwrap_or(100));
 
@@ -54,20 +62,32 @@ impl ScriptWatcher {
                     Ok(events) => {
                         for event in events {
                             let path = event.path.clone();
-                            let watched = watched_paths_clone.lock();
 
-                            // Check if this path is one we're watching
-                            let is_watched =
-                                watched.iter().any(|p| path.starts_with(p) || path == *p);
+                            // The guard is dropped before `importers` is locked;
+                            // `watch_imports` takes the two locks the other way round
+                            let report = {
+                                let watched = watched_paths_clone.lock();
+
+                                // Check if this path is one we're watching
+                                let is_watched =
+                                    watched.iter().any(|p| path.starts_with(p) || path == *p);
 
-                            if is_watched {
                                 // Check if it's a .rhai file or the exact watched path
                                 let is_rhai = path.extension().is_some_and(|e| e == "rhai");
-                                let is_exact = watched.contains(&path);
+                                is_watched && (is_rhai || watched.contains(&path))
+                            };
+
+                            if report {
+                                // All debounced events are treated as modifications
+                                let _ = tx.send(WatchEvent::Modified(path.clone()));
+                            }
 
-                                if is_rhai || is_exact {
-                                    // All debounced events are treated as modifications
-                                    let _ = tx.send(WatchEvent::Modified(path));
+                            // Scripts importing this file need re-evaluating too.
+                            // Imports are watched through their directories, so
+                            // events for other files there stop here.
+                            if let Some(scripts) = importers_clone.lock().get(&path) {
+                                for script in scripts {
+                                    let _ = tx.send(WatchEvent::Modified(script.clone()));
                                 }
                             }
                         }
@@ -84,6 +104,7 @@ impl ScriptWatcher {
             _debouncer: debouncer,
             receiver: rx,
             watched_paths,
+            importers,
         })
     }
 
@@ -129,6 +150,58 @@ impl ScriptWatcher {
         Ok(())
     }
 
+    /// Watch the files a script imports, reporting their changes as changes
+    /// to the script
+    ///
+    /// Replaces the imports previously registered for `script`, so call it
+    /// again after every evaluation with the new list (see
+    /// `SceneResult::imports`). Files no script imports any more stop being
+    /// watched.
+    pub fn watch_imports(&mut self, script: impl AsRef<Path>, imports: &[PathBuf]) -> Result<()> {
+        let script = script.as_ref();
+        let script = script
+            .canonicalize()
+            .unwrap_or_else(|_| script.to_path_buf());
+        let imports: Vec<PathBuf> = imports
+            .iter()
+            .map(|p| p.canonicalize().unwrap_or_else(|_| p.clone()))
+            .collect();
+
+        let mut importers = self.importers.lock();
+        let dirs_before = import_dirs(&importers);
+
+        importers.retain(|file, scripts| {
+            if !imports.contains(file) {
+                scripts.retain(|s| s != &script);
+            }
+            !scripts.is_empty()
+        });
+        for file in imports {
+            let scripts = importers.entry(file).or_default();
+            if !scripts.contains(&script) {
+                scripts.push(script.clone());
+            }
+        }
+
+        // Watching the directory rather than the file keeps the watch alive
+        // when an editor saves by renaming a new file over the old one
+        let dirs_after = import_dirs(&importers);
+        let watcher = self._debouncer.watcher();
+        for dir in dirs_before.difference(&dirs_after) {
+            if !self.watched_paths.lock().contains(dir) {
+                // Already gone if the directory was deleted
+                let _ = watcher.unwatch(dir);
+            }
+        }
+        for dir in dirs_after.difference(&dirs_before) {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|e| anyhow!("Failed to watch imports in {}: {}", dir.display(), e))?;
+        }
+
+        Ok(())
+    }
+
     /// Try to receive a watch event (non-blocking)
     pub fn try_recv(&self) -> Option<WatchEvent> {
         self.receiver.try_recv().ok()
@@ -156,6 +229,15 @@ impl ScriptWatcher {
     }
 }
 
+/// Directories holding the imported files, which are what gets watched
+fn import_dirs(importers: &HashMap<PathBuf, Vec<PathBuf>>) -> HashSet<PathBuf> {
+    importers
+        .keys()
+        .filter_map(|file| file.parent())
+        .map(Path::to_path_buf)
+        .collect()
+}
+
 /// A simple callback-based watcher for integration with event loops
 pub struct CallbackWatcher {
     watcher: ScriptWatcher,
@@ -226,4 +308,59 @@ mod tests {
         let result = watcher.watch("/nonexistent/path/test.rhai");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_import_change_reports_script() {
+        let dir = std::env::temp_dir().join(format!("soyuz_watch_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("main.rhai");
+        let part = dir.join("part.rhai");
+        std::fs::write(&script, "import \"part\" as part; part::shape()").unwrap();
+        std::fs::write(&part, "fn shape() { sphere(0.5) }").unwrap();
+
+        let mut watcher = ScriptWatcher::new(Some(20)).unwrap();
+        watcher
+            .watch_imports(&script, std::slice::from_ref(&part))
+            .unwrap();
+        std::fs::write(&part, "fn shape() { cube(0.5) }").unwrap();
+
+        let script = script.canonicalize().unwrap();
+        let reported = (0..50)
+            .filter_map(|_| watcher.recv_timeout(Duration::from_millis(100)))
+            .any(|event| matches!(event, WatchEvent::Modified(p) if p == script));
+        assert!(reported);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_import_saved_by_rename_reports_script() {
+        let dir = std::env::temp_dir().join(format!("soyuz_watch_rename_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("main.rhai");
+        let part = dir.join("part.rhai");
+        let saved = dir.join("part.rhai.tmp");
+        std::fs::write(&script, "import \"part\" as part; part::shape()").unwrap();
+        std::fs::write(&part, "fn shape() { sphere(0.5) }").unwrap();
+
+        let mut watcher = ScriptWatcher::new(Some(20)).unwrap();
+        watcher
+            .watch_imports(&script, std::slice::from_ref(&part))
+            .unwrap();
+        let script = script.canonicalize().unwrap();
+        let reported = |watcher: &ScriptWatcher| {
+            (0..50)
+                .filter_map(|_| watcher.recv_timeout(Duration::from_millis(100)))
+                .any(|event| matches!(event, WatchEvent::Modified(p) if p == script))
+        };
+
+        // Two saves, so the second shows the watch survived the first rename
+        for body in ["fn shape() { cube(0.5) }", "fn shape() { torus(0.5, 0.1) }"] {
+            std::fs::write(&saved, body).unwrap();
+            std::fs::rename(&saved, &part).unwrap();
+            assert!(reported(&watcher));
+        }
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }
@@ -177,3 +177,268 @@ fn script_with_modifiers() {
     let distance = cpu_sdf.distance(Vec3::ZERO);
     assert!(distance > 0.0, "Should be outside hollow cube at origin");
 }
+
+#[test]
+fn script_with_materials() {
+    let script = r##"
+        let grip = cube(0.5).material(1).color(0.4, 0.25, 0.1);
+        let barrel = cylinder(0.1, 1.0).translate_x(1.0).color_hex("#808080");
+        grip.smooth_union(barrel, 0.05)
+    "##;
+
+    let engine = ScriptEngine::new();
+    let sdf_op = engine
+        .eval_to_sdf_op(script)
+        .expect("Material script should evaluate");
+
+    let cpu_sdf = CpuSdf::new(sdf_op);
+    assert!(cpu_sdf.has_materials());
+
+    // Chained material/color calls merge into one tag
+    let grip = cpu_sdf.material_at(Vec3::new(0.25, 0.0, 0.0), [1.0; 3]);
+    assert_eq!(grip.id, 1);
+    assert!((grip.color[0] - 0.4).abs() < 1e-5);
+
+    // color() alone keeps the default material ID
+    let barrel = cpu_sdf.material_at(Vec3::new(1.1, 0.0, 0.0), [1.0; 3]);
+    assert_eq!(barrel.id, 0);
+    assert!((barrel.color[1] - 128.0 / 255.0).abs() < 1e-5);
+
+    // Tags don't change the distance field
+    let untagged = CpuSdf::new(
+        engine
+            .eval_to_sdf_op("cube(0.5).smooth_union(cylinder(0.1, 1.0).translate_x(1.0), 0.05)")
+            .unwrap(),
+    );
+    let p = Vec3::new(0.6, 0.1, 0.0);
+    assert!((cpu_sdf.distance(p) - untagged.distance(p)).abs() < 1e-6);
+    assert!(!untagged.has_materials());
+}
+
+#[test]
+fn materials_deep_in_a_folded_union() {
+    let script = r#"
+        let row = sphere(0.2).material(3);
+        for i in 1..200 {
+            row = row.union(sphere(0.2).translate_x(i.to_float()));
+        }
+        row.translate_y(1.0)
+    "#;
+
+    let engine = ScriptEngine::new();
+    let cpu_sdf = CpuSdf::new(engine.eval_to_sdf_op(script).expect("should evaluate"));
+    assert!(cpu_sdf.has_materials());
+
+    // The one tagged sphere sits under every union; the rest keep the default
+    let tagged = cpu_sdf.material_at(Vec3::new(0.2, 1.0, 0.0), [1.0; 3]);
+    assert_eq!(tagged.id, 3);
+    let untagged = cpu_sdf.material_at(Vec3::new(150.2, 1.0, 0.0), [1.0; 3]);
+    assert_eq!(untagged.id, 0);
+}
+
+#[test]
+fn fractal_iterations_are_capped() {
+    let engine = ScriptEngine::new();
+    assert!(engine.eval_to_sdf_op("menger_sponge(1.0, 32)").is_ok());
+
+    let err = engine
+        .eval_to_sdf_op("mandelbulb(8.0, 1000000000)")
+        .expect_err("Huge iteration count should fail");
+    assert!(err.to_string().contains("at most 32"), "{err}");
+}
+
+#[test]
+fn polar_repeat_counts_are_checked() {
+    let engine = ScriptEngine::new();
+    assert!(engine.eval_to_sdf_op("sphere(0.1).repeat_polar(8)").is_ok());
+    assert!(
+        engine
+            .eval_to_sdf_op("circle(0.1).repeat_polar(8).extrude(0.1)")
+            .is_ok()
+    );
+
+    for script in [
+        "sphere(0.1).repeat_polar(0)",
+        "sphere(0.1).repeat_polar(100000)",
+        "circle(0.1).repeat_polar(-1).extrude(0.1)",
+    ] {
+        let err = engine
+            .eval_to_sdf_op(script)
+            .expect_err("Bad repeat count should fail");
+        assert!(
+            err.to_string().contains("between 1 and 1024"),
+            "{script}: {err}"
+        );
+    }
+}
+
+#[test]
+fn noise_octaves_are_capped() {
+    let engine = ScriptEngine::new();
+    assert!(
+        engine
+            .eval_scene("let t = fbm(16, 4.0); sphere(1.0)")
+            .is_ok()
+    );
+
+    for script in [
+        "let t = fbm(4000000000, 4.0); sphere(1.0)",
+        "let t = fbm(17, 4.0, 7); sphere(1.0)",
+        "let t = ridged(1000, 4.0); sphere(1.0)",
+        "let t = turbulence(1000, 4.0); sphere(1.0)",
+    ] {
+        let err = engine
+            .eval_scene(script)
+            .expect_err("Huge octave count should fail");
+        assert!(err.to_string().contains("at most 16"), "{script}: {err}");
+    }
+}
+
+#[test]
+fn cones_take_radii_before_height() {
+    let engine = ScriptEngine::new();
+    let sdf = |script: &str| {
+        CpuSdf::new(
+            engine
+                .eval_to_sdf_op(script)
+                .expect("Cone script should evaluate"),
+        )
+    };
+
+    // Wide at the bottom, narrow at the top, one unit tall
+    let capped = sdf("capped_cone(0.5, 0.2, 1.0)");
+    assert!(capped.distance(Vec3::new(0.4, -0.45, 0.0)) < 0.0);
+    assert!(capped.distance(Vec3::new(0.3, 0.45, 0.0)) > 0.0);
+    assert!(capped.distance(Vec3::new(0.0, 0.6, 0.0)) > 0.0);
+
+    let round = sdf("round_cone(0.5, 0.2, 1.0)");
+    assert!(round.distance(Vec3::new(0.4, -0.5, 0.0)) < 0.0);
+    assert!(round.distance(Vec3::new(0.0, 0.65, 0.0)) < 0.0);
+    assert!(round.distance(Vec3::new(0.3, 0.5, 0.0)) > 0.0);
+}
+
+#[test]
+fn script_with_sweeps() {
+    let script = r#"
+        let handle = bezier([[-1, 0, 0], [0, 1.5, 0], [1, 0, 0]]).tube(0.15, 0.1);
+        let rail = polyline([[-1, -0.5, 0], [1, -0.5, 0], [1, -0.5, 1]]).sweep_rect(0.2, 0.1);
+        handle.union(rail)
+    "#;
+
+    let engine = ScriptEngine::new();
+    let cpu_sdf = CpuSdf::new(
+        engine
+            .eval_to_sdf_op(script)
+            .expect("Sweep script should evaluate"),
+    );
+
+    // Halfway along, the curve is halfway to its control point
+    assert!(cpu_sdf.distance(Vec3::new(0.0, 0.75, 0.0)) < 0.0);
+    // The rail turns the corner without a gap on the outside
+    assert!(cpu_sdf.distance(Vec3::new(1.07, -0.5, -0.08)) < 0.0);
+    assert!(cpu_sdf.distance(Vec3::new(0.0, 0.0, 0.0)) > 0.0);
+
+    // Malformed points are reported instead of silently dropped
+    let err = engine
+        .eval_to_sdf_op("polyline([[0, 0, 0], [1, 0]]).tube(0.1)")
+        .expect_err("Two-component point should fail");
+    assert!(err.to_string().contains("point 1"), "{err}");
+    assert!(
+        engine
+            .eval_to_sdf_op("bezier([[0, 0, 0], [1, 0, 0]]).tube(0.1)")
+            .is_err()
+    );
+}
+
+#[test]
+fn script_with_2d_profiles() {
+    let script = r#"
+        let beam = polygon([
+            [-0.5, -0.5], [0.5, -0.5], [0.5, -0.4], [0.05, -0.4],
+            [0.05, 0.4], [0.5, 0.4], [0.5, 0.5], [-0.5, 0.5],
+            [-0.5, 0.4], [-0.05, 0.4], [-0.05, -0.4], [-0.5, -0.4],
+        ]).extrude(1.0);
+        let gear = circle(0.3)
+            .union(rect(0.2, 0.1).translate(0.35, 0.0).repeat_polar(8))
+            .subtract(circle(0.1))
+            .extrude(0.1)
+            .translate(2.0, 0.0, 0.0);
+        let ring = rounded_rect(0.2, 0.4, 0.05).shell(0.02).revolve(0.5).translate(-2.0, 0.0, 0.0);
+        beam.union(gear).union(ring)
+    "#;
+
+    let engine = ScriptEngine::new();
+    let cpu_sdf = CpuSdf::new(
+        engine
+            .eval_to_sdf_op(script)
+            .expect("Profile script should evaluate"),
+    );
+
+    // The I-beam's web and flanges are solid, the notch beside the web is not
+    assert!(cpu_sdf.distance(Vec3::new(0.0, 0.0, 0.9)) < 0.0);
+    assert!(cpu_sdf.distance(Vec3::new(0.4, 0.45, 0.0)) < 0.0);
+    assert!(cpu_sdf.distance(Vec3::new(0.3, 0.0, 0.0)) > 0.0);
+    // Gear teeth repeat around the hub, which has a hole through it
+    assert!(cpu_sdf.distance(Vec3::new(2.0, 0.42, 0.0)) < 0.0);
+    assert!(cpu_sdf.distance(Vec3::new(2.42, 0.0, 0.0)) < 0.0);
+    assert!(cpu_sdf.distance(Vec3::new(2.0, 0.0, 0.0)) > 0.0);
+    // The shelled profile leaves the ring hollow
+    assert!(cpu_sdf.distance(Vec3::new(-2.4, 0.0, 0.0)) < 0.0);
+    assert!(cpu_sdf.distance(Vec3::new(-2.5, 0.0, 0.0)) > 0.0);
+
+    let err = engine
+        .eval_to_sdf_op("polygon([[0, 0], [1, 0]]).extrude(0.1)")
+        .expect_err("Two-point polygon should fail");
+    assert!(err.to_string().contains("at least 3"), "{err}");
+    let err = engine
+        .eval_to_sdf_op("polygon([[0, 0], [1, 0], [1, 1, 1]]).extrude(0.1)")
+        .expect_err("Three-component point should fail");
+    assert!(err.to_string().contains("point 2"), "{err}");
+}
+
+#[test]
+fn script_with_general_transforms() {
+    // A strut between (0, 0, 0) and (1, 1, 1), an ellipsoid-like stretched
+    // box and a sheared block
+    let script = r#"
+        let strut = cylinder(0.05, 1.732).align_to(1.0, 1.0, 1.0).translate(0.5, 0.5, 0.5);
+        let pod = cube(0.5).scale(2.0, 1.0, 0.5).translate(0.0, 0.0, -2.0);
+        let ramp = cube(0.5).transform([[1, 0.5, 0, 2], [0, 1, 0, 0], [0, 0, 1, 0]]);
+        strut.union(pod).union(ramp)
+    "#;
+
+    let engine = ScriptEngine::new();
+    let cpu_sdf = CpuSdf::new(
+        engine
+            .eval_to_sdf_op(script)
+            .expect("Transform script should evaluate"),
+    );
+
+    // The strut runs along the diagonal
+    assert!(cpu_sdf.distance(Vec3::splat(0.9)) < 0.0);
+    assert!(cpu_sdf.distance(Vec3::new(0.9, 0.1, 0.5)) > 0.0);
+    // Stretched along X and squashed along Z
+    assert!(cpu_sdf.distance(Vec3::new(0.45, 0.0, -2.0)) < 0.0);
+    assert!(cpu_sdf.distance(Vec3::new(0.0, 0.0, -1.8)) > 0.0);
+    // The sheared top leans toward +X
+    assert!(cpu_sdf.distance(Vec3::new(2.3, 0.2, 0.0)) < 0.0);
+    assert!(cpu_sdf.distance(Vec3::new(1.8, 0.2, 0.0)) > 0.0);
+
+    // A shape placed with look_at faces its target
+    let op = engine
+        .eval_to_sdf_op("box3(0.1, 0.1, 1.0).look_at(0.0, 0.0, 0.0, 2.0, 0.0, 0.0)")
+        .expect("look_at should evaluate");
+    let sdf = CpuSdf::new(op);
+    assert!(sdf.distance(Vec3::new(0.45, 0.0, 0.0)) < 0.0);
+    assert!(sdf.distance(Vec3::new(0.0, 0.0, 0.45)) > 0.0);
+
+    let err = engine
+        .eval_to_sdf_op("cube(1.0).transform([[1, 0, 0], [0, 1, 0], [1, 1, 0]])")
+        .expect_err("Singular matrix should fail");
+    assert!(err.to_string().contains("inverted"), "{err}");
+    assert!(
+        engine
+            .eval_to_sdf_op("cube(1.0).transform([[1, 0, 0], [0, 1, 0]])")
+            .is_err()
+    );
+}
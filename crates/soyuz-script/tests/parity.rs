@@ -0,0 +1,1058 @@
+//! CPU/GPU parity tests for every `SdfOp` variant
+//!
+//! The preview renders the generated WGSL while export meshes `CpuSdf`, so
+//! every variant needs both paths. Formulas shared through soyuz-math are
+//! checked against their TOML test vectors in that crate; these tests make
+//! sure each variant actually reaches them on the CPU, produces a shader that
+//! compiles and gives the CPU's distances when run, and reports bounds that
+//! enclose the surface.
+
+// Tests are allowed to use expect/unwrap for cleaner error messages
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+// The variant table is long but flat
+#![allow(clippy::too_many_lines)]
+
+use soyuz_core::prelude::{Quat, SdfExt, SdfNode, Vec2, Vec3, Vec4, box3};
+use soyuz_math::wgsl_eval::{Value, WgslModule};
+use soyuz_script::{CpuSdf, Sdf};
+use soyuz_sdf::{Affine, MaterialTag, Sdf2dOp, SdfOp, SweepCurve, SweepPath, build_shader};
+use std::f32::consts::FRAC_PI_2;
+use std::sync::Arc;
+
+fn sphere(radius: f32) -> Arc<SdfOp> {
+    Arc::new(SdfOp::Sphere { radius })
+}
+
+fn circle(radius: f32) -> Arc<Sdf2dOp> {
+    Arc::new(Sdf2dOp::Circle { radius })
+}
+
+fn rect(half_x: f32, half_y: f32) -> Arc<Sdf2dOp> {
+    Arc::new(Sdf2dOp::Rectangle {
+        half_extents: [half_x, half_y],
+    })
+}
+
+fn bar() -> Arc<SdfOp> {
+    Arc::new(SdfOp::Box {
+        half_extents: [0.6, 0.2, 0.3],
+    })
+}
+
+/// One instance of every `SdfOp` variant, named for failure messages
+fn every_variant() -> Vec<(&'static str, SdfOp)> {
+    vec![
+        ("sphere", SdfOp::Sphere { radius: 0.5 }),
+        (
+            "box",
+            SdfOp::Box {
+                half_extents: [0.5, 0.3, 0.2],
+            },
+        ),
+        (
+            "rounded_box",
+            SdfOp::RoundedBox {
+                half_extents: [0.5, 0.3, 0.2],
+                radius: 0.1,
+            },
+        ),
+        (
+            "cylinder",
+            SdfOp::Cylinder {
+                radius: 0.4,
+                half_height: 0.6,
+            },
+        ),
+        (
+            "capsule",
+            SdfOp::Capsule {
+                radius: 0.3,
+                half_height: 0.5,
+            },
+        ),
+        (
+            "torus",
+            SdfOp::Torus {
+                major_radius: 0.6,
+                minor_radius: 0.2,
+            },
+        ),
+        (
+            "cone",
+            SdfOp::Cone {
+                radius: 0.5,
+                height: 1.0,
+            },
+        ),
+        (
+            "plane",
+            SdfOp::Plane {
+                normal: [0.0, 2.0, 0.0],
+                offset: 0.5,
+            },
+        ),
+        (
+            "ellipsoid",
+            SdfOp::Ellipsoid {
+                radii: [0.6, 0.3, 0.4],
+            },
+        ),
+        ("octahedron", SdfOp::Octahedron { size: 0.6 }),
+        (
+            "hex_prism",
+            SdfOp::HexPrism {
+                half_height: 0.3,
+                radius: 0.5,
+            },
+        ),
+        ("tri_prism", SdfOp::TriPrism { size: [0.6, 0.3] }),
+        ("pyramid", SdfOp::Pyramid { height: 1.2 }),
+        (
+            "link",
+            SdfOp::Link {
+                length: 0.3,
+                major_radius: 0.3,
+                minor_radius: 0.1,
+            },
+        ),
+        (
+            "capped_cone",
+            SdfOp::CappedCone {
+                half_height: 0.4,
+                bottom_radius: 0.5,
+                top_radius: 0.2,
+            },
+        ),
+        (
+            "round_cone",
+            SdfOp::RoundCone {
+                bottom_radius: 0.4,
+                top_radius: 0.2,
+                height: 0.8,
+            },
+        ),
+        (
+            "box_frame",
+            SdfOp::BoxFrame {
+                half_extents: [0.6, 0.4, 0.5],
+                half_thickness: 0.05,
+            },
+        ),
+        (
+            "capped_torus",
+            SdfOp::CappedTorus {
+                half_angle: 2.0,
+                major_radius: 0.5,
+                minor_radius: 0.1,
+            },
+        ),
+        (
+            "cut_sphere",
+            SdfOp::CutSphere {
+                radius: 0.6,
+                cut_height: 0.2,
+            },
+        ),
+        (
+            "cut_hollow_sphere",
+            SdfOp::CutHollowSphere {
+                radius: 0.6,
+                cut_height: 0.2,
+                thickness: 0.05,
+            },
+        ),
+        (
+            "rhombus",
+            SdfOp::Rhombus {
+                half_diagonals: [0.6, 0.3],
+                half_height: 0.1,
+                radius: 0.02,
+            },
+        ),
+        (
+            "solid_angle",
+            SdfOp::SolidAngle {
+                half_angle: 0.6,
+                radius: 0.8,
+            },
+        ),
+        (
+            "capsule_between",
+            SdfOp::CapsuleBetween {
+                a: [-0.5, -0.3, 0.1],
+                b: [0.4, 0.5, -0.2],
+                radius: 0.15,
+            },
+        ),
+        (
+            "cylinder_between",
+            SdfOp::CylinderBetween {
+                a: [-0.5, -0.3, 0.1],
+                b: [0.4, 0.5, -0.2],
+                radius: 0.15,
+            },
+        ),
+        (
+            "infinite_cylinder",
+            SdfOp::InfiniteCylinder {
+                axis: [0.0, 0.0, 1.0],
+                radius: 0.3,
+            },
+        ),
+        (
+            "mandelbulb",
+            SdfOp::Mandelbulb {
+                power: 8.0,
+                iterations: 6,
+            },
+        ),
+        (
+            "menger_sponge",
+            SdfOp::MengerSponge {
+                size: 0.6,
+                iterations: 3,
+            },
+        ),
+        (
+            "sierpinski",
+            SdfOp::Sierpinski {
+                size: 0.6,
+                iterations: 4,
+            },
+        ),
+        (
+            "julia",
+            SdfOp::Julia {
+                c: [-0.2, 0.6, 0.2, 0.2],
+                iterations: 8,
+            },
+        ),
+        (
+            "union",
+            SdfOp::Union {
+                a: sphere(0.5),
+                b: bar(),
+            },
+        ),
+        (
+            "subtract",
+            SdfOp::Subtract {
+                a: sphere(0.5),
+                b: bar(),
+            },
+        ),
+        (
+            "intersect",
+            SdfOp::Intersect {
+                a: sphere(0.5),
+                b: bar(),
+            },
+        ),
+        (
+            "smooth_union",
+            SdfOp::SmoothUnion {
+                a: sphere(0.5),
+                b: bar(),
+                k: 0.2,
+            },
+        ),
+        (
+            "smooth_subtract",
+            SdfOp::SmoothSubtract {
+                a: sphere(0.5),
+                b: bar(),
+                k: 0.2,
+            },
+        ),
+        (
+            "smooth_intersect",
+            SdfOp::SmoothIntersect {
+                a: sphere(0.5),
+                b: bar(),
+                k: 0.2,
+            },
+        ),
+        (
+            "xor",
+            SdfOp::Xor {
+                a: sphere(0.5),
+                b: bar(),
+            },
+        ),
+        (
+            "shell",
+            SdfOp::Shell {
+                inner: sphere(0.5),
+                thickness: 0.05,
+            },
+        ),
+        (
+            "round",
+            SdfOp::Round {
+                inner: bar(),
+                radius: 0.1,
+            },
+        ),
+        (
+            "onion",
+            SdfOp::Onion {
+                inner: sphere(0.5),
+                thickness: 0.05,
+            },
+        ),
+        (
+            "elongate",
+            SdfOp::Elongate {
+                inner: sphere(0.3),
+                h: [0.4, 0.0, 0.1],
+            },
+        ),
+        (
+            "translate",
+            SdfOp::Translate {
+                inner: bar(),
+                offset: [0.5, -0.25, 1.0],
+            },
+        ),
+        (
+            "rotate_x",
+            SdfOp::RotateX {
+                inner: bar(),
+                angle: 0.7,
+            },
+        ),
+        (
+            "rotate_y",
+            SdfOp::RotateY {
+                inner: bar(),
+                angle: 0.7,
+            },
+        ),
+        (
+            "rotate_z",
+            SdfOp::RotateZ {
+                inner: bar(),
+                angle: 0.7,
+            },
+        ),
+        (
+            "scale",
+            SdfOp::Scale {
+                inner: bar(),
+                factor: 1.5,
+            },
+        ),
+        (
+            "rotate_quat",
+            SdfOp::Rotate {
+                inner: bar(),
+                rotation: [0.3, -0.2, 0.5, 0.8],
+            },
+        ),
+        (
+            "non_uniform_scale",
+            SdfOp::NonUniformScale {
+                inner: sphere(0.4),
+                factors: [2.0, 0.5, -1.0],
+            },
+        ),
+        (
+            "transform",
+            SdfOp::Transform {
+                inner: bar(),
+                transform: Affine::new(
+                    [[1.0, 0.2, 0.0], [0.8, 1.0, 0.0], [0.0, 0.3, 1.5]],
+                    [0.2, -0.1, 0.3],
+                )
+                .expect("invertible"),
+            },
+        ),
+        (
+            "mirror",
+            SdfOp::Mirror {
+                inner: Arc::new(SdfOp::Translate {
+                    inner: sphere(0.3),
+                    offset: [-0.5, 0.0, 0.0],
+                }),
+                axis: [-1.0, 0.0, 0.0],
+            },
+        ),
+        (
+            "symmetry_x",
+            SdfOp::SymmetryX {
+                inner: Arc::new(SdfOp::Translate {
+                    inner: sphere(0.3),
+                    offset: [0.5, 0.0, 0.0],
+                }),
+            },
+        ),
+        (
+            "symmetry_y",
+            SdfOp::SymmetryY {
+                inner: Arc::new(SdfOp::Translate {
+                    inner: sphere(0.3),
+                    offset: [0.0, 0.5, 0.0],
+                }),
+            },
+        ),
+        (
+            "symmetry_z",
+            SdfOp::SymmetryZ {
+                inner: Arc::new(SdfOp::Translate {
+                    inner: sphere(0.3),
+                    offset: [0.0, 0.0, 0.5],
+                }),
+            },
+        ),
+        (
+            "twist",
+            SdfOp::Twist {
+                inner: Arc::new(SdfOp::Box {
+                    half_extents: [0.5, 0.8, 0.2],
+                }),
+                amount: 1.5,
+            },
+        ),
+        (
+            "bend",
+            SdfOp::Bend {
+                inner: bar(),
+                amount: 1.5,
+            },
+        ),
+        (
+            "displacement",
+            SdfOp::Displacement {
+                inner: sphere(0.5),
+                amount: 0.1,
+                frequency: 4.0,
+            },
+        ),
+        (
+            "extrude_circle",
+            SdfOp::Extrude {
+                profile: circle(0.4),
+                depth: 0.3,
+            },
+        ),
+        (
+            "extrude_rectangle",
+            SdfOp::Extrude {
+                profile: rect(0.5, 0.3),
+                depth: 0.3,
+            },
+        ),
+        (
+            "extrude_rounded_rectangle",
+            SdfOp::Extrude {
+                profile: Arc::new(Sdf2dOp::RoundedRectangle {
+                    half_extents: [0.5, 0.3],
+                    radius: 0.15,
+                }),
+                depth: 0.3,
+            },
+        ),
+        (
+            "revolve_circle",
+            SdfOp::Revolve {
+                profile: circle(0.2),
+                offset: 0.6,
+            },
+        ),
+        (
+            "revolve_rectangle",
+            SdfOp::Revolve {
+                profile: rect(0.15, 0.25),
+                offset: 0.6,
+            },
+        ),
+        (
+            "extrude_polygon",
+            SdfOp::Extrude {
+                profile: Arc::new(Sdf2dOp::Polygon {
+                    points: vec![
+                        [-0.5, -0.4],
+                        [0.5, -0.4],
+                        [0.5, -0.2],
+                        [0.1, -0.2],
+                        [0.1, 0.2],
+                        [0.5, 0.2],
+                        [0.5, 0.4],
+                        [-0.5, 0.4],
+                        [-0.5, 0.2],
+                        [-0.1, 0.2],
+                        [-0.1, -0.2],
+                        [-0.5, -0.2],
+                    ],
+                }),
+                depth: 0.3,
+            },
+        ),
+        (
+            "revolve_arc",
+            SdfOp::Revolve {
+                profile: Arc::new(Sdf2dOp::Arc {
+                    half_angle: 1.2,
+                    radius: 0.4,
+                    half_thickness: 0.05,
+                }),
+                offset: 0.3,
+            },
+        ),
+        (
+            "extrude_profile_booleans",
+            SdfOp::Extrude {
+                profile: Arc::new(Sdf2dOp::Subtract {
+                    a: Arc::new(Sdf2dOp::Union {
+                        a: rect(0.5, 0.2),
+                        b: circle(0.35),
+                    }),
+                    b: Arc::new(Sdf2dOp::Intersect {
+                        a: circle(0.2),
+                        b: rect(0.1, 0.3),
+                    }),
+                }),
+                depth: 0.2,
+            },
+        ),
+        (
+            "extrude_profile_smooth_booleans",
+            SdfOp::Extrude {
+                profile: Arc::new(Sdf2dOp::SmoothSubtract {
+                    a: Arc::new(Sdf2dOp::SmoothUnion {
+                        a: rect(0.5, 0.2),
+                        b: circle(0.35),
+                        k: 0.1,
+                    }),
+                    b: Arc::new(Sdf2dOp::SmoothIntersect {
+                        a: circle(0.2),
+                        b: rect(0.1, 0.3),
+                        k: 0.05,
+                    }),
+                    k: 0.05,
+                }),
+                depth: 0.2,
+            },
+        ),
+        (
+            "revolve_profile_offset_shell",
+            SdfOp::Revolve {
+                profile: Arc::new(Sdf2dOp::Shell {
+                    inner: Arc::new(Sdf2dOp::Offset {
+                        inner: rect(0.1, 0.3),
+                        distance: 0.05,
+                    }),
+                    thickness: 0.03,
+                }),
+                offset: 0.4,
+            },
+        ),
+        (
+            "extrude_profile_transforms",
+            SdfOp::Extrude {
+                profile: Arc::new(Sdf2dOp::Translate {
+                    inner: Arc::new(Sdf2dOp::Rotate {
+                        inner: Arc::new(Sdf2dOp::Scale {
+                            inner: rect(0.5, 0.1),
+                            factor: 1.5,
+                        }),
+                        angle: 0.6,
+                    }),
+                    offset: [0.2, -0.1],
+                }),
+                depth: 0.2,
+            },
+        ),
+        (
+            "extrude_profile_repeat_infinite",
+            SdfOp::Extrude {
+                profile: Arc::new(Sdf2dOp::RepeatInfinite {
+                    inner: circle(0.15),
+                    spacing: [0.5, 0.5],
+                }),
+                depth: 0.1,
+            },
+        ),
+        (
+            "extrude_profile_repeat_limited",
+            SdfOp::Extrude {
+                profile: Arc::new(Sdf2dOp::RepeatLimited {
+                    inner: circle(0.15),
+                    spacing: [0.4, 0.4],
+                    count: [2.0, 1.0],
+                }),
+                depth: 0.1,
+            },
+        ),
+        (
+            "extrude_profile_repeat_polar",
+            SdfOp::Extrude {
+                profile: Arc::new(Sdf2dOp::Union {
+                    a: circle(0.3),
+                    b: Arc::new(Sdf2dOp::RepeatPolar {
+                        inner: Arc::new(Sdf2dOp::Translate {
+                            inner: rect(0.15, 0.08),
+                            offset: [0.4, 0.0],
+                        }),
+                        count: 8,
+                    }),
+                }),
+                depth: 0.1,
+            },
+        ),
+        (
+            "tube",
+            SdfOp::Tube {
+                path: SweepPath::new(
+                    SweepCurve::Polyline,
+                    vec![[-0.6, 0.0, 0.0], [0.0, 0.4, 0.0], [0.4, 0.0, 0.5]],
+                ),
+                start_radius: 0.2,
+                end_radius: 0.2,
+            },
+        ),
+        (
+            "tube_bezier_tapered",
+            SdfOp::Tube {
+                path: SweepPath::new(
+                    SweepCurve::QuadraticBezier,
+                    vec![[-0.6, 0.0, 0.0], [0.0, 0.8, 0.0], [0.6, 0.0, 0.0]],
+                ),
+                start_radius: 0.25,
+                end_radius: 0.05,
+            },
+        ),
+        (
+            "sweep_catmull_rom",
+            SdfOp::Sweep {
+                path: SweepPath::new(
+                    SweepCurve::CatmullRom,
+                    vec![
+                        [-0.6, 0.0, 0.0],
+                        [-0.2, 0.3, 0.2],
+                        [0.2, -0.3, -0.2],
+                        [0.6, 0.0, 0.0],
+                    ],
+                ),
+                profile: Arc::new(Sdf2dOp::RoundedRectangle {
+                    half_extents: [0.15, 0.1],
+                    radius: 0.05,
+                }),
+            },
+        ),
+        (
+            "sweep_polyline",
+            SdfOp::Sweep {
+                path: SweepPath::new(
+                    SweepCurve::Polyline,
+                    vec![[-0.6, 0.0, 0.0], [0.3, 0.0, 0.0], [0.3, 0.0, 0.6]],
+                ),
+                profile: rect(0.15, 0.1),
+            },
+        ),
+        (
+            "repeat_infinite",
+            SdfOp::RepeatInfinite {
+                inner: sphere(0.2),
+                spacing: [1.0, 0.0, 1.0],
+            },
+        ),
+        (
+            "repeat_limited",
+            SdfOp::RepeatLimited {
+                inner: sphere(0.2),
+                spacing: [0.6, 0.6, 0.0],
+                count: [2.0, 1.0, 0.0],
+            },
+        ),
+        (
+            "repeat_polar",
+            SdfOp::RepeatPolar {
+                inner: Arc::new(SdfOp::Translate {
+                    inner: sphere(0.15),
+                    offset: [0.7, 0.0, 0.0],
+                }),
+                count: 6,
+            },
+        ),
+        (
+            "material",
+            SdfOp::Material {
+                inner: bar(),
+                material: MaterialTag {
+                    id: 1,
+                    color: Some([0.8, 0.2, 0.1]),
+                },
+            },
+        ),
+    ]
+}
+
+/// Regular grid of `n`^3 points covering `[min, max]`
+fn grid(min: Vec3, max: Vec3, n: usize) -> impl Iterator<Item = Vec3> {
+    let step = (max - min) / (n - 1) as f32;
+    (0..n * n * n).map(move |i| {
+        let (x, y, z) = (i % n, (i / n) % n, i / (n * n));
+        min + step * Vec3::new(x as f32, y as f32, z as f32)
+    })
+}
+
+#[test]
+fn cpu_distance_is_finite_for_every_variant() {
+    for (name, op) in every_variant() {
+        let sdf = CpuSdf::new(op);
+        for p in grid(Vec3::splat(-3.0), Vec3::splat(3.0), 13) {
+            let d = sdf.distance(p);
+            assert!(
+                d.is_finite() && d.abs() < 1.0e6,
+                "{name}: distance at {p} is {d}"
+            );
+        }
+    }
+}
+
+#[test]
+fn cpu_bounds_contain_the_surface() {
+    // Infinite by construction: the plane, the infinite cylinder, infinite
+    // repetition (in 3D or of a profile) and the onion's endless concentric
+    // shells
+    let unbounded = [
+        "plane",
+        "infinite_cylinder",
+        "repeat_infinite",
+        "extrude_profile_repeat_infinite",
+        "onion",
+    ];
+
+    for (name, op) in every_variant() {
+        if unbounded.contains(&name) {
+            continue;
+        }
+
+        let sdf = CpuSdf::new(op);
+        let bounds = sdf.bounds();
+        let search = bounds.expand(1.0);
+        let tolerance = 1.0e-3;
+
+        for p in grid(search.min, search.max, 40) {
+            if sdf.distance(p) >= 0.0 {
+                continue;
+            }
+            let outside = (bounds.min - p).max(p - bounds.max).max_element();
+            assert!(
+                outside < tolerance,
+                "{name}: interior point {p} lies outside bounds {:?}..{:?}",
+                bounds.min,
+                bounds.max
+            );
+        }
+    }
+}
+
+#[test]
+fn generated_shader_validates_for_every_variant() {
+    for (name, op) in every_variant() {
+        let shader = build_shader(&op);
+
+        let module = naga::front::wgsl::parse_str(&shader)
+            .unwrap_or_else(|e| panic!("{name}: {}", e.emit_to_string(&shader)));
+
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .unwrap_or_else(|e| panic!("{name}: {}", e.emit_to_string(&shader)));
+    }
+}
+
+#[test]
+fn shader_distances_match_the_cpu_for_every_variant() {
+    for (name, op) in every_variant() {
+        let shader = build_shader(&op);
+        let module = WgslModule::parse(&shader).unwrap_or_else(|e| panic!("{name}: {e}"));
+        let sdf = CpuSdf::new(op);
+
+        for p in grid(Vec3::splat(-1.5), Vec3::splat(1.5), 7) {
+            let gpu = module
+                .call("scene_sdf", &[Value::from(p)])
+                .unwrap_or_else(|e| panic!("{name}: {e}"))
+                .to_f32s()
+                .expect("scene_sdf returns a float")[0];
+            let cpu = sdf.distance(p);
+            assert!(
+                (gpu - cpu).abs() <= 1.0e-4 * cpu.abs().max(1.0),
+                "{name}: at {p} the shader gives {gpu} and the CPU {cpu}"
+            );
+        }
+    }
+}
+
+#[test]
+fn cpu_uses_shared_formulas() {
+    // Leaves whose WGSL comes from soyuz-math must evaluate the same
+    // generated function on the CPU
+    let p = Vec3::new(0.3, 0.4, -0.2);
+    let cases: Vec<(SdfOp, f32)> = vec![
+        (
+            SdfOp::Cone {
+                radius: 0.5,
+                height: 1.0,
+            },
+            soyuz_math::sd_cone(p, 0.5, 1.0),
+        ),
+        (
+            SdfOp::Pyramid { height: 1.2 },
+            soyuz_math::sd_pyramid(p, 1.2),
+        ),
+        (
+            SdfOp::Link {
+                length: 0.3,
+                major_radius: 0.3,
+                minor_radius: 0.1,
+            },
+            soyuz_math::sd_link(p, 0.3, 0.3, 0.1),
+        ),
+        (
+            SdfOp::CappedTorus {
+                half_angle: 2.0,
+                major_radius: 0.5,
+                minor_radius: 0.1,
+            },
+            soyuz_math::sd_capped_torus(p, Vec2::new(2.0_f32.sin(), 2.0_f32.cos()), 0.5, 0.1),
+        ),
+        (
+            SdfOp::InfiniteCylinder {
+                axis: [0.0, 0.0, 2.0],
+                radius: 0.3,
+            },
+            soyuz_math::sd_infinite_cylinder(p, Vec3::Z, 0.3),
+        ),
+        (
+            SdfOp::MengerSponge {
+                size: 0.6,
+                iterations: 3,
+            },
+            soyuz_math::sd_menger_sponge(p, 0.6, 3.0),
+        ),
+        (
+            SdfOp::Julia {
+                c: [-0.2, 0.6, 0.2, 0.2],
+                iterations: 8,
+            },
+            soyuz_math::sd_julia(p, Vec4::new(-0.2, 0.6, 0.2, 0.2), 8.0),
+        ),
+        (
+            SdfOp::Xor {
+                a: sphere(0.5),
+                b: sphere(0.3),
+            },
+            soyuz_math::op_xor(p.length() - 0.5, p.length() - 0.3),
+        ),
+        (
+            SdfOp::Displacement {
+                inner: sphere(0.5),
+                amount: 0.1,
+                frequency: 4.0,
+            },
+            soyuz_math::op_displacement(p.length() - 0.5, p, 0.1, 4.0),
+        ),
+        (
+            SdfOp::Extrude {
+                profile: Arc::new(Sdf2dOp::RoundedRectangle {
+                    half_extents: [0.5, 0.3],
+                    radius: 0.15,
+                }),
+                depth: 0.3,
+            },
+            soyuz_math::op_extrude(
+                soyuz_math::sd_rounded_box_2d(Vec2::new(p.x, p.y), Vec2::new(0.5, 0.3), 0.15),
+                p.z,
+                0.3,
+            ),
+        ),
+        (
+            SdfOp::Revolve {
+                profile: circle(0.2),
+                offset: 0.6,
+            },
+            soyuz_math::sd_circle_2d(soyuz_math::op_revolve(p, 0.6), 0.2),
+        ),
+        (
+            SdfOp::Extrude {
+                profile: Arc::new(Sdf2dOp::Polygon {
+                    points: vec![[-0.5, -0.5], [0.5, -0.5], [0.0, 0.5]],
+                }),
+                depth: 0.3,
+            },
+            {
+                let q = Vec2::new(p.x, p.y);
+                let points = [
+                    Vec2::new(-0.5, -0.5),
+                    Vec2::new(0.5, -0.5),
+                    Vec2::new(0.0, 0.5),
+                ];
+                let mut acc = Vec2::new(1.0e20, 1.0);
+                for i in 0..3 {
+                    acc = soyuz_math::sd_polygon_edge(q, points[i], points[(i + 2) % 3], acc);
+                }
+                soyuz_math::op_extrude(acc.y * acc.x.sqrt(), p.z, 0.3)
+            },
+        ),
+        (
+            SdfOp::Revolve {
+                profile: Arc::new(Sdf2dOp::Arc {
+                    half_angle: 1.2,
+                    radius: 0.4,
+                    half_thickness: 0.05,
+                }),
+                offset: 0.3,
+            },
+            soyuz_math::sd_arc_2d(
+                soyuz_math::op_revolve(p, 0.3),
+                Vec2::new(1.2_f32.sin(), 1.2_f32.cos()),
+                0.4,
+                0.05,
+            ),
+        ),
+        (
+            SdfOp::Rotate {
+                inner: bar(),
+                rotation: [0.0, 0.0, 0.6, 0.8],
+            },
+            soyuz_math::sd_box(
+                soyuz_math::op_rotate_quat(p, Vec4::new(0.0, 0.0, 0.6, 0.8)),
+                Vec3::new(0.6, 0.2, 0.3),
+            ),
+        ),
+        (
+            SdfOp::NonUniformScale {
+                inner: sphere(0.4),
+                factors: [2.0, 0.5, -1.0],
+            },
+            soyuz_math::sd_sphere(soyuz_math::op_scale_xyz(p, Vec3::new(2.0, 0.5, -1.0)), 0.4)
+                * 0.5,
+        ),
+        (
+            SdfOp::Transform {
+                inner: sphere(0.4),
+                transform: Affine::new(
+                    [[2.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.5]],
+                    [0.1, 0.0, 0.0],
+                )
+                .expect("invertible"),
+            },
+            soyuz_math::sd_sphere(
+                soyuz_math::op_affine(
+                    p,
+                    Vec3::new(0.5, 0.0, 0.0),
+                    Vec3::Y,
+                    Vec3::new(0.0, 0.0, 2.0),
+                    Vec3::new(0.1, 0.0, 0.0),
+                ),
+                0.4,
+            ) * 0.5,
+        ),
+        (
+            SdfOp::Tube {
+                path: SweepPath::new(
+                    SweepCurve::Polyline,
+                    vec![[0.0, -0.5, 0.0], [0.0, 0.5, 0.0]],
+                ),
+                start_radius: 0.3,
+                end_radius: 0.1,
+            },
+            soyuz_math::sd_round_cone_between(
+                p,
+                Vec3::new(0.0, -0.5, 0.0),
+                Vec3::new(0.0, 0.5, 0.0),
+                0.3,
+                0.1,
+            ),
+        ),
+        (
+            SdfOp::Sweep {
+                path: SweepPath::new(
+                    SweepCurve::Polyline,
+                    vec![[-0.5, 0.0, 0.0], [0.5, 0.0, 0.0]],
+                ),
+                profile: circle(0.3),
+            },
+            {
+                let f = soyuz_math::op_sweep_frame(
+                    p,
+                    Vec3::new(-0.5, 0.0, 0.0),
+                    Vec3::new(0.5, 0.0, 0.0),
+                    Vec3::Z,
+                    Vec3::Y,
+                    Vec3::X,
+                    Vec3::X,
+                );
+                soyuz_math::op_sweep_cap(soyuz_math::sd_circle_2d(f.truncate(), 0.3), f.z)
+            },
+        ),
+    ];
+
+    for (op, expected) in cases {
+        let d = CpuSdf::new(op.clone()).distance(p);
+        assert!((d - expected).abs() < 1.0e-6, "{op:?}: {d} != {expected}");
+    }
+}
+
+#[test]
+fn rotations_match_the_core_library() {
+    // soyuz-core rotates shapes by +angle (right-hand rule); the CPU
+    // evaluator and the generated shader both follow it
+    let angle = 0.7;
+    let cases: Vec<(SdfOp, SdfNode)> = vec![
+        (
+            SdfOp::RotateX {
+                inner: bar(),
+                angle,
+            },
+            box3(Vec3::new(0.6, 0.2, 0.3)).rotate_x(angle),
+        ),
+        (
+            SdfOp::RotateY {
+                inner: bar(),
+                angle,
+            },
+            box3(Vec3::new(0.6, 0.2, 0.3)).rotate_y(angle),
+        ),
+        (
+            SdfOp::RotateZ {
+                inner: bar(),
+                angle,
+            },
+            box3(Vec3::new(0.6, 0.2, 0.3)).rotate_z(angle),
+        ),
+        (
+            SdfOp::Rotate {
+                inner: bar(),
+                rotation: Quat::from_axis_angle(Vec3::new(1.0, 2.0, -0.5).normalize(), angle)
+                    .to_array(),
+            },
+            box3(Vec3::new(0.6, 0.2, 0.3)).rotate(Vec3::new(1.0, 2.0, -0.5), angle),
+        ),
+    ];
+
+    for (op, reference) in cases {
+        let sdf = CpuSdf::new(op.clone());
+        for p in grid(Vec3::splat(-1.0), Vec3::splat(1.0), 9) {
+            let (d, expected) = (sdf.distance(p), reference.distance(p));
+            assert!(
+                (d - expected).abs() < 1.0e-5,
+                "{op:?} at {p}: {d} != {expected}"
+            );
+        }
+    }
+
+    // A bar along X turned a quarter turn around Z lies along Y
+    let sdf = CpuSdf::new(SdfOp::RotateZ {
+        inner: bar(),
+        angle: FRAC_PI_2,
+    });
+    assert!(sdf.distance(Vec3::new(0.0, 0.5, 0.0)) < 0.0);
+    assert!(sdf.distance(Vec3::new(0.5, 0.0, 0.0)) > 0.0);
+}
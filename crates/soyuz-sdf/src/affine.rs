@@ -0,0 +1,207 @@
+//! General linear transforms
+//!
+//! An [`Affine`] maps a shape by any invertible 3x3 matrix and then moves it:
+//! rotation, non-uniform scale, shear and mirroring in one node. Evaluators
+//! need the inverse and a distance correction at every point, so both are
+//! computed once, when the transform is built, and rebuilt when one is
+//! deserialized; only the matrix and offset are stored.
+
+use serde::{Deserialize, Serialize};
+
+/// An invertible matrix followed by an offset
+///
+/// Distances measured in the shape's local space are multiplied by
+/// [`distance_scale`](Self::distance_scale), the matrix's smallest singular
+/// value. That is the least any direction is stretched by, so the corrected
+/// distance never overshoots the surface; it is exact for rotations and
+/// uniform scales and an underestimate otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "AffineData", into = "AffineData")]
+pub struct Affine {
+    columns: [[f32; 3]; 3],
+    offset: [f32; 3],
+    inverse: [[f32; 3]; 3],
+    distance_scale: f32,
+}
+
+/// Serialized form of an [`Affine`]: the matrix and offset it was built from
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct AffineData {
+    columns: [[f32; 3]; 3],
+    offset: [f32; 3],
+}
+
+impl TryFrom<AffineData> for Affine {
+    type Error = &'static str;
+
+    fn try_from(data: AffineData) -> Result<Self, Self::Error> {
+        Self::new(data.columns, data.offset).ok_or("transform matrix can't be inverted")
+    }
+}
+
+impl From<Affine> for AffineData {
+    fn from(transform: Affine) -> Self {
+        Self {
+            columns: transform.columns,
+            offset: transform.offset,
+        }
+    }
+}
+
+impl Affine {
+    /// Build a transform from the matrix's columns and an offset
+    ///
+    /// Returns `None` if the matrix flattens space and can't be inverted.
+    pub fn new(columns: [[f32; 3]; 3], offset: [f32; 3]) -> Option<Self> {
+        let m = columns.map(|c| c.map(f64::from));
+        let [a, b, c] = m;
+        let det = dot(a, cross(b, c));
+        if !det.is_finite() || det.abs() < 1e-12 {
+            return None;
+        }
+
+        // Rows of the inverse are the cross products of the columns
+        let rows = [cross(b, c), cross(c, a), cross(a, b)].map(|r| r.map(|v| v / det));
+        let inverse = [0, 1, 2].map(|j| rows.map(|r| r[j] as f32));
+
+        Some(Self {
+            columns,
+            offset,
+            inverse,
+            distance_scale: min_eigenvalue(gram(m)).max(0.0).sqrt() as f32,
+        })
+    }
+
+    /// Columns of the matrix
+    pub fn columns(&self) -> [[f32; 3]; 3] {
+        self.columns
+    }
+
+    /// Where the shape's origin ends up
+    pub fn offset(&self) -> [f32; 3] {
+        self.offset
+    }
+
+    /// Columns of the inverse matrix
+    pub fn inverse(&self) -> [[f32; 3]; 3] {
+        self.inverse
+    }
+
+    /// Factor that turns local distances into conservative world distances
+    pub fn distance_scale(&self) -> f32 {
+        self.distance_scale
+    }
+
+    /// Map a point from the shape's local space
+    pub fn apply(&self, p: [f32; 3]) -> [f32; 3] {
+        let [a, b, c] = self.columns;
+        [0, 1, 2].map(|i| a[i] * p[0] + b[i] * p[1] + c[i] * p[2] + self.offset[i])
+    }
+}
+
+/// `MᵀM` for the matrix with columns `m`, as its six distinct entries
+/// `[xx, yy, zz, xy, xz, yz]`
+fn gram(m: [[f64; 3]; 3]) -> [f64; 6] {
+    [
+        dot(m[0], m[0]),
+        dot(m[1], m[1]),
+        dot(m[2], m[2]),
+        dot(m[0], m[1]),
+        dot(m[0], m[2]),
+        dot(m[1], m[2]),
+    ]
+}
+
+/// Smallest eigenvalue of a symmetric 3x3 matrix, in closed form
+fn min_eigenvalue([xx, yy, zz, xy, xz, yz]: [f64; 6]) -> f64 {
+    let off = xy * xy + xz * xz + yz * yz;
+    let q = (xx + yy + zz) / 3.0;
+    let spread = (xx - q).powi(2) + (yy - q).powi(2) + (zz - q).powi(2) + 2.0 * off;
+    if spread <= 1e-24 {
+        // A multiple of the identity
+        return q;
+    }
+    let p = (spread / 6.0).sqrt();
+    let (bx, by, bz) = ((xx - q) / p, (yy - q) / p, (zz - q) / p);
+    let (bxy, bxz, byz) = (xy / p, xz / p, yz / p);
+    let det =
+        bx * (by * bz - byz * byz) - bxy * (bxy * bz - byz * bxz) + bxz * (bxy * byz - by * bxz);
+    let phi = (det / 2.0).clamp(-1.0, 1.0).acos() / 3.0;
+    q + 2.0 * p * (phi + 2.0 * std::f64::consts::FRAC_PI_3).cos()
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    fn assert_close(a: [f32; 3], b: [f32; 3]) {
+        let d = (0..3).map(|i| (a[i] - b[i]).abs()).fold(0.0, f32::max);
+        assert!(d < 1e-5, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_inverse_undoes_the_matrix() {
+        let transform = Affine::new(
+            [[2.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 0.5, 3.0]],
+            [1.0, 2.0, 3.0],
+        )
+        .expect("invertible");
+        let point = [0.3, -0.7, 1.1];
+        let moved = transform.apply(point);
+        let offset = transform.offset();
+        let local = [0, 1, 2].map(|i| moved[i] - offset[i]);
+        let inverse = transform.inverse();
+        assert_close(
+            [0, 1, 2].map(|i| (0..3).map(|j| inverse[j][i] * local[j]).sum()),
+            point,
+        );
+    }
+
+    #[test]
+    fn test_distance_scale_is_smallest_stretch() {
+        let t = Affine::new(IDENTITY, [5.0, 0.0, 0.0]).expect("invertible");
+        assert!((t.distance_scale() - 1.0).abs() < 1e-6);
+
+        let t = Affine::new(
+            [[3.0, 0.0, 0.0], [0.0, 0.5, 0.0], [0.0, 0.0, 2.0]],
+            [0.0; 3],
+        )
+        .expect("invertible");
+        assert!((t.distance_scale() - 0.5).abs() < 1e-6);
+
+        // A rotation stretches nothing
+        let (s, c) = 0.6_f32.sin_cos();
+        let t = Affine::new([[c, s, 0.0], [-s, c, 0.0], [0.0, 0.0, 1.0]], [0.0; 3])
+            .expect("invertible");
+        assert!((t.distance_scale() - 1.0).abs() < 1e-5);
+
+        // Shearing by 1 squashes the diagonal to (sqrt(5) - 1) / 2
+        let t = Affine::new(
+            [[1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            [0.0; 3],
+        )
+        .expect("invertible");
+        assert!((t.distance_scale() - (5.0_f32.sqrt() - 1.0) / 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_singular_matrix_is_rejected() {
+        let flat = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0]];
+        assert!(Affine::new(flat, [0.0; 3]).is_none());
+    }
+}
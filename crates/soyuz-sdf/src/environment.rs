@@ -4,9 +4,13 @@
 //! that can be controlled from Rhai scripts.
 
 use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
 
 /// Environment settings that control the appearance of the scene
-#[derive(Debug, Clone)]
+///
+/// Fields missing from serialized settings take their default values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Environment {
     // Lighting
     /// Sun light direction (will be normalized)
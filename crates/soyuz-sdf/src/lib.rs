@@ -6,6 +6,9 @@
 //! ## Key Types
 //!
 //! - [`SdfOp`] - The SDF operation tree representation
+//! - [`Affine`] - General matrix transforms with their distance correction
+//! - [`Sdf2dOp`] - 2D profile shapes for extrusion, revolution and sweeps
+//! - [`SweepPath`] - Tessellated paths for swept tubes and profiles
 //! - [`WgslGenerator`] - Converts [`SdfOp`] trees to WGSL shader code
 //! - [`Environment`] - Lighting, material, and background settings
 //!
@@ -21,10 +24,20 @@
 //! let shader = build_shader(&sdf);
 //! ```
 
+mod affine;
 mod environment;
+mod sdf2d;
 mod sdf_op;
+mod sweep;
 mod wgsl_gen;
 
+pub use affine::Affine;
 pub use environment::{Environment, EnvironmentUniforms};
-pub use sdf_op::{ExtrudeProfile, RevolveProfile, SdfOp};
-pub use wgsl_gen::{WgslGenerator, build_shader, get_base_shader, inject_scene_sdf};
+pub use sdf_op::{MaterialTag, SdfOp, TaggedNodes};
+pub use sdf2d::Sdf2dOp;
+#[allow(deprecated)]
+pub use sdf2d::{ExtrudeProfile, RevolveProfile};
+pub use sweep::{CURVE_SEGMENTS, SweepCurve, SweepPath, SweepSegment};
+pub use wgsl_gen::{
+    WgslGenerator, build_shader, get_base_shader, inject_scene_color, inject_scene_sdf,
+};
@@ -0,0 +1,201 @@
+//! 2D profile shapes
+//!
+//! [`Sdf2dOp`] trees describe the cross-sections used by extrusion,
+//! revolution and sweeps. They mirror the 3D tree: primitives, booleans,
+//! offsets, transforms and repetition, all evaluated in the profile plane.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Represents a 2D profile shape in a format suitable for shader generation.
+///
+/// Like [`SdfOp`](crate::SdfOp), children are shared through `Arc` and the
+/// enum is `#[non_exhaustive]` so new shapes can be added later. It
+/// serializes the same way too.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Sdf2dOp {
+    // Primitives
+    Circle {
+        radius: f32,
+    },
+    Rectangle {
+        half_extents: [f32; 2],
+    },
+    RoundedRectangle {
+        half_extents: [f32; 2],
+        radius: f32,
+    },
+    Polygon {
+        points: Vec<[f32; 2]>,
+    },
+    Arc {
+        half_angle: f32,
+        radius: f32,
+        half_thickness: f32,
+    },
+
+    // Boolean operations
+    Union {
+        a: Arc<Sdf2dOp>,
+        b: Arc<Sdf2dOp>,
+    },
+    Subtract {
+        a: Arc<Sdf2dOp>,
+        b: Arc<Sdf2dOp>,
+    },
+    Intersect {
+        a: Arc<Sdf2dOp>,
+        b: Arc<Sdf2dOp>,
+    },
+    SmoothUnion {
+        a: Arc<Sdf2dOp>,
+        b: Arc<Sdf2dOp>,
+        k: f32,
+    },
+    SmoothSubtract {
+        a: Arc<Sdf2dOp>,
+        b: Arc<Sdf2dOp>,
+        k: f32,
+    },
+    SmoothIntersect {
+        a: Arc<Sdf2dOp>,
+        b: Arc<Sdf2dOp>,
+        k: f32,
+    },
+
+    // Modifiers
+    Offset {
+        inner: Arc<Sdf2dOp>,
+        distance: f32,
+    },
+    Shell {
+        inner: Arc<Sdf2dOp>,
+        thickness: f32,
+    },
+
+    // Transforms
+    Translate {
+        inner: Arc<Sdf2dOp>,
+        offset: [f32; 2],
+    },
+    Rotate {
+        inner: Arc<Sdf2dOp>,
+        angle: f32,
+    },
+    Scale {
+        inner: Arc<Sdf2dOp>,
+        factor: f32,
+    },
+
+    // Repetition
+    RepeatInfinite {
+        inner: Arc<Sdf2dOp>,
+        spacing: [f32; 2],
+    },
+    RepeatLimited {
+        inner: Arc<Sdf2dOp>,
+        spacing: [f32; 2],
+        count: [f32; 2],
+    },
+    RepeatPolar {
+        inner: Arc<Sdf2dOp>,
+        count: u32,
+    },
+}
+
+impl Sdf2dOp {
+    /// Nesting depth of the tree, 1 for a single shape
+    pub fn depth(&self) -> usize {
+        match self {
+            Sdf2dOp::Union { a, b }
+            | Sdf2dOp::Subtract { a, b }
+            | Sdf2dOp::Intersect { a, b }
+            | Sdf2dOp::SmoothUnion { a, b, .. }
+            | Sdf2dOp::SmoothSubtract { a, b, .. }
+            | Sdf2dOp::SmoothIntersect { a, b, .. } => 1 + a.depth().max(b.depth()),
+
+            Sdf2dOp::Offset { inner, .. }
+            | Sdf2dOp::Shell { inner, .. }
+            | Sdf2dOp::Translate { inner, .. }
+            | Sdf2dOp::Rotate { inner, .. }
+            | Sdf2dOp::Scale { inner, .. }
+            | Sdf2dOp::RepeatInfinite { inner, .. }
+            | Sdf2dOp::RepeatLimited { inner, .. }
+            | Sdf2dOp::RepeatPolar { inner, .. } => 1 + inner.depth(),
+
+            _ => 1,
+        }
+    }
+}
+
+/// Profile shape for 2D-to-3D extrusion operations
+///
+/// Kept for code written against the fixed profiles. [`SdfOp::Extrude`] and
+/// [`SdfOp::Sweep`](crate::SdfOp::Sweep) now take an [`Sdf2dOp`] tree, which
+/// this converts into: `Arc::new(profile.into())`.
+///
+/// [`SdfOp::Extrude`]: crate::SdfOp::Extrude
+#[deprecated(note = "use `Sdf2dOp`, which this converts into")]
+#[derive(Debug, Clone)]
+pub enum ExtrudeProfile {
+    Circle {
+        radius: f32,
+    },
+    Rectangle {
+        width: f32,
+        height: f32,
+    },
+    RoundedRectangle {
+        width: f32,
+        height: f32,
+        radius: f32,
+    },
+}
+
+/// Profile shape for 2D-to-3D revolution (lathe) operations
+///
+/// Kept for code written against the fixed profiles. [`SdfOp::Revolve`] now
+/// takes an [`Sdf2dOp`] tree, which this converts into.
+///
+/// [`SdfOp::Revolve`]: crate::SdfOp::Revolve
+#[deprecated(note = "use `Sdf2dOp`, which this converts into")]
+#[derive(Debug, Clone)]
+pub enum RevolveProfile {
+    Circle { radius: f32 },
+    Rectangle { width: f32, height: f32 },
+}
+
+#[allow(deprecated)]
+impl From<ExtrudeProfile> for Sdf2dOp {
+    fn from(profile: ExtrudeProfile) -> Self {
+        // The old profiles took full widths; `Sdf2dOp` uses half extents
+        match profile {
+            ExtrudeProfile::Circle { radius } => Sdf2dOp::Circle { radius },
+            ExtrudeProfile::Rectangle { width, height } => Sdf2dOp::Rectangle {
+                half_extents: [width / 2.0, height / 2.0],
+            },
+            ExtrudeProfile::RoundedRectangle {
+                width,
+                height,
+                radius,
+            } => Sdf2dOp::RoundedRectangle {
+                half_extents: [width / 2.0, height / 2.0],
+                radius,
+            },
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl From<RevolveProfile> for Sdf2dOp {
+    fn from(profile: RevolveProfile) -> Self {
+        match profile {
+            RevolveProfile::Circle { radius } => ExtrudeProfile::Circle { radius }.into(),
+            RevolveProfile::Rectangle { width, height } => {
+                ExtrudeProfile::Rectangle { width, height }.into()
+            }
+        }
+    }
+}
@@ -3,21 +3,53 @@
 //! This module defines the SDF operation tree representation that can be
 //! converted to WGSL shader code for GPU raymarching.
 
+use crate::{Affine, Sdf2dOp, SweepPath};
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-/// Profile shape for 2D-to-3D extrusion operations
-#[derive(Debug, Clone)]
-pub enum ExtrudeProfile {
-    Circle { radius: f32 },
-    Rectangle { width: f32, height: f32 },
-    RoundedRectangle { width: f32, height: f32, radius: f32 },
+/// Material assigned to part of an SDF tree by [`SdfOp::Material`]
+///
+/// The material ID groups surfaces for export (one glTF primitive or OBJ
+/// material per ID). ID 0 is the default material. Without an explicit color,
+/// nonzero IDs get a color from a fixed palette and ID 0 uses the
+/// environment's material color.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct MaterialTag {
+    pub id: u32,
+    pub color: Option<[f32; 3]>,
 }
 
-/// Profile shape for 2D-to-3D revolution (lathe) operations
-#[derive(Debug, Clone)]
-pub enum RevolveProfile {
-    Circle { radius: f32 },
-    Rectangle { width: f32, height: f32 },
+impl MaterialTag {
+    /// Untagged geometry: default material, environment color
+    pub const DEFAULT: Self = Self { id: 0, color: None };
+
+    /// Distinct preview colors for material IDs without an explicit color
+    const PALETTE: [[f32; 3]; 8] = [
+        [0.80, 0.25, 0.20],
+        [0.25, 0.55, 0.85],
+        [0.30, 0.70, 0.35],
+        [0.90, 0.70, 0.20],
+        [0.60, 0.35, 0.75],
+        [0.95, 0.50, 0.20],
+        [0.25, 0.75, 0.75],
+        [0.55, 0.40, 0.30],
+    ];
+
+    /// Palette color for a material ID (`None` for the default material)
+    pub fn palette_color(id: u32) -> Option<[f32; 3]> {
+        if id == 0 {
+            None
+        } else {
+            Some(Self::PALETTE[(id as usize - 1) % Self::PALETTE.len()])
+        }
+    }
+
+    /// Resolve the display color, falling back to `default` for the default material
+    pub fn resolve_color(&self, default: [f32; 3]) -> [f32; 3] {
+        self.color
+            .or_else(|| Self::palette_color(self.id))
+            .unwrap_or(default)
+    }
 }
 
 /// Represents an SDF operation in a format suitable for shader generation.
@@ -27,7 +59,12 @@ pub enum RevolveProfile {
 ///
 /// Marked `#[non_exhaustive]` to allow adding new SDF primitives and operations
 /// in future versions without breaking downstream code.
-#[derive(Debug, Clone)]
+///
+/// Serializes as `{"<variant in snake_case>": { <fields> }}`, with children
+/// nested the same way. Variants and fields are only ever added, so older
+/// files keep loading; see "Saved Scenes" in the README for the schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 #[non_exhaustive]
 pub enum SdfOp {
     // Primitives
@@ -82,6 +119,75 @@ pub enum SdfOp {
         major_radius: f32,
         minor_radius: f32,
     },
+    CappedCone {
+        half_height: f32,
+        bottom_radius: f32,
+        top_radius: f32,
+    },
+    RoundCone {
+        bottom_radius: f32,
+        top_radius: f32,
+        height: f32,
+    },
+    BoxFrame {
+        half_extents: [f32; 3],
+        half_thickness: f32,
+    },
+    CappedTorus {
+        half_angle: f32,
+        major_radius: f32,
+        minor_radius: f32,
+    },
+    CutSphere {
+        radius: f32,
+        cut_height: f32,
+    },
+    CutHollowSphere {
+        radius: f32,
+        cut_height: f32,
+        thickness: f32,
+    },
+    Rhombus {
+        half_diagonals: [f32; 2],
+        half_height: f32,
+        radius: f32,
+    },
+    SolidAngle {
+        half_angle: f32,
+        radius: f32,
+    },
+    CapsuleBetween {
+        a: [f32; 3],
+        b: [f32; 3],
+        radius: f32,
+    },
+    CylinderBetween {
+        a: [f32; 3],
+        b: [f32; 3],
+        radius: f32,
+    },
+    InfiniteCylinder {
+        axis: [f32; 3],
+        radius: f32,
+    },
+
+    // Fractals
+    Mandelbulb {
+        power: f32,
+        iterations: u32,
+    },
+    MengerSponge {
+        size: f32,
+        iterations: u32,
+    },
+    Sierpinski {
+        size: f32,
+        iterations: u32,
+    },
+    Julia {
+        c: [f32; 4],
+        iterations: u32,
+    },
 
     // Boolean operations
     Union {
@@ -155,6 +261,21 @@ pub enum SdfOp {
         inner: Arc<SdfOp>,
         factor: f32,
     },
+    /// Rotation around any axis by a quaternion `[x, y, z, w]`
+    Rotate {
+        inner: Arc<SdfOp>,
+        rotation: [f32; 4],
+    },
+    /// Scale by a separate factor along each axis
+    NonUniformScale {
+        inner: Arc<SdfOp>,
+        factors: [f32; 3],
+    },
+    /// Any invertible matrix plus an offset
+    Transform {
+        inner: Arc<SdfOp>,
+        transform: Affine,
+    },
     Mirror {
         inner: Arc<SdfOp>,
         axis: [f32; 3],
@@ -186,14 +307,25 @@ pub enum SdfOp {
 
     // 2D-to-3D Operations
     Extrude {
-        profile: ExtrudeProfile,
+        profile: Arc<Sdf2dOp>,
         depth: f32,
     },
     Revolve {
-        profile: RevolveProfile,
+        profile: Arc<Sdf2dOp>,
         offset: f32,
     },
 
+    // Sweeps
+    Tube {
+        path: SweepPath,
+        start_radius: f32,
+        end_radius: f32,
+    },
+    Sweep {
+        path: SweepPath,
+        profile: Arc<Sdf2dOp>,
+    },
+
     // Repetition
     RepeatInfinite {
         inner: Arc<SdfOp>,
@@ -208,4 +340,153 @@ pub enum SdfOp {
         inner: Arc<SdfOp>,
         count: u32,
     },
+
+    // Materials
+    Material {
+        inner: Arc<SdfOp>,
+        material: MaterialTag,
+    },
+}
+
+impl SdfOp {
+    /// Check whether any node in this tree assigns a material
+    ///
+    /// Untagged trees can skip material evaluation entirely.
+    pub fn has_material(&self) -> bool {
+        self.tagged_nodes().is_tagged()
+    }
+
+    /// Which nodes have a [`SdfOp::Material`] tag at or below them
+    ///
+    /// Found in one walk, so code generators and evaluators can skip untagged
+    /// branches with a lookup instead of calling
+    /// [`has_material`](Self::has_material) at every node.
+    pub fn tagged_nodes(&self) -> TaggedNodes {
+        let children = match self {
+            SdfOp::Material { inner, .. } => {
+                return TaggedNodes {
+                    tagged: true,
+                    children: vec![inner.tagged_nodes()],
+                };
+            }
+
+            SdfOp::Union { a, b }
+            | SdfOp::Subtract { a, b }
+            | SdfOp::Intersect { a, b }
+            | SdfOp::SmoothUnion { a, b, .. }
+            | SdfOp::SmoothSubtract { a, b, .. }
+            | SdfOp::SmoothIntersect { a, b, .. }
+            | SdfOp::Xor { a, b } => vec![a.tagged_nodes(), b.tagged_nodes()],
+
+            SdfOp::Shell { inner, .. }
+            | SdfOp::Round { inner, .. }
+            | SdfOp::Onion { inner, .. }
+            | SdfOp::Elongate { inner, .. }
+            | SdfOp::Translate { inner, .. }
+            | SdfOp::RotateX { inner, .. }
+            | SdfOp::RotateY { inner, .. }
+            | SdfOp::RotateZ { inner, .. }
+            | SdfOp::Scale { inner, .. }
+            | SdfOp::Rotate { inner, .. }
+            | SdfOp::NonUniformScale { inner, .. }
+            | SdfOp::Transform { inner, .. }
+            | SdfOp::Mirror { inner, .. }
+            | SdfOp::SymmetryX { inner }
+            | SdfOp::SymmetryY { inner }
+            | SdfOp::SymmetryZ { inner }
+            | SdfOp::Twist { inner, .. }
+            | SdfOp::Bend { inner, .. }
+            | SdfOp::Displacement { inner, .. }
+            | SdfOp::RepeatInfinite { inner, .. }
+            | SdfOp::RepeatLimited { inner, .. }
+            | SdfOp::RepeatPolar { inner, .. } => vec![inner.tagged_nodes()],
+
+            _ => return TaggedNodes::default(),
+        };
+
+        if children.iter().any(TaggedNodes::is_tagged) {
+            TaggedNodes {
+                tagged: true,
+                children,
+            }
+        } else {
+            TaggedNodes::default()
+        }
+    }
+
+    /// Nesting depth of the tree, including extruded and swept profiles;
+    /// 1 for a single primitive
+    pub fn depth(&self) -> usize {
+        match self {
+            SdfOp::Union { a, b }
+            | SdfOp::Subtract { a, b }
+            | SdfOp::Intersect { a, b }
+            | SdfOp::SmoothUnion { a, b, .. }
+            | SdfOp::SmoothSubtract { a, b, .. }
+            | SdfOp::SmoothIntersect { a, b, .. }
+            | SdfOp::Xor { a, b } => 1 + a.depth().max(b.depth()),
+
+            SdfOp::Shell { inner, .. }
+            | SdfOp::Round { inner, .. }
+            | SdfOp::Onion { inner, .. }
+            | SdfOp::Elongate { inner, .. }
+            | SdfOp::Translate { inner, .. }
+            | SdfOp::RotateX { inner, .. }
+            | SdfOp::RotateY { inner, .. }
+            | SdfOp::RotateZ { inner, .. }
+            | SdfOp::Scale { inner, .. }
+            | SdfOp::Rotate { inner, .. }
+            | SdfOp::NonUniformScale { inner, .. }
+            | SdfOp::Transform { inner, .. }
+            | SdfOp::Mirror { inner, .. }
+            | SdfOp::SymmetryX { inner }
+            | SdfOp::SymmetryY { inner }
+            | SdfOp::SymmetryZ { inner }
+            | SdfOp::Twist { inner, .. }
+            | SdfOp::Bend { inner, .. }
+            | SdfOp::Displacement { inner, .. }
+            | SdfOp::RepeatInfinite { inner, .. }
+            | SdfOp::RepeatLimited { inner, .. }
+            | SdfOp::RepeatPolar { inner, .. }
+            | SdfOp::Material { inner, .. } => 1 + inner.depth(),
+
+            SdfOp::Extrude { profile, .. }
+            | SdfOp::Revolve { profile, .. }
+            | SdfOp::Sweep { profile, .. } => 1 + profile.depth(),
+
+            _ => 1,
+        }
+    }
+}
+
+/// Which nodes of an [`SdfOp`] tree have a material tag at or below them,
+/// from [`SdfOp::tagged_nodes`]
+///
+/// Mirrors the shape of the tagged part of the tree rather than pointing into
+/// it, so it describes any copy of the tree: walk it alongside the tree with
+/// [`child`](Self::child).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaggedNodes {
+    tagged: bool,
+    /// One entry per child node, empty for untagged nodes
+    children: Vec<TaggedNodes>,
+}
+
+/// Entry for every node below an untagged one
+static UNTAGGED: TaggedNodes = TaggedNodes {
+    tagged: false,
+    children: Vec::new(),
+};
+
+impl TaggedNodes {
+    /// Whether this node has a material tag at or below it
+    pub fn is_tagged(&self) -> bool {
+        self.tagged
+    }
+
+    /// Entry for the node's child at `index`: `a` is 0 and `b` 1 for
+    /// booleans, `inner` is 0 for everything else
+    pub fn child(&self, index: usize) -> &TaggedNodes {
+        self.children.get(index).unwrap_or(&UNTAGGED)
+    }
 }
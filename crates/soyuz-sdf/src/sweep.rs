@@ -0,0 +1,347 @@
+//! Paths for swept tubes and profiles
+//!
+//! A [`SweepPath`] turns control points into a chain of straight
+//! [`SweepSegment`]s once, when it is built, so the GPU and CPU evaluators
+//! both work from the same pieces. Curves are sampled at a fixed number of
+//! segments per span. Each segment carries a cross-section frame that is
+//! parallel-transported along the path (so profiles don't twist around it)
+//! and the cut planes where it meets its neighbours.
+//!
+//! Only the curve and its control points are serialized; the segments are
+//! rebuilt on load.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Number of straight segments each curve span is split into
+pub const CURVE_SEGMENTS: usize = 16;
+
+/// How control points define a sweep path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SweepCurve {
+    /// Straight lines through every point
+    Polyline,
+    /// Quadratic Bézier spans: on-curve points alternate with control points
+    /// (`p0, c0, p1, c1, p2, ...`), so there is an odd number of points
+    QuadraticBezier,
+    /// Uniform Catmull-Rom spline through every point
+    CatmullRom,
+}
+
+/// One straight piece of a tessellated sweep path
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepSegment {
+    pub start: [f32; 3],
+    pub end: [f32; 3],
+    /// Fraction of the path's length at `start`
+    pub start_t: f32,
+    /// Fraction of the path's length at `end`
+    pub end_t: f32,
+    /// Unit vector across the segment along the profile's x axis
+    pub side: [f32; 3],
+    /// Unit vector across the segment along the profile's y axis
+    pub up: [f32; 3],
+    /// Unit normal of the cut plane through `start`, pointing along the path
+    pub start_miter: [f32; 3],
+    /// Unit normal of the cut plane through `end`, pointing along the path
+    pub end_miter: [f32; 3],
+}
+
+/// A path to sweep a tube or profile along
+///
+/// The tessellation is computed on construction and shared between clones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "SweepPathData", into = "SweepPathData")]
+pub struct SweepPath {
+    curve: SweepCurve,
+    points: Vec<[f32; 3]>,
+    segments: Arc<[SweepSegment]>,
+}
+
+/// Serialized form of a [`SweepPath`]: its inputs without the tessellation
+#[derive(Serialize, Deserialize)]
+struct SweepPathData {
+    curve: SweepCurve,
+    points: Vec<[f32; 3]>,
+}
+
+impl From<SweepPathData> for SweepPath {
+    fn from(data: SweepPathData) -> Self {
+        Self::new(data.curve, data.points)
+    }
+}
+
+impl From<SweepPath> for SweepPathData {
+    fn from(path: SweepPath) -> Self {
+        Self {
+            curve: path.curve,
+            points: path.points,
+        }
+    }
+}
+
+impl SweepPath {
+    /// Build a path from control points
+    ///
+    /// A trailing point that doesn't complete a Bézier span is ignored. A
+    /// path without two distinct points is a single zero-length segment, so
+    /// a tube along it degrades to a sphere.
+    pub fn new(curve: SweepCurve, points: Vec<[f32; 3]>) -> Self {
+        let segments = build_segments(&tessellate(curve, &points)).into();
+        Self {
+            curve,
+            points,
+            segments,
+        }
+    }
+
+    pub fn curve(&self) -> SweepCurve {
+        self.curve
+    }
+
+    pub fn points(&self) -> &[[f32; 3]] {
+        &self.points
+    }
+
+    pub fn segments(&self) -> &[SweepSegment] {
+        &self.segments
+    }
+
+    /// Smallest cosine between a segment and one of its cut planes
+    ///
+    /// A profile reaching `r` from the path extends at most `r / cos` from
+    /// the segment ends, which bounds sharp mitered corners.
+    pub fn min_miter_cos(&self) -> f32 {
+        self.segments
+            .iter()
+            .map(|s| {
+                let t = normalize(sub(s.end, s.start)).unwrap_or(s.start_miter);
+                dot(t, s.start_miter).min(dot(t, s.end_miter))
+            })
+            .fold(1.0, f32::min)
+    }
+}
+
+/// Sample the curve into the points the straight segments run between
+fn tessellate(curve: SweepCurve, points: &[[f32; 3]]) -> Vec<[f32; 3]> {
+    let mut samples = Vec::new();
+    match curve {
+        SweepCurve::Polyline => samples.extend_from_slice(points),
+        SweepCurve::QuadraticBezier => {
+            samples.extend(points.first());
+            for span in points.windows(3).step_by(2) {
+                for i in 1..=CURVE_SEGMENTS {
+                    let t = i as f32 / CURVE_SEGMENTS as f32;
+                    let u = 1.0 - t;
+                    samples.push(combine(&[
+                        (u * u, span[0]),
+                        (2.0 * u * t, span[1]),
+                        (t * t, span[2]),
+                    ]));
+                }
+            }
+        }
+        SweepCurve::CatmullRom => {
+            samples.extend(points.first());
+            let last = points.len().saturating_sub(1);
+            for i in 0..last {
+                let p0 = points[i.saturating_sub(1)];
+                let p1 = points[i];
+                let p2 = points[i + 1];
+                let p3 = points[(i + 2).min(last)];
+                for j in 1..=CURVE_SEGMENTS {
+                    let t = j as f32 / CURVE_SEGMENTS as f32;
+                    let (t2, t3) = (t * t, t * t * t);
+                    samples.push(combine(&[
+                        (0.5 * (-t3 + 2.0 * t2 - t), p0),
+                        (0.5 * (3.0 * t3 - 5.0 * t2 + 2.0), p1),
+                        (0.5 * (-3.0 * t3 + 4.0 * t2 + t), p2),
+                        (0.5 * (t3 - t2), p3),
+                    ]));
+                }
+            }
+        }
+    }
+    samples.dedup_by(|b, a| length(sub(*b, *a)) <= 1e-6);
+    samples
+}
+
+/// Turn consecutive samples into segments with frames and cut planes
+fn build_segments(samples: &[[f32; 3]]) -> Vec<SweepSegment> {
+    let Some(&first) = samples.first() else {
+        return Vec::new();
+    };
+    if samples.len() < 2 {
+        return vec![SweepSegment {
+            start: first,
+            end: first,
+            start_t: 0.0,
+            end_t: 1.0,
+            side: [1.0, 0.0, 0.0],
+            up: [0.0, 1.0, 0.0],
+            start_miter: [0.0, 0.0, 1.0],
+            end_miter: [0.0, 0.0, 1.0],
+        }];
+    }
+
+    let dirs: Vec<[f32; 3]> = samples
+        .windows(2)
+        .map(|w| normalize(sub(w[1], w[0])).unwrap_or([1.0, 0.0, 0.0]))
+        .collect();
+    let lengths: Vec<f32> = samples
+        .windows(2)
+        .map(|w| length(sub(w[1], w[0])))
+        .collect();
+    let total: f32 = lengths.iter().sum();
+
+    // Start with the profile's y axis as close to world up as the direction
+    // allows, then carry it along with the smallest rotation at each joint
+    let t0 = dirs[0];
+    let reference = if t0[1].abs() > 0.999 {
+        [0.0, 0.0, 1.0]
+    } else {
+        [0.0, 1.0, 0.0]
+    };
+    let mut side = normalize(cross(t0, reference)).unwrap_or([1.0, 0.0, 0.0]);
+
+    let mut segments = Vec::with_capacity(dirs.len());
+    let mut travelled = 0.0;
+    for (i, &t) in dirs.iter().enumerate() {
+        if i > 0 {
+            side = transport(side, dirs[i - 1], t);
+        }
+        let miter = |j: usize| normalize(add(dirs[j], dirs[j + 1])).unwrap_or(dirs[j]);
+        let start_miter = if i == 0 { t } else { miter(i - 1) };
+        let end_miter = if i + 1 == dirs.len() { t } else { miter(i) };
+
+        let start_t = travelled / total;
+        travelled += lengths[i];
+        segments.push(SweepSegment {
+            start: samples[i],
+            end: samples[i + 1],
+            start_t,
+            end_t: travelled / total,
+            side,
+            up: cross(side, t),
+            start_miter,
+            end_miter,
+        });
+    }
+    segments
+}
+
+/// Rotate `v` by the smallest rotation taking unit vector `from` to `to`,
+/// keeping it perpendicular to `to`
+fn transport(v: [f32; 3], from: [f32; 3], to: [f32; 3]) -> [f32; 3] {
+    let c = dot(from, to);
+    let rotated = if c > -0.9999 {
+        // Rodrigues' formula with the axis scaled by sin(angle)
+        let a = cross(from, to);
+        combine(&[(c, v), (1.0, cross(a, v)), (dot(a, v) / (1.0 + c), a)])
+    } else {
+        // Reversal: every axis is as good as another, keep the vector
+        v
+    };
+    let projected = sub(rotated, scale(to, dot(rotated, to)));
+    normalize(projected).unwrap_or(v)
+}
+
+fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: [f32; 3], s: f32) -> [f32; 3] {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn length(a: [f32; 3]) -> f32 {
+    dot(a, a).sqrt()
+}
+
+fn normalize(a: [f32; 3]) -> Option<[f32; 3]> {
+    let len = length(a);
+    (len > 1e-6).then(|| scale(a, 1.0 / len))
+}
+
+/// Weighted sum of points
+fn combine(terms: &[(f32, [f32; 3])]) -> [f32; 3] {
+    terms
+        .iter()
+        .fold([0.0; 3], |acc, &(w, p)| add(acc, scale(p, w)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: [f32; 3], b: [f32; 3]) {
+        assert!(length(sub(a, b)) < 1e-4, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_polyline_corner_miter() {
+        let path = SweepPath::new(
+            SweepCurve::Polyline,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 0.0, 1.0]],
+        );
+        let segs = path.segments();
+        assert_eq!(segs.len(), 2);
+        let h = std::f32::consts::FRAC_1_SQRT_2;
+        assert_close(segs[0].end_miter, [h, 0.0, h]);
+        assert_close(segs[0].end_miter, segs[1].start_miter);
+        assert_close(segs[0].start_miter, [1.0, 0.0, 0.0]);
+        assert!((segs[0].end_t - 0.5).abs() < 1e-6);
+        assert!((path.min_miter_cos() - h).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_frame_is_transported_without_twist() {
+        let path = SweepPath::new(
+            SweepCurve::Polyline,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 0.0, 1.0]],
+        );
+        let segs = path.segments();
+        // A horizontal path keeps the profile upright through the bend
+        assert_close(segs[0].up, [0.0, 1.0, 0.0]);
+        assert_close(segs[1].up, [0.0, 1.0, 0.0]);
+        assert_close(segs[1].side, [-1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_curves_pass_through_end_points() {
+        let points = vec![[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [2.0, 0.0, 0.0]];
+        for curve in [SweepCurve::QuadraticBezier, SweepCurve::CatmullRom] {
+            let path = SweepPath::new(curve, points.clone());
+            let segs = path.segments();
+            assert!(segs.len() >= CURVE_SEGMENTS);
+            assert_close(segs[0].start, points[0]);
+            assert_close(segs[segs.len() - 1].end, points[2]);
+            assert!((segs[segs.len() - 1].end_t - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_degenerate_path_is_one_point() {
+        let path = SweepPath::new(SweepCurve::Polyline, vec![[1.0, 2.0, 3.0]; 2]);
+        let segs = path.segments();
+        assert_eq!(segs.len(), 1);
+        assert_close(segs[0].start, segs[0].end);
+    }
+}
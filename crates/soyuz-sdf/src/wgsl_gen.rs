@@ -11,16 +11,51 @@
 
 use std::fmt::Write;
 
-use crate::{ExtrudeProfile, RevolveProfile, SdfOp};
+use crate::{MaterialTag, Sdf2dOp, SdfOp, TaggedNodes};
 
 /// Generate WGSL code for an SDF operation tree
 pub struct WgslGenerator {
     var_counter: usize,
+    /// Helper functions the entry point being generated calls, emitted
+    /// before it
+    helpers: String,
+    /// Name of the entry point being generated, which prefixes its helpers'
+    /// names so `scene_sdf` and `scene_color` can share a module
+    entry: &'static str,
+    helper_counter: usize,
 }
 
 impl WgslGenerator {
     pub fn new() -> Self {
-        Self { var_counter: 0 }
+        Self {
+            var_counter: 0,
+            helpers: String::new(),
+            entry: "scene_sdf",
+            helper_counter: 0,
+        }
+    }
+
+    /// Start generating the entry point `entry`
+    fn begin(&mut self, entry: &'static str) {
+        self.var_counter = 0;
+        self.helpers.clear();
+        self.entry = entry;
+        self.helper_counter = 0;
+    }
+
+    /// Emit a helper function returning the distance to a 2D profile, and
+    /// return its name
+    fn profile_helper(&mut self, profile: &Sdf2dOp) -> String {
+        let name = format!("{}_profile_{}", self.entry, self.helper_counter);
+        self.helper_counter += 1;
+
+        let mut body = String::new();
+        let result = self.generate_2d(profile, "p", &mut body);
+        writeln!(self.helpers, "fn {}(p: vec2<f32>) -> f32 {{", name).unwrap();
+        self.helpers.push_str(&body);
+        writeln!(self.helpers, "    return {};", result).unwrap();
+        writeln!(self.helpers, "}}\n").unwrap();
+        name
     }
 
     fn next_var(&mut self) -> String {
@@ -35,23 +70,501 @@ impl WgslGenerator {
         var
     }
 
+    fn next_color_var(&mut self) -> String {
+        let var = format!("c{}", self.var_counter);
+        self.var_counter += 1;
+        var
+    }
+
     /// Generate the complete `scene_sdf` function
+    ///
+    /// Any helper functions it calls come first.
     pub fn generate(&mut self, sdf: &SdfOp) -> String {
-        self.var_counter = 0;
+        self.begin("scene_sdf");
         let mut code = String::new();
 
         writeln!(code, "fn scene_sdf(p: vec3<f32>) -> f32 {{").unwrap();
 
-        let result = self.generate_op(sdf, "p", &mut code);
+        let (result, _) = self.generate_op(sdf, &TaggedNodes::default(), "p", None, &mut code);
 
         writeln!(code, "    return {};", result).unwrap();
         writeln!(code, "}}").unwrap();
 
-        code
+        std::mem::take(&mut self.helpers) + &code
+    }
+
+    /// Generate the complete `scene_color` function
+    ///
+    /// Returns the surface color at a point, following [`SdfOp::Material`] tags
+    /// through the tree. Untagged geometry uses the environment material color.
+    /// Any helper functions it calls come first.
+    pub fn generate_color(&mut self, sdf: &SdfOp) -> String {
+        self.begin("scene_color");
+        let mut code = String::new();
+
+        writeln!(code, "fn scene_color(p: vec3<f32>) -> vec3<f32> {{").unwrap();
+
+        let tags = sdf.tagged_nodes();
+        if tags.is_tagged() {
+            writeln!(code, "    let c_default = env.material_color;").unwrap();
+            let (_, color) = self.generate_op(sdf, &tags, "p", Some("c_default"), &mut code);
+            writeln!(
+                code,
+                "    return {};",
+                color.as_deref().unwrap_or("c_default")
+            )
+            .unwrap();
+        } else {
+            writeln!(code, "    return env.material_color;").unwrap();
+        }
+
+        writeln!(code, "}}").unwrap();
+
+        std::mem::take(&mut self.helpers) + &code
+    }
+
+    /// Generate code for a single SDF operation
+    ///
+    /// Returns the variable holding the distance and, when `color` is set
+    /// (generating `scene_color`), the variable holding the surface color.
+    /// `color` is the color inherited from enclosing material tags, and
+    /// `tags` the entry for `op` in the tree's [`SdfOp::tagged_nodes`].
+    fn generate_op(
+        &mut self,
+        op: &SdfOp,
+        tags: &TaggedNodes,
+        pos_var: &str,
+        color: Option<&str>,
+        code: &mut String,
+    ) -> (String, Option<String>) {
+        // Untagged branches keep the inherited color, so only their distance
+        // is needed
+        if let Some(color) = color
+            && !tags.is_tagged()
+        {
+            let (var, _) = self.generate_op(op, tags, pos_var, None, code);
+            return (var, Some(color.to_string()));
+        }
+
+        match op {
+            // Boolean operations
+            SdfOp::Union { a, b } => {
+                let (a_var, a_col) = self.generate_op(a, tags.child(0), pos_var, color, code);
+                let (b_var, b_col) = self.generate_op(b, tags.child(1), pos_var, color, code);
+                let var = self.next_var();
+                writeln!(code, "    let {} = op_union({}, {});", var, a_var, b_var).unwrap();
+                let col = self.blend_colors(a_col, b_col, code, |a, b| {
+                    format!("select({}, {}, {} < {})", b, a, a_var, b_var)
+                });
+                (var, col)
+            }
+            SdfOp::Subtract { a, b } => {
+                let (a_var, a_col) = self.generate_op(a, tags.child(0), pos_var, color, code);
+                let (b_var, _) = self.generate_op(b, tags.child(1), pos_var, color, code);
+                let var = self.next_var();
+                writeln!(code, "    let {} = op_subtract({}, {});", var, a_var, b_var).unwrap();
+                // Cut surfaces keep the color of the shape being carved
+                (var, a_col)
+            }
+            SdfOp::Intersect { a, b } => {
+                let (a_var, a_col) = self.generate_op(a, tags.child(0), pos_var, color, code);
+                let (b_var, b_col) = self.generate_op(b, tags.child(1), pos_var, color, code);
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = op_intersect({}, {});",
+                    var, a_var, b_var
+                )
+                .unwrap();
+                let col = self.blend_colors(a_col, b_col, code, |a, b| {
+                    format!("select({}, {}, {} > {})", b, a, a_var, b_var)
+                });
+                (var, col)
+            }
+            SdfOp::SmoothUnion { a, b, k } => {
+                let (a_var, a_col) = self.generate_op(a, tags.child(0), pos_var, color, code);
+                let (b_var, b_col) = self.generate_op(b, tags.child(1), pos_var, color, code);
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = op_smooth_union({}, {}, {:.6});",
+                    var, a_var, b_var, k
+                )
+                .unwrap();
+                // Blend colors with the same weight as the distances
+                let col = self.blend_colors(a_col, b_col, code, |a, b| {
+                    format!(
+                        "mix({}, {}, smooth_union_blend({}, {}, {:.6}))",
+                        b, a, a_var, b_var, k
+                    )
+                });
+                (var, col)
+            }
+            SdfOp::SmoothSubtract { a, b, k } => {
+                let (a_var, a_col) = self.generate_op(a, tags.child(0), pos_var, color, code);
+                let (b_var, _) = self.generate_op(b, tags.child(1), pos_var, color, code);
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = op_smooth_subtract({}, {}, {:.6});",
+                    var, a_var, b_var, k
+                )
+                .unwrap();
+                (var, a_col)
+            }
+            SdfOp::SmoothIntersect { a, b, k } => {
+                let (a_var, a_col) = self.generate_op(a, tags.child(0), pos_var, color, code);
+                let (b_var, b_col) = self.generate_op(b, tags.child(1), pos_var, color, code);
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = op_smooth_intersect({}, {}, {:.6});",
+                    var, a_var, b_var, k
+                )
+                .unwrap();
+                let col = self.blend_colors(a_col, b_col, code, |a, b| {
+                    format!(
+                        "mix({}, {}, smooth_intersect_blend({}, {}, {:.6}))",
+                        b, a, a_var, b_var, k
+                    )
+                });
+                (var, col)
+            }
+            SdfOp::Xor { a, b } => {
+                let (a_var, a_col) = self.generate_op(a, tags.child(0), pos_var, color, code);
+                let (b_var, b_col) = self.generate_op(b, tags.child(1), pos_var, color, code);
+                let var = self.next_var();
+                writeln!(code, "    let {} = op_xor({}, {});", var, a_var, b_var).unwrap();
+                let col = self.blend_colors(a_col, b_col, code, |a, b| {
+                    format!("select({}, {}, abs({}) < abs({}))", b, a, a_var, b_var)
+                });
+                (var, col)
+            }
+
+            // Modifiers
+            SdfOp::Shell { inner, thickness } => {
+                let (inner_var, col) = self.generate_op(inner, tags.child(0), pos_var, color, code);
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = op_shell({}, {:.6});",
+                    var, inner_var, thickness
+                )
+                .unwrap();
+                (var, col)
+            }
+            SdfOp::Round { inner, radius } => {
+                let (inner_var, col) = self.generate_op(inner, tags.child(0), pos_var, color, code);
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = op_round({}, {:.6});",
+                    var, inner_var, radius
+                )
+                .unwrap();
+                (var, col)
+            }
+            SdfOp::Onion { inner, thickness } => {
+                let (inner_var, col) = self.generate_op(inner, tags.child(0), pos_var, color, code);
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = op_onion({}, {:.6});",
+                    var, inner_var, thickness
+                )
+                .unwrap();
+                (var, col)
+            }
+            SdfOp::Elongate { inner, h } => {
+                // xyz is the point for the inner shape, w corrects its distance
+                let elongated = self.next_pos_var();
+                writeln!(
+                    code,
+                    "    let {} = op_elongate({}, vec3<f32>({:.6}, {:.6}, {:.6}));",
+                    elongated, pos_var, h[0], h[1], h[2]
+                )
+                .unwrap();
+                let new_pos = self.next_pos_var();
+                writeln!(code, "    let {} = {}.xyz;", new_pos, elongated).unwrap();
+                let (inner_var, col) =
+                    self.generate_op(inner, tags.child(0), &new_pos, color, code);
+                let var = self.next_var();
+                writeln!(code, "    let {} = {} + {}.w;", var, inner_var, elongated).unwrap();
+                (var, col)
+            }
+            // Transforms
+            SdfOp::Translate { inner, offset } => {
+                let new_pos = self.next_pos_var();
+                writeln!(
+                    code,
+                    "    let {} = op_translate({}, vec3<f32>({:.6}, {:.6}, {:.6}));",
+                    new_pos, pos_var, offset[0], offset[1], offset[2]
+                )
+                .unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+            SdfOp::RotateX { inner, angle } => {
+                let new_pos = self.next_pos_var();
+                // Pre-compute sin/cos at code generation time for better GPU performance
+                writeln!(
+                    code,
+                    "    let {} = op_rotate_x({}, {:.8}, {:.8});",
+                    new_pos,
+                    pos_var,
+                    angle.cos(),
+                    angle.sin()
+                )
+                .unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+            SdfOp::RotateY { inner, angle } => {
+                let new_pos = self.next_pos_var();
+                writeln!(
+                    code,
+                    "    let {} = op_rotate_y({}, {:.8}, {:.8});",
+                    new_pos,
+                    pos_var,
+                    angle.cos(),
+                    angle.sin()
+                )
+                .unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+            SdfOp::RotateZ { inner, angle } => {
+                let new_pos = self.next_pos_var();
+                writeln!(
+                    code,
+                    "    let {} = op_rotate_z({}, {:.8}, {:.8});",
+                    new_pos,
+                    pos_var,
+                    angle.cos(),
+                    angle.sin()
+                )
+                .unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+            SdfOp::Scale { inner, factor } => {
+                let new_pos = self.next_pos_var();
+                writeln!(
+                    code,
+                    "    let {} = op_scale({}, {:.6});",
+                    new_pos, pos_var, factor
+                )
+                .unwrap();
+                let (inner_var, col) =
+                    self.generate_op(inner, tags.child(0), &new_pos, color, code);
+                let var = self.next_var();
+                writeln!(code, "    let {} = {} * {:.6};", var, inner_var, factor).unwrap();
+                (var, col)
+            }
+            SdfOp::Rotate { inner, rotation } => {
+                let new_pos = self.next_pos_var();
+                let [x, y, z, w] = normalize_quat(*rotation);
+                writeln!(
+                    code,
+                    "    let {} = op_rotate_quat({}, vec4<f32>({:.8}, {:.8}, {:.8}, {:.8}));",
+                    new_pos, pos_var, x, y, z, w
+                )
+                .unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+            SdfOp::NonUniformScale { inner, factors } => {
+                let new_pos = self.next_pos_var();
+                writeln!(
+                    code,
+                    "    let {} = op_scale_xyz({}, {});",
+                    new_pos,
+                    pos_var,
+                    vec3(*factors)
+                )
+                .unwrap();
+                let (inner_var, col) =
+                    self.generate_op(inner, tags.child(0), &new_pos, color, code);
+                let var = self.next_var();
+                let correction = factors.iter().fold(f32::INFINITY, |m, f| m.min(f.abs()));
+                writeln!(code, "    let {} = {} * {:.6};", var, inner_var, correction).unwrap();
+                (var, col)
+            }
+            SdfOp::Transform { inner, transform } => {
+                let new_pos = self.next_pos_var();
+                let [c0, c1, c2] = transform.inverse();
+                writeln!(
+                    code,
+                    "    let {} = op_affine({}, {}, {}, {}, {});",
+                    new_pos,
+                    pos_var,
+                    vec3(c0),
+                    vec3(c1),
+                    vec3(c2),
+                    vec3(transform.offset())
+                )
+                .unwrap();
+                let (inner_var, col) =
+                    self.generate_op(inner, tags.child(0), &new_pos, color, code);
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = {} * {:.6};",
+                    var,
+                    inner_var,
+                    transform.distance_scale()
+                )
+                .unwrap();
+                (var, col)
+            }
+            SdfOp::Mirror { inner, axis } => {
+                let new_pos = self.next_pos_var();
+                let [x, y, z] = normalize(*axis);
+                writeln!(
+                    code,
+                    "    let {} = op_mirror({}, vec3<f32>({:.6}, {:.6}, {:.6}));",
+                    new_pos, pos_var, x, y, z
+                )
+                .unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+            SdfOp::SymmetryX { inner } => {
+                let new_pos = self.next_pos_var();
+                writeln!(code, "    let {} = op_symmetry_x({});", new_pos, pos_var).unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+            SdfOp::SymmetryY { inner } => {
+                let new_pos = self.next_pos_var();
+                writeln!(code, "    let {} = op_symmetry_y({});", new_pos, pos_var).unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+            SdfOp::SymmetryZ { inner } => {
+                let new_pos = self.next_pos_var();
+                writeln!(code, "    let {} = op_symmetry_z({});", new_pos, pos_var).unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+
+            // Deformations
+            SdfOp::Twist { inner, amount } => {
+                let new_pos = self.next_pos_var();
+                writeln!(
+                    code,
+                    "    let {} = op_twist({}, {:.6});",
+                    new_pos, pos_var, amount
+                )
+                .unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+            SdfOp::Bend { inner, amount } => {
+                let new_pos = self.next_pos_var();
+                writeln!(
+                    code,
+                    "    let {} = op_bend({}, {:.6});",
+                    new_pos, pos_var, amount
+                )
+                .unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+            SdfOp::Displacement {
+                inner,
+                amount,
+                frequency,
+            } => {
+                let (inner_var, col) = self.generate_op(inner, tags.child(0), pos_var, color, code);
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = op_displacement({}, {}, {:.6}, {:.6});",
+                    var, inner_var, pos_var, amount, frequency
+                )
+                .unwrap();
+                (var, col)
+            }
+
+            // Repetition
+            SdfOp::RepeatInfinite { inner, spacing } => {
+                let new_pos = self.next_pos_var();
+                writeln!(
+                    code,
+                    "    let {} = op_repeat({}, vec3<f32>({:.6}, {:.6}, {:.6}));",
+                    new_pos, pos_var, spacing[0], spacing[1], spacing[2]
+                )
+                .unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+            SdfOp::RepeatLimited {
+                inner,
+                spacing,
+                count,
+            } => {
+                let new_pos = self.next_pos_var();
+                writeln!(
+                    code,
+                    "    let {} = op_repeat_limited({}, vec3<f32>({:.6}, {:.6}, {:.6}), vec3<f32>({:.6}, {:.6}, {:.6}));",
+                    new_pos, pos_var,
+                    spacing[0], spacing[1], spacing[2],
+                    count[0], count[1], count[2]
+                ).unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+            SdfOp::RepeatPolar { inner, count } => {
+                let new_pos = self.next_pos_var();
+                writeln!(
+                    code,
+                    "    let {} = op_repeat_polar({}, {:.1});",
+                    new_pos, pos_var, *count as f32
+                )
+                .unwrap();
+                self.generate_op(inner, tags.child(0), &new_pos, color, code)
+            }
+
+            // Materials
+            SdfOp::Material { inner, material } => {
+                // Materials only affect the color pass
+                let tagged = color.map(|_| {
+                    let var = self.next_color_var();
+                    let resolved = material
+                        .color
+                        .or_else(|| MaterialTag::palette_color(material.id));
+                    match resolved {
+                        Some([r, g, b]) => writeln!(
+                            code,
+                            "    let {} = vec3<f32>({:.6}, {:.6}, {:.6});",
+                            var, r, g, b
+                        )
+                        .unwrap(),
+                        None => writeln!(code, "    let {} = env.material_color;", var).unwrap(),
+                    }
+                    var
+                });
+                self.generate_op(
+                    inner,
+                    tags.child(0),
+                    pos_var,
+                    tagged.as_deref().or(color),
+                    code,
+                )
+            }
+
+            // Primitives and 2D-to-3D operations have no children
+            _ => (
+                self.generate_primitive(op, pos_var, code),
+                color.map(str::to_string),
+            ),
+        }
+    }
+
+    /// Emit a color variable combining two child colors (color pass only)
+    fn blend_colors(
+        &mut self,
+        a: Option<String>,
+        b: Option<String>,
+        code: &mut String,
+        expr: impl FnOnce(&str, &str) -> String,
+    ) -> Option<String> {
+        let (a, b) = (a?, b?);
+        let var = self.next_color_var();
+        writeln!(code, "    let {} = {};", var, expr(&a, &b)).unwrap();
+        Some(var)
     }
 
-    /// Generate code for a single SDF operation, returns the variable name containing the result
-    fn generate_op(&mut self, op: &SdfOp, pos_var: &str, code: &mut String) -> String {
+    /// Generate code for a leaf SDF, returns the variable name containing the distance
+    fn generate_primitive(&mut self, op: &SdfOp, pos_var: &str, code: &mut String) -> String {
         match op {
             // Primitives
             SdfOp::Sphere { radius } => {
@@ -138,10 +651,11 @@ impl WgslGenerator {
             }
             SdfOp::Plane { normal, offset } => {
                 let var = self.next_var();
+                let [x, y, z] = normalize(*normal);
                 writeln!(
                     code,
                     "    let {} = sd_plane({}, vec3<f32>({:.6}, {:.6}, {:.6}), {:.6});",
-                    var, pos_var, normal[0], normal[1], normal[2], offset
+                    var, pos_var, x, y, z, offset
                 )
                 .unwrap();
                 var
@@ -213,319 +727,443 @@ impl WgslGenerator {
                 .unwrap();
                 var
             }
-
-            // Boolean operations
-            SdfOp::Union { a, b } => {
-                let a_var = self.generate_op(a, pos_var, code);
-                let b_var = self.generate_op(b, pos_var, code);
+            SdfOp::CappedCone {
+                half_height,
+                bottom_radius,
+                top_radius,
+            } => {
                 let var = self.next_var();
-                writeln!(code, "    let {} = op_union({}, {});", var, a_var, b_var).unwrap();
+                writeln!(
+                    code,
+                    "    let {} = sd_capped_cone({}, {:.6}, {:.6}, {:.6});",
+                    var, pos_var, half_height, bottom_radius, top_radius
+                )
+                .unwrap();
+                var
+            }
+            SdfOp::RoundCone {
+                bottom_radius,
+                top_radius,
+                height,
+            } => {
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = sd_round_cone({}, {:.6}, {:.6}, {:.6});",
+                    var, pos_var, bottom_radius, top_radius, height
+                )
+                .unwrap();
+                var
+            }
+            SdfOp::BoxFrame {
+                half_extents,
+                half_thickness,
+            } => {
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = sd_box_frame({}, vec3<f32>({:.6}, {:.6}, {:.6}), {:.6});",
+                    var, pos_var, half_extents[0], half_extents[1], half_extents[2], half_thickness
+                )
+                .unwrap();
+                var
+            }
+            SdfOp::CappedTorus {
+                half_angle,
+                major_radius,
+                minor_radius,
+            } => {
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = sd_capped_torus({}, vec2<f32>({:.6}, {:.6}), {:.6}, {:.6});",
+                    var,
+                    pos_var,
+                    half_angle.sin(),
+                    half_angle.cos(),
+                    major_radius,
+                    minor_radius
+                )
+                .unwrap();
+                var
+            }
+            SdfOp::CutSphere { radius, cut_height } => {
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = sd_cut_sphere({}, {:.6}, {:.6});",
+                    var, pos_var, radius, cut_height
+                )
+                .unwrap();
+                var
+            }
+            SdfOp::CutHollowSphere {
+                radius,
+                cut_height,
+                thickness,
+            } => {
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = sd_cut_hollow_sphere({}, {:.6}, {:.6}, {:.6});",
+                    var, pos_var, radius, cut_height, thickness
+                )
+                .unwrap();
+                var
+            }
+            SdfOp::Rhombus {
+                half_diagonals,
+                half_height,
+                radius,
+            } => {
+                let var = self.next_var();
+                writeln!(
+                    code,
+                    "    let {} = sd_rhombus({}, {:.6}, {:.6}, {:.6}, {:.6});",
+                    var, pos_var, half_diagonals[0], half_diagonals[1], half_height, radius
+                )
+                .unwrap();
                 var
             }
-            SdfOp::Subtract { a, b } => {
-                let a_var = self.generate_op(a, pos_var, code);
-                let b_var = self.generate_op(b, pos_var, code);
+            SdfOp::SolidAngle { half_angle, radius } => {
                 let var = self.next_var();
-                writeln!(code, "    let {} = op_subtract({}, {});", var, a_var, b_var).unwrap();
+                writeln!(
+                    code,
+                    "    let {} = sd_solid_angle({}, vec2<f32>({:.6}, {:.6}), {:.6});",
+                    var,
+                    pos_var,
+                    half_angle.sin(),
+                    half_angle.cos(),
+                    radius
+                )
+                .unwrap();
                 var
             }
-            SdfOp::Intersect { a, b } => {
-                let a_var = self.generate_op(a, pos_var, code);
-                let b_var = self.generate_op(b, pos_var, code);
+            SdfOp::CapsuleBetween { a, b, radius } => {
                 let var = self.next_var();
                 writeln!(
                     code,
-                    "    let {} = op_intersect({}, {});",
-                    var, a_var, b_var
+                    "    let {} = sd_capsule_between({}, vec3<f32>({:.6}, {:.6}, {:.6}), vec3<f32>({:.6}, {:.6}, {:.6}), {:.6});",
+                    var, pos_var, a[0], a[1], a[2], b[0], b[1], b[2], radius
                 )
                 .unwrap();
                 var
             }
-            SdfOp::SmoothUnion { a, b, k } => {
-                let a_var = self.generate_op(a, pos_var, code);
-                let b_var = self.generate_op(b, pos_var, code);
+            SdfOp::CylinderBetween { a, b, radius } => {
                 let var = self.next_var();
                 writeln!(
                     code,
-                    "    let {} = op_smooth_union({}, {}, {:.6});",
-                    var, a_var, b_var, k
+                    "    let {} = sd_cylinder_between({}, vec3<f32>({:.6}, {:.6}, {:.6}), vec3<f32>({:.6}, {:.6}, {:.6}), {:.6});",
+                    var, pos_var, a[0], a[1], a[2], b[0], b[1], b[2], radius
                 )
                 .unwrap();
                 var
             }
-            SdfOp::SmoothSubtract { a, b, k } => {
-                let a_var = self.generate_op(a, pos_var, code);
-                let b_var = self.generate_op(b, pos_var, code);
+            SdfOp::InfiniteCylinder { axis, radius } => {
                 let var = self.next_var();
+                let [x, y, z] = normalize(*axis);
                 writeln!(
                     code,
-                    "    let {} = op_smooth_subtract({}, {}, {:.6});",
-                    var, a_var, b_var, k
+                    "    let {} = sd_infinite_cylinder({}, vec3<f32>({:.6}, {:.6}, {:.6}), {:.6});",
+                    var, pos_var, x, y, z, radius
                 )
                 .unwrap();
                 var
             }
-            SdfOp::SmoothIntersect { a, b, k } => {
-                let a_var = self.generate_op(a, pos_var, code);
-                let b_var = self.generate_op(b, pos_var, code);
+            SdfOp::Mandelbulb { power, iterations } => {
                 let var = self.next_var();
                 writeln!(
                     code,
-                    "    let {} = op_smooth_intersect({}, {}, {:.6});",
-                    var, a_var, b_var, k
+                    "    let {} = sd_mandelbulb({}, {:.6}, {:.1});",
+                    var, pos_var, power, *iterations as f32
                 )
                 .unwrap();
                 var
             }
-            SdfOp::Xor { a, b } => {
-                let a_var = self.generate_op(a, pos_var, code);
-                let b_var = self.generate_op(b, pos_var, code);
+            SdfOp::MengerSponge { size, iterations } => {
                 let var = self.next_var();
-                writeln!(code, "    let {} = op_xor({}, {});", var, a_var, b_var).unwrap();
+                writeln!(
+                    code,
+                    "    let {} = sd_menger_sponge({}, {:.6}, {:.1});",
+                    var, pos_var, size, *iterations as f32
+                )
+                .unwrap();
                 var
             }
-
-            // Modifiers
-            SdfOp::Shell { inner, thickness } => {
-                let inner_var = self.generate_op(inner, pos_var, code);
+            SdfOp::Sierpinski { size, iterations } => {
                 let var = self.next_var();
                 writeln!(
                     code,
-                    "    let {} = op_shell({}, {:.6});",
-                    var, inner_var, thickness
+                    "    let {} = sd_sierpinski({}, {:.6}, {:.1});",
+                    var, pos_var, size, *iterations as f32
                 )
                 .unwrap();
                 var
             }
-            SdfOp::Round { inner, radius } => {
-                let inner_var = self.generate_op(inner, pos_var, code);
+            SdfOp::Julia { c, iterations } => {
                 let var = self.next_var();
                 writeln!(
                     code,
-                    "    let {} = op_round({}, {:.6});",
-                    var, inner_var, radius
+                    "    let {} = sd_julia({}, vec4<f32>({:.6}, {:.6}, {:.6}, {:.6}), {:.1});",
+                    var, pos_var, c[0], c[1], c[2], c[3], *iterations as f32
                 )
                 .unwrap();
                 var
             }
-            SdfOp::Onion { inner, thickness } => {
-                let inner_var = self.generate_op(inner, pos_var, code);
+
+            // 2D-to-3D Operations
+            SdfOp::Extrude { profile, depth } => {
+                let d2d = self.generate_2d(profile, &format!("{}.xy", pos_var), code);
                 let var = self.next_var();
                 writeln!(
                     code,
-                    "    let {} = op_onion({}, {:.6});",
-                    var, inner_var, thickness
+                    "    let {} = op_extrude({}, {}.z, {:.6});",
+                    var, d2d, pos_var, depth
                 )
                 .unwrap();
                 var
             }
-            SdfOp::Elongate { inner, h } => {
-                let new_pos = self.next_pos_var();
+            SdfOp::Revolve { profile, offset } => {
+                let p2d = self.next_pos_var();
                 writeln!(
                     code,
-                    "    let {} = op_elongate({}, vec3<f32>({:.6}, {:.6}, {:.6}));",
-                    new_pos, pos_var, h[0], h[1], h[2]
+                    "    let {} = op_revolve({}, {:.6});",
+                    p2d, pos_var, offset
                 )
                 .unwrap();
-                self.generate_op(inner, &new_pos, code)
+                self.generate_2d(profile, &p2d, code)
             }
 
-            // Transforms
-            SdfOp::Translate { inner, offset } => {
-                let new_pos = self.next_pos_var();
+            // Sweeps
+            SdfOp::Tube {
+                path,
+                start_radius,
+                end_radius,
+            } => {
+                let var = self.next_var();
+                writeln!(code, "    var {} = 1e10;", var).unwrap();
+                let radius = |t: f32| start_radius + (end_radius - start_radius) * t;
+                for seg in path.segments() {
+                    writeln!(
+                        code,
+                        "    {} = min({}, sd_round_cone_between({}, {}, {}, {:.6}, {:.6}));",
+                        var,
+                        var,
+                        pos_var,
+                        vec3(seg.start),
+                        vec3(seg.end),
+                        radius(seg.start_t),
+                        radius(seg.end_t)
+                    )
+                    .unwrap();
+                }
+                var
+            }
+            SdfOp::Sweep { path, profile } => {
+                // The profile is the same for every segment, so its code is
+                // emitted once and called per segment
+                let profile_fn = self.profile_helper(profile);
+                let var = self.next_var();
+                let frame = self.next_pos_var();
+                writeln!(code, "    var {} = 1e10;", var).unwrap();
+                writeln!(code, "    var {} = vec3<f32>(0.0);", frame).unwrap();
+                for seg in path.segments() {
+                    writeln!(
+                        code,
+                        "    {} = op_sweep_frame({}, {}, {}, {}, {}, {}, {});",
+                        frame,
+                        pos_var,
+                        vec3(seg.start),
+                        vec3(seg.end),
+                        vec3(seg.side),
+                        vec3(seg.up),
+                        vec3(seg.start_miter),
+                        vec3(seg.end_miter)
+                    )
+                    .unwrap();
+                    writeln!(
+                        code,
+                        "    {} = min({}, op_sweep_cap({}({}.xy), {}.z));",
+                        var, var, profile_fn, frame, frame
+                    )
+                    .unwrap();
+                }
+                var
+            }
+
+            // Operations with children are handled by generate_op
+            _ => {
+                let var = self.next_var();
+                writeln!(code, "    let {} = 1000.0;", var).unwrap();
+                var
+            }
+        }
+    }
+
+    /// Generate the distance to a 2D profile at the `vec2` position `pos_var`
+    fn generate_2d(&mut self, op: &Sdf2dOp, pos_var: &str, code: &mut String) -> String {
+        match op {
+            // Primitives
+            Sdf2dOp::Circle { radius } => {
+                let var = self.next_var();
                 writeln!(
                     code,
-                    "    let {} = op_translate({}, vec3<f32>({:.6}, {:.6}, {:.6}));",
-                    new_pos, pos_var, offset[0], offset[1], offset[2]
+                    "    let {} = sd_circle_2d({}, {:.6});",
+                    var, pos_var, radius
                 )
                 .unwrap();
-                self.generate_op(inner, &new_pos, code)
+                var
             }
-            SdfOp::RotateX { inner, angle } => {
-                let new_pos = self.next_pos_var();
-                // Pre-compute sin/cos at code generation time for better GPU performance
-                let c = angle.cos();
-                let s = angle.sin();
+            Sdf2dOp::Rectangle { half_extents } => {
+                let var = self.next_var();
                 writeln!(
                     code,
-                    "    let {} = vec3<f32>({}.x, {:.8} * {}.y - {:.8} * {}.z, {:.8} * {}.y + {:.8} * {}.z);",
-                    new_pos, pos_var, c, pos_var, s, pos_var, s, pos_var, c, pos_var
-                ).unwrap();
-                self.generate_op(inner, &new_pos, code)
+                    "    let {} = sd_box_2d({}, vec2<f32>({:.6}, {:.6}));",
+                    var, pos_var, half_extents[0], half_extents[1]
+                )
+                .unwrap();
+                var
             }
-            SdfOp::RotateY { inner, angle } => {
-                let new_pos = self.next_pos_var();
-                let c = angle.cos();
-                let s = angle.sin();
+            Sdf2dOp::RoundedRectangle {
+                half_extents,
+                radius,
+            } => {
+                let var = self.next_var();
                 writeln!(
                     code,
-                    "    let {} = vec3<f32>({:.8} * {}.x + {:.8} * {}.z, {}.y, -{:.8} * {}.x + {:.8} * {}.z);",
-                    new_pos, c, pos_var, s, pos_var, pos_var, s, pos_var, c, pos_var
-                ).unwrap();
-                self.generate_op(inner, &new_pos, code)
+                    "    let {} = sd_rounded_box_2d({}, vec2<f32>({:.6}, {:.6}), {:.6});",
+                    var, pos_var, half_extents[0], half_extents[1], radius
+                )
+                .unwrap();
+                var
             }
-            SdfOp::RotateZ { inner, angle } => {
-                let new_pos = self.next_pos_var();
-                let c = angle.cos();
-                let s = angle.sin();
-                writeln!(
-                    code,
-                    "    let {} = vec3<f32>({:.8} * {}.x - {:.8} * {}.y, {:.8} * {}.x + {:.8} * {}.y, {}.z);",
-                    new_pos, c, pos_var, s, pos_var, s, pos_var, c, pos_var, pos_var
-                ).unwrap();
-                self.generate_op(inner, &new_pos, code)
+            Sdf2dOp::Polygon { points } => {
+                // Running (squared distance, sign) pair, one edge at a time
+                let acc = self.next_var();
+                writeln!(code, "    var {} = vec2<f32>(1e20, 1.0);", acc).unwrap();
+                for (i, a) in points.iter().enumerate() {
+                    let b = points[(i + points.len() - 1) % points.len()];
+                    writeln!(
+                        code,
+                        "    {} = sd_polygon_edge({}, vec2<f32>({:.6}, {:.6}), vec2<f32>({:.6}, {:.6}), {});",
+                        acc, pos_var, a[0], a[1], b[0], b[1], acc
+                    )
+                    .unwrap();
+                }
+                let var = self.next_var();
+                writeln!(code, "    let {} = {}.y * sqrt({}.x);", var, acc, acc).unwrap();
+                var
             }
-            SdfOp::Scale { inner, factor } => {
-                let new_pos = self.next_pos_var();
+            Sdf2dOp::Arc {
+                half_angle,
+                radius,
+                half_thickness,
+            } => {
+                let var = self.next_var();
                 writeln!(
                     code,
-                    "    let {} = op_scale({}, {:.6});",
-                    new_pos, pos_var, factor
+                    "    let {} = sd_arc_2d({}, vec2<f32>({:.6}, {:.6}), {:.6}, {:.6});",
+                    var,
+                    pos_var,
+                    half_angle.sin(),
+                    half_angle.cos(),
+                    radius,
+                    half_thickness
                 )
                 .unwrap();
-                let inner_var = self.generate_op(inner, &new_pos, code);
-                let var = self.next_var();
-                writeln!(code, "    let {} = {} * {:.6};", var, inner_var, factor).unwrap();
                 var
             }
-            SdfOp::Mirror { inner, axis } => {
-                // Simple mirror using abs on the appropriate axis
-                let new_pos = self.next_pos_var();
-                if axis[0].abs() > 0.5 {
-                    writeln!(code, "    let {} = op_symmetry_x({});", new_pos, pos_var).unwrap();
-                } else if axis[1].abs() > 0.5 {
-                    writeln!(code, "    let {} = op_symmetry_y({});", new_pos, pos_var).unwrap();
-                } else {
-                    writeln!(code, "    let {} = op_symmetry_z({});", new_pos, pos_var).unwrap();
-                }
-                self.generate_op(inner, &new_pos, code)
+
+            // Boolean operations
+            Sdf2dOp::Union { a, b } => self.generate_2d_pair(a, b, None, "op_union", pos_var, code),
+            Sdf2dOp::Subtract { a, b } => {
+                self.generate_2d_pair(a, b, None, "op_subtract", pos_var, code)
             }
-            SdfOp::SymmetryX { inner } => {
-                let new_pos = self.next_pos_var();
-                writeln!(code, "    let {} = op_symmetry_x({});", new_pos, pos_var).unwrap();
-                self.generate_op(inner, &new_pos, code)
+            Sdf2dOp::Intersect { a, b } => {
+                self.generate_2d_pair(a, b, None, "op_intersect", pos_var, code)
             }
-            SdfOp::SymmetryY { inner } => {
-                let new_pos = self.next_pos_var();
-                writeln!(code, "    let {} = op_symmetry_y({});", new_pos, pos_var).unwrap();
-                self.generate_op(inner, &new_pos, code)
+            Sdf2dOp::SmoothUnion { a, b, k } => {
+                self.generate_2d_pair(a, b, Some(*k), "op_smooth_union", pos_var, code)
             }
-            SdfOp::SymmetryZ { inner } => {
-                let new_pos = self.next_pos_var();
-                writeln!(code, "    let {} = op_symmetry_z({});", new_pos, pos_var).unwrap();
-                self.generate_op(inner, &new_pos, code)
+            Sdf2dOp::SmoothSubtract { a, b, k } => {
+                self.generate_2d_pair(a, b, Some(*k), "op_smooth_subtract", pos_var, code)
+            }
+            Sdf2dOp::SmoothIntersect { a, b, k } => {
+                self.generate_2d_pair(a, b, Some(*k), "op_smooth_intersect", pos_var, code)
             }
 
-            // Deformations
-            SdfOp::Twist { inner, amount } => {
-                let new_pos = self.next_pos_var();
+            // Modifiers
+            Sdf2dOp::Offset { inner, distance } => {
+                let inner_var = self.generate_2d(inner, pos_var, code);
+                let var = self.next_var();
                 writeln!(
                     code,
-                    "    let {} = op_twist({}, {:.6});",
-                    new_pos, pos_var, amount
+                    "    let {} = op_round({}, {:.6});",
+                    var, inner_var, distance
                 )
                 .unwrap();
-                self.generate_op(inner, &new_pos, code)
+                var
             }
-            SdfOp::Bend { inner, amount } => {
-                let new_pos = self.next_pos_var();
+            Sdf2dOp::Shell { inner, thickness } => {
+                let inner_var = self.generate_2d(inner, pos_var, code);
+                let var = self.next_var();
                 writeln!(
                     code,
-                    "    let {} = op_bend({}, {:.6});",
-                    new_pos, pos_var, amount
+                    "    let {} = op_shell({}, {:.6});",
+                    var, inner_var, thickness
                 )
                 .unwrap();
-                self.generate_op(inner, &new_pos, code)
+                var
             }
-            SdfOp::Displacement {
-                inner,
-                amount,
-                frequency,
-            } => {
-                let inner_var = self.generate_op(inner, pos_var, code);
-                let var = self.next_var();
+
+            // Transforms and repetition reuse the 3D formulas in the XY plane
+            Sdf2dOp::Translate { inner, offset } => {
+                let new_pos = self.next_pos_var();
                 writeln!(
                     code,
-                    "    let {} = op_displacement({}, {}, {:.6}, {:.6});",
-                    var, inner_var, pos_var, amount, frequency
+                    "    let {} = op_translate(vec3<f32>({}, 0.0), vec3<f32>({:.6}, {:.6}, 0.0)).xy;",
+                    new_pos, pos_var, offset[0], offset[1]
                 )
                 .unwrap();
-                var
+                self.generate_2d(inner, &new_pos, code)
             }
-
-            // 2D-to-3D Operations
-            SdfOp::Extrude { profile, depth } => {
-                let var = self.next_var();
-                match profile {
-                    ExtrudeProfile::Circle { radius } => {
-                        writeln!(
-                            code,
-                            "    let {} = op_extrude(sd_circle_2d({}.xy, {:.6}), {}.z, {:.6});",
-                            var, pos_var, radius, pos_var, depth
-                        )
-                        .unwrap();
-                    }
-                    ExtrudeProfile::Rectangle { width, height } => {
-                        writeln!(
-                            code,
-                            "    let {} = op_extrude(sd_box_2d({}.xy, vec2<f32>({:.6}, {:.6})), {}.z, {:.6});",
-                            var, pos_var, width / 2.0, height / 2.0, pos_var, depth
-                        )
-                        .unwrap();
-                    }
-                    ExtrudeProfile::RoundedRectangle {
-                        width,
-                        height,
-                        radius,
-                    } => {
-                        writeln!(
-                            code,
-                            "    let {} = op_extrude(sd_rounded_box_2d({}.xy, vec2<f32>({:.6}, {:.6}), {:.6}), {}.z, {:.6});",
-                            var, pos_var, width / 2.0, height / 2.0, radius, pos_var, depth
-                        )
-                        .unwrap();
-                    }
-                }
-                var
+            Sdf2dOp::Rotate { inner, angle } => {
+                let new_pos = self.next_pos_var();
+                writeln!(
+                    code,
+                    "    let {} = op_rotate_z(vec3<f32>({}, 0.0), {:.8}, {:.8}).xy;",
+                    new_pos,
+                    pos_var,
+                    angle.cos(),
+                    angle.sin()
+                )
+                .unwrap();
+                self.generate_2d(inner, &new_pos, code)
             }
-            SdfOp::Revolve { profile, offset } => {
-                let var = self.next_var();
-                let p2d = self.next_pos_var();
+            Sdf2dOp::Scale { inner, factor } => {
+                let new_pos = self.next_pos_var();
                 writeln!(
                     code,
-                    "    let {} = op_revolve({}, {:.6});",
-                    p2d, pos_var, offset
+                    "    let {} = op_scale(vec3<f32>({}, 0.0), {:.6}).xy;",
+                    new_pos, pos_var, factor
                 )
                 .unwrap();
-                match profile {
-                    RevolveProfile::Circle { radius } => {
-                        writeln!(code, "    let {} = sd_circle_2d({}, {:.6});", var, p2d, radius)
-                            .unwrap();
-                    }
-                    RevolveProfile::Rectangle { width, height } => {
-                        writeln!(
-                            code,
-                            "    let {} = sd_box_2d({}, vec2<f32>({:.6}, {:.6}));",
-                            var, p2d, width / 2.0, height / 2.0
-                        )
-                        .unwrap();
-                    }
-                }
+                let inner_var = self.generate_2d(inner, &new_pos, code);
+                let var = self.next_var();
+                writeln!(code, "    let {} = {} * {:.6};", var, inner_var, factor).unwrap();
                 var
             }
-
-            // Repetition
-            SdfOp::RepeatInfinite { inner, spacing } => {
+            Sdf2dOp::RepeatInfinite { inner, spacing } => {
                 let new_pos = self.next_pos_var();
                 writeln!(
                     code,
-                    "    let {} = op_repeat({}, vec3<f32>({:.6}, {:.6}, {:.6}));",
-                    new_pos, pos_var, spacing[0], spacing[1], spacing[2]
+                    "    let {} = op_repeat(vec3<f32>({}, 0.0), vec3<f32>({:.6}, {:.6}, 0.0)).xy;",
+                    new_pos, pos_var, spacing[0], spacing[1]
                 )
                 .unwrap();
-                self.generate_op(inner, &new_pos, code)
+                self.generate_2d(inner, &new_pos, code)
             }
-            SdfOp::RepeatLimited {
+            Sdf2dOp::RepeatLimited {
                 inner,
                 spacing,
                 count,
@@ -533,25 +1171,50 @@ impl WgslGenerator {
                 let new_pos = self.next_pos_var();
                 writeln!(
                     code,
-                    "    let {} = op_repeat_limited({}, vec3<f32>({:.6}, {:.6}, {:.6}), vec3<f32>({:.6}, {:.6}, {:.6}));",
-                    new_pos, pos_var,
-                    spacing[0], spacing[1], spacing[2],
-                    count[0], count[1], count[2]
-                ).unwrap();
-                self.generate_op(inner, &new_pos, code)
+                    "    let {} = op_repeat_limited(vec3<f32>({}, 0.0), vec3<f32>({:.6}, {:.6}, 0.0), vec3<f32>({:.6}, {:.6}, 0.0)).xy;",
+                    new_pos, pos_var, spacing[0], spacing[1], count[0], count[1]
+                )
+                .unwrap();
+                self.generate_2d(inner, &new_pos, code)
             }
-            SdfOp::RepeatPolar { inner, count } => {
+            Sdf2dOp::RepeatPolar { inner, count } => {
+                // The 3D polar repeat works in XZ, so carry Y through Z
                 let new_pos = self.next_pos_var();
                 writeln!(
                     code,
-                    "    let {} = op_repeat_polar({}, {:.1});",
-                    new_pos, pos_var, *count as f32
+                    "    let {} = op_repeat_polar(vec3<f32>({}.x, 0.0, {}.y), {:.1}).xz;",
+                    new_pos, pos_var, pos_var, *count as f32
                 )
                 .unwrap();
-                self.generate_op(inner, &new_pos, code)
+                self.generate_2d(inner, &new_pos, code)
             }
         }
     }
+
+    /// Generate a 2D boolean: `func(a, b)`, or `func(a, b, k)` for smooth blends
+    fn generate_2d_pair(
+        &mut self,
+        a: &Sdf2dOp,
+        b: &Sdf2dOp,
+        k: Option<f32>,
+        func: &str,
+        pos_var: &str,
+        code: &mut String,
+    ) -> String {
+        let a_var = self.generate_2d(a, pos_var, code);
+        let b_var = self.generate_2d(b, pos_var, code);
+        let var = self.next_var();
+        match k {
+            Some(k) => writeln!(
+                code,
+                "    let {} = {}({}, {}, {:.6});",
+                var, func, a_var, b_var, k
+            ),
+            None => writeln!(code, "    let {} = {}({}, {});", var, func, a_var, b_var),
+        }
+        .unwrap();
+        var
+    }
 }
 
 impl Default for WgslGenerator {
@@ -560,6 +1223,31 @@ impl Default for WgslGenerator {
     }
 }
 
+/// WGSL literal for a point or direction
+fn vec3(v: [f32; 3]) -> String {
+    format!("vec3<f32>({:.6}, {:.6}, {:.6})", v[0], v[1], v[2])
+}
+
+/// Normalize a quaternion at code generation time (zero becomes the identity)
+fn normalize_quat(q: [f32; 4]) -> [f32; 4] {
+    let len = (q[0] * q[0] + q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt();
+    if len > 0.0 {
+        q.map(|v| v / len)
+    } else {
+        [0.0, 0.0, 0.0, 1.0]
+    }
+}
+
+/// Normalize a direction at code generation time (zero vectors are left alone)
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let len = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if len > 0.0 {
+        [v[0] / len, v[1] / len, v[2] / len]
+    } else {
+        v
+    }
+}
+
 /// Get the base shader code (everything except the `scene_sdf` function)
 pub fn get_base_shader() -> &'static str {
     include_str!("shaders/raymarch.wgsl")
@@ -567,13 +1255,32 @@ pub fn get_base_shader() -> &'static str {
 
 /// Replace the `scene_sdf` function in the base shader with custom code
 pub fn inject_scene_sdf(base_shader: &str, scene_sdf_code: &str) -> String {
-    // Find the default scene_sdf function and replace it
-    let marker = "// SCENE_SDF_PLACEHOLDER";
+    inject_function(
+        base_shader,
+        "// SCENE_SDF_PLACEHOLDER",
+        "fn scene_sdf",
+        scene_sdf_code,
+    )
+}
+
+/// Replace the `scene_color` function in the base shader with custom code
+pub fn inject_scene_color(base_shader: &str, scene_color_code: &str) -> String {
+    inject_function(
+        base_shader,
+        "// SCENE_COLOR_PLACEHOLDER",
+        "fn scene_color",
+        scene_color_code,
+    )
+}
+
+/// Replace the default function following `marker` with custom code
+fn inject_function(base_shader: &str, marker: &str, fn_signature: &str, code: &str) -> String {
+    // Find the default function and replace it
     if let Some(pos) = base_shader.find(marker) {
-        // Find the end of the default scene_sdf function
+        // Find the end of the default function
         let start = pos;
         // Find the closing brace of the function
-        if let Some(func_start) = base_shader[start..].find("fn scene_sdf") {
+        if let Some(func_start) = base_shader[start..].find(fn_signature) {
             let func_start = start + func_start;
             // Count braces to find end of function
             let mut brace_count = 0;
@@ -596,27 +1303,29 @@ pub fn inject_scene_sdf(base_shader: &str, scene_sdf_code: &str) -> String {
             // Replace the function
             let mut result = String::new();
             result.push_str(&base_shader[..start]);
-            result.push_str(scene_sdf_code);
+            result.push_str(code);
             result.push_str(&base_shader[func_end..]);
             return result;
         }
     }
 
     // If we can't find the marker, just append at the end (fallback)
-    format!("{}\n{}", base_shader, scene_sdf_code)
+    format!("{}\n{}", base_shader, code)
 }
 
 /// Build a complete shader from an SDF operation tree
 pub fn build_shader(sdf: &SdfOp) -> String {
     let mut generator = WgslGenerator::new();
     let scene_code = generator.generate(sdf);
+    let color_code = generator.generate_color(sdf);
     let base = get_base_shader();
 
     // Inject SSOT formulas from soyuz-math
     let with_formulas = inject_ssot_formulas(base);
 
-    // Inject the scene SDF
-    inject_scene_sdf(&with_formulas, &scene_code)
+    // Inject the scene SDF and per-material colors
+    let with_sdf = inject_scene_sdf(&with_formulas, &scene_code);
+    inject_scene_color(&with_sdf, &color_code)
 }
 
 /// Inject SSOT formulas from soyuz-math into the shader
@@ -678,4 +1387,126 @@ mod tests {
         assert!(code.contains("2.0"));
         assert!(code.contains("3.0"));
     }
+
+    #[test]
+    fn test_untagged_color_uses_environment() {
+        let sdf = SdfOp::Sphere { radius: 1.0 };
+        let mut generator = WgslGenerator::new();
+        let code = generator.generate_color(&sdf);
+        assert!(code.contains("fn scene_color"));
+        assert!(code.contains("return env.material_color;"));
+    }
+
+    #[test]
+    fn test_material_colors_through_union() {
+        let sdf = SdfOp::SmoothUnion {
+            a: Arc::new(SdfOp::Material {
+                inner: Arc::new(SdfOp::Sphere { radius: 1.0 }),
+                material: MaterialTag {
+                    id: 1,
+                    color: Some([0.5, 0.25, 0.125]),
+                },
+            }),
+            b: Arc::new(SdfOp::Material {
+                inner: Arc::new(SdfOp::Box {
+                    half_extents: [0.5, 0.5, 0.5],
+                }),
+                material: MaterialTag { id: 2, color: None },
+            }),
+            k: 0.1,
+        };
+        let mut generator = WgslGenerator::new();
+
+        // The distance function ignores materials
+        let sdf_code = generator.generate(&sdf);
+        assert!(!sdf_code.contains("vec3<f32>(0.500000, 0.250000, 0.125000)"));
+
+        let color_code = generator.generate_color(&sdf);
+        assert!(color_code.contains("vec3<f32>(0.500000, 0.250000, 0.125000)"));
+        assert!(color_code.contains("mix("));
+
+        let shader = build_shader(&sdf);
+        assert_eq!(shader.matches("fn scene_color").count(), 1);
+        assert_eq!(shader.matches("fn scene_sdf").count(), 1);
+    }
+
+    #[test]
+    fn test_untagged_branches_skip_color_code() {
+        let untagged = SdfOp::Union {
+            a: Arc::new(SdfOp::Sphere { radius: 1.0 }),
+            b: Arc::new(SdfOp::Sphere { radius: 0.5 }),
+        };
+        let sdf = SdfOp::Union {
+            a: Arc::new(SdfOp::Material {
+                inner: Arc::new(SdfOp::Sphere { radius: 1.0 }),
+                material: MaterialTag { id: 1, color: None },
+            }),
+            b: Arc::new(untagged),
+        };
+        let code = WgslGenerator::new().generate_color(&sdf);
+
+        // Only the outer union picks between colors
+        assert_eq!(code.matches("select(").count(), 1);
+        assert!(code.contains("c_default"));
+    }
+
+    #[test]
+    fn test_tagged_nodes_follow_tree_shape() {
+        let build = || SdfOp::Union {
+            a: Arc::new(SdfOp::Translate {
+                inner: Arc::new(SdfOp::Material {
+                    inner: Arc::new(SdfOp::Sphere { radius: 1.0 }),
+                    material: MaterialTag { id: 1, color: None },
+                }),
+                offset: [1.0, 0.0, 0.0],
+            }),
+            b: Arc::new(SdfOp::Sphere { radius: 0.5 }),
+        };
+        let tags = build().tagged_nodes();
+        assert!(tags.is_tagged());
+        assert!(tags.child(0).is_tagged());
+        assert!(tags.child(0).child(0).is_tagged());
+        assert!(!tags.child(0).child(0).child(0).is_tagged());
+        assert!(!tags.child(1).is_tagged());
+
+        // A separately built copy of the tree shares no nodes but gets the
+        // same entries and the same color code
+        assert_eq!(build().tagged_nodes(), tags);
+        assert_eq!(
+            WgslGenerator::new().generate_color(&build()),
+            WgslGenerator::new().generate_color(&build())
+        );
+    }
+
+    #[test]
+    fn test_sweep_profile_is_emitted_once() {
+        let path = crate::SweepPath::new(
+            crate::SweepCurve::Polyline,
+            vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [1.0, 1.0, 1.0],
+            ],
+        );
+        let sdf = SdfOp::Sweep {
+            path,
+            profile: Arc::new(Sdf2dOp::Circle { radius: 0.1 }),
+        };
+        let mut generator = WgslGenerator::new();
+        let code = generator.generate(&sdf);
+
+        assert_eq!(code.matches("sd_circle_2d(").count(), 1);
+        assert_eq!(code.matches("scene_sdf_profile_0(").count(), 4);
+        assert!(code.find("fn scene_sdf_profile_0") < code.find("fn scene_sdf("));
+
+        // scene_color names its own helpers, so both fit in one shader
+        let tagged = SdfOp::Material {
+            inner: Arc::new(sdf),
+            material: MaterialTag { id: 1, color: None },
+        };
+        let shader = build_shader(&tagged);
+        assert_eq!(shader.matches("fn scene_sdf_profile_0").count(), 1);
+        assert_eq!(shader.matches("fn scene_color_profile_0").count(), 1);
+    }
 }